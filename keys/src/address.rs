@@ -87,11 +87,32 @@ impl Address {
     /// ETH Address.
     /// Ref: EIP-55 Mixed-case checksum address encoding
     pub fn to_eth_address(&self) -> String {
-        let mut addr = format!("0x{}", hex::encode(self.as_tvm_bytes()));
-        eip55_checksum(unsafe { &mut addr.as_bytes_mut()[2..] });
+        format!("0x{}", self.checksum_hex())
+    }
+
+    /// EIP-55 checksummed hex of the 20-byte address, without the network byte and `0x`.
+    pub fn checksum_hex(&self) -> String {
+        let mut addr = hex::encode(self.as_tvm_bytes());
+        eip55_checksum(unsafe { addr.as_bytes_mut() });
         addr
     }
 
+    /// Address from EIP-55 checksummed hex, `0x` is optional. All-lowercase or all-uppercase hex has no checksum
+    /// and is accepted as is.
+    pub fn from_checksum_hex(s: &str) -> Result<Address, Error> {
+        let hex_part = s.strip_prefix("0x").unwrap_or(s);
+        if hex_part.len() != 40 || !hex_part.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::InvalidAddress);
+        }
+        let addr = Address::from_tvm_bytes(&Vec::from_hex(hex_part).map_err(|_| Error::InvalidAddress)?);
+        let is_mixed_case =
+            hex_part.bytes().any(|c| c.is_ascii_lowercase()) && hex_part.bytes().any(|c| c.is_ascii_uppercase());
+        if is_mixed_case && addr.checksum_hex() != hex_part {
+            return Err(Error::InvalidChecksum);
+        }
+        Ok(addr)
+    }
+
     /// Is this a well-known address.
     pub fn to_well_known_name(&self) -> Option<String> {
         WELLKNOWN_ADDRESS
@@ -172,28 +193,36 @@ impl FromStr for Address {
     where
         Self: Sized,
     {
-        if s.len() == 34 {
-            b58decode_check(s).and_then(Address::try_from)
-        } else if s.len() == 42 && s[..2] == hex::encode(&[ADDRESS_TYPE_PREFIX]) {
-            Vec::from_hex(s)
-                .map_err(|_| Error::InvalidAddress)
-                .and_then(Address::try_from)
-        } else if s.len() == 44 && (s.starts_with("0x") || s.starts_with("0X")) {
-            Vec::from_hex(&s.as_bytes()[2..])
-                .map_err(|_| Error::InvalidAddress)
-                .and_then(Address::try_from)
-        } else if s == "_" || s == "0x0" || s == "/0" {
-            "410000000000000000000000000000000000000000".parse()
-        } else if s.starts_with("/") {
+        if s == "_" || s == "0x0" || s == "/0" {
+            return "410000000000000000000000000000000000000000".parse();
+        }
+        if s.starts_with('/') {
             // look up from well-known addresses
-            WELLKNOWN_ADDRESS
+            return WELLKNOWN_ADDRESS
                 .iter()
                 .find(|(_addr, name)| &&s[1..] == name)
                 .ok_or_else(|| Error::InvalidAddress)?
                 .0
-                .parse()
+                .parse();
+        }
+
+        let hex_part = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s);
+        if hex_part.bytes().all(|c| c.is_ascii_hexdigit()) {
+            // 21-byte form with 0x41 prefix, or 20-byte form as shown by Ethereum explorers
+            match hex_part.len() {
+                42 if hex_part.starts_with("41") => Vec::from_hex(hex_part)
+                    .map_err(|_| Error::InvalidAddress)
+                    .and_then(Address::try_from),
+                40 => {
+                    let mut raw = vec![ADDRESS_TYPE_PREFIX];
+                    raw.extend(Vec::from_hex(hex_part).map_err(|_| Error::InvalidAddress)?);
+                    Address::try_from(raw)
+                }
+                _ => Err(Error::InvalidAddress),
+            }
+        } else if s.len() == 34 {
+            b58decode_check(s).and_then(Address::try_from)
         } else {
-            eprintln!("len={} prefix={:x}", s.len(), s.as_bytes()[0]);
             Err(Error::InvalidAddress)
         }
     }
@@ -284,6 +313,75 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_address_from_str() {
+        let addr: Address = "TPhiVyQZ5xyvVK2KS2LTke8YvXJU5wxnbN".parse().unwrap();
+
+        assert_eq!(addr, "4196a3bace5adacf637eb7cc79d5787f4247da4bbe".parse().unwrap());
+        assert_eq!(addr, "0x4196a3bace5adacf637eb7cc79d5787f4247da4bbe".parse().unwrap());
+        assert_eq!(addr, "0X4196A3BACE5ADACF637EB7CC79D5787F4247DA4BBE".parse().unwrap());
+        assert_eq!(addr, "0x96a3bace5adacf637eb7cc79d5787f4247da4bbe".parse().unwrap());
+        assert_eq!(addr, "96a3bace5adacf637eb7cc79d5787f4247da4bbe".parse().unwrap());
+
+        assert!("4296a3bace5adacf637eb7cc79d5787f4247da4bbe".parse::<Address>().is_err());
+        assert!("0x96a3bace5adacf637eb7cc79d5787f4247da4b".parse::<Address>().is_err());
+        assert!("0x0x96a3bace5adacf637eb7cc79d5787f4247da4bbe"
+            .parse::<Address>()
+            .is_err());
+        assert!("0x0X96a3bace5adacf637eb7cc79d5787f4247da4bbe"
+            .parse::<Address>()
+            .is_err());
+        assert!("TPhiVyQZ5xyvVK2KS2LTke8YvXJU5wxnbM".parse::<Address>().is_err());
+        assert!("".parse::<Address>().is_err());
+    }
+
+    #[test]
+    fn test_eth_address_checksum() {
+        // test vectors from EIP-55
+        for eth_addr in &[
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        ] {
+            let addr = Address::from_tvm_bytes(&hex::decode(&eth_addr[2..]).unwrap());
+            assert_eq!(&addr.to_eth_address(), eth_addr);
+        }
+
+        let addr: Address = "TPhiVyQZ5xyvVK2KS2LTke8YvXJU5wxnbN".parse().unwrap();
+        assert_eq!(addr.to_eth_address(), format!("0x{}", addr.checksum_hex()));
+        assert_eq!(
+            addr.to_eth_address().to_lowercase(),
+            "0x96a3bace5adacf637eb7cc79d5787f4247da4bbe"
+        );
+    }
+
+    #[test]
+    fn test_from_checksum_hex() {
+        // test vectors from EIP-55, all-caps and all-lower included
+        for eth_addr in &[
+            "0x52908400098527886E0F7030069857D2E4169EE7",
+            "0xde709f2102306220921060314715629080e2fb77",
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let addr = Address::from_checksum_hex(eth_addr).unwrap();
+            assert_eq!(addr.as_bytes()[0], ADDRESS_TYPE_PREFIX);
+            assert_eq!(addr.as_tvm_bytes(), &hex::decode(&eth_addr[2..]).unwrap()[..]);
+            assert_eq!(Address::from_checksum_hex(&eth_addr[2..]).unwrap(), addr);
+        }
+
+        let addr = Address::from_checksum_hex("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap();
+        assert_eq!(addr.checksum_hex(), "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+        assert_eq!(
+            Address::from_checksum_hex("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeaED"),
+            Err(Error::InvalidChecksum)
+        );
+        assert!(Address::from_checksum_hex("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA").is_err());
+        assert!(Address::from_checksum_hex("0x0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+        assert!(Address::from_checksum_hex("TPhiVyQZ5xyvVK2KS2LTke8YvXJU5wxnbN").is_err());
+    }
+
     #[test]
     fn test_address_from_public() {
         let public = Public::from_hex("56f19ba7de92264d94f9b6600ec05c16c0b25a064e2ee1cf5bf0dd9661d04515c99c3a6b42b2c574232a5b951bf57cf706bbfd36377b406f9313772f65612cd0").unwrap();
@@ -20,6 +20,8 @@ pub enum Error {
     InvalidAddress,
     /// Unable to generate a key pair.
     FailedKeyGeneration,
+    /// The signer is unable to sign, e.g. the key is not available.
+    FailedSigning,
 }
 
 impl fmt::Display for Error {
@@ -32,6 +34,7 @@ impl fmt::Display for Error {
             Error::InvalidPrivate => "Invalid Private",
             Error::InvalidAddress => "Invalid Address",
             Error::FailedKeyGeneration => "Key generation failed",
+            Error::FailedSigning => "Signing failed",
         };
 
         msg.fmt(f)
@@ -8,6 +8,7 @@ mod keypair;
 mod private;
 mod public;
 mod signature;
+mod signer;
 
 pub use address::{b58decode_check, b58encode_check, Address};
 pub use error::Error;
@@ -15,3 +16,4 @@ pub use keypair::KeyPair;
 pub use private::Private;
 pub use public::Public;
 pub use signature::Signature;
+pub use signer::{MemorySigner, Signer};
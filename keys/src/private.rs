@@ -7,11 +7,12 @@ use std::str::FromStr;
 use hex::{FromHex, ToHex};
 use secp256k1::{Message, SecretKey};
 use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
 
 use crate::error::Error;
 use crate::signature::Signature;
 
-/// Private key of Secp256k1.
+/// Private key of Secp256k1, zeroized on drop.
 #[derive(PartialEq, Hash, Clone)]
 pub struct Private([u8; 32]);
 
@@ -43,6 +44,12 @@ impl Private {
     }
 }
 
+impl Drop for Private {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl fmt::Display for Private {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.0.encode_hex::<String>().fmt(f)
@@ -0,0 +1,82 @@
+//! Signers of transaction hashes, decoupled from where the private key is kept.
+
+use std::fmt;
+
+use crate::address::Address;
+use crate::error::Error;
+use crate::private::Private;
+
+/// Something that can sign a 32-byte hash on behalf of an address.
+pub trait Signer {
+    /// Sign a hash, returns the 65-byte recoverable signature.
+    fn sign(&self, hash: &[u8; 32]) -> Result<[u8; 65], Error>;
+
+    /// Address of the signing key.
+    fn address(&self) -> Address;
+}
+
+/// A signer holding the private key in memory, mostly for testing.
+///
+/// Not `Clone`, and `Debug` prints the address only, so that the key is not copied or logged by accident.
+pub struct MemorySigner {
+    private: Private,
+    address: Address,
+}
+
+impl MemorySigner {
+    /// Construct a signer from a private key.
+    pub fn new(private: Private) -> Self {
+        let address = Address::from_private(&private);
+        MemorySigner { private, address }
+    }
+}
+
+impl fmt::Debug for MemorySigner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemorySigner").field("address", &self.address).finish()
+    }
+}
+
+impl Signer for MemorySigner {
+    fn sign(&self, hash: &[u8; 32]) -> Result<[u8; 65], Error> {
+        let signature = self.private.sign_digest(hash)?;
+        let mut raw = [0u8; 65];
+        raw.copy_from_slice(&signature[..]);
+        Ok(raw)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::public::Public;
+    use crate::signature::Signature;
+
+    #[test]
+    fn test_memory_signer() {
+        let private: Private = "b5a4cea271ff424d7c31dc12a3e43e401df7a40d7412a15750f3f0b6b5449a28"
+            .parse()
+            .unwrap();
+        let signer: Box<dyn Signer> = Box::new(MemorySigner::new(private.clone()));
+        assert_eq!(signer.address(), Address::from_private(&private));
+
+        let hash = [0x42u8; 32];
+        let signature = Signature::from(signer.sign(&hash).unwrap());
+        let public = Public::recover_digest(&hash, &signature).unwrap();
+        assert_eq!(Address::from_public(&public), signer.address());
+    }
+
+    #[test]
+    fn test_memory_signer_debug() {
+        let private: Private = "b5a4cea271ff424d7c31dc12a3e43e401df7a40d7412a15750f3f0b6b5449a28"
+            .parse()
+            .unwrap();
+        let debug = format!("{:?}", MemorySigner::new(private));
+        assert!(debug.contains("address"));
+        assert!(!debug.contains("b5a4cea2"));
+    }
+}
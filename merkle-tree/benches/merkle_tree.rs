@@ -0,0 +1,46 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use merkle_tree::{MerkleHasher, MerkleTree, H256};
+use sha2::{Digest, Sha256};
+
+struct BytesSha256Hasher;
+
+impl MerkleHasher for BytesSha256Hasher {
+    type Input = Vec<u8>;
+
+    fn hash(input: &Self::Input) -> H256 {
+        H256::from_slice(&Sha256::digest(input))
+    }
+
+    fn hash_nodes(left: &H256, right: &H256) -> H256 {
+        H256::from_slice(&Sha256::new().chain(left.as_bytes()).chain(right.as_bytes()).finalize())
+    }
+}
+
+fn from_vec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_vec");
+    for &count in &[1_000u32, 10_000, 100_000] {
+        let values: Vec<Vec<u8>> = (0..count).map(|i| i.to_be_bytes().to_vec()).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(count), &values, |b, values| {
+            b.iter(|| MerkleTree::<BytesSha256Hasher>::from_vec(values.clone()))
+        });
+    }
+    group.finish();
+}
+
+#[cfg(feature = "parallel")]
+fn par_from_vec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("par_from_vec");
+    for &count in &[1_000u32, 10_000, 100_000] {
+        let values: Vec<Vec<u8>> = (0..count).map(|i| i.to_be_bytes().to_vec()).collect();
+        group.bench_with_input(BenchmarkId::from_parameter(count), &values, |b, values| {
+            b.iter(|| MerkleTree::<BytesSha256Hasher>::par_from_vec(values.clone()))
+        });
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "parallel"))]
+criterion_group!(benches, from_vec);
+#[cfg(feature = "parallel")]
+criterion_group!(benches, from_vec, par_from_vec);
+criterion_main!(benches);
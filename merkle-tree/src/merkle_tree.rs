@@ -1,6 +1,8 @@
 use crate::tree::{LeavesIntoIterator, LeavesIterator, Tree};
-use primitive_types::H256;
 use crate::MerkleHasher;
+use primitive_types::H256;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 /// A Merkle tree is a binary tree, with values of type `T` at the leafs,
 /// and where every internal node holds the hash of the concatenation of the hashes of its children nodes.
@@ -16,10 +18,29 @@ pub struct MerkleTree<H: MerkleHasher> {
     count: usize,
 }
 
+/// Levels with fewer nodes are hashed on the current thread, spawning costs more than hashing.
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 256;
+
+/// Two nodes to be joined, or an odd last node of a level.
+type Pair<T> = (Tree<T>, Option<Tree<T>>);
+
 impl<H: MerkleHasher> MerkleTree<H> {
     /// Constructs a Merkle Tree from a vector of data blocks.
-    /// Returns `None` if `values` is empty.
+    /// Returns an empty tree if `values` is empty.
     pub fn from_vec(values: Vec<H::Input>) -> Self {
+        Self::build(
+            values,
+            |values| values.into_iter().map(Self::leaf).collect(),
+            |pairs| pairs.into_iter().map(Self::join).collect(),
+        )
+    }
+
+    fn build<L, J>(values: Vec<H::Input>, leaves: L, join: J) -> Self
+    where
+        L: FnOnce(Vec<H::Input>) -> Vec<Tree<H::Input>>,
+        J: Fn(Vec<Pair<H::Input>>) -> Vec<Tree<H::Input>>,
+    {
         if values.is_empty() {
             return MerkleTree {
                 root: Tree::empty(H::hash_empty()),
@@ -30,38 +51,11 @@ impl<H: MerkleHasher> MerkleTree<H> {
 
         let count = values.len();
         let mut height = 0;
-        let mut cur = Vec::with_capacity(count);
-
-        for v in values {
-            let hash = H::hash(&v);
-            let leaf = Tree::new(hash, v);
-            cur.push(leaf);
-        }
+        let mut cur = leaves(values);
 
         while cur.len() > 1 {
-            let mut next = Vec::new();
-            while !cur.is_empty() {
-                if cur.len() == 1 {
-                    next.push(cur.remove(0));
-                } else {
-                    let left = cur.remove(0);
-                    let right = cur.remove(0);
-
-                    let combined_hash = H::hash_nodes(left.hash(), right.hash());
-
-                    let node = Tree::Node {
-                        hash: combined_hash,
-                        left: Box::new(left),
-                        right: Box::new(right),
-                    };
-
-                    next.push(node);
-                }
-            }
-
+            cur = join(Self::pairs(cur));
             height += 1;
-
-            cur = next;
         }
 
         debug_assert!(cur.len() == 1);
@@ -75,6 +69,32 @@ impl<H: MerkleHasher> MerkleTree<H> {
         }
     }
 
+    fn leaf(value: H::Input) -> Tree<H::Input> {
+        Tree::new(H::hash(&value), value)
+    }
+
+    /// Pair up nodes of a level, in order.
+    fn pairs(level: Vec<Tree<H::Input>>) -> Vec<Pair<H::Input>> {
+        let mut pairs = Vec::with_capacity((level.len() + 1) / 2);
+        let mut iter = level.into_iter();
+        while let Some(left) = iter.next() {
+            pairs.push((left, iter.next()));
+        }
+        pairs
+    }
+
+    /// Join a pair into a node of the next level, an odd last node is moved up as is.
+    fn join((left, right): Pair<H::Input>) -> Tree<H::Input> {
+        match right {
+            Some(right) => Tree::Node {
+                hash: H::hash_nodes(left.hash(), right.hash()),
+                left: Box::new(left),
+                right: Box::new(right),
+            },
+            None => left,
+        }
+    }
+
     /// Returns the root hash of Merkle tree
     pub fn root_hash(&self) -> &H256 {
         self.root.hash()
@@ -101,6 +121,34 @@ impl<H: MerkleHasher> MerkleTree<H> {
     }
 }
 
+#[cfg(feature = "parallel")]
+impl<H: MerkleHasher> MerkleTree<H>
+where
+    H::Input: Send,
+{
+    /// Same as `from_vec`, but leaves and levels of at least `PARALLEL_THRESHOLD` nodes
+    /// are hashed on the rayon thread pool.
+    pub fn par_from_vec(values: Vec<H::Input>) -> Self {
+        Self::build(
+            values,
+            |values| {
+                if values.len() < PARALLEL_THRESHOLD {
+                    values.into_iter().map(Self::leaf).collect()
+                } else {
+                    values.into_par_iter().map(Self::leaf).collect()
+                }
+            },
+            |pairs| {
+                if pairs.len() * 2 < PARALLEL_THRESHOLD {
+                    pairs.into_iter().map(Self::join).collect()
+                } else {
+                    pairs.into_par_iter().map(Self::join).collect()
+                }
+            },
+        )
+    }
+}
+
 impl<H: MerkleHasher> IntoIterator for MerkleTree<H> {
     type Item = H::Input;
     type IntoIter = LeavesIntoIterator<H::Input>;
@@ -121,3 +169,23 @@ impl<'a, H: MerkleHasher> IntoIterator for &'a MerkleTree<H> {
         self.root.iter()
     }
 }
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+    use super::*;
+    use crate::tests::BytesSha256Hasher;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn parallel_root_equals_sequential(values in vec(vec(any::<u8>(), 0..8), 0..2_000)) {
+            let sequential = MerkleTree::<BytesSha256Hasher>::from_vec(values.clone());
+            let parallel = MerkleTree::<BytesSha256Hasher>::par_from_vec(values);
+            prop_assert_eq!(parallel.root_hash(), sequential.root_hash());
+            prop_assert_eq!(parallel.height(), sequential.height());
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Standalone ABI encoding and decoding, with the same rules as contract calls.
+
+use clap::ArgMatches;
+use hex::ToHex;
+use serde_json::json;
+
+use crate::error::Error;
+use crate::utils::abi::{self, AbiValue};
+use crate::utils::trx;
+
+/// Split a type list like `address,(uint256,bytes)[],bool` by top-level commas.
+fn split_types(types: &str) -> Result<Vec<&str>, Error> {
+    let mut ret = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in types.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Err(Error::Runtime("unbalanced parentheses in --types")),
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                ret.push(types[start..i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    if depth != 0 {
+        return Err(Error::Runtime("unbalanced parentheses in --types"));
+    }
+    ret.push(types[start..].trim());
+    if ret.iter().any(|ty| ty.is_empty()) {
+        return Err(Error::Runtime("empty type in --types"));
+    }
+    Ok(ret)
+}
+
+fn decoded_to_json(types: &[&str], values: &[AbiValue]) -> serde_json::Value {
+    json!(types
+        .iter()
+        .zip(values.iter())
+        .map(|(ty, value)| json!({"type": ty, "value": value}))
+        .collect::<Vec<_>>())
+}
+
+/// Encode command line values, addresses can be in any format.
+fn encode_args(types: &[&str], values: &[&str]) -> Result<Vec<u8>, Error> {
+    let values = types
+        .iter()
+        .zip(values.iter())
+        .map(|(&ty, &value)| {
+            if ty == "address" {
+                trx::parse_address(value).map(|addr| addr.as_tvm_bytes().encode_hex::<String>())
+            } else {
+                Ok(value.to_owned())
+            }
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    abi::encode_params(types, &values)
+}
+
+pub fn decode(matches: &ArgMatches) -> Result<(), Error> {
+    let types = split_types(matches.value_of("types").expect("required in cli.yml; qed"))?;
+    let data = matches.value_of("DATA").expect("required in cli.yml; qed");
+
+    let values = abi::decode_params(&types, data.trim_start_matches("0x"))?;
+    println!("{}", serde_json::to_string_pretty(&decoded_to_json(&types, &values))?);
+    Ok(())
+}
+
+pub fn encode(matches: &ArgMatches) -> Result<(), Error> {
+    let types = split_types(matches.value_of("types").expect("required in cli.yml; qed"))?;
+    let values: Vec<&str> = matches
+        .values_of("VALUES")
+        .map(Iterator::collect)
+        .unwrap_or_else(Vec::new);
+    if values.len() != types.len() {
+        return Err(Error::Runtime("number of VALUES does not match --types"));
+    }
+
+    println!("{}", encode_args(&types, &values)?.encode_hex::<String>());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_types() {
+        assert_eq!(
+            split_types("address,uint256,bytes").unwrap(),
+            vec!["address", "uint256", "bytes"]
+        );
+        assert_eq!(
+            split_types("address, (uint256,bytes)[],bool").unwrap(),
+            vec!["address", "(uint256,bytes)[]", "bool"]
+        );
+        assert!(split_types("address,,bool").is_err());
+        assert!(split_types("(uint256,bytes").is_err());
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let types = split_types("address,uint256,bytes").unwrap();
+        let data = encode_args(&types, &["TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt", "125500000", "cafe"]).unwrap();
+        assert_eq!(data.len(), 32 * 5);
+        let decoded = abi::decode_params(&types, &hex::encode(&data)).unwrap();
+        assert_eq!(
+            decoded_to_json(&types, &decoded),
+            json!([
+                {"type": "address", "value": "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt"},
+                {"type": "uint256", "value": "125500000"},
+                {"type": "bytes", "value": "cafe"},
+            ])
+        );
+
+        let types = split_types("bool,string").unwrap();
+        let data = encode_args(&types, &["true", "hello"]).unwrap();
+        let decoded = abi::decode_params(&types, &hex::encode(&data)).unwrap();
+        assert_eq!(decoded, vec![AbiValue::Bool(true), AbiValue::String("hello".into())]);
+    }
+}
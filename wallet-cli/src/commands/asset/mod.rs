@@ -2,20 +2,34 @@
 
 use chrono::{DateTime, Utc};
 use clap::ArgMatches;
+use futures::executor;
 use keys::Address;
+use log::{info, warn};
 use proto::core::{
-    AssetIssueContract, AssetIssueContract_FrozenSupply as FrozenSupply, ParticipateAssetIssueContract,
-    TransferAssetContract, UnfreezeAssetContract, UpdateAssetContract,
+    Account, Account_Frozen as Frozen, AssetIssueContract, AssetIssueContract_FrozenSupply as FrozenSupply,
+    ParticipateAssetIssueContract, TransferAssetContract, UnfreezeAssetContract, UpdateAssetContract,
 };
 
 use crate::error::Error;
+use crate::utils::client;
+use crate::utils::time;
 use crate::utils::trx;
 
+/// Max bytes of a TRC10 description, empty is allowed. Limits are by `TransactionUtil` of java-tron.
+const MAX_DESCRIPTION_LENGTH: usize = 200;
+/// Max bytes of a TRC10 URL, which must not be empty.
+const MAX_URL_LENGTH: usize = 256;
+/// Free bandwidth limits must be below `getOneDayNetLimit`, a chain constant.
+const ONE_DAY_NET_LIMIT: i64 = 57_600_000_000;
+/// Fields fixed when issuing, rejected by `asset update` with a per-field error.
+const IMMUTABLE_FIELDS: &[&str] = &["name", "abbr", "total-supply", "precision", "exchange-rate", "freeze"];
+
 pub fn issue_asset(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
 
     let name = matches.value_of("NAME").expect("required in cli.yml; qed");
     let abbr = matches.value_of("abbr").unwrap_or(name);
@@ -90,12 +104,11 @@ pub fn issue_asset(matches: &ArgMatches) -> Result<(), Error> {
 pub fn transfer_asset(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong sender address format"))?;
-    let recipient = matches
-        .value_of("RECIPIENT")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong recipient address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing sender address"))?;
+    let recipient = trx::parse_address(matches.value_of("RECIPIENT").expect("required in cli.yml; qed"))?;
+    let activates = trx::check_activation(&recipient, true)?;
     let amount = matches.value_of("AMOUNT").expect("required in cli.yml; qed");
     let assert_id = matches.value_of("token-id").expect("required in cli.yml; qed");
 
@@ -107,32 +120,163 @@ pub fn transfer_asset(matches: &ArgMatches) -> Result<(), Error> {
         ..Default::default()
     };
 
-    eprintln!("sender:    {:}", sender);
-    eprintln!("recipient: {:}", recipient);
+    info!("sender:    {:}", sender);
+    info!("recipient: {:}", recipient);
+    info!(
+        "amount:    {}",
+        trx::format_asset_amount(transfer_contract.amount, assert_id)
+    );
+    if activates && !matches.is_present("yes") && !trx::confirm("Activate the recipient account?")? {
+        return Err(Error::Runtime("aborted by user"));
+    }
 
     trx::TransactionHandler::handle(transfer_contract, matches).run()
 }
 
+fn get_account(addr: &Address) -> Result<Account, Error> {
+    let mut req = Account::new();
+    req.set_address(addr.as_bytes().to_owned());
+    let account = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
+    if account.get_address().is_empty() {
+        return Err(Error::NotFound("account not found on chain"));
+    }
+    Ok(account)
+}
+
+/// Check an update against protocol limits. Each invalid field is warned.
+fn check_asset_update(update: &UpdateAssetContract) -> Result<(), Error> {
+    let mut errors = vec![];
+    if update.get_description().len() > MAX_DESCRIPTION_LENGTH {
+        errors.push(format!(
+            "description: {} bytes, at most {}",
+            update.get_description().len(),
+            MAX_DESCRIPTION_LENGTH
+        ));
+    }
+    if update.get_url().is_empty() || update.get_url().len() > MAX_URL_LENGTH {
+        errors.push(format!(
+            "url: {} bytes, must be 1 to {}",
+            update.get_url().len(),
+            MAX_URL_LENGTH
+        ));
+    }
+    for &(name, limit) in &[
+        ("bandwidth-limit-for-issuer", update.get_new_limit()),
+        ("bandwidth-limit-per-account", update.get_new_public_limit()),
+    ] {
+        if limit < 0 || limit >= ONE_DAY_NET_LIMIT {
+            errors.push(format!("{}: {}, must be 0 to {}", name, limit, ONE_DAY_NET_LIMIT - 1));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        errors.iter().for_each(|error| warn!("Invalid {}", error));
+        Err(Error::InvalidInput("invalid asset update"))
+    }
+}
+
+/// Current vs proposed value of each updatable field, `None` if nothing changes.
+fn diff_asset_update(current: &AssetIssueContract, update: &UpdateAssetContract) -> Option<Vec<String>> {
+    let fields = [
+        (
+            "description",
+            format!("{:?}", String::from_utf8_lossy(current.get_description())),
+            format!("{:?}", String::from_utf8_lossy(update.get_description())),
+        ),
+        (
+            "url",
+            format!("{:?}", String::from_utf8_lossy(current.get_url())),
+            format!("{:?}", String::from_utf8_lossy(update.get_url())),
+        ),
+        (
+            "bandwidth-limit-for-issuer",
+            current.get_free_asset_net_limit().to_string(),
+            update.get_new_limit().to_string(),
+        ),
+        (
+            "bandwidth-limit-per-account",
+            current.get_public_free_asset_net_limit().to_string(),
+            update.get_new_public_limit().to_string(),
+        ),
+    ];
+    if fields.iter().all(|(_, current, proposed)| current == proposed) {
+        return None;
+    }
+    Some(
+        fields
+            .iter()
+            .map(|(name, current, proposed)| {
+                if current == proposed {
+                    format!("{}: {} (unchanged)", name, current)
+                } else {
+                    format!("{}: {} -> {}", name, current, proposed)
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Update the TRC10 token issued by the sender, fields not given are kept.
 pub fn update_asset_settings(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
+
+    let immutable = IMMUTABLE_FIELDS
+        .iter()
+        .filter(|&&field| matches.is_present(field))
+        .collect::<Vec<_>>();
+    if !immutable.is_empty() {
+        immutable
+            .iter()
+            .for_each(|field| warn!("Invalid {}: can not be changed after issuing", field));
+        return Err(Error::InvalidInput("immutable TRC10 fields can not be updated"));
+    }
 
-    let description = matches.value_of("description").expect("required in cli.yml; qed");
-    let url = matches.value_of("url").expect("required in cli.yml; qed");
+    let account = get_account(&sender)?;
+    let current = match (account.get_asset_issued_ID(), account.get_asset_issued_name()) {
+        (id, _) if !id.is_empty() => trx::get_asset(&String::from_utf8_lossy(id))?,
+        (_, name) if !name.is_empty() => trx::get_asset(&String::from_utf8_lossy(name))?,
+        _ => return Err(Error::NotFound("the sender has not issued a TRC10 token")),
+    };
 
     let mut update_contract = UpdateAssetContract::new();
     update_contract.set_owner_address(sender.as_bytes().to_owned());
 
-    update_contract.set_description(description.as_bytes().to_owned());
-    update_contract.set_url(url.as_bytes().to_owned());
-
-    if let Some(limit) = matches.value_of("bandwidth-limit-for-issuer") {
-        update_contract.set_new_limit(limit.parse()?);
+    update_contract.set_description(
+        matches
+            .value_of("description")
+            .map_or_else(|| current.get_description().to_owned(), |s| s.as_bytes().to_owned()),
+    );
+    update_contract.set_url(
+        matches
+            .value_of("url")
+            .map_or_else(|| current.get_url().to_owned(), |s| s.as_bytes().to_owned()),
+    );
+    update_contract.set_new_limit(match matches.value_of("bandwidth-limit-for-issuer") {
+        Some(limit) => limit.parse()?,
+        None => current.get_free_asset_net_limit(),
+    });
+    update_contract.set_new_public_limit(match matches.value_of("bandwidth-limit-per-account") {
+        Some(limit) => limit.parse()?,
+        None => current.get_public_free_asset_net_limit(),
+    });
+    check_asset_update(&update_contract)?;
+
+    info!(
+        "TRC10 {} {:?}",
+        current.get_id(),
+        String::from_utf8_lossy(current.get_abbr())
+    );
+    match diff_asset_update(&current, &update_contract) {
+        Some(lines) => lines.iter().for_each(|line| info!("  {}", line)),
+        None => return Err(Error::InvalidInput("nothing to update, all values are current")),
     }
-    if let Some(public_limit) = matches.value_of("bandwidth-limit-per-account") {
-        update_contract.set_new_public_limit(public_limit.parse()?);
+    if !matches.is_present("yes") && !trx::confirm("Update the token?")? {
+        return Err(Error::Runtime("aborted by user"));
     }
 
     trx::TransactionHandler::handle(update_contract, matches).run()
@@ -141,12 +285,14 @@ pub fn update_asset_settings(matches: &ArgMatches) -> Result<(), Error> {
 fn participate_asset_issue(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong sender address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing sender address"))?;
     let recipient = matches
         .value_of("RECIPIENT")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong recipient address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing recipient address"))?;
     // NOTE: this contract is used for get ICO token with TRX
     let amount = matches.value_of("AMOUNT").expect("required in cli.yml; qed");
     let assert_id = matches.value_of("token-id").expect("required in cli.yml; qed");
@@ -165,8 +311,26 @@ fn participate_asset_issue(matches: &ArgMatches) -> Result<(), Error> {
 fn unfreeze_asset(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong sender address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing sender address"))?;
+
+    let account = get_account(&sender)?;
+    let now = Utc::now().timestamp_millis();
+    let (unfreezable, next_expire) = unfreezable_supply(account.get_frozen_supply(), now);
+    if unfreezable == 0 {
+        if let Some(expire_time) = next_expire {
+            info!(
+                "Next frozen supply unfreezable at {}",
+                time::format_timestamp(expire_time / 1_000, 0)
+            );
+        }
+        return Err(Error::InvalidInput("no frozen supply can be unfrozen yet"));
+    }
+    info!(
+        "Unfreezing {} of the frozen supply, in the smallest unit",
+        trx::format_number(unfreezable)
+    );
 
     let unfreeze_contract = UnfreezeAssetContract {
         owner_address: sender.as_bytes().to_owned(),
@@ -176,6 +340,21 @@ fn unfreeze_asset(matches: &ArgMatches) -> Result<(), Error> {
     trx::TransactionHandler::handle(unfreeze_contract, matches).run()
 }
 
+/// Frozen supply expired at `now`, and the next expire time of the rest.
+fn unfreezable_supply(frozen: &[Frozen], now: i64) -> (i64, Option<i64>) {
+    let unfreezable = frozen
+        .iter()
+        .filter(|frozen| frozen.get_expire_time() <= now)
+        .map(|frozen| frozen.get_frozen_balance())
+        .sum();
+    let next_expire = frozen
+        .iter()
+        .map(|frozen| frozen.get_expire_time())
+        .filter(|&expire_time| expire_time > now)
+        .min();
+    (unfreezable, next_expire)
+}
+
 pub fn main(matches: &ArgMatches) -> Result<(), Error> {
     match matches.subcommand() {
         ("issue", Some(arg_matches)) => issue_asset(arg_matches),
@@ -189,3 +368,83 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset() -> AssetIssueContract {
+        let mut asset = AssetIssueContract::new();
+        asset.set_description(b"old".to_vec());
+        asset.set_url(b"https://example.com".to_vec());
+        asset.set_free_asset_net_limit(1_000);
+        asset
+    }
+
+    fn update_of(asset: &AssetIssueContract) -> UpdateAssetContract {
+        let mut update = UpdateAssetContract::new();
+        update.set_description(asset.get_description().to_owned());
+        update.set_url(asset.get_url().to_owned());
+        update.set_new_limit(asset.get_free_asset_net_limit());
+        update.set_new_public_limit(asset.get_public_free_asset_net_limit());
+        update
+    }
+
+    #[test]
+    fn test_diff_asset_update() {
+        let asset = asset();
+        let mut update = update_of(&asset);
+        assert_eq!(diff_asset_update(&asset, &update), None);
+
+        update.set_description(b"new".to_vec());
+        update.set_new_public_limit(10);
+        assert_eq!(
+            diff_asset_update(&asset, &update).unwrap(),
+            vec![
+                "description: \"old\" -> \"new\"",
+                "url: \"https://example.com\" (unchanged)",
+                "bandwidth-limit-for-issuer: 1000 (unchanged)",
+                "bandwidth-limit-per-account: 0 -> 10",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_asset_update() {
+        let mut update = update_of(&asset());
+        assert!(check_asset_update(&update).is_ok());
+
+        update.set_description(vec![b'x'; MAX_DESCRIPTION_LENGTH]);
+        assert!(check_asset_update(&update).is_ok());
+        update.set_description(vec![b'x'; MAX_DESCRIPTION_LENGTH + 1]);
+        assert!(check_asset_update(&update).is_err());
+
+        let mut update = update_of(&asset());
+        update.set_url(vec![]);
+        assert!(check_asset_update(&update).is_err());
+        update.set_url(vec![b'x'; MAX_URL_LENGTH + 1]);
+        assert!(check_asset_update(&update).is_err());
+
+        let mut update = update_of(&asset());
+        update.set_new_public_limit(ONE_DAY_NET_LIMIT);
+        assert!(check_asset_update(&update).is_err());
+        update.set_new_public_limit(0);
+        update.set_new_limit(-1);
+        assert!(check_asset_update(&update).is_err());
+    }
+
+    #[test]
+    fn test_unfreezable_supply() {
+        let frozen = [(100, 1_000), (200, 2_000), (400, 3_000)]
+            .iter()
+            .map(|&(frozen_balance, expire_time)| Frozen {
+                frozen_balance,
+                expire_time,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(unfreezable_supply(&frozen, 500), (0, Some(1_000)));
+        assert_eq!(unfreezable_supply(&frozen, 2_000), (300, Some(3_000)));
+        assert_eq!(unfreezable_supply(&frozen, 5_000), (700, None));
+    }
+}
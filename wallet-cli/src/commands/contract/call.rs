@@ -1,27 +1,40 @@
 //! Subcommand to call a contract.
+//!
+//! Without `--fee-limit`, the fee limit is suggested from energy used by previous calls of the same method, see
+//! `utils::journal`.
 
 use clap::ArgMatches;
 use futures::executor;
 use hex::{FromHex, ToHex};
 use keys::Address;
+use log::{info, warn};
+use proto::api::EmptyMessage;
 use proto::core::TriggerSmartContract;
 use serde_json::json;
 
 use crate::error::Error;
 use crate::utils::abi;
 use crate::utils::client;
+use crate::utils::journal;
 use crate::utils::jsont;
 use crate::utils::trx;
 
+/// Fee limit without previous calls, in SUN.
+const DEFAULT_FEE_LIMIT: i64 = 5_000_000;
+/// Margin over the p95 energy of previous calls.
+const FEE_LIMIT_SAFETY_FACTOR: f64 = 1.3;
+
 pub fn main(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong sender address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing sender address"))?;
     let contract = matches
         .value_of("CONTRACT")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong contract address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing contract address"))?;
     let method = matches.value_of("METHOD").expect("required in cli.yml; qed");
 
     let data = match (matches.values_of("ARGS"), matches.value_of("data")) {
@@ -35,19 +48,17 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
                 .zip(types.iter())
                 .map(|(arg, &ty)| {
                     if ty == "address" {
-                        arg.parse::<Address>()
-                            .map(|addr| addr.as_tvm_bytes().encode_hex::<String>())
-                            .map_err(Error::from)
+                        trx::parse_address(arg).map(|addr| addr.as_tvm_bytes().encode_hex::<String>())
                     } else {
                         if ty.starts_with("address[") {
-                            eprintln!("! array of address detected, TVM address should be converted by hand");
+                            info!("array of address detected, TVM address should be converted by hand");
                         }
                         Ok(arg.to_owned())
                     }
                 })
                 .collect::<Result<Vec<_>, Error>>()?;
             let mut data = (&abi::fnhash(method)[..]).to_owned();
-            data.append(&mut abi::encode_params(&types, &values)?);
+            data.append(&mut abi::encode_values(&abi::parse_values(&types, &values)?));
             data
         }
         (None, Some(data_hex)) => Vec::from_hex(data_hex)?,
@@ -56,10 +67,11 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
         (_, _) => unreachable!("set conflicts in cli.yml; qed"),
     };
 
-    eprintln!("! Calling {}", method);
+    info!("Calling {}", method);
     if !data.is_empty() {
-        eprintln!("! DATA = {:}", data.encode_hex::<String>());
+        info!("DATA = {:}", data.encode_hex::<String>());
     }
+    let selector = data[..data.len().min(4)].to_vec();
 
     let mut trigger_contract = TriggerSmartContract {
         owner_address: sender.as_bytes().to_owned(),
@@ -100,9 +112,86 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
         Ok(())
     } else {
         let mut handler = trx::TransactionHandler::handle(trigger_contract, matches);
-        handler.map_raw_transaction(|raw| raw.set_fee_limit(5_000_000));
+        // NOTE: --fee-limit always overrides
+        if !matches.is_present("fee-limit") {
+            let fee_limit = suggested_fee_limit(&contract, &selector);
+            handler.map_raw_transaction(move |raw| raw.set_fee_limit(fee_limit));
+        }
         handler.run()?;
-        handler.watch(|info| handle_contract_result(&contract, method, &info.get_contractResult()[0]))
+        handler.watch(|info| {
+            journal::append_energy(&contract, &selector, info.get_receipt().energy_usage_total);
+            handle_contract_result(&contract, method, &info.get_contractResult()[0])
+        })
+    }
+}
+
+/// The 95th percentile, by nearest rank.
+fn percentile_95(values: &[i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    let rank = (sorted.len() * 95 + 99) / 100;
+    Some(sorted[rank - 1])
+}
+
+/// Fee limit in SUN, from energy used by previous calls and energy price in SUN.
+fn fee_limit_of_history(history: &[i64], energy_fee: i64) -> Option<i64> {
+    percentile_95(history).map(|energy| (energy as f64 * energy_fee as f64 * FEE_LIMIT_SAFETY_FACTOR).ceil() as i64)
+}
+
+/// Suggest a fee limit from the energy history. Falls back to the default when there is no history or lookups fail.
+fn suggested_fee_limit(contract: &Address, selector: &[u8]) -> i64 {
+    fee_limit_from_history(contract, selector).unwrap_or_else(|| {
+        info!(
+            "Fee limit: {} (default)",
+            trx::format_amount_with_surfix(DEFAULT_FEE_LIMIT, "TRX", 6)
+        );
+        DEFAULT_FEE_LIMIT
+    })
+}
+
+/// Fee limit from energy used by previous calls of the method, `None` when there is no history or lookups fail.
+pub fn fee_limit_from_history(contract: &Address, selector: &[u8]) -> Option<i64> {
+    let records = match journal::energy_path().map(|path| journal::load(&path)).transpose() {
+        Ok(records) => records.unwrap_or_default(),
+        Err(e) => {
+            warn!("Can not load energy history: {}", e);
+            vec![]
+        }
+    };
+    let history = journal::energy_history(&records, contract, selector);
+    if history.is_empty() {
+        info!("No previous calls to suggest a fee limit from");
+        return None;
+    }
+
+    let params = match executor::block_on(
+        client::GRPC_CLIENT
+            .get_chain_parameters(Default::default(), EmptyMessage::new())
+            .drop_metadata(),
+    ) {
+        Ok(params) => params,
+        Err(e) => {
+            warn!("Can not get energy price: {}", e);
+            return None;
+        }
+    };
+    match trx::chain_parameter(&params, "getEnergyFee", 0) {
+        energy_fee if energy_fee > 0 => {
+            let fee_limit = fee_limit_of_history(&history, energy_fee).expect("history is not empty; qed");
+            info!(
+                "Fee limit: {} (based on {} previous calls)",
+                trx::format_amount_with_surfix(fee_limit, "TRX", 6),
+                history.len()
+            );
+            Some(fee_limit)
+        }
+        _ => {
+            warn!("Energy price is not reported by the node");
+            None
+        }
     }
 }
 
@@ -115,22 +204,40 @@ fn extract_types(fnname: &str) -> Result<Vec<&str>, Error> {
 
 fn handle_contract_result(contract: &Address, method: &str, result: &[u8]) -> Result<(), Error> {
     let abi = trx::get_contract_abi(contract)?;
-    abi.iter()
-        .find(|entry| abi::entry_to_method_name(entry) == method)
-        .ok_or(Error::Runtime("ABI not found, can not parse result"))
-        .and_then(|entry| {
-            let types = abi::entry_to_output_types(&entry);
-            let output = abi::decode_params(&types, &result.encode_hex::<String>())?;
-            if !types.is_empty() {
-                eprintln!("! Parsed result:");
-                for (ty, param) in types.iter().zip(output.iter()) {
-                    if param.len() >= 74 {
-                        eprintln!("  {}:\n{}", ty, param);
-                    } else {
-                        eprintln!("  {}: {}", ty, param);
-                    }
-                }
+    // the call itself succeeded, a contract without (this method in) its ABI only leaves the result unparsed
+    let entry = match abi::find_entry_by_selector(&abi, &abi::fnhash(method)) {
+        Some(entry) => entry,
+        None => {
+            info!("(ABI not found, can not parse result)");
+            return Ok(());
+        }
+    };
+    let types = abi::entry_to_output_types(&entry);
+    let output = abi::decode_params(&types, &result.encode_hex::<String>())?;
+    if !types.is_empty() {
+        info!("Parsed result:");
+        for (ty, param) in types.iter().zip(output.iter().map(ToString::to_string)) {
+            if param.len() >= 74 {
+                info!("  {}:\n{}", ty, param);
+            } else {
+                info!("  {}: {}", ty, param);
             }
-            Ok(())
-        })
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_limit_of_history() {
+        assert_eq!(percentile_95(&[]), None);
+        assert_eq!(percentile_95(&[10]), Some(10));
+        let history = (1..=20).rev().map(|i| i * 1_000).collect::<Vec<_>>();
+        assert_eq!(percentile_95(&history), Some(19_000));
+        assert_eq!(fee_limit_of_history(&history, 140), Some(3_458_000));
+        assert_eq!(fee_limit_of_history(&[], 140), None);
+    }
 }
@@ -1,6 +1,7 @@
 use clap::ArgMatches;
 use hex::{FromHex, ToHex};
 use keys::Address;
+use log::warn;
 use proto::core::{
     CreateSmartContract, SmartContract, SmartContract_ABI as Abi, SmartContract_ABI_Entry_EntryType as AbiEntryType,
 };
@@ -14,11 +15,11 @@ use crate::utils::trx;
 
 pub fn main(matches: &ArgMatches) -> Result<(), Error> {
     if matches.is_present("libraries") {
-        eprintln!("For now, library addresses should be filled by hand.");
+        warn!("For now, library addresses should be filled by hand.");
         return Err(Error::Runtime("--libraries unimplemented"));
     }
 
-    let owner_address: Address = matches.value_of("OWNER").expect("required in cli.yml; qed").parse()?;
+    let owner_address = trx::parse_address(matches.value_of("OWNER").expect("required in cli.yml; qed"))?;
     let abi = load_abi_from_param(matches.value_of("abi").expect("has default in cli.yml; qed"))?;
     let mut bytecode: Vec<u8> = load_code_from_param(matches.value_of("code").expect("required in cli.yml; qed"))?;
 
@@ -41,9 +42,7 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
                 .zip(types.iter())
                 .map(|(arg, ty)| {
                     if ty == &"address" {
-                        arg.parse::<Address>()
-                            .map(|addr| addr.as_tvm_bytes().encode_hex::<String>())
-                            .map_err(Error::from)
+                        trx::parse_address(arg).map(|addr| addr.as_tvm_bytes().encode_hex::<String>())
                     } else {
                         Ok(arg.to_owned())
                     }
@@ -107,13 +106,13 @@ fn load_abi_from_param(param: &str) -> Result<Abi, Error> {
     match param {
         fname if Path::new(fname).exists() => {
             let raw_json = fs::read_to_string(Path::new(fname))?;
-            Ok(abi::json_to_abi(&serde_json::from_str(&raw_json)?))
+            abi::json_to_abi(&serde_json::from_str(&raw_json)?)
         }
         fname if fname.starts_with('@') => {
             let raw_json = fs::read_to_string(Path::new(&fname[1..]))?;
-            Ok(abi::json_to_abi(&serde_json::from_str(&raw_json)?))
+            abi::json_to_abi(&serde_json::from_str(&raw_json)?)
         }
-        raw_json if raw_json.trim_start().starts_with("[") => Ok(abi::json_to_abi(&serde_json::from_str(&raw_json)?)),
+        raw_json if raw_json.trim_start().starts_with("[") => abi::json_to_abi(&serde_json::from_str(&raw_json)?),
         _ => Err(Error::Runtime("can not determine ABI format")),
     }
 }
@@ -1,19 +1,24 @@
 use clap::ArgMatches;
-use keys::Address;
-use proto::core::{ClearABIContract, UpdateEnergyLimitContract, UpdateSettingContract};
+use log::info;
+use proto::core::{ClearABIContract, SmartContract_ABI as Abi, UpdateEnergyLimitContract, UpdateSettingContract};
+use std::fs;
 
 use crate::error::Error;
+use crate::utils::abi;
 use crate::utils::trx;
 
-mod call;
+pub mod call;
 mod create;
+mod predict;
+mod verify;
 
 pub fn update_contract_settings(matches: &ArgMatches) -> Result<(), Error> {
-    let owner_address: Address = matches.value_of("OWNER").expect("required in cli.yml; qed").parse()?;
+    let owner_address = trx::parse_address(matches.value_of("OWNER").expect("required in cli.yml; qed"))?;
     let contract = matches
         .value_of("CONTRACT")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong contract address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing contract address"))?;
 
     match (
         matches.value_of("user-resource-percent"),
@@ -44,11 +49,12 @@ pub fn update_contract_settings(matches: &ArgMatches) -> Result<(), Error> {
 }
 
 pub fn clear_contract_abi(matches: &ArgMatches) -> Result<(), Error> {
-    let owner_address: Address = matches.value_of("OWNER").expect("required in cli.yml; qed").parse()?;
+    let owner_address = trx::parse_address(matches.value_of("OWNER").expect("required in cli.yml; qed"))?;
     let contract = matches
         .value_of("CONTRACT")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong contract address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing contract address"))?;
 
     let clear_contract = ClearABIContract {
         owner_address: owner_address.as_bytes().to_owned(),
@@ -58,12 +64,37 @@ pub fn clear_contract_abi(matches: &ArgMatches) -> Result<(), Error> {
     trx::TransactionHandler::handle(clear_contract, matches).run()
 }
 
+pub fn get_contract_abi_json(matches: &ArgMatches) -> Result<(), Error> {
+    let contract = trx::parse_address(matches.value_of("CONTRACT").expect("required in cli.yml; qed"))?;
+
+    let abi = Abi {
+        entrys: trx::get_contract_abi(&contract)?.into(),
+        ..Default::default()
+    };
+    if abi.get_entrys().is_empty() {
+        return Err(Error::Runtime("contract has no ABI on chain"));
+    }
+    let json = serde_json::to_string_pretty(&abi::abi_to_json(&abi))?;
+
+    match matches.value_of("out") {
+        Some(path) => {
+            fs::write(path, json)?;
+            info!("ABI of {} written to {}", contract, path);
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
 pub fn main(matches: &ArgMatches) -> Result<(), Error> {
     match matches.subcommand() {
         ("create", Some(arg_matches)) => create::main(arg_matches),
         ("call", Some(arg_matches)) => call::main(arg_matches),
         ("update", Some(arg_matches)) => update_contract_settings(arg_matches),
         ("clear_abi", Some(arg_matches)) => clear_contract_abi(arg_matches),
+        ("abi", Some(arg_matches)) => get_contract_abi_json(arg_matches),
+        ("predict_address", Some(arg_matches)) => predict::main(arg_matches),
+        ("verify", Some(arg_matches)) => verify::main(arg_matches),
         _ => {
             eprintln!("{}", matches.usage());
             Err(Error::Runtime("error parsing command line"))
@@ -0,0 +1,105 @@
+//! Predict address of a contract before deployment.
+//!
+//! - CREATE, i.e. a `CreateSmartContract` transaction: `keccak256(txid ++ owner_address)`
+//! - CREATE2, from a deployed factory contract: `keccak256(factory_address ++ salt ++ keccak256(init_code))`
+//!
+//! Addresses in hash input are 21 bytes, with the `0x41` prefix. The last 20 bytes of the hash, prefixed with
+//! `0x41`, is the contract address.
+
+use clap::ArgMatches;
+use hex::FromHex;
+use keys::Address;
+use log::info;
+use std::convert::TryFrom;
+
+use crate::error::Error;
+use crate::utils::crypto;
+use crate::utils::trx;
+
+fn address_of_hash(hash: &[u8; 32]) -> Address {
+    let mut raw = [0x41; 21];
+    raw[1..].copy_from_slice(&hash[12..]);
+    Address::try_from(&raw[..]).expect("21 bytes with 0x41 prefix; qed")
+}
+
+/// Contract address created by a `CreateSmartContract` transaction.
+pub fn create_address(owner: &Address, txid: &[u8; 32]) -> Address {
+    let mut data = txid.to_vec();
+    data.extend_from_slice(owner.as_bytes());
+    address_of_hash(&crypto::keccak256(&data))
+}
+
+/// Contract address created by the CREATE2 opcode of a factory contract.
+pub fn create2_address(factory: &Address, salt: &[u8; 32], init_code: &[u8]) -> Address {
+    let mut data = factory.as_bytes().to_vec();
+    data.extend_from_slice(salt);
+    data.extend_from_slice(&crypto::keccak256(init_code));
+    address_of_hash(&crypto::keccak256(&data))
+}
+
+/// Parse salt as hex, left-padded to 32 bytes.
+fn parse_salt(salt: &str) -> Result<[u8; 32], Error> {
+    let raw = Vec::from_hex(salt.trim_start_matches("0x"))?;
+    if raw.len() > 32 {
+        return Err(Error::Runtime("salt must be at most 32 bytes"));
+    }
+    let mut ret = [0u8; 32];
+    ret[32 - raw.len()..].copy_from_slice(&raw);
+    Ok(ret)
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let owner = trx::parse_address(matches.value_of("OWNER").expect("required in cli.yml; qed"))?;
+
+    let addr = match (matches.value_of("txid"), matches.value_of("salt")) {
+        (Some(txid), None) => {
+            let txid = <[u8; 32]>::from_hex(txid)?;
+            create_address(&owner, &txid)
+        }
+        (None, Some(salt)) => {
+            let code = matches
+                .value_of("code")
+                .ok_or(Error::Runtime("--code is required for CREATE2"))?;
+            let code = Vec::from_hex(code.trim_start_matches("0x"))?;
+            create2_address(&owner, &parse_salt(salt)?, &code)
+        }
+        _ => return Err(Error::Runtime("one of --txid or --salt required")),
+    };
+
+    println!("{}", addr);
+    info!("Contract Address(hex) = {}", hex::encode(addr.as_bytes()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_address() {
+        // the deployment of the example in docs/contract.md, address from `get transaction_info` of the txid
+        let owner: Address = "TGQgfK497YXmjdgvun9Bg5Zu3xE15v17cu".parse().unwrap();
+        let txid = <[u8; 32]>::from_hex("d8228648cb275bb548da9a8b2d11beb956275413774015a63e390de85bc1fb57").unwrap();
+        assert_eq!(
+            hex::encode(create_address(&owner, &txid).as_bytes()),
+            "4123ff5e2eac2926b5ed72948eb9e69a07f24b49ba"
+        );
+    }
+
+    #[test]
+    fn test_create2_address() {
+        // NOTE: not from a deployment, nor computed by java-tron or TronWeb. Computed outside this code, with the
+        // layout of java-tron's `generateContractAddress2` and a keccak256 checked against the EIP-1014 examples.
+        // Replace with the address of a real CREATE2 deployment.
+        let factory: Address = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap();
+        let code = Vec::from_hex(
+            "6080604052348015600f57600080fd5b50603f80601d6000396000f3fe6080604052600080fdfea164736f6c6343000607000a",
+        )
+        .unwrap();
+        assert_eq!(
+            create2_address(&factory, &parse_salt("01").unwrap(), &code).to_string(),
+            "TJNxRFb2o2n9yMAE9Kr7MGazRwLQwN1A7D"
+        );
+        assert!(parse_salt(&"00".repeat(33)).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Verify deployed bytecode against a local compiled artifact.
+//!
+//! The node reports `SmartContract.bytecode` as submitted by `CreateSmartContract`, the creation code with the
+//! ABI-encoded constructor arguments appended. It is compared with the creation code of a solc `.bin` artifact:
+//!
+//! - Bytes after the artifact are the constructor arguments.
+//! - The CBOR metadata solc appends, whose length is the last 2 bytes of the code, is allowed to differ. It changes
+//!   with source paths and comments, not with the code.
+//!
+//! Immutables are written into the runtime code by the constructor, they are not part of the creation code.
+
+use clap::ArgMatches;
+use hex::FromHex;
+use log::{info, warn};
+use std::fs;
+use std::ops::Range;
+
+use crate::error::Error;
+use crate::utils::crypto;
+use crate::utils::trx;
+
+#[derive(Debug, PartialEq)]
+enum Verification {
+    Match {
+        constructor_args: Vec<u8>,
+        metadata_differs: bool,
+    },
+    /// Offset of the first differing byte, or the end of the deployed code when it is shorter.
+    Mismatch { offset: usize },
+}
+
+/// Range of the trailing solc metadata, a CBOR map followed by its 2-byte length.
+fn metadata_range(code: &[u8]) -> Option<Range<usize>> {
+    if code.len() < 2 {
+        return None;
+    }
+    let len = u16::from_be_bytes([code[code.len() - 2], code[code.len() - 1]]) as usize;
+    let start = code.len().checked_sub(len + 2)?;
+    match code[start] {
+        // map of 1 to 5 entries, like `{"ipfs": ..., "solc": ...}`
+        0xa1..=0xa5 => Some(start..code.len()),
+        _ => None,
+    }
+}
+
+fn verify_bytecode(deployed: &[u8], artifact: &[u8]) -> Verification {
+    let metadata = metadata_range(artifact);
+    let mut metadata_differs = false;
+    for (offset, &byte) in artifact.iter().enumerate() {
+        match deployed.get(offset) {
+            Some(&deployed_byte) if deployed_byte == byte => (),
+            Some(_) if metadata.as_ref().map_or(false, |range| range.contains(&offset)) => metadata_differs = true,
+            _ => return Verification::Mismatch { offset },
+        }
+    }
+    Verification::Match {
+        constructor_args: deployed[artifact.len()..].to_vec(),
+        metadata_differs,
+    }
+}
+
+/// Parse a `.bin` artifact, hex with optional `0x`.
+fn parse_artifact(content: &str) -> Result<Vec<u8>, Error> {
+    let content = content.trim();
+    if content.contains("__") {
+        return Err(Error::Runtime(
+            "artifact has unlinked library placeholders, link it first",
+        ));
+    }
+    Ok(Vec::from_hex(content.trim_start_matches("0x"))?)
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let contract = trx::parse_address(matches.value_of("CONTRACT").expect("required in cli.yml; qed"))?;
+    let path = matches.value_of("bin").expect("required in cli.yml; qed");
+    let artifact = parse_artifact(&fs::read_to_string(path)?)?;
+
+    let payload = trx::get_smart_contract(&contract)?;
+    if payload.get_contract_address().is_empty() {
+        return Err(Error::NotFound("contract not found on chain"));
+    }
+    let deployed = payload.get_bytecode();
+    if deployed.is_empty() {
+        return Err(Error::Runtime(
+            "no bytecode on chain, contracts created by other contracts can not be verified",
+        ));
+    }
+    info!("Deployed code keccak256: {}", hex::encode(crypto::keccak256(deployed)));
+    info!("Artifact code keccak256: {}", hex::encode(crypto::keccak256(&artifact)));
+
+    match verify_bytecode(deployed, &artifact) {
+        Verification::Match {
+            constructor_args,
+            metadata_differs,
+        } => {
+            info!("Bytecode of {} matches {}", contract, path);
+            if metadata_differs {
+                warn!("Metadata differs, compiled from other source paths or comments");
+            }
+            if !constructor_args.is_empty() {
+                info!("Constructor arguments: {} bytes", constructor_args.len());
+                for word in constructor_args.chunks(32) {
+                    println!("{}", hex::encode(word));
+                }
+            }
+            Ok(())
+        }
+        Verification::Mismatch { offset } => {
+            warn!(
+                "Bytecode differs at offset {}, deployed {} bytes, artifact {} bytes",
+                offset,
+                deployed.len(),
+                artifact.len()
+            );
+            Err(Error::Mismatch("bytecode does not match the artifact"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Code with metadata `{"solc": <3 bytes>}`, like solc 0.5+.
+    fn code_with_metadata(code: &[u8], solc: [u8; 3]) -> Vec<u8> {
+        let mut ret = code.to_vec();
+        let metadata = [&b"\xa1\x64solc\x43"[..], &solc[..]].concat();
+        ret.extend_from_slice(&metadata);
+        ret.extend_from_slice(&(metadata.len() as u16).to_be_bytes());
+        ret
+    }
+
+    #[test]
+    fn test_verify_bytecode() {
+        let artifact = code_with_metadata(&[0x60, 0x80, 0x60, 0x40, 0x52], [0, 5, 17]);
+        assert_eq!(metadata_range(&artifact), Some(5..artifact.len()));
+
+        let args = [0u8; 32].to_vec();
+        let deployed = [artifact.clone(), args.clone()].concat();
+        assert_eq!(
+            verify_bytecode(&deployed, &artifact),
+            Verification::Match {
+                constructor_args: args,
+                metadata_differs: false,
+            }
+        );
+
+        let other_metadata = code_with_metadata(&[0x60, 0x80, 0x60, 0x40, 0x52], [0, 5, 16]);
+        assert_eq!(
+            verify_bytecode(&other_metadata, &artifact),
+            Verification::Match {
+                constructor_args: vec![],
+                metadata_differs: true,
+            }
+        );
+
+        let other_code = code_with_metadata(&[0x60, 0x80, 0x60, 0x60, 0x52], [0, 5, 17]);
+        assert_eq!(
+            verify_bytecode(&other_code, &artifact),
+            Verification::Mismatch { offset: 3 }
+        );
+        assert_eq!(
+            verify_bytecode(&artifact[..4], &artifact),
+            Verification::Mismatch { offset: 4 }
+        );
+    }
+
+    #[test]
+    fn test_parse_artifact() {
+        assert_eq!(parse_artifact("0x6080\n").unwrap(), vec![0x60, 0x80]);
+        assert_eq!(parse_artifact("6080").unwrap(), vec![0x60, 0x80]);
+        assert!(parse_artifact("6080__$1234$__").is_err());
+        assert!(parse_artifact("608").is_err());
+    }
+}
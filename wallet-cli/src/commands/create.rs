@@ -42,7 +42,7 @@ pub fn create_zkey(matches: &ArgMatches) -> Result<(), Error> {
     // pkD: the public key of the address, g_d^ivk
     // pkD + d => z-addr
     for key in &["sk", "ask", "nsk", "ovk", "ak", "nk", "ivk", "d", "pkD"] {
-        addr_info[key] = json!(jsont::bytes_to_hex_string(&addr_info[key]));
+        jsont::fix_hex_field(&mut addr_info, &[*key]);
     }
     println!("{}", serde_json::to_string_pretty(&addr_info)?);
     Ok(())
@@ -0,0 +1,364 @@
+//! Diff of an account between two blocks.
+//!
+//! Nodes only serve the current account state, there is no historical state query in the gRPC API. So the
+//! delta is computed locally, by scanning blocks in range and replaying transactions that touched the account.
+//!
+//! Replayed: TRX transfers, TRC10 transfers, call values of contract calls, freeze/unfreeze, reward withdrawal,
+//! votes and transaction fees. Internal transactions of smart contracts are not replayed.
+
+use clap::ArgMatches;
+use keys::Address;
+use log::info;
+use proto::api::BlockExtention;
+use proto::core::{
+    FreezeBalanceContract, ResourceCode, Transaction, TransactionInfo, TransactionInfo_code as TransactionInfoCode,
+    Transaction_Result_code as TransactionResultCode, Transaction_Result_contractResult as ContractResult,
+    TransferAssetContract, TransferContract, TriggerSmartContract, UnfreezeBalanceContract, VoteWitnessContract,
+};
+use protobuf::parse_from_bytes;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+
+use crate::error::Error;
+use crate::utils::block::BlockBatches;
+use crate::utils::client;
+use crate::utils::trx;
+
+#[derive(Debug, Default)]
+struct AccountDelta {
+    balance: i64,
+    assets: BTreeMap<String, i64>,
+    frozen_bandwidth: i64,
+    frozen_energy: i64,
+    fee: i64,
+    /// Votes are overwritten, not accumulated. Last vote in range.
+    votes: Option<Vec<(Address, i64)>>,
+    transactions: Vec<String>,
+}
+
+impl AccountDelta {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "balance": self.balance,
+            "assets": self.assets,
+            "frozen": {
+                "bandwidth": self.frozen_bandwidth,
+                "energy": self.frozen_energy,
+            },
+            "fee": self.fee,
+            "votes": self.votes.as_ref().map(|votes| {
+                votes
+                    .iter()
+                    .map(|(addr, count)| json!({ "vote_address": addr.to_string(), "vote_count": count }))
+                    .collect::<Vec<_>>()
+            }),
+            "transactions": self.transactions,
+        })
+    }
+}
+
+fn is_addr(raw: &[u8], addr: &Address) -> bool {
+    raw == addr.as_bytes()
+}
+
+/// Did the transaction succeed. Failed transactions are in blocks and pay fees, but move no balances.
+///
+/// Old blocks leave `contractRet` of system contracts as `DEFAULT`, which is a success.
+fn is_successful(txn: &Transaction, info: Option<&TransactionInfo>) -> bool {
+    let is_ok = |ret| matches!(ret, ContractResult::DEFAULT | ContractResult::SUCCESS);
+    let ret_ok = txn.get_ret().get(0).map_or(true, |ret| {
+        ret.get_ret() == TransactionResultCode::SUCESS && is_ok(ret.get_contractRet())
+    });
+    let info_ok = info.map_or(true, |info| {
+        info.get_result() == TransactionInfoCode::SUCESS && is_ok(info.get_receipt().get_result())
+    });
+    ret_ok && info_ok
+}
+
+/// Replay a transaction on the delta, with its info from the block. Returns true if the transaction touched the
+/// account.
+fn replay_transaction(
+    txn: &Transaction,
+    info: Option<&TransactionInfo>,
+    addr: &Address,
+    delta: &mut AccountDelta,
+) -> Result<bool, Error> {
+    let contract = match txn.get_raw_data().get_contract().get(0) {
+        Some(contract) => contract,
+        None => return Ok(false),
+    };
+    let param = contract.get_parameter();
+    let is_owner = trx::extract_owner_address_from_parameter(param)
+        .map(|owner| owner == *addr)
+        .unwrap_or(false);
+    // a failed transaction still touches the account, by its fee
+    let is_successful = is_successful(txn, info);
+
+    let touched = match param.get_type_url() {
+        "type.googleapis.com/protocol.TransferContract" => {
+            let pb = parse_from_bytes::<TransferContract>(param.get_value())?;
+            if is_successful && is_addr(pb.get_to_address(), addr) {
+                delta.balance += pb.amount;
+            }
+            if is_successful && is_owner {
+                delta.balance -= pb.amount;
+            }
+            is_owner || is_addr(pb.get_to_address(), addr)
+        }
+        "type.googleapis.com/protocol.TransferAssetContract" => {
+            let pb = parse_from_bytes::<TransferAssetContract>(param.get_value())?;
+            let asset = String::from_utf8_lossy(pb.get_asset_name()).into_owned();
+            if is_successful && is_addr(pb.get_to_address(), addr) {
+                *delta.assets.entry(asset.clone()).or_default() += pb.amount;
+            }
+            if is_successful && is_owner {
+                *delta.assets.entry(asset).or_default() -= pb.amount;
+            }
+            is_owner || is_addr(pb.get_to_address(), addr)
+        }
+        "type.googleapis.com/protocol.TriggerSmartContract" => {
+            let pb = parse_from_bytes::<TriggerSmartContract>(param.get_value())?;
+            if is_successful && is_addr(pb.get_contract_address(), addr) {
+                delta.balance += pb.call_value;
+            }
+            if is_successful && is_owner {
+                delta.balance -= pb.call_value;
+            }
+            is_owner || is_addr(pb.get_contract_address(), addr)
+        }
+        "type.googleapis.com/protocol.FreezeBalanceContract" if is_owner && is_successful => {
+            let pb = parse_from_bytes::<FreezeBalanceContract>(param.get_value())?;
+            delta.balance -= pb.frozen_balance;
+            match pb.get_resource() {
+                ResourceCode::BANDWIDTH => delta.frozen_bandwidth += pb.frozen_balance,
+                ResourceCode::ENERGY => delta.frozen_energy += pb.frozen_balance,
+            }
+            true
+        }
+        "type.googleapis.com/protocol.UnfreezeBalanceContract" if is_owner && is_successful => {
+            let pb = parse_from_bytes::<UnfreezeBalanceContract>(param.get_value())?;
+            let amount = info.map(|info| info.unfreeze_amount).unwrap_or_default();
+            delta.balance += amount;
+            match pb.get_resource() {
+                ResourceCode::BANDWIDTH => delta.frozen_bandwidth -= amount,
+                ResourceCode::ENERGY => delta.frozen_energy -= amount,
+            }
+            true
+        }
+        "type.googleapis.com/protocol.WithdrawBalanceContract" if is_owner && is_successful => {
+            delta.balance += info.map(|info| info.withdraw_amount).unwrap_or_default();
+            true
+        }
+        "type.googleapis.com/protocol.VoteWitnessContract" if is_owner && is_successful => {
+            let pb = parse_from_bytes::<VoteWitnessContract>(param.get_value())?;
+            delta.votes = Some(
+                pb.get_votes()
+                    .iter()
+                    .map(|vote| Ok((Address::try_from(vote.get_vote_address())?, vote.get_vote_count())))
+                    .collect::<Result<Vec<_>, Error>>()?,
+            );
+            true
+        }
+        _ => is_owner,
+    };
+
+    if touched && is_owner {
+        // fee is always paid by the owner
+        let fee = info.map(|info| info.fee).unwrap_or_default();
+        delta.fee += fee;
+        delta.balance -= fee;
+    }
+    Ok(touched)
+}
+
+/// Infos of transactions of a block by id, fetched once per block. Empty blocks need no request.
+fn transaction_infos_of(block: &BlockExtention) -> Result<HashMap<Vec<u8>, TransactionInfo>, Error> {
+    if block.get_transactions().is_empty() {
+        return Ok(HashMap::new());
+    }
+    let infos = client::get_transaction_info_by_block_num(block.get_block_header().get_raw_data().number)?;
+    Ok(infos.into_iter().map(|info| (info.get_id().to_vec(), info)).collect())
+}
+
+/// A TRX amount with its sign, like `+1.000000` or `-0.267000`.
+fn format_signed_trx(amount: i64) -> String {
+    if amount > 0 {
+        format!("+{}", trx::format_trx(amount))
+    } else {
+        trx::format_trx(amount)
+    }
+}
+
+fn account_diff(addr: &Address, from_block: i64, to_block: i64, verify: bool) -> Result<(), Error> {
+    if from_block >= to_block {
+        return Err(Error::Runtime("--and-block must be greater than --at-block"));
+    }
+    info!("Historical account state is not available from nodes, replaying blocks in range");
+
+    let mut delta = AccountDelta::default();
+    // state after `from_block`, so replay from the next block
+    for batch in BlockBatches::new(from_block + 1, to_block).verify(verify) {
+        let (last, blocks) = batch?;
+        for block in &blocks {
+            let block_num = block.get_block_header().get_raw_data().number;
+            let infos = transaction_infos_of(block)?;
+            for txn_ext in block.get_transactions() {
+                let txn = txn_ext.get_transaction();
+                let txid = trx::transaction_hash(txn.get_raw_data())?;
+                if replay_transaction(txn, infos.get(&txid[..]), addr, &mut delta)? {
+                    let txid = hex::encode(txid);
+                    info!(
+                        "#{} {} {}",
+                        block_num,
+                        txid,
+                        trx::format_contract_type(txn.get_raw_data().get_contract()[0].get_field_type())
+                    );
+                    delta.transactions.push(txid);
+                }
+            }
+        }
+        info!(
+            "Scanned to block #{} ({}%)",
+            last,
+            (last - from_block) * 100 / (to_block - from_block)
+        );
+    }
+
+    let ret = json!({
+        "address": addr.to_string(),
+        "at_block": from_block,
+        "and_block": to_block,
+        "delta": delta.to_json(),
+    });
+    println!("{}", serde_json::to_string_pretty(&ret)?);
+
+    info!("Balance: {} TRX", format_signed_trx(delta.balance));
+    info!(
+        "Frozen: {} TRX for bandwidth, {} TRX for energy",
+        format_signed_trx(delta.frozen_bandwidth),
+        format_signed_trx(delta.frozen_energy)
+    );
+    info!("Transactions: {}", delta.transactions.len());
+    Ok(())
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let addr = trx::parse_address(matches.value_of("ADDRESS").expect("required in cli.yml; qed"))?;
+    let from_block = matches
+        .value_of("at-block")
+        .expect("required in cli.yml; qed")
+        .parse()?;
+    let to_block = matches
+        .value_of("and-block")
+        .expect("required in cli.yml; qed")
+        .parse()?;
+    account_diff(&addr, from_block, to_block, matches.is_present("verify"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::trx::ContractPbExt;
+    use proto::core::Transaction_Result as TransactionResult;
+
+    fn addr(i: u8) -> Address {
+        Address::from_tvm_bytes(&[i; 20])
+    }
+
+    fn txn_of<T: ContractPbExt>(pb: &T, result: ContractResult) -> Transaction {
+        let mut txn = trx::test_transaction_of(pb);
+        let mut ret = TransactionResult::new();
+        ret.set_contractRet(result);
+        txn.mut_ret().push(ret);
+        txn
+    }
+
+    fn transfer(from: u8, to: u8, amount: i64, result: ContractResult) -> Transaction {
+        let mut transfer = TransferContract::new();
+        transfer.set_owner_address(addr(from).as_bytes().to_vec());
+        transfer.set_to_address(addr(to).as_bytes().to_vec());
+        transfer.set_amount(amount);
+        txn_of(&transfer, result)
+    }
+
+    fn trigger(caller: u8, contract: u8, call_value: i64, result: ContractResult) -> Transaction {
+        let mut trigger = TriggerSmartContract::new();
+        trigger.set_owner_address(addr(caller).as_bytes().to_vec());
+        trigger.set_contract_address(addr(contract).as_bytes().to_vec());
+        trigger.set_call_value(call_value);
+        txn_of(&trigger, result)
+    }
+
+    fn info_of(fee: i64, result: ContractResult) -> TransactionInfo {
+        let mut info = TransactionInfo::new();
+        info.set_fee(fee);
+        if !matches!(result, ContractResult::DEFAULT | ContractResult::SUCCESS) {
+            info.set_result(TransactionInfoCode::FAILED);
+        }
+        info.mut_receipt().set_result(result);
+        info
+    }
+
+    #[test]
+    fn test_replay_transaction() {
+        let me = addr(1);
+        let mut delta = AccountDelta::default();
+
+        let received = transfer(2, 1, 5_000_000, ContractResult::SUCCESS);
+        assert!(replay_transaction(&received, None, &me, &mut delta).unwrap());
+        let sent = transfer(1, 2, 1_000_000, ContractResult::SUCCESS);
+        let info = info_of(100_000, ContractResult::DEFAULT);
+        assert!(replay_transaction(&sent, Some(&info), &me, &mut delta).unwrap());
+        assert_eq!(delta.balance, 5_000_000 - 1_000_000 - 100_000);
+
+        let called = trigger(1, 9, 2_000_000, ContractResult::SUCCESS);
+        let info = info_of(300_000, ContractResult::SUCCESS);
+        assert!(replay_transaction(&called, Some(&info), &me, &mut delta).unwrap());
+        assert_eq!(delta.balance, 5_000_000 - 1_000_000 - 100_000 - 2_000_000 - 300_000);
+        assert_eq!(delta.fee, 400_000);
+
+        // not touching the account
+        let other = transfer(2, 3, 1_000_000, ContractResult::SUCCESS);
+        assert!(!replay_transaction(&other, None, &me, &mut delta).unwrap());
+        assert_eq!(delta.balance, 1_600_000);
+    }
+
+    #[test]
+    fn test_replay_failed_transaction() {
+        let me = addr(1);
+        let mut delta = AccountDelta::default();
+
+        // a failed transfer moves no balance, the sender pays the fee
+        let mut failed = transfer(1, 2, 1_000_000, ContractResult::DEFAULT);
+        failed.mut_ret()[0].set_ret(TransactionResultCode::FAILED);
+        let info = info_of(100_000, ContractResult::DEFAULT);
+        assert!(replay_transaction(&failed, Some(&info), &me, &mut delta).unwrap());
+        assert_eq!(delta.balance, -100_000);
+        let mut received = AccountDelta::default();
+        assert!(replay_transaction(&failed, Some(&info), &addr(2), &mut received).unwrap());
+        assert_eq!(received.balance, 0);
+
+        // a reverted trigger returns its call value, the caller pays the energy burnt
+        let reverted = trigger(1, 9, 2_000_000, ContractResult::REVERT);
+        let info = info_of(1_500_000, ContractResult::REVERT);
+        assert!(replay_transaction(&reverted, Some(&info), &me, &mut delta).unwrap());
+        assert_eq!(delta.balance, -1_600_000);
+        assert_eq!(delta.fee, 1_600_000);
+
+        // the receipt fails even when the transaction result is missing
+        let mut out_of_energy = trigger(1, 9, 2_000_000, ContractResult::SUCCESS);
+        out_of_energy.mut_ret().clear();
+        let info = info_of(0, ContractResult::OUT_OF_ENERGY);
+        let mut contract = AccountDelta::default();
+        assert!(replay_transaction(&out_of_energy, Some(&info), &addr(9), &mut contract).unwrap());
+        assert_eq!(contract.balance, 0);
+    }
+
+    #[test]
+    fn test_format_signed_trx() {
+        assert_eq!(format_signed_trx(1_000_000), "+1.000000");
+        assert_eq!(format_signed_trx(-267_000), "-0.267000");
+        assert_eq!(format_signed_trx(0), "0.000000");
+    }
+}
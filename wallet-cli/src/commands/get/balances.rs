@@ -0,0 +1,379 @@
+//! Balances of a list of addresses.
+
+use chrono::Utc;
+use clap::ArgMatches;
+use futures::executor;
+use futures::future;
+use futures::stream::{self, StreamExt};
+use keys::Address;
+use log::{info, warn};
+use proto::core::Account;
+use serde_json::json;
+use std::fs;
+
+use crate::error::Error;
+use crate::utils::client;
+use crate::utils::price::{self, FiatRate};
+use crate::utils::trc20::{self, TokenMeta};
+use crate::utils::trx;
+
+/// Balance of one address. Accounts not on chain are inactive, with zero balances.
+#[derive(Debug, Clone, PartialEq)]
+struct BalanceRow {
+    address: Address,
+    active: bool,
+    /// TRX balance in sun.
+    balance: i64,
+    /// TRC20 balance in token units, when `--trc20` is given.
+    token_balance: Option<ethabi::Uint>,
+}
+
+/// The TRC20 token to check, with its metadata.
+struct Token {
+    address: Address,
+    meta: TokenMeta,
+}
+
+impl Token {
+    fn symbol(&self) -> &str {
+        self.meta.symbol.as_deref().unwrap_or("TOKEN")
+    }
+
+    fn format(&self, amount: &ethabi::Uint) -> String {
+        trc20::format_units(amount, self.meta.decimals.unwrap_or(0))
+    }
+}
+
+/// Exact totals, TRX in sun and TRC20 in token units. The TRX supply fits `i64` many times over.
+struct Totals {
+    balance: i64,
+    token_balance: ethabi::Uint,
+    inactive: usize,
+}
+
+fn totals(rows: &[BalanceRow]) -> Totals {
+    rows.iter().fold(
+        Totals {
+            balance: 0,
+            token_balance: 0.into(),
+            inactive: 0,
+        },
+        |acc, row| Totals {
+            balance: acc.balance + row.balance,
+            token_balance: acc.token_balance + row.token_balance.unwrap_or_default(),
+            inactive: acc.inactive + !row.active as usize,
+        },
+    )
+}
+
+/// Parse one address per line, blank lines and `#` comments are skipped. Invalid lines are returned with their
+/// 1-based line numbers.
+fn parse_address_list(content: &str) -> (Vec<Address>, Vec<(usize, String)>) {
+    let mut addrs = vec![];
+    let mut invalid = vec![];
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match trx::parse_address(line) {
+            Ok(addr) => addrs.push(addr),
+            Err(_) => invalid.push((i + 1, line.to_owned())),
+        }
+    }
+    (addrs, invalid)
+}
+
+async fn fetch_balance(address: Address, token: Option<&Address>) -> Result<BalanceRow, Error> {
+    let mut req = Account::new();
+    req.set_address(address.as_bytes().to_owned());
+    let account = client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata();
+    let token_balance = async {
+        match token {
+            Some(token) => trc20::fetch_balance_of(token, &address).await.map(Some),
+            None => Ok(None),
+        }
+    };
+
+    let (account, token_balance) = future::join(account, token_balance).await;
+    let account = account?;
+    Ok(BalanceRow {
+        address,
+        active: !account.get_address().is_empty(),
+        balance: account.balance,
+        token_balance: token_balance?,
+    })
+}
+
+fn render_table(rows: &[BalanceRow], token: Option<&Token>) -> String {
+    let mut out = format!("{:<34}  {:>24}", "ADDRESS", "TRX");
+    if let Some(token) = token {
+        out += &format!("  {:>24}", token.symbol());
+    }
+    out.push('\n');
+
+    for row in rows {
+        out += &format!("{:<34}  {:>24}", row.address.to_string(), trx::format_trx(row.balance));
+        if let Some(token) = token {
+            out += &format!("  {:>24}", token.format(&row.token_balance.unwrap_or_default()));
+        }
+        if !row.active {
+            out += "  inactive";
+        }
+        out.push('\n');
+    }
+
+    let totals = totals(rows);
+    let label = format!("TOTAL ({} addresses, {} inactive)", rows.len(), totals.inactive);
+    out += &format!("{:<34}  {:>24}", label, trx::format_trx(totals.balance));
+    if let Some(token) = token {
+        out += &format!("  {:>24}", token.format(&totals.token_balance));
+    }
+    out.push('\n');
+    out
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// A fiat column follows the TRX column when `fiat` is given.
+fn render_csv(rows: &[BalanceRow], token: Option<&Token>, fiat: Option<&FiatRate>) -> String {
+    let mut out = String::from("address,status,trx");
+    if let Some(rate) = fiat {
+        out += &format!(",{}", csv_field(&rate.currency.to_lowercase()));
+    }
+    if let Some(token) = token {
+        out += &format!(",{}", csv_field(token.symbol()));
+    }
+    out.push('\n');
+
+    for row in rows {
+        out += &format!(
+            "{},{},{}",
+            row.address,
+            if row.active { "active" } else { "inactive" },
+            csv_field(&trx::format_trx(row.balance))
+        );
+        if let Some(rate) = fiat {
+            out += &format!(",{}", rate.value_of(row.balance));
+        }
+        if let Some(token) = token {
+            out += &format!(",{}", token.format(&row.token_balance.unwrap_or_default()));
+        }
+        out.push('\n');
+    }
+
+    let totals = totals(rows);
+    out += &format!("TOTAL,,{}", csv_field(&trx::format_trx(totals.balance)));
+    if let Some(rate) = fiat {
+        out += &format!(",{}", rate.value_of(totals.balance));
+    }
+    if let Some(token) = token {
+        out += &format!(",{}", token.format(&totals.token_balance));
+    }
+    out.push('\n');
+    out
+}
+
+/// Integer amounts are strings, so they are exact for any JSON consumer.
+fn render_json(rows: &[BalanceRow], token: Option<&Token>) -> serde_json::Value {
+    let accounts = rows
+        .iter()
+        .map(|row| {
+            let mut account = json!({
+                "address": row.address.to_string(),
+                "active": row.active,
+                "balance": row.balance.to_string(),
+                "trx": trx::format_trx(row.balance),
+            });
+            if let Some(token) = token {
+                let token_balance = row.token_balance.unwrap_or_default();
+                account["token_balance"] = json!(token_balance.to_string());
+                account["token_amount"] = json!(token.format(&token_balance));
+            }
+            account
+        })
+        .collect::<Vec<_>>();
+
+    let totals = totals(rows);
+    let mut total = json!({
+        "addresses": rows.len(),
+        "inactive": totals.inactive,
+        "balance": totals.balance.to_string(),
+        "trx": trx::format_trx(totals.balance),
+    });
+    if let Some(token) = token {
+        total["token_balance"] = json!(totals.token_balance.to_string());
+        total["token_amount"] = json!(token.format(&totals.token_balance));
+    }
+
+    let mut ret = json!({
+        "accounts": accounts,
+        "total": total,
+    });
+    if let Some(token) = token {
+        ret["token"] = json!({
+            "address": token.address.to_string(),
+            "symbol": token.meta.symbol,
+            "decimals": token.meta.decimals,
+        });
+    }
+    ret
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let content = fs::read_to_string(matches.value_of("file").expect("required in cli.yml; qed"))?;
+    let concurrency = matches
+        .value_of("concurrency")
+        .expect("has default in cli.yml; qed")
+        .parse::<usize>()?;
+    if concurrency == 0 {
+        return Err(Error::Runtime("concurrency must be positive"));
+    }
+    let token = match matches.value_of("trc20") {
+        Some(addr) => {
+            let address = trx::parse_address(addr)?;
+            Some(Token {
+                address,
+                meta: trc20::get_token_meta(&address)?,
+            })
+        }
+        None => None,
+    };
+
+    let (addrs, invalid) = parse_address_list(&content);
+    for (line_no, line) in &invalid {
+        warn!("Line {}: invalid address {:?}, skipped", line_no, line);
+    }
+
+    let token_address = token.as_ref().map(|token| &token.address);
+    let rows = executor::block_on(
+        stream::iter(addrs)
+            .map(|addr| fetch_balance(addr, token_address))
+            .buffered(concurrency)
+            .collect::<Vec<_>>(),
+    )
+    .into_iter()
+    .collect::<Result<Vec<_>, Error>>()?;
+
+    if matches.is_present("json") {
+        println!("{}", serde_json::to_string_pretty(&render_json(&rows, token.as_ref()))?);
+    } else if matches.is_present("csv") {
+        // balances are current, the rate of today (UTC) applies
+        let fiat = price::fiat_price_of(matches)?;
+        let rate = fiat.as_ref().and_then(|fiat| fiat.rate_on(Utc::today().naive_utc()));
+        print!("{}", render_csv(&rows, token.as_ref(), rate));
+        if let Some(rate) = rate {
+            info!("{} values at {}", rate.currency, rate.label());
+        }
+    } else {
+        print!("{}", render_table(&rows, token.as_ref()));
+    }
+    if !invalid.is_empty() {
+        info!("{} invalid lines skipped", invalid.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<BalanceRow> {
+        vec![
+            BalanceRow {
+                address: "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap(),
+                active: true,
+                balance: 9_007_199_254_740_993,
+                token_balance: Some(1_500_000.into()),
+            },
+            BalanceRow {
+                address: "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap(),
+                active: false,
+                balance: 0,
+                token_balance: Some(0.into()),
+            },
+            BalanceRow {
+                address: "TPhiVyQZ5xyvVK2KS2LTke8YvXJU5wxnbN".parse().unwrap(),
+                active: true,
+                balance: 7,
+                token_balance: Some(250_000.into()),
+            },
+        ]
+    }
+
+    fn token() -> Token {
+        Token {
+            address: "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap(),
+            meta: TokenMeta {
+                symbol: Some("USDT".into()),
+                decimals: Some(6),
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_address_list() {
+        let content =
+            "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t\n\n# deposit\n  TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt  \nnot-an-address\n";
+        let (addrs, invalid) = parse_address_list(content);
+        assert_eq!(addrs.len(), 2);
+        assert_eq!(addrs[1].to_string(), "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt");
+        assert_eq!(invalid, vec![(5, "not-an-address".to_owned())]);
+    }
+
+    #[test]
+    fn test_exact_totals() {
+        let totals = totals(&rows());
+        // larger than 2^53, would lose precision as f64
+        assert_eq!(totals.balance, 9_007_199_254_741_000);
+        assert_eq!(trx::format_trx(totals.balance), "9007199254.741000");
+        assert_eq!(totals.token_balance, 1_750_000.into());
+        assert_eq!(totals.inactive, 1);
+    }
+
+    #[test]
+    fn test_render_csv() {
+        assert_eq!(
+            render_csv(&rows(), Some(&token()), None),
+            "address,status,trx,USDT\n\
+             TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t,active,9007199254.740993,1.5\n\
+             TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt,inactive,0.000000,0\n\
+             TPhiVyQZ5xyvVK2KS2LTke8YvXJU5wxnbN,active,0.000007,0.25\n\
+             TOTAL,,9007199254.741000,1.75\n"
+        );
+    }
+
+    #[test]
+    fn test_render_csv_with_fiat() {
+        let rate = FiatRate::parse("USD", "0.065", "--price").unwrap();
+        assert_eq!(
+            render_csv(&rows(), None, Some(&rate)),
+            "address,status,trx,usd\n\
+             TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t,active,9007199254.740993,585467951.56\n\
+             TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt,inactive,0.000000,0.00\n\
+             TPhiVyQZ5xyvVK2KS2LTke8YvXJU5wxnbN,active,0.000007,0.00\n\
+             TOTAL,,9007199254.741000,585467951.56\n"
+        );
+    }
+
+    #[test]
+    fn test_render_table_and_json() {
+        let table = render_table(&rows(), None);
+        let lines = table.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[2].ends_with("  inactive"));
+        assert!(lines[4].starts_with("TOTAL (3 addresses, 1 inactive)"));
+
+        let json = render_json(&rows(), Some(&token()));
+        assert_eq!(json["accounts"][1]["active"], json!(false));
+        assert_eq!(json["total"]["balance"], json!("9007199254741000"));
+        assert_eq!(json["total"]["token_amount"], json!("1.75"));
+        assert_eq!(json["token"]["symbol"], json!("USDT"));
+    }
+}
@@ -0,0 +1,128 @@
+//! The most recent blocks, a quick check that the chain is producing blocks and the node is following it.
+
+use clap::ArgMatches;
+use futures::executor;
+use keys::Address;
+use log::{info, warn};
+use proto::api::{BlockExtention, NumberMessage};
+use std::convert::TryFrom;
+
+use crate::error::Error;
+use crate::utils::client;
+use crate::utils::jsont;
+use crate::utils::time;
+
+/// Max number of blocks of `GetBlockByLatestNum`, java-tron requires it to be below `BLOCK_LIMIT_NUM` (100) and
+/// returns nothing otherwise.
+const MAX_LATEST_BLOCKS: i64 = 99;
+
+/// Blocks ordered newest-first, duplicates dropped.
+fn newest_first(mut blocks: Vec<BlockExtention>) -> Vec<BlockExtention> {
+    blocks.sort_by_key(|block| -block.get_block_header().get_raw_data().number);
+    blocks.dedup_by_key(|block| block.get_block_header().get_raw_data().number);
+    blocks
+}
+
+/// A tab-separated row of height, id prefix, time, witness and number of transactions.
+///
+/// The first 8 bytes of a block id are its height, the id prefix is the 8 bytes of hash after them.
+fn format_row(block: &BlockExtention) -> String {
+    let raw = block.get_block_header().get_raw_data();
+    let id_prefix = block.get_blockid().get(8..16).map(hex::encode).unwrap_or_default();
+    let witness = Address::try_from(raw.get_witness_address())
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|_| hex::encode(raw.get_witness_address()));
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        raw.number,
+        id_prefix,
+        time::format_timestamp(raw.timestamp / 1_000, 0),
+        witness,
+        block.get_transactions().len()
+    )
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let last = matches
+        .value_of("last")
+        .expect("has default in cli.yml; qed")
+        .parse::<i64>()
+        .map_err(|_| Error::InvalidInput("invalid --last, use a positive number"))?;
+    if last <= 0 {
+        return Err(Error::InvalidInput("invalid --last, use a positive number"));
+    }
+    if last > MAX_LATEST_BLOCKS {
+        warn!(
+            "Node returns at most {} latest blocks, showing the last {}",
+            MAX_LATEST_BLOCKS, MAX_LATEST_BLOCKS
+        );
+    }
+
+    let mut req = NumberMessage::new();
+    req.num = last.min(MAX_LATEST_BLOCKS);
+    let mut payload = executor::block_on(
+        client::GRPC_CLIENT
+            .get_block_by_latest_num2(Default::default(), req)
+            .drop_metadata(),
+    )?;
+    let blocks = newest_first(payload.take_block().into_vec());
+    if blocks.is_empty() {
+        return Err(Error::NotFound("no blocks returned by the node"));
+    }
+
+    if matches.is_present("json") {
+        let blocks = blocks
+            .iter()
+            .map(|block| {
+                let mut block = serde_json::to_value(block)?;
+                jsont::fix_block(&mut block)?;
+                Ok(block)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        println!("{}", serde_json::to_string_pretty(&blocks)?);
+    } else {
+        for block in &blocks {
+            println!("{}", format_row(block));
+        }
+    }
+    if (blocks.len() as i64) < last.min(MAX_LATEST_BLOCKS) {
+        info!("Node returned {} of {} blocks", blocks.len(), last);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::api::TransactionExtention;
+
+    fn block_of(number: i64) -> BlockExtention {
+        let mut block = BlockExtention::new();
+        block.mut_block_header().mut_raw_data().number = number;
+        block.mut_block_header().mut_raw_data().timestamp = 1_600_000_000_000;
+        block.blockid = [&number.to_be_bytes()[..], &[0xab; 24][..]].concat();
+        block
+    }
+
+    #[test]
+    fn test_newest_first() {
+        let blocks = newest_first(vec![block_of(10), block_of(12), block_of(11), block_of(12)]);
+        let numbers = blocks
+            .iter()
+            .map(|block| block.get_block_header().get_raw_data().number)
+            .collect::<Vec<_>>();
+        assert_eq!(numbers, vec![12, 11, 10]);
+    }
+
+    #[test]
+    fn test_format_row() {
+        let mut block = block_of(12);
+        block.mut_transactions().push(TransactionExtention::new());
+        let row = format_row(&block);
+        let columns = row.split('\t').collect::<Vec<_>>();
+        assert_eq!(columns.len(), 5);
+        assert_eq!(columns[0], "12");
+        assert_eq!(columns[1], "abababababababab");
+        assert_eq!(columns[4], "1");
+    }
+}
@@ -1,5 +1,6 @@
 use futures::executor;
 use keys::Address;
+use log::info;
 use proto::api::BytesMessage;
 use protobuf::ProtobufEnum;
 use serde_json::json;
@@ -21,16 +22,16 @@ pub fn run(addr: &str) -> Result<(), Error> {
             .drop_metadata(),
     )?;
     if payload.get_contract_address().is_empty() {
-        return Err(Error::Runtime("contract not found on chain"));
+        return Err(Error::NotFound("contract not found on chain"));
     }
 
     let mut contract = serde_json::to_value(&payload)?;
-    contract["contract_address"] = json!(jsont::bytes_to_hex_string(&contract["contract_address"]));
-    contract["origin_address"] = json!(jsont::bytes_to_hex_string(&contract["origin_address"]));
-    contract["bytecode"] = json!(jsont::bytes_to_hex_string(&contract["bytecode"]));
-    contract["code_hash"] = json!(jsont::bytes_to_hex_string(&contract["code_hash"]));
+    jsont::fix_hex_field(&mut contract, &["contract_address"]);
+    jsont::fix_hex_field(&mut contract, &["origin_address"]);
+    jsont::fix_hex_field(&mut contract, &["bytecode"]);
+    jsont::fix_hex_field(&mut contract, &["code_hash"]);
     // NOTE: Only available when this contract is created by CREATE or CREATE2 opcode.
-    contract["trx_hash"] = json!(jsont::bytes_to_hex_string(&contract["trx_hash"]));
+    jsont::fix_hex_field(&mut contract, &["trx_hash"]);
 
     println!("{}", serde_json::to_string_pretty(&contract)?);
     // re-order abi entry by types
@@ -49,7 +50,7 @@ fn pprint_abi_entries(abi: &::proto::core::SmartContract_ABI) -> Result<(), Erro
         match entry.get_field_type() {
             AbiEntryType::Function => {
                 let fnhash = abi::fnhash(&method);
-                eprintln!(
+                info!(
                     "{}\n    => {} [{}]",
                     abi::entry_to_method_name_pretty(entry)?,
                     method,
@@ -59,14 +60,14 @@ fn pprint_abi_entries(abi: &::proto::core::SmartContract_ABI) -> Result<(), Erro
             AbiEntryType::Event => {
                 // will be the first in `log.topics` field
                 let event_hash = crypto::keccak256(method.as_bytes());
-                eprintln!(
+                info!(
                     "{}\n    => {}",
                     abi::entry_to_method_name_pretty(entry)?,
                     hex::encode(event_hash)
                 );
             }
             _ => {
-                eprintln!("{:}", abi::entry_to_method_name_pretty(entry)?);
+                info!("{:}", abi::entry_to_method_name_pretty(entry)?);
             }
         }
     }
@@ -0,0 +1,101 @@
+//! Resource delegation relationships between accounts.
+
+use futures::executor;
+use keys::Address;
+use log::info;
+use proto::api::{BytesMessage, DelegatedResourceMessage};
+use proto::core::DelegatedResource;
+use serde_json::json;
+use std::convert::TryFrom;
+
+use crate::error::Error;
+use crate::utils::client;
+use crate::utils::time;
+use crate::utils::trx;
+
+fn delegated_resource_to_json(delegate: &DelegatedResource) -> Result<serde_json::Value, Error> {
+    Ok(json!({
+        "from": Address::try_from(delegate.get_from())?.to_string(),
+        "to": Address::try_from(delegate.get_to())?.to_string(),
+        "frozen_balance_for_bandwidth": delegate.frozen_balance_for_bandwidth,
+        "frozen_balance_for_energy": delegate.frozen_balance_for_energy,
+        "expire_time_for_bandwidth": delegate.expire_time_for_bandwidth,
+        "expire_time_for_energy": delegate.expire_time_for_energy,
+    }))
+}
+
+pub fn get_delegated_resource(from: &str, to: &str) -> Result<(), Error> {
+    let from = trx::parse_address(from)?;
+    let to = trx::parse_address(to)?;
+
+    let mut req = DelegatedResourceMessage::new();
+    req.set_fromAddress(from.as_bytes().to_owned());
+    req.set_toAddress(to.as_bytes().to_owned());
+    let payload = executor::block_on(
+        client::GRPC_CLIENT
+            .get_delegated_resource(Default::default(), req)
+            .drop_metadata(),
+    )?;
+
+    let delegates = payload
+        .get_delegatedResource()
+        .iter()
+        .map(delegated_resource_to_json)
+        .collect::<Result<Vec<_>, Error>>()?;
+    println!("{}", serde_json::to_string_pretty(&delegates)?);
+
+    if payload.get_delegatedResource().is_empty() {
+        info!("No resource delegated from {} to {}", from, to);
+    }
+    for delegate in payload.get_delegatedResource() {
+        if delegate.frozen_balance_for_bandwidth > 0 {
+            info!(
+                "Bandwidth {}_TRX expiration={}",
+                delegate.frozen_balance_for_bandwidth as f64 / 1_000_000.0,
+                time::format_timestamp(delegate.expire_time_for_bandwidth / 1_000, 0)
+            );
+        }
+        if delegate.frozen_balance_for_energy > 0 {
+            info!(
+                "Energy    {}_TRX expiration={}",
+                delegate.frozen_balance_for_energy as f64 / 1_000_000.0,
+                time::format_timestamp(delegate.expire_time_for_energy / 1_000, 0)
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn get_delegated_resource_index(addr: &str) -> Result<(), Error> {
+    let addr = trx::parse_address(addr)?;
+
+    let payload = executor::block_on(
+        client::GRPC_CLIENT
+            .get_delegated_resource_account_index(
+                Default::default(),
+                BytesMessage {
+                    value: addr.as_bytes().to_owned(),
+                    ..Default::default()
+                },
+            )
+            .drop_metadata(),
+    )?;
+
+    let to_addresses = |raws: &[Vec<u8>]| {
+        raws.iter()
+            .map(|raw| Address::try_from(raw).map(|addr| addr.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let ret = json!({
+        "account": addr.to_string(),
+        "from_accounts": to_addresses(payload.get_fromAccounts())?,
+        "to_accounts": to_addresses(payload.get_toAccounts())?,
+    });
+    println!("{}", serde_json::to_string_pretty(&ret)?);
+    info!(
+        "Received delegation from {} accounts, delegated to {} accounts",
+        payload.get_fromAccounts().len(),
+        payload.get_toAccounts().len()
+    );
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! Merkle root of a list of transaction hashes, the `txTrieRoot` of a block.
+
+use clap::ArgMatches;
+use merkle_tree::H256;
+use std::fs;
+
+use crate::error::Error;
+use crate::utils::block;
+
+fn parse_hash(s: &str) -> Result<H256, Error> {
+    let raw = hex::decode(s.trim().trim_start_matches("0x"))?;
+    if raw.len() != 32 {
+        return Err(Error::InvalidInput("hash must be 32 bytes"));
+    }
+    Ok(H256::from_slice(&raw))
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let mut hashes = matches
+        .values_of("HASH")
+        .map(|hashes| hashes.map(parse_hash).collect::<Result<Vec<_>, _>>())
+        .transpose()?
+        .unwrap_or_default();
+    if let Some(path) = matches.value_of("file") {
+        for line in fs::read_to_string(path)?.lines().filter(|line| !line.trim().is_empty()) {
+            hashes.push(parse_hash(line)?);
+        }
+    }
+
+    let root = block::merkle_root(hashes);
+    println!("{}", hex::encode(root.as_bytes()));
+    match matches.value_of("expected").map(parse_hash).transpose()? {
+        Some(expected) if expected != root => Err(Error::Mismatch("merkle root mismatch")),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hash() {
+        let hash = "df3f619804a92fdb4057192dc43dd748ea778adc52bc498ce80524c014b81119";
+        let expected: H256 = hash.parse().unwrap();
+        assert_eq!(parse_hash(hash).unwrap(), expected);
+        assert_eq!(parse_hash(&format!(" 0x{}\n", hash)).unwrap(), expected);
+
+        assert!(matches!(parse_hash(&hash[2..]), Err(Error::InvalidInput(_))));
+        assert!(matches!(parse_hash("zz"), Err(Error::FromHex(_))));
+    }
+}
@@ -1,22 +1,37 @@
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
+use std::time::Duration;
 
-use chrono::{Local, TimeZone};
 use clap::ArgMatches;
 use futures::executor;
 use hex::FromHex;
 use keys::Address;
+use log::{info, warn};
 use proto::api::{BytesMessage, DelegatedResourceMessage, EmptyMessage, NumberMessage};
-use proto::core::Account;
+use proto::core::{Account, AssetIssueContract, Block};
 use serde_json::json;
 
 use crate::error::Error;
+use crate::utils::block;
 use crate::utils::client;
 use crate::utils::jsont;
+use crate::utils::price;
+use crate::utils::record::{self, RecordKind};
+use crate::utils::time;
 use crate::utils::trx;
 
+mod account_diff;
+mod balances;
+mod blocks;
 mod contract;
-mod transaction;
+mod delegation;
+mod merkle;
+mod paginated;
+mod spot_price;
+mod trace;
+pub mod transaction;
+mod trigger_count;
+mod votes;
 
 fn node_info() -> Result<(), Error> {
     let payload = executor::block_on(
@@ -34,14 +49,54 @@ fn get_next_maintenance_time() -> Result<(), Error> {
             .get_next_maintenance_time(Default::default(), Default::default())
             .drop_metadata(),
     )?;
-    eprintln!("! Next Maintenance: {}", payload.num);
-    eprintln!(
-        "! Timestamp: {}",
-        Local.timestamp(payload.num / 1_000, (payload.num % 1_000 * 1_000_000) as u32)
+    info!("Next Maintenance: {}", payload.num);
+    info!(
+        "Timestamp: {}",
+        time::format_timestamp(payload.num / 1_000, (payload.num % 1_000 * 1_000_000) as u32)
     );
     Ok(())
 }
 
+/// Gap between the head block and the latest confirmed block that hints at consensus issues.
+const CONFIRMED_BLOCK_GAP_ALERT: i64 = 100;
+
+/// Block number in `NodeInfo`, formatted like `Num:24113460,ID:000000000170f334...`.
+fn parse_node_info_block_num(block: &str) -> Option<i64> {
+    block
+        .split(',')
+        .find_map(|part| part.strip_prefix("Num:"))
+        .and_then(|num| num.trim().parse().ok())
+}
+
+/// Head block, and the latest confirmed (solidified) block, which is confirmed by 2/3+1 of SRs.
+fn get_confirmed_block_count() -> Result<(), Error> {
+    let block = trx::get_latest_block()?;
+    let node_info = executor::block_on(
+        client::GRPC_CLIENT
+            .get_node_info(Default::default(), EmptyMessage::new())
+            .drop_metadata(),
+    )?;
+    let head = block.get_block_header().get_raw_data().number;
+    let confirmed = parse_node_info_block_num(node_info.get_solidityBlock())
+        .ok_or(Error::Runtime("node does not report its confirmed block"))?;
+    let gap = head - confirmed;
+
+    info!("Head Block:      #{}", head);
+    info!("Confirmed Block: #{}", confirmed);
+    info!(
+        "Gap:             {} blocks (finality needs {} blocks)",
+        gap,
+        transaction::DEFAULT_CONFIRM_COUNT
+    );
+    if gap > CONFIRMED_BLOCK_GAP_ALERT {
+        warn!(
+            "Confirmed block is more than {} blocks behind, SRs might not be reaching consensus",
+            CONFIRMED_BLOCK_GAP_ALERT
+        );
+    }
+    Ok(())
+}
+
 fn visit_node(ip: &str, edges: &mut HashSet<(String, String)>) -> Result<(), Error> {
     let mut stack = vec![ip.to_owned()];
     let mut visited = HashSet::new();
@@ -49,14 +104,14 @@ fn visit_node(ip: &str, edges: &mut HashSet<(String, String)>) -> Result<(), Err
     while let Some(self_ip) = stack.pop() {
         visited.insert(self_ip.clone());
 
-        eprintln!("({})visiting ... {}", edges.len(), self_ip);
+        info!("({})visiting ... {}", edges.len(), self_ip);
         if let Ok(grpc_client) = client::new_grpc_client(&format!("{}:50051", self_ip)) {
             if let Ok(node_info) = executor::block_on(
                 grpc_client
                     .get_node_info(Default::default(), EmptyMessage::new())
                     .drop_metadata(),
             ) {
-                eprintln!(
+                info!(
                     "p2p version: {}, node version: {}",
                     node_info.get_configNodeInfo().get_p2pVersion(),
                     node_info.get_configNodeInfo().get_codeVersion()
@@ -246,18 +301,18 @@ fn get_merkle_tree(matches: &ArgMatches) -> Result<(), Error> {
             hex::encode(txn_hash)
         );
         if txn.get_ret().len() > 1 {
-            eprintln!("      ! len(Transaction.ret) = {}", txn.get_ret().len());
+            warn!("len(Transaction.ret) = {}", txn.get_ret().len());
         }
         for ret in txn.get_ret().iter() {
             if let Some(ref ufields) = ret.unknown_fields.fields {
-                eprintln!("      ! malformed ret {:?}", ufields);
+                warn!("malformed ret {:?}", ufields);
             }
         }
         if let Some(ref ufields) = txn.get_raw_data().unknown_fields.fields {
-            eprintln!("      ! malformed raw_data {:?}", ufields);
+            warn!("malformed raw_data {:?}", ufields);
         }
         if let Some(ref ufields) = txn.unknown_fields.fields {
-            eprintln!("      ! malformed txn {:?}", ufields);
+            warn!("malformed txn {:?}", ufields);
         }
     }
 
@@ -265,51 +320,74 @@ fn get_merkle_tree(matches: &ArgMatches) -> Result<(), Error> {
 }
 
 fn get_block(matches: &ArgMatches) -> Result<(), Error> {
-    let mut block = match matches.value_of("BLOCK") {
+    // the block for verification, with the block id reported by the node
+    let (mut block, payload, reported_id) = match matches.value_of("BLOCK") {
         Some(id) if id.starts_with("0000") => {
-            let mut req = BytesMessage::new();
-            req.value = Vec::from_hex(id)?;
-            let payload = executor::block_on(
-                client::GRPC_CLIENT
-                    .get_block_by_id(Default::default(), req)
-                    .drop_metadata(),
-            )?;
-            serde_json::to_value(&payload)?
+            let block_id = Vec::from_hex(id)?;
+            let payload = record::fetch(RecordKind::Block, &block_id, || {
+                let mut req = BytesMessage::new();
+                req.value = block_id.clone();
+                Ok(executor::block_on(
+                    client::GRPC_CLIENT
+                        .get_block_by_id(Default::default(), req)
+                        .drop_metadata(),
+                )?)
+            })?;
+            (serde_json::to_value(&payload)?, payload, Some(block_id))
         }
         Some(num) => {
-            let mut req = NumberMessage::new();
-            req.num = num.parse()?;
-            let payload = executor::block_on(
-                client::GRPC_CLIENT
-                    .get_block_by_num2(Default::default(), req)
-                    .drop_metadata(),
-            )?;
-            serde_json::to_value(&payload)?
+            let num = num.parse()?;
+            if let Some(payload) = record::replay_block(Some(num))? {
+                (serde_json::to_value(&payload)?, payload, None)
+            } else {
+                let mut req = NumberMessage::new();
+                req.num = num;
+                let payload = executor::block_on(
+                    client::GRPC_CLIENT
+                        .get_block_by_num2(Default::default(), req)
+                        .drop_metadata(),
+                )?;
+                record::record(RecordKind::Block, payload.get_blockid(), &record::block_of(&payload))?;
+                (
+                    serde_json::to_value(&payload)?,
+                    record::block_of(&payload),
+                    Some(payload.get_blockid().to_owned()),
+                )
+            }
         }
         None => {
-            let payload = executor::block_on(
-                client::GRPC_CLIENT
-                    .get_now_block(Default::default(), EmptyMessage::new())
-                    .drop_metadata(),
-            )?;
-            serde_json::to_value(&payload)?
+            if let Some(payload) = record::replay_block(None)? {
+                (serde_json::to_value(&payload)?, payload, None)
+            } else {
+                let payload = executor::block_on(
+                    client::GRPC_CLIENT
+                        .get_now_block(Default::default(), EmptyMessage::new())
+                        .drop_metadata(),
+                )?;
+                record::record(RecordKind::Block, &record::block_id(&payload)?, &payload)?;
+                (serde_json::to_value(&payload)?, payload, None)
+            }
         }
     };
     if block["block_header"].is_null() {
-        return Err(Error::Runtime("block not found on chain"));
+        return Err(Error::NotFound("block not found on chain"));
+    }
+    if matches.is_present("verify") {
+        block::verify_block(&payload, reported_id.as_deref())?;
+        info!("Block verified: block id, txTrieRoot and witness signature");
     }
 
     jsont::fix_block(&mut block)?;
 
     println!("{:}", serde_json::to_string_pretty(&block)?);
-    eprintln!("! Block Number: {}", block["block_header"]["raw_data"]["number"]);
-    eprintln!(
-        "! Number of Transactions: {}",
+    info!("Block Number: {}", block["block_header"]["raw_data"]["number"]);
+    info!(
+        "Number of Transactions: {}",
         block["transactions"].as_array().unwrap().len()
     );
-    eprintln!(
-        "! Generated At: {}",
-        Local.timestamp(
+    info!(
+        "Generated At: {}",
+        time::format_timestamp(
             block["block_header"]["raw_data"]["timestamp"].as_i64().unwrap() / 1_000,
             0
         )
@@ -319,16 +397,118 @@ fn get_block(matches: &ArgMatches) -> Result<(), Error> {
         .unwrap()
         .parse::<Address>()
         .map(|addr| {
-            eprintln!("! Witness: {}", addr);
+            info!("Witness: {}", addr);
         });
 
     Ok(())
 }
 
+/// Get the header of a block, transactions are dropped.
+fn get_block_header(matches: &ArgMatches) -> Result<(), Error> {
+    let mut payload = match matches.value_of("BLOCK") {
+        Some(id) if id.starts_with("0000") => {
+            let mut req = BytesMessage::new();
+            req.value = Vec::from_hex(id)?;
+            executor::block_on(
+                client::GRPC_CLIENT
+                    .get_block_by_id(Default::default(), req)
+                    .drop_metadata(),
+            )?
+        }
+        Some(num) => {
+            let mut req = NumberMessage::new();
+            req.num = num.parse()?;
+            executor::block_on(
+                client::GRPC_CLIENT
+                    .get_block_by_num(Default::default(), req)
+                    .drop_metadata(),
+            )?
+        }
+        None => executor::block_on(
+            client::GRPC_CLIENT
+                .get_now_block(Default::default(), EmptyMessage::new())
+                .drop_metadata(),
+        )?,
+    };
+    if !payload.has_block_header() {
+        return Err(Error::NotFound("block not found on chain"));
+    }
+    payload.clear_transactions();
+
+    println!("{}", serde_json::to_string_pretty(&block_header_summary(&payload)?)?);
+    info!(
+        "Generated At: {}",
+        time::format_timestamp(payload.get_block_header().get_raw_data().timestamp / 1_000, 0)
+    );
+
+    Ok(())
+}
+
+fn block_header_summary(block: &Block) -> Result<serde_json::Value, Error> {
+    let header = block.get_block_header();
+    let raw = header.get_raw_data();
+    let witness = Address::try_from(raw.get_witness_address())
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|_| hex::encode(raw.get_witness_address()));
+    Ok(json!({
+        "number": raw.number,
+        "blockid": hex::encode(record::block_id(block)?),
+        "timestamp": raw.timestamp,
+        "witness_address": witness,
+        "parent_hash": hex::encode(raw.get_parentHash()),
+        "tx_trie_root": hex::encode(raw.get_txTrieRoot()),
+        "witness_signature": hex::encode(header.get_witness_signature()),
+    }))
+}
+
+/// Get a finalized block from the solidity node, with its confirmation depth on the full node.
+fn get_solidity_block(num: Option<&str>) -> Result<(), Error> {
+    let solidity_client = client::new_solidity_client()?;
+    let solid_head = executor::block_on(
+        solidity_client
+            .get_now_block(Default::default(), EmptyMessage::new())
+            .drop_metadata(),
+    )?;
+    let mut payload = match num {
+        Some(num) => {
+            let mut req = NumberMessage::new();
+            req.num = num.parse()?;
+            executor::block_on(
+                solidity_client
+                    .get_block_by_num(Default::default(), req)
+                    .drop_metadata(),
+            )?
+        }
+        None => solid_head.clone(),
+    };
+    if !payload.has_block_header() {
+        return Err(Error::NotFound("block not finalized or not found on the solidity node"));
+    }
+    payload.clear_transactions();
+    println!("{}", serde_json::to_string_pretty(&block_header_summary(&payload)?)?);
+
+    let number = payload.get_block_header().get_raw_data().number;
+    let solid_number = solid_head.get_block_header().get_raw_data().number;
+    info!("Finalized Block: #{}", trx::format_number(solid_number));
+    let head = executor::block_on(
+        client::GRPC_CLIENT
+            .get_now_block(Default::default(), EmptyMessage::new())
+            .drop_metadata(),
+    )?;
+    let head_number = head.get_block_header().get_raw_data().number;
+    info!(
+        "Full Node Head: #{}, {} blocks ahead of finalized",
+        trx::format_number(head_number),
+        trx::format_number(head_number - solid_number)
+    );
+    info!("Confirmation Depth: {}", trx::format_number(head_number - number));
+    Ok(())
+}
+
 /// Get account infomation.
-fn get_account(name: &str) -> Result<(), Error> {
+fn get_account(name: &str, usd: bool) -> Result<(), Error> {
     let mut req = Account::new();
-    let addr = name.parse::<Address>()?;
+    let addr = trx::parse_address(name)?;
     req.set_address(addr.as_bytes().to_owned());
     // FIXME: account name not supported
     // req.set_account_name(name.as_bytes().to_owned());
@@ -336,38 +516,44 @@ fn get_account(name: &str) -> Result<(), Error> {
     let payload = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
     if payload.get_address().is_empty() {
         println!("{}", serde_json::to_string_pretty(&payload)?);
-        return Err(Error::Runtime("account not found on chain"));
+        return Err(Error::NotFound("account not found on chain"));
     }
 
     let mut account = serde_json::to_value(&payload)?;
     jsont::fix_account(&mut account);
 
     println!("{}", serde_json::to_string_pretty(&account)?);
+    let price = if usd { price::trx_usd_price() } else { None };
 
-    eprintln!(
-        "! Type = {:?}{}",
+    info!(
+        "Type = {:?}{}",
         payload.field_type,
         if payload.is_witness { " | Witness" } else { "" }
     );
-    eprintln!("! Address(Base58Check) = {:}", addr);
-    eprintln!("! Created At: {}", Local.timestamp(payload.create_time / 1_000, 0));
+    info!("Address(Base58Check) = {:}", addr);
+    info!("Created At: {}", time::format_timestamp(payload.create_time / 1_000, 0));
 
     let mut total_balance = 0;
     if payload.balance != 0 {
         total_balance += payload.balance;
-        eprintln!(
-            "! Balance = {}",
-            trx::format_amount_with_surfix(payload.balance, "TRX", 6)
+        info!(
+            "Balance = {}{}",
+            trx::format_amount_with_surfix(payload.balance, "TRX", 6),
+            price::usd_suffix(price, payload.balance)
         );
     }
     if payload.get_account_resource().get_delegated_frozen_balance_for_energy() != 0 {
         total_balance += payload.get_account_resource().get_delegated_frozen_balance_for_energy();
-        eprintln!(
-            "! Delegate Balance for Energy = {}",
+        info!(
+            "Delegate Balance for Energy = {}{}",
             trx::format_amount_with_surfix(
                 payload.get_account_resource().get_delegated_frozen_balance_for_energy(),
                 "TRX",
                 6
+            ),
+            price::usd_suffix(
+                price,
+                payload.get_account_resource().get_delegated_frozen_balance_for_energy()
             )
         )
     }
@@ -381,8 +567,8 @@ fn get_account(name: &str) -> Result<(), Error> {
             .get_account_resource()
             .get_frozen_balance_for_energy()
             .frozen_balance;
-        eprintln!(
-            "! Frozen Balance for Energy = {}",
+        info!(
+            "Frozen Balance for Energy = {}{}",
             trx::format_amount_with_surfix(
                 payload
                     .get_account_resource()
@@ -390,45 +576,65 @@ fn get_account(name: &str) -> Result<(), Error> {
                     .frozen_balance,
                 "TRX",
                 6
+            ),
+            price::usd_suffix(
+                price,
+                payload
+                    .get_account_resource()
+                    .get_frozen_balance_for_energy()
+                    .frozen_balance
             )
         )
     }
     // NOTE: tron is ugly everywhere
     if !payload.get_frozen().is_empty() {
         total_balance += payload.get_frozen()[0].frozen_balance;
-        eprintln!(
-            "! Frozen/Delegate Balance for Bandwidth = {}",
-            trx::format_amount_with_surfix(payload.get_frozen()[0].frozen_balance, "TRX", 6)
+        info!(
+            "Frozen/Delegate Balance for Bandwidth = {}{}",
+            trx::format_amount_with_surfix(payload.get_frozen()[0].frozen_balance, "TRX", 6),
+            price::usd_suffix(price, payload.get_frozen()[0].frozen_balance)
         )
     }
     if payload.allowance != 0 {
         total_balance += payload.allowance;
-        eprintln!(
-            "! Unwithdrawn SR/Voting Reward = {}",
-            trx::format_amount_with_surfix(payload.allowance, "TRX", 6)
+        info!(
+            "Unwithdrawn SR/Voting Reward = {}{}",
+            trx::format_amount_with_surfix(payload.allowance, "TRX", 6),
+            price::usd_suffix(price, payload.allowance)
         );
     }
 
     if total_balance > 0 {
-        eprintln!(
-            "! Total Balance = {}",
-            trx::format_amount_with_surfix(total_balance, "TRX", 6)
+        info!(
+            "Total Balance = {}{}",
+            trx::format_amount_with_surfix(total_balance, "TRX", 6),
+            price::usd_suffix(price, total_balance)
         )
     }
 
+    let mut assets = payload
+        .get_assetV2()
+        .iter()
+        .filter(|(_, &amount)| amount != 0)
+        .collect::<Vec<_>>();
+    assets.sort();
+    for (id, &amount) in assets {
+        info!("TRC10 {} = {}", id, trx::format_asset_amount(amount, id));
+    }
+
     Ok(())
 }
 
 /// Get account permission info.
 fn get_account_permission(name: &str) -> Result<(), Error> {
     let mut req = Account::new();
-    let addr = name.parse::<Address>()?;
+    let addr = trx::parse_address(name)?;
     req.set_address(addr.as_bytes().to_owned());
 
     let payload = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
     if payload.get_address().is_empty() {
         println!("{}", serde_json::to_string_pretty(&payload)?);
-        return Err(Error::Runtime("account not found on chain"));
+        return Err(Error::NotFound("account not found on chain"));
     }
 
     let mut account = serde_json::to_value(&payload)?;
@@ -446,7 +652,7 @@ fn get_account_permission(name: &str) -> Result<(), Error> {
 /// Get account energy and bandwidth infomation.
 fn get_account_resource(name: &str) -> Result<(), Error> {
     let mut req = Account::new();
-    let addr = name.parse::<Address>()?;
+    let addr = trx::parse_address(name)?;
     req.set_address(addr.as_bytes().to_owned());
 
     let payload = executor::block_on(
@@ -457,29 +663,34 @@ fn get_account_resource(name: &str) -> Result<(), Error> {
 
     println!("{}", serde_json::to_string_pretty(&payload)?);
     if payload.get_freeNetLimit() == 0 {
-        return Err(Error::Runtime("account not found on chain"));
+        return Err(Error::NotFound("account not found on chain"));
     }
-    eprintln!(
-        "! Free Bandwith Usage: {}/{}",
-        payload.freeNetUsed, payload.freeNetLimit
+    info!(
+        "Free Bandwith Usage: {}/{}",
+        trx::format_number(payload.freeNetUsed),
+        trx::format_number(payload.freeNetLimit)
     );
     if payload.NetLimit > 0 {
-        eprintln!("! Bandwidth Usage: {}/{}", payload.NetUsed, payload.NetLimit);
+        info!(
+            "Bandwidth Usage: {}/{}",
+            trx::format_number(payload.NetUsed),
+            trx::format_number(payload.NetLimit)
+        );
     }
     if payload.EnergyLimit > 0 {
-        eprintln!(
-            "! Energy Usage: {}/{} ({})",
-            payload.EnergyUsed,
-            payload.EnergyLimit,
-            payload.EnergyLimit - payload.EnergyUsed
+        info!(
+            "Energy Usage: {}/{} ({})",
+            trx::format_number(payload.EnergyUsed),
+            trx::format_number(payload.EnergyLimit),
+            trx::format_number(payload.EnergyLimit - payload.EnergyUsed)
         );
     }
-    eprintln!(
-        "! Energy By Freezing    1_TRX = {:.5}",
+    info!(
+        "Energy By Freezing    1_TRX = {:.5}",
         payload.TotalEnergyLimit as f64 / (payload.TotalEnergyWeight as f64 + 1.0)
     );
-    eprintln!(
-        "! Bandwidth By Freezing 1_TRX = {:.5}",
+    info!(
+        "Bandwidth By Freezing 1_TRX = {:.5}",
         payload.TotalNetLimit as f64 / (payload.TotalNetWeight as f64 + 1.0)
     );
 
@@ -509,26 +720,26 @@ fn get_account_resource(name: &str) -> Result<(), Error> {
         )?;
         for delegate in &delegates.delegatedResource {
             if delegate.frozen_balance_for_energy > 0 {
-                eprintln!(
-                    "! Delegate Energy    From {} {}_TRX expiration={}",
+                info!(
+                    "Delegate Energy    From {} {}_TRX expiration={}",
                     Address::try_from(from).unwrap(),
                     delegate.frozen_balance_for_energy as f64 / 1_000_000.0,
-                    Local.timestamp(delegate.expire_time_for_energy / 1_000, 0)
+                    time::format_timestamp(delegate.expire_time_for_energy / 1_000, 0)
                 );
             }
             if delegate.frozen_balance_for_bandwidth > 0 {
-                eprintln!(
-                    "! Delegate Bandwidth From {} {}_TRX expiration={}",
+                info!(
+                    "Delegate Bandwidth From {} {}_TRX expiration={}",
                     Address::try_from(from).unwrap(),
                     delegate.frozen_balance_for_bandwidth as f64 / 1_000_000.0,
-                    Local.timestamp(delegate.expire_time_for_bandwidth / 1_000, 0)
+                    time::format_timestamp(delegate.expire_time_for_bandwidth / 1_000, 0)
                 );
             }
         }
     }
 
     if payload.toAccounts.len() > 2 {
-        eprintln!("! Delegate resources to {} accounts", payload.toAccounts.len());
+        info!("Delegate resources to {} accounts", payload.toAccounts.len());
     }
     for to in &payload.toAccounts {
         let mut req = DelegatedResourceMessage::new();
@@ -542,19 +753,19 @@ fn get_account_resource(name: &str) -> Result<(), Error> {
         )?;
         for delegate in &delegates.delegatedResource {
             if delegate.frozen_balance_for_energy > 0 {
-                eprintln!(
-                    "! Delegate Energy    To {} {}_TRX expiration={}",
+                info!(
+                    "Delegate Energy    To {} {}_TRX expiration={}",
                     Address::try_from(to).unwrap(),
                     delegate.frozen_balance_for_energy as f64 / 1_000_000.0,
-                    Local.timestamp(delegate.expire_time_for_energy / 1_000, 0)
+                    time::format_timestamp(delegate.expire_time_for_energy / 1_000, 0)
                 );
             }
             if delegate.frozen_balance_for_bandwidth > 0 {
-                eprintln!(
-                    "! Delegate Bandwidth To {} {}_TRX expiration={}",
+                info!(
+                    "Delegate Bandwidth To {} {}_TRX expiration={}",
                     Address::try_from(to).unwrap(),
                     delegate.frozen_balance_for_bandwidth as f64 / 1_000_000.0,
-                    Local.timestamp(delegate.expire_time_for_bandwidth / 1_000, 0)
+                    time::format_timestamp(delegate.expire_time_for_bandwidth / 1_000, 0)
                 );
             }
         }
@@ -574,7 +785,7 @@ fn get_proposal_by_id(id: &str) -> Result<(), Error> {
             .drop_metadata(),
     )?;
     if payload.get_proposal_id() == 0 {
-        return Err(Error::Runtime("proposal not found on chain"));
+        return Err(Error::NotFound("proposal not found on chain"));
     }
     let mut proposal = serde_json::to_value(&payload)?;
 
@@ -623,32 +834,82 @@ fn get_proposal_by_id(id: &str) -> Result<(), Error> {
         });
 
     println!("{}", serde_json::to_string_pretty(&proposal)?);
-    eprintln!("! Created At: {}", Local.timestamp(payload.create_time / 1_000, 0));
-    eprintln!("! Expired At: {}", Local.timestamp(payload.expiration_time / 1_000, 0));
+    info!("Created At: {}", time::format_timestamp(payload.create_time / 1_000, 0));
+    info!(
+        "Expired At: {}",
+        time::format_timestamp(payload.expiration_time / 1_000, 0)
+    );
 
     Ok(())
 }
 
-fn get_asset_by_id(id: &str) -> Result<(), Error> {
-    let mut req = BytesMessage::new();
-    req.set_value(id.as_bytes().to_owned());
-
-    let payload = executor::block_on(
-        client::GRPC_CLIENT
-            .get_asset_issue_by_id(Default::default(), req)
-            .drop_metadata(),
-    )?;
-    if payload.get_id().is_empty() {
-        return Err(Error::Runtime("asset not found"));
-    }
+fn get_asset(id_or_name: &str, json_only: bool) -> Result<(), Error> {
+    let payload = trx::get_asset(id_or_name)?;
     let mut asset = serde_json::to_value(&payload)?;
     jsont::fix_asset_issue_contract(&mut asset);
     println!("{}", serde_json::to_string_pretty(&asset)?);
+    if !json_only {
+        pprint_asset_summary(&payload);
+    }
     Ok(())
 }
 
+/// Balance of a TRC10 asset, 0 if the account holds none.
+fn asset_balance_of(account: &Account, asset_id: &str) -> i64 {
+    account.get_assetV2().get(asset_id).copied().unwrap_or_default()
+}
+
+/// Print the balance of a TRC10 asset held by an account. Accounts not on chain hold 0.
+fn get_asset_balance(name: &str, id_or_name: &str) -> Result<(), Error> {
+    let addr = trx::parse_address(name)?;
+    let asset = trx::get_asset(id_or_name)?;
+
+    let mut req = Account::new();
+    req.set_address(addr.as_bytes().to_owned());
+    let account = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
+
+    let balance = asset_balance_of(&account, asset.get_id());
+    println!(
+        "{}",
+        trx::format_token_amount(balance, asset.get_precision() as u32, "")
+    );
+    info!(
+        "TRC10 {} {:?} of {}",
+        asset.get_id(),
+        String::from_utf8_lossy(asset.get_abbr()),
+        addr
+    );
+    Ok(())
+}
+
+pub(crate) fn pprint_asset_summary(asset: &AssetIssueContract) {
+    info!(
+        "Asset {} {:?}",
+        asset.get_id(),
+        String::from_utf8_lossy(asset.get_name())
+    );
+    info!("  Issuer: {}", keys::b58encode_check(asset.get_owner_address()));
+    info!("  Precision: {}", asset.get_precision());
+    info!(
+        "  Total Supply: {}",
+        trx::format_token_amount(
+            asset.get_total_supply(),
+            asset.get_precision() as u32,
+            &String::from_utf8_lossy(asset.get_abbr())
+        )
+    );
+    info!(
+        "  Start Time: {}",
+        time::format_timestamp(asset.get_start_time() / 1_000, 0)
+    );
+    info!(
+        "  End Time: {}",
+        time::format_timestamp(asset.get_end_time() / 1_000, 0)
+    );
+}
+
 fn get_reward_info(addr: &str) -> Result<(), Error> {
-    let addr = addr.parse::<Address>()?;
+    let addr = trx::parse_address(addr)?;
     let mut req = BytesMessage::new();
     req.set_value(addr.as_bytes().to_owned());
 
@@ -662,17 +923,9 @@ fn get_reward_info(addr: &str) -> Result<(), Error> {
 }
 
 fn get_brokerage_info(addr: &str) -> Result<(), Error> {
-    let addr = addr.parse::<Address>()?;
-    let mut req = BytesMessage::new();
-    req.set_value(addr.as_bytes().to_owned());
-
-    let payload = executor::block_on(
-        client::GRPC_CLIENT
-            .get_brokerage_info(Default::default(), req)
-            .drop_metadata(),
-    )?;
-    println!("sharing percent = {}%", 100 - payload.get_num());
-    println!("kept percent    = {}%", payload.get_num());
+    let brokerage = trx::get_brokerage(&trx::parse_address(addr)?)?;
+    println!("sharing percent = {}%", 100 - brokerage);
+    println!("kept percent    = {}%", brokerage);
     Ok(())
 }
 
@@ -681,19 +934,43 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
         ("node", _) => node_info(),
         ("node_graph", _) => get_node_graph(),
         ("block", Some(arg_matches)) => get_block(arg_matches),
+        ("block_header", Some(arg_matches)) => get_block_header(arg_matches),
+        ("blocks", Some(arg_matches)) => blocks::main(arg_matches),
         ("merkle_tree", Some(arg_matches)) => get_merkle_tree(arg_matches),
+        ("merkle_root", Some(arg_matches)) => merkle::main(arg_matches),
         ("transaction", Some(tr_matches)) => {
             let id = tr_matches.value_of("ID").expect("required in cli.yml; qed");
-            transaction::get_transaction(id)
+            transaction::get_transaction(id, tr_matches.is_present("decode-input"), tr_matches.is_present("size"))?;
+            if tr_matches.is_present("confirm-count") {
+                let confirm_count = tr_matches
+                    .value_of("confirm-count")
+                    .map(|n| n.parse())
+                    .transpose()?
+                    .unwrap_or(transaction::DEFAULT_CONFIRM_COUNT);
+                let timeout = tr_matches
+                    .value_of("confirm-timeout")
+                    .expect("has default in cli.yml; qed")
+                    .parse()?;
+                transaction::wait_for_confirmations(id, confirm_count, Duration::from_secs(timeout))?;
+            }
+            Ok(())
         }
         ("transaction_info", Some(tr_matches)) => {
             let id = tr_matches.value_of("ID").expect("required in cli.yml; qed");
-            transaction::get_transaction_info(id)
+            transaction::get_transaction_info(
+                id,
+                tr_matches.is_present("usd"),
+                price::fiat_price_of(tr_matches)?.as_ref(),
+                tr_matches.value_of("expected-result"),
+            )
         }
+        ("trace", Some(arg_matches)) => trace::main(arg_matches),
+        ("solidity_block", Some(arg_matches)) => get_solidity_block(arg_matches.value_of("NUM")),
         ("account", Some(arg_matches)) => {
             let name = arg_matches.value_of("NAME").expect("required is cli.yml; qed");
-            get_account(name)
+            get_account(name, arg_matches.is_present("usd"))
         }
+        ("voted_witnesses", Some(arg_matches)) => votes::main(arg_matches),
         ("account_permission", Some(arg_matches)) => {
             let name = arg_matches.value_of("NAME").expect("required is cli.yml; qed");
             get_account_permission(name)
@@ -702,17 +979,32 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
             let name = arg_matches.value_of("NAME").expect("required is cli.yml; qed");
             get_account_resource(name)
         }
+        ("delegated_resource", Some(arg_matches)) => {
+            let from = arg_matches.value_of("FROM").expect("required in cli.yml; qed");
+            let to = arg_matches.value_of("TO").expect("required in cli.yml; qed");
+            delegation::get_delegated_resource(from, to)
+        }
+        ("delegated_resource_index", Some(arg_matches)) => {
+            let addr = arg_matches.value_of("ADDR").expect("required in cli.yml; qed");
+            delegation::get_delegated_resource_index(addr)
+        }
         ("contract", Some(arg_matches)) => {
             let addr = arg_matches.value_of("ADDR").expect("required is cli.yml; qed");
             contract::run(addr)
         }
+        ("contract_trigger_count", Some(arg_matches)) => trigger_count::main(arg_matches),
         ("proposal", Some(arg_matches)) => {
             let id = arg_matches.value_of("ID").expect("required in cli.yml; qed");
             get_proposal_by_id(&id)
         }
         ("asset", Some(arg_matches)) => {
             let id = arg_matches.value_of("ID").expect("required in cli.yml; qed");
-            get_asset_by_id(&id)
+            get_asset(&id, arg_matches.is_present("json"))
+        }
+        ("asset_balance", Some(arg_matches)) => {
+            let name = arg_matches.value_of("NAME").expect("required in cli.yml; qed");
+            let id = arg_matches.value_of("ASSET").expect("required in cli.yml; qed");
+            get_asset_balance(name, id)
         }
         ("reward", Some(arg_matches)) => {
             let addr = arg_matches.value_of("ADDR").expect("required in cli.yml; qed");
@@ -723,9 +1015,40 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
             get_brokerage_info(&addr)
         }
         ("maintenance", _) => get_next_maintenance_time(),
+        ("confirmed_block_count", _) => get_confirmed_block_count(),
+        ("account_diff", Some(arg_matches)) => account_diff::main(arg_matches),
+        ("assets", Some(arg_matches)) => paginated::get_assets(arg_matches),
+        ("witnesses", Some(arg_matches)) => paginated::get_witnesses(arg_matches),
+        ("balances", Some(arg_matches)) => balances::main(arg_matches),
+        ("price", Some(arg_matches)) => spot_price::main(arg_matches),
         _ => {
             eprintln!("{}", matches.usage());
             Err(Error::Runtime("error parsing command line"))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_node_info_block_num() {
+        assert_eq!(
+            parse_node_info_block_num(
+                "Num:24113460,ID:000000000170f334a2f85fa1e0ba84a7c4e4b1d6e3a1b0f0e6c51c3d0b4a1c2f"
+            ),
+            Some(24113460)
+        );
+        assert_eq!(parse_node_info_block_num(""), None);
+    }
+
+    #[test]
+    fn test_asset_balance_of() {
+        let mut account = Account::new();
+        account.mut_assetV2().insert("1002000".into(), 1_500_000);
+        assert_eq!(asset_balance_of(&account, "1002000"), 1_500_000);
+        assert_eq!(asset_balance_of(&account, "1000001"), 0);
+        assert_eq!(asset_balance_of(&Account::new(), "1002000"), 0);
+    }
+}
@@ -0,0 +1,128 @@
+//! Paginated listing of assets and witnesses.
+
+use clap::ArgMatches;
+use futures::executor;
+use log::info;
+use proto::api::{EmptyMessage, PaginatedMessage};
+use serde_json::json;
+
+use crate::error::Error;
+use crate::utils::client;
+use crate::utils::jsont;
+use crate::utils::trx;
+
+/// Page position, 1-based page number.
+struct Page {
+    page: usize,
+    page_size: usize,
+}
+
+impl Page {
+    fn from_matches(matches: &ArgMatches) -> Result<Self, Error> {
+        let page = matches
+            .value_of("page")
+            .expect("has default in cli.yml; qed")
+            .parse::<usize>()?;
+        let page_size = matches
+            .value_of("page-size")
+            .expect("has default in cli.yml; qed")
+            .parse::<usize>()?;
+        if page == 0 || page_size == 0 {
+            return Err(Error::Runtime("page and page size must be positive"));
+        }
+        Ok(Page { page, page_size })
+    }
+
+    fn offset(&self) -> usize {
+        (self.page - 1).saturating_mul(self.page_size)
+    }
+
+    /// Slice a full list. Pages past the end yield an empty list.
+    fn slice<T>(&self, items: Vec<T>) -> Vec<T> {
+        items.into_iter().skip(self.offset()).take(self.page_size).collect()
+    }
+}
+
+pub fn get_assets(matches: &ArgMatches) -> Result<(), Error> {
+    let page = Page::from_matches(matches)?;
+    let name_contains = matches.value_of("name-contains");
+    let issuer = matches
+        .value_of("issuer")
+        .map(trx::parse_address)
+        .transpose()?
+        .map(|addr| format!("0x{}", hex::encode(addr.as_bytes())));
+
+    let mut payload = if name_contains.is_none() && issuer.is_none() {
+        let mut req = PaginatedMessage::new();
+        req.set_offset(page.offset() as i64);
+        req.set_limit(page.page_size as i64);
+        executor::block_on(
+            client::GRPC_CLIENT
+                .get_paginated_asset_issue_list(Default::default(), req)
+                .drop_metadata(),
+        )?
+    } else {
+        executor::block_on(
+            client::GRPC_CLIENT
+                .get_asset_issue_list(Default::default(), EmptyMessage::new())
+                .drop_metadata(),
+        )?
+    };
+
+    let mut assets = payload
+        .take_assetIssue()
+        .into_iter()
+        .map(|pb| {
+            let mut asset = serde_json::to_value(&pb)?;
+            jsont::fix_asset_issue_contract(&mut asset);
+            Ok((pb, asset))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    if name_contains.is_some() || issuer.is_some() {
+        assets = assets
+            .into_iter()
+            .filter(|(_, asset)| {
+                name_contains.map_or(true, |pat| asset["name"].as_str().unwrap_or_default().contains(pat)) &&
+                    issuer
+                        .as_ref()
+                        .map_or(true, |addr| asset["owner_address"].as_str() == Some(addr))
+            })
+            .collect();
+        assets = page.slice(assets);
+    }
+
+    let (pbs, assets): (Vec<_>, Vec<_>) = assets.into_iter().unzip();
+    println!("{}", serde_json::to_string_pretty(&assets)?);
+    if !matches.is_present("json") {
+        pbs.iter().map(super::pprint_asset_summary).last();
+        info!("Page {}, {} assets", page.page, assets.len());
+    }
+    Ok(())
+}
+
+pub fn get_witnesses(matches: &ArgMatches) -> Result<(), Error> {
+    let page = Page::from_matches(matches)?;
+
+    let mut payload = executor::block_on(
+        client::GRPC_CLIENT
+            .list_witnesses(Default::default(), EmptyMessage::new())
+            .drop_metadata(),
+    )?;
+    let mut witnesses = payload.take_witnesses().into_vec();
+    witnesses.sort_by_key(|wit| -wit.get_voteCount());
+
+    let witnesses = page
+        .slice(witnesses)
+        .iter()
+        .map(|wit| {
+            let mut witness = serde_json::to_value(wit)?;
+            jsont::fix_hex_field(&mut witness, &["address"]);
+            Ok(witness)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    println!("{}", serde_json::to_string_pretty(&witnesses)?);
+    info!("Page {}, {} witnesses", page.page, witnesses.len());
+    Ok(())
+}
@@ -0,0 +1,240 @@
+//! Spot price of a TRC20 token from a SunSwap V2 pair, read on chain by constant calls.
+
+use clap::ArgMatches;
+use futures::executor;
+use futures::future;
+use keys::Address;
+
+use crate::error::Error;
+use crate::utils::abi::{self, AbiValue};
+use crate::utils::trc20;
+use crate::utils::trx;
+
+/// SunSwap V2 factory on mainnet, used to look up pairs by `getPair`.
+const SUNSWAP_V2_FACTORY: &str = "TKWJdrQkqHisa1X8HUdHEfREvTzw4pMAaY";
+
+/// Quote tokens accepted by symbol in `--against`. Pairs with TRX are WTRX pairs.
+const QUOTE_TOKENS: &[(&str, &str)] = &[
+    ("USDT", "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"),
+    ("TRX", "TNUC9Qb1rRpS5CbWLmNMxXBjyFoydXjWFR"),
+    ("WTRX", "TNUC9Qb1rRpS5CbWLmNMxXBjyFoydXjWFR"),
+];
+
+/// Decimals kept when formatting a price, trailing zeros trimmed.
+const PRICE_DECIMALS: u8 = 12;
+
+/// Reserves of a pair contract, in contract token order.
+struct Pool {
+    token0: Address,
+    token1: Address,
+    reserve0: ethabi::Uint,
+    reserve1: ethabi::Uint,
+}
+
+/// One side of a pool, with its token metadata.
+#[derive(Debug, Clone, PartialEq)]
+struct Side {
+    symbol: String,
+    decimals: u8,
+    reserve: ethabi::Uint,
+}
+
+impl Side {
+    fn format(&self, amount: &ethabi::Uint) -> String {
+        format!("{} {}", trc20::format_units(amount, self.decimals), self.symbol)
+    }
+}
+
+fn parse_token(name: &str) -> Result<Address, Error> {
+    match QUOTE_TOKENS
+        .iter()
+        .find(|(symbol, _)| symbol.eq_ignore_ascii_case(name))
+    {
+        Some((_, addr)) => trx::parse_address(addr),
+        None => trx::parse_address(name),
+    }
+}
+
+fn decode_values(ret: Option<Vec<u8>>, types: &[&str]) -> Option<Vec<AbiValue>> {
+    ret.and_then(|ret| abi::decode_params(types, &hex::encode(ret)).ok())
+}
+
+fn address_of(ret: Option<Vec<u8>>) -> Option<Address> {
+    match decode_values(ret, &["address"])?.into_iter().next()? {
+        AbiValue::Address(addr) => Some(addr),
+        _ => None,
+    }
+}
+
+/// Pair of two tokens from the factory, `None` if not created.
+async fn get_pair(factory: &Address, token_a: &Address, token_b: &Address) -> Result<Option<Address>, Error> {
+    let mut data = abi::fnhash("getPair(address,address)").to_vec();
+    data.extend(abi::encode_values(&[
+        AbiValue::Address(*token_a),
+        AbiValue::Address(*token_b),
+    ]));
+    Ok(address_of(trc20::call_constant_with_data(factory, data).await?).filter(|pair| *pair != Address::default()))
+}
+
+async fn fetch_pool(pair: &Address) -> Result<Pool, Error> {
+    let call = |method: &str| trc20::call_constant_with_data(pair, abi::fnhash(method).to_vec());
+    let (token0, token1, reserves) = future::join3(call("token0()"), call("token1()"), call("getReserves()")).await;
+
+    let reserves = decode_values(reserves?, &["uint112", "uint112", "uint32"]).unwrap_or_default();
+    match (address_of(token0?), address_of(token1?), &reserves[..]) {
+        (Some(token0), Some(token1), [AbiValue::Uint(reserve0), AbiValue::Uint(reserve1), _]) => Ok(Pool {
+            token0,
+            token1,
+            reserve0: *reserve0,
+            reserve1: *reserve1,
+        }),
+        _ => Err(Error::Runtime("not a SunSwap V2 pair contract")),
+    }
+}
+
+/// Price of one `base` in `quote` units, scaled by `PRICE_DECIMALS`. `None` if either reserve is empty.
+fn spot_price(base: &Side, quote: &Side) -> Result<Option<ethabi::Uint>, Error> {
+    if base.reserve.is_zero() || quote.reserve.is_zero() {
+        return Ok(None);
+    }
+    let exp10 = |n: u8| ethabi::Uint::from(10).checked_pow(n.into());
+    let numerator = exp10(base.decimals.saturating_add(PRICE_DECIMALS)).and_then(|e| quote.reserve.checked_mul(e));
+    let denominator = exp10(quote.decimals).and_then(|e| base.reserve.checked_mul(e));
+    match (numerator, denominator) {
+        (Some(numerator), Some(denominator)) => Ok(Some(numerator / denominator)),
+        _ => Err(Error::Runtime("token decimals out of range")),
+    }
+}
+
+fn render(pool: &Address, base: &Side, quote: &Side) -> Result<String, Error> {
+    let mut out = format!("Pool:      {} ({}/{})\n", pool, base.symbol, quote.symbol);
+    match (spot_price(base, quote)?, spot_price(quote, base)?) {
+        (Some(price), Some(inverse)) => {
+            out += &format!(
+                "Price:     1 {} = {} {}\n",
+                base.symbol,
+                trc20::format_units(&price, PRICE_DECIMALS),
+                quote.symbol
+            );
+            out += &format!(
+                "           1 {} = {} {}\n",
+                quote.symbol,
+                trc20::format_units(&inverse, PRICE_DECIMALS),
+                base.symbol
+            );
+        }
+        _ => out += "Price:     no liquidity\n",
+    }
+    // both sides are of equal value in a constant product pool
+    let depth = quote.reserve.saturating_mul(2.into());
+    out += &format!(
+        "Liquidity: {} + {} (≈ {})\n",
+        base.format(&base.reserve),
+        quote.format(&quote.reserve),
+        quote.format(&depth)
+    );
+    Ok(out)
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let base = trx::parse_address(matches.value_of("TOKEN").expect("required in cli.yml; qed"))?;
+    let quote = parse_token(matches.value_of("against").expect("has default in cli.yml; qed"))?;
+    if base == quote {
+        return Err(Error::Runtime("can not price a token against itself"));
+    }
+
+    executor::block_on(async {
+        let pool_address = match matches.value_of("pool") {
+            Some(addr) => trx::parse_address(addr)?,
+            None => get_pair(&trx::parse_address(SUNSWAP_V2_FACTORY)?, &base, &quote)
+                .await?
+                .ok_or(Error::Runtime("no SunSwap V2 pool for the pair, use --pool"))?,
+        };
+
+        let (pool, base_meta, quote_meta) = future::join3(
+            fetch_pool(&pool_address),
+            trc20::fetch_token_meta(&base),
+            trc20::fetch_token_meta(&quote),
+        )
+        .await;
+        let pool = pool?;
+        let (base_reserve, quote_reserve) = if (pool.token0, pool.token1) == (base, quote) {
+            (pool.reserve0, pool.reserve1)
+        } else if (pool.token0, pool.token1) == (quote, base) {
+            (pool.reserve1, pool.reserve0)
+        } else {
+            return Err(Error::Runtime("pool is not a pair of the token and --against"));
+        };
+
+        // without decimals the reserves can not be scaled, a price off by 10^n is worse than none
+        let side = |meta: trc20::TokenMeta, reserve| -> Result<Side, Error> {
+            Ok(Side {
+                decimals: meta
+                    .decimals
+                    .ok_or(Error::Runtime("token does not implement decimals(), can not price it"))?,
+                symbol: meta.symbol.unwrap_or_else(|| "TOKEN".into()),
+                reserve,
+            })
+        };
+        print!(
+            "{}",
+            render(
+                &pool_address,
+                &side(base_meta?, base_reserve)?,
+                &side(quote_meta?, quote_reserve)?
+            )?
+        );
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side(symbol: &str, decimals: u8, reserve: u64) -> Side {
+        Side {
+            symbol: symbol.into(),
+            decimals,
+            reserve: reserve.into(),
+        }
+    }
+
+    #[test]
+    fn test_spot_price_decimals() {
+        // 2_000 SUN (18 decimals) against 100 USDT (6 decimals)
+        let sun = Side {
+            reserve: ethabi::Uint::from(2_000) * ethabi::Uint::exp10(18),
+            ..side("SUN", 18, 0)
+        };
+        let usdt = side("USDT", 6, 100_000_000);
+        let price = spot_price(&sun, &usdt).unwrap().unwrap();
+        assert_eq!(trc20::format_units(&price, PRICE_DECIMALS), "0.05");
+        let inverse = spot_price(&usdt, &sun).unwrap().unwrap();
+        assert_eq!(trc20::format_units(&inverse, PRICE_DECIMALS), "20");
+    }
+
+    #[test]
+    fn test_render_no_liquidity() {
+        let pool = "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap();
+        let out = render(&pool, &side("WBTT", 6, 0), &side("USDT", 6, 0)).unwrap();
+        assert!(out.contains("Price:     no liquidity\n"));
+        assert!(out.ends_with("Liquidity: 0 WBTT + 0 USDT (≈ 0 USDT)\n"));
+
+        let out = render(&pool, &side("WBTT", 6, 3_000_000), &side("USDT", 6, 1_500_000)).unwrap();
+        assert!(out.contains("Price:     1 WBTT = 0.5 USDT\n           1 USDT = 2 WBTT\n"));
+        assert!(out.ends_with("Liquidity: 3 WBTT + 1.5 USDT (≈ 3 USDT)\n"));
+    }
+
+    #[test]
+    fn test_parse_token() {
+        assert_eq!(
+            parse_token("trx").unwrap().to_string(),
+            "TNUC9Qb1rRpS5CbWLmNMxXBjyFoydXjWFR"
+        );
+        assert_eq!(
+            parse_token("TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt").unwrap().to_string(),
+            "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt"
+        );
+    }
+}
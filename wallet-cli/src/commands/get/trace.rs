@@ -0,0 +1,349 @@
+//! Execution trace of a transaction, internal transactions and event logs as a call tree.
+//!
+//! The node reports internal transactions and logs as flat lists, without call nesting. The tree is rebuilt by
+//! a heuristic:
+//!
+//! - Internal transactions are in execution order. Each is nested under the most recent open call whose callee is
+//!   its caller, calls returned from are closed. A call whose caller is not found is placed under the root call.
+//! - A log is grouped under the last call, in execution order, whose callee is the emitting contract, or under the
+//!   root call.
+//!
+//! Delegate calls and callbacks into a contract already on the stack can be misplaced. `--flat` shows the lists as
+//! reported.
+
+use clap::ArgMatches;
+use keys::Address;
+use log::info;
+use proto::core::{
+    InternalTransaction, TransactionInfo, Transaction_Contract_ContractType as ContractType, TriggerSmartContract,
+};
+use std::convert::TryFrom;
+
+use super::transaction::{self, DecodedLog};
+use crate::error::Error;
+use crate::utils::client;
+use crate::utils::trx;
+
+/// A call in the trace tree.
+#[derive(Debug, PartialEq)]
+struct TraceNode {
+    caller: Address,
+    callee: Address,
+    /// `call`, `create`, `suicide`, or empty for the transaction itself.
+    note: String,
+    /// Amounts transferred, by token id. An empty token id is TRX in SUN.
+    values: Vec<(String, i64)>,
+    rejected: bool,
+    /// Indices of logs emitted by the callee, in `TransactionInfo.log`.
+    logs: Vec<usize>,
+    children: Vec<TraceNode>,
+}
+
+impl TraceNode {
+    fn new(caller: Address, callee: Address, note: String, values: Vec<(String, i64)>, rejected: bool) -> Self {
+        TraceNode {
+            caller,
+            callee,
+            note,
+            values,
+            rejected,
+            logs: vec![],
+            children: vec![],
+        }
+    }
+
+    fn descendants(&self) -> usize {
+        self.children.iter().map(|child| 1 + child.descendants()).sum()
+    }
+}
+
+/// Internal transactions use 21-byte addresses, logs 20-byte TVM addresses.
+fn address_of(raw: &[u8]) -> Result<Address, Error> {
+    if raw.len() == 20 {
+        Ok(Address::from_tvm_bytes(raw))
+    } else {
+        Ok(Address::try_from(raw)?)
+    }
+}
+
+fn node_of_internal_transaction(txn: &InternalTransaction) -> Result<TraceNode, Error> {
+    let values = txn
+        .get_callValueInfo()
+        .iter()
+        .filter(|info| info.get_callValue() != 0)
+        .map(|info| (info.get_tokenId().to_owned(), info.get_callValue()))
+        .collect();
+    Ok(TraceNode::new(
+        address_of(txn.get_caller_address())?,
+        address_of(txn.get_transferTo_address())?,
+        String::from_utf8_lossy(txn.get_note()).into_owned(),
+        values,
+        txn.get_rejected(),
+    ))
+}
+
+/// Build the call tree under `root`, see the module doc for the heuristic.
+fn build_tree(mut root: TraceNode, calls: Vec<TraceNode>, log_addresses: &[Address]) -> TraceNode {
+    // arena of calls, parent index of each, the root is 0
+    let mut nodes = vec![];
+    let mut parents = vec![None];
+    let mut stack = vec![0];
+    let mut callees = vec![root.callee];
+    for call in calls {
+        while stack.len() > 1 && callees[*stack.last().unwrap()] != call.caller {
+            stack.pop();
+        }
+        parents.push(stack.last().copied());
+        callees.push(call.callee);
+        stack.push(nodes.len() + 1);
+        nodes.push(Some(call));
+    }
+
+    for (i, addr) in log_addresses.iter().enumerate() {
+        match callees.iter().rposition(|callee| callee == addr) {
+            Some(idx) if idx > 0 => nodes[idx - 1].as_mut().unwrap().logs.push(i),
+            _ => root.logs.push(i),
+        }
+    }
+
+    // attach children bottom-up, a child always comes after its parent
+    for idx in (1..=nodes.len()).rev() {
+        let node = nodes[idx - 1].take().unwrap();
+        match parents[idx] {
+            Some(0) | None => root.children.push(node),
+            Some(parent) => nodes[parent - 1].as_mut().unwrap().children.push(node),
+        }
+    }
+    reverse_children(&mut root);
+    root
+}
+
+fn reverse_children(node: &mut TraceNode) {
+    node.children.reverse();
+    node.children.iter_mut().for_each(reverse_children);
+}
+
+fn format_address(addr: &Address) -> String {
+    match addr.to_well_known_name() {
+        Some(name) => format!("{} {}", addr, name),
+        None => addr.to_string(),
+    }
+}
+
+fn format_values(values: &[(String, i64)]) -> String {
+    values
+        .iter()
+        .map(|(token_id, amount)| {
+            if token_id.is_empty() {
+                trx::format_amount_with_surfix(*amount, "TRX", 6)
+            } else {
+                format!("{} of TRC10 {}", trx::format_number(amount), token_id)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_call(node: &TraceNode) -> String {
+    let mut line = format!("{} -> {}", format_address(&node.caller), format_address(&node.callee));
+    if !node.note.is_empty() {
+        line += &format!(" [{}]", node.note);
+    }
+    if !node.values.is_empty() {
+        line += &format!(" {}", format_values(&node.values));
+    }
+    if node.rejected {
+        line += " REJECTED";
+    }
+    line
+}
+
+/// Render the tree, one line per call or event, indented by depth. Calls deeper than `max_depth` are elided.
+fn render(
+    node: &TraceNode,
+    depth: usize,
+    max_depth: Option<usize>,
+    describe_log: &dyn Fn(usize) -> String,
+    lines: &mut Vec<String>,
+) {
+    let indent = "  ".repeat(depth);
+    lines.push(format!("{}{}", indent, format_call(node)));
+
+    for &i in &node.logs {
+        lines.push(format!("{}  * {}", indent, describe_log(i)));
+    }
+    if max_depth.map_or(false, |max_depth| depth >= max_depth) {
+        if !node.children.is_empty() {
+            lines.push(format!("{}  ... ({} calls below)", indent, node.descendants()));
+        }
+        return;
+    }
+    for child in &node.children {
+        render(child, depth + 1, max_depth, describe_log, lines);
+    }
+}
+
+fn describe_decoded_log(log: &DecodedLog) -> String {
+    match log.event {
+        Some(ref event) => format!(
+            "{}({})",
+            event.event_pretty.split('(').next().unwrap_or_default(),
+            event
+                .params
+                .iter()
+                .map(|param| format!("{}={}", param.name, param.value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => describe_raw_log(&log.topics),
+    }
+}
+
+/// An undecoded log by its first topic, the event signature of non-anonymous events.
+fn describe_raw_log(topics: &[Vec<u8>]) -> String {
+    match topics.first() {
+        Some(topic) => format!("topic[0] {} (ABI not found)", hex::encode(topic)),
+        None => "(no topics)".to_owned(),
+    }
+}
+
+/// The transaction itself, owner to the called contract with its call value.
+fn root_of(info: &TransactionInfo, txid: &[u8]) -> Result<TraceNode, Error> {
+    let txn = client::get_transaction_by_id(txid)?;
+    let contract = txn
+        .get_raw_data()
+        .get_contract()
+        .get(0)
+        .ok_or(Error::Runtime("transaction has no contract"))?;
+    let owner = trx::extract_owner_address_from_parameter(contract.get_parameter())?;
+
+    let mut values = vec![];
+    if contract.get_field_type() == ContractType::TriggerSmartContract {
+        let trigger: TriggerSmartContract = protobuf::parse_from_bytes(contract.get_parameter().get_value())?;
+        if trigger.get_call_value() != 0 {
+            values.push((String::new(), trigger.get_call_value()));
+        }
+        if trigger.get_call_token_value() != 0 {
+            values.push((trigger.get_token_id().to_string(), trigger.get_call_token_value()));
+        }
+    }
+    let callee = if info.get_contract_address().is_empty() {
+        owner
+    } else {
+        address_of(info.get_contract_address())?
+    };
+    Ok(TraceNode::new(owner, callee, String::new(), values, false))
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let txid = transaction::parse_txid(matches.value_of("ID").expect("required in cli.yml; qed"))?;
+    let max_depth = matches.value_of("depth").map(str::parse).transpose()?;
+
+    let info = client::get_transaction_info_by_id(&txid)?;
+    if info.get_id().is_empty() {
+        return Err(Error::NotFound("transaction not found"));
+    }
+    let logs = info
+        .get_log()
+        .iter()
+        .map(|log| transaction::decode_contract_log(log).map(describe_decoded_log))
+        .collect::<Vec<_>>();
+    let describe_log = |i: usize| match logs[i] {
+        Ok(ref desc) => desc.clone(),
+        Err(_) => describe_raw_log(info.get_log()[i].get_topics()),
+    };
+    let calls = info
+        .get_internal_transactions()
+        .iter()
+        .map(node_of_internal_transaction)
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    if matches.is_present("flat") {
+        for (i, call) in calls.iter().enumerate() {
+            info!("Call#{} {}", i, format_call(call));
+        }
+        for (i, log) in info.get_log().iter().enumerate() {
+            info!(
+                "Event#{} {} {}",
+                i,
+                format_address(&address_of(log.get_address())?),
+                describe_log(i)
+            );
+        }
+        return Ok(());
+    }
+
+    let log_addresses = info
+        .get_log()
+        .iter()
+        .map(|log| address_of(log.get_address()))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let tree = build_tree(root_of(&info, &txid)?, calls, &log_addresses);
+    let mut lines = vec![];
+    render(&tree, 0, max_depth, &describe_log, &mut lines);
+    for line in lines {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(i: u8) -> Address {
+        Address::from_tvm_bytes(&[i; 20])
+    }
+
+    fn call(caller: u8, callee: u8) -> TraceNode {
+        TraceNode::new(addr(caller), addr(callee), "call".into(), vec![], false)
+    }
+
+    #[test]
+    fn test_describe_raw_log() {
+        assert_eq!(describe_raw_log(&[]), "(no topics)");
+        assert_eq!(
+            describe_raw_log(&[vec![0xdd, 0xf2], vec![0]]),
+            "topic[0] ddf2 (ABI not found)"
+        );
+    }
+
+    #[test]
+    fn test_build_tree() {
+        // 1 calls 2, 2 calls 3 and 4, 3 calls 5, then 2 calls 3 again
+        let root = TraceNode::new(addr(1), addr(2), String::new(), vec![], false);
+        let calls = vec![call(2, 3), call(3, 5), call(2, 4), call(2, 3)];
+        let tree = build_tree(root, calls, &[addr(5), addr(3), addr(9)]);
+
+        assert_eq!(tree.logs, vec![2]);
+        let callees = tree.children.iter().map(|child| child.callee).collect::<Vec<_>>();
+        assert_eq!(callees, vec![addr(3), addr(4), addr(3)]);
+        assert_eq!(tree.children[0].children[0].callee, addr(5));
+        assert_eq!(tree.children[0].children[0].logs, vec![0]);
+        assert!(tree.children[0].logs.is_empty());
+        assert_eq!(tree.children[2].logs, vec![1]);
+        assert_eq!(tree.descendants(), 4);
+    }
+
+    #[test]
+    fn test_render_depth() {
+        let mut root = TraceNode::new(addr(1), addr(2), String::new(), vec![(String::new(), 1_500_000)], false);
+        let mut child = call(2, 3);
+        child.children.push(call(3, 4));
+        child.children[0].rejected = true;
+        root.children.push(child);
+        root.logs.push(0);
+
+        let mut lines = vec![];
+        render(&root, 0, None, &|i| format!("log{}", i), &mut lines);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], format!("{} -> {} 1.5 TRX", addr(1), addr(2)));
+        assert_eq!(lines[1], "  * log0");
+        assert_eq!(lines[3], format!("    {} -> {} [call] REJECTED", addr(3), addr(4)));
+
+        let mut lines = vec![];
+        render(&root, 0, Some(1), &|i| format!("log{}", i), &mut lines);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[3], "    ... (1 calls below)");
+    }
+}
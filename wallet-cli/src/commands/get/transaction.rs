@@ -1,75 +1,298 @@
 use std::convert::TryFrom;
-use std::str;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use chrono::{Local, TimeZone, Utc};
+use chrono::{NaiveDateTime, Utc};
 use futures::executor;
+use futures::future::{self, FutureExt, LocalBoxFuture};
 use itertools::Itertools;
 use keys::Address;
-use proto::api::BytesMessage;
+use log::{info, warn};
 use proto::core::{
-    TransactionInfo_Log as Log, Transaction_Contract_ContractType as ContractType,
+    AccountCreateContract, AccountType, SmartContract_ABI_Entry as AbiEntry, Transaction, TransactionInfo_Log as Log,
+    TransactionInfo_code as TransactionInfoCode, Transaction_Contract_ContractType as ContractType,
     Transaction_Result_code as ResultCode, Transaction_Result_contractResult as ContractResult,
 };
-use protobuf::Message;
+use protobuf::{Message, ProtobufEnum};
+use serde_json::json;
 
 use crate::error::Error;
-use crate::utils::abi;
+use crate::utils::abi::{self, AbiValue};
 use crate::utils::client;
 use crate::utils::crypto;
 use crate::utils::jsont;
+use crate::utils::price::{self, FiatPrice};
+use crate::utils::record::{self, RecordKind};
+use crate::utils::time;
+use crate::utils::trc20::{self, TokenMeta, Trc20Transfer};
+use crate::utils::trc721::{self, Trc721Call};
 use crate::utils::trx;
 
-pub fn get_transaction(id: &str) -> Result<(), Error> {
-    let mut req = BytesMessage::new();
-    req.value = parse_hex(id)?;
+/// Network lookups used to annotate a contract call.
+pub trait ContractLookup {
+    fn contract_abi<'a>(&'a self, contract: &'a Address) -> LocalBoxFuture<'a, Result<Vec<AbiEntry>, Error>>;
+    fn is_trc721<'a>(&'a self, contract: &'a Address) -> LocalBoxFuture<'a, Result<bool, Error>>;
+    fn token_meta<'a>(&'a self, token: &'a Address) -> LocalBoxFuture<'a, Result<TokenMeta, Error>>;
+}
+
+/// Lookups through the gRPC client, token meta and TRC721 checks are cached.
+pub struct GrpcLookup;
+
+impl ContractLookup for GrpcLookup {
+    fn contract_abi<'a>(&'a self, contract: &'a Address) -> LocalBoxFuture<'a, Result<Vec<AbiEntry>, Error>> {
+        trx::fetch_contract_abi(contract).boxed_local()
+    }
+
+    fn is_trc721<'a>(&'a self, contract: &'a Address) -> LocalBoxFuture<'a, Result<bool, Error>> {
+        trc721::fetch_is_trc721(contract).boxed_local()
+    }
+
+    fn token_meta<'a>(&'a self, token: &'a Address) -> LocalBoxFuture<'a, Result<TokenMeta, Error>> {
+        trc20::fetch_token_meta(token).boxed_local()
+    }
+}
+
+/// A transaction fetched from chain, with everything `get transaction` annotates.
+#[derive(Debug)]
+pub struct TransactionDetail {
+    /// The normalized transaction JSON, as printed to stdout.
+    pub transaction: serde_json::Value,
+    /// Raw `raw_data.data`, the memo.
+    pub data: Vec<u8>,
+    /// Timestamp in millis, `None` if it is in the future and can not be trusted.
+    pub timestamp: Option<i64>,
+    /// Expiration in millis.
+    pub expiration: i64,
+    pub sender: Address,
+    /// Type of the first contract in the transaction.
+    pub contract_type: ContractType,
+    /// Result in `Transaction.ret`, `None` if pending or unknown, when some nodes return no ret entry.
+    pub result: Option<ResultCode>,
+    /// Only available for successful `TriggerSmartContract`.
+    pub contract_call: Option<ContractCall>,
+    /// Bandwidth consumed, `Transaction.ret` excluded.
+    pub bandwidth: usize,
+    /// Parts of the serialized size that make up `bandwidth`.
+    pub size: SizeBreakdown,
+}
+
+/// Serialized size of a transaction, `Transaction.ret` excluded.
+#[derive(Debug, PartialEq)]
+pub struct SizeBreakdown {
+    pub raw_data: usize,
+    pub signatures: Vec<usize>,
+    /// Field tags and length prefixes.
+    pub overhead: usize,
+}
+
+impl SizeBreakdown {
+    pub fn of(txn: &Transaction) -> Self {
+        let raw_data = txn.get_raw_data().compute_size() as usize;
+        let signatures = txn.get_signature().iter().map(Vec::len).collect::<Vec<_>>();
+        let overhead = trx::bandwidth_with_result_reserve(txn, 0) - raw_data - signatures.iter().sum::<usize>();
+        SizeBreakdown {
+            raw_data,
+            signatures,
+            overhead,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.raw_data + self.signatures.iter().sum::<usize>() + self.overhead
+    }
+
+    /// Bandwidth consumed, with the reserved result size.
+    pub fn bandwidth(&self) -> usize {
+        self.total() + trx::result_reserve()
+    }
+}
+
+/// A smart contract call in a transaction.
+#[derive(Debug)]
+pub struct ContractCall {
+    pub contract_address: Address,
+    pub contract_result: ContractResult,
+    /// Decoded call data, fails when the ABI is not available.
+    pub decoded: Result<DecodedCall, Error>,
+    /// Set when the call is a TRC20 transfer or transferFrom.
+    pub trc20_transfer: Option<Trc20Transfer>,
+    /// Set when the contract implements TRC721 and the call is one of its transfer or approval methods.
+    pub nft_call: Option<Trc721Call>,
+}
+
+/// Call data decoded with the contract ABI.
+#[derive(Debug, Clone)]
+pub struct DecodedCall {
+    /// Human readable method signature.
+    pub method_pretty: String,
+    /// Canonical method signature, used to calculate the selector.
+    pub method: String,
+    pub selector: [u8; 4],
+    pub arguments: Vec<DecodedParam>,
+}
+
+impl DecodedCall {
+    /// Machine-readable arguments, as an array of `{name, type, value}`.
+    pub fn to_json(&self) -> serde_json::Value {
+        self.arguments
+            .iter()
+            .map(|param| {
+                json!({
+                    "name": param.name,
+                    "type": param.ty,
+                    "value": param.value,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A decoded ABI parameter.
+#[derive(Debug, Clone)]
+pub struct DecodedParam {
+    pub name: String,
+    pub ty: String,
+    pub indexed: bool,
+    pub value: AbiValue,
+}
 
-    let mut payload = executor::block_on(
-        client::GRPC_CLIENT
-            .get_transaction_by_id(Default::default(), req)
-            .drop_metadata(),
-    )?;
+/// A transaction receipt fetched from chain, with everything `get transaction_info` annotates.
+#[derive(Debug)]
+pub struct TransactionInfoDetail {
+    /// The normalized transaction info JSON, as printed to stdout.
+    pub info: serde_json::Value,
+    /// Block timestamp in millis.
+    pub block_timestamp: i64,
+    pub net_usage: i64,
+    pub net_fee: i64,
+    pub energy_usage: i64,
+    pub energy_fee: i64,
+    pub origin_energy_usage: i64,
+    pub energy_usage_total: i64,
+    pub fee: i64,
+    /// Type of the first contract in the transaction.
+    pub contract_type: Option<ContractType>,
+    /// The new account and its type, for an `AccountCreateContract`.
+    pub created_account: Option<(Address, AccountType)>,
+    pub contract_result: ContractResult,
+    /// `TransactionInfo.result` is `FAILED`.
+    pub failed: bool,
+    pub contract_address: Option<Address>,
+    /// Current `consume_user_resource_percent` of the called contract, `None` if it can not be fetched.
+    pub user_resource_percent: Option<i64>,
+    /// Revert reason, decoded by `trx::decode_revert_reason`.
+    pub revert_message: Option<String>,
+    pub logs: Vec<DecodedLog>,
+}
+
+/// Energy of a contract call, split between the caller and the contract owner.
+#[derive(Debug, PartialEq)]
+pub struct EnergySplit {
+    /// Paid by the caller, frozen energy or TRX burnt.
+    pub caller: i64,
+    /// Subsidized by the contract owner, `origin_energy_usage`.
+    pub owner: i64,
+    pub total: i64,
+}
+
+impl EnergySplit {
+    /// `None` if no energy is used.
+    pub fn of(origin_energy_usage: i64, energy_usage_total: i64) -> Option<Self> {
+        if energy_usage_total <= 0 {
+            return None;
+        }
+        Some(EnergySplit {
+            caller: energy_usage_total - origin_energy_usage,
+            owner: origin_energy_usage,
+            total: energy_usage_total,
+        })
+    }
+
+    fn percent(&self, energy: i64) -> f64 {
+        energy as f64 * 100.0 / self.total as f64
+    }
+
+    /// Lines of the breakdown. The owner pays `100 - user_resource_percent` of the energy, capped by the
+    /// contract's `origin_energy_limit`, so the actual split can differ from the setting.
+    pub fn describe(&self, user_resource_percent: Option<i64>) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "Energy Paid by Caller: {} ({:.1}%)",
+                trx::format_number(self.caller),
+                self.percent(self.caller)
+            ),
+            format!(
+                "Energy Subsidized by Contract Owner: {} ({:.1}%)",
+                trx::format_number(self.owner),
+                self.percent(self.owner)
+            ),
+            format!("Total Energy: {}", trx::format_number(self.total)),
+        ];
+        if let Some(percent) = user_resource_percent {
+            lines.push(format!(
+                "  contract setting: caller pays {}%, owner {}% up to origin_energy_limit",
+                percent,
+                100 - percent
+            ));
+        }
+        lines
+    }
+}
+
+/// An event log, decoded when the emitting contract's ABI is known.
+#[derive(Debug, Clone)]
+pub struct DecodedLog {
+    pub address: Address,
+    pub topics: Vec<Vec<u8>>,
+    pub data: Vec<u8>,
+    pub event: Option<DecodedEvent>,
+}
 
+/// An event decoded with the contract ABI.
+#[derive(Debug, Clone)]
+pub struct DecodedEvent {
+    pub event_pretty: String,
+    pub params: Vec<DecodedParam>,
+}
+
+/// Fetch a transaction and collect its annotations, without printing.
+///
+/// ```no_run
+/// let detail = wallet_cli::get_transaction_detail(
+///     "0x26ff621bcb2c74da8a14c77c20d55cbad8cb0ea022e1174d9522c283c133355c",
+/// )?;
+/// assert!(detail.transaction["raw_data"].is_object());
+/// println!("sent by {}, bandwidth {}", detail.sender, detail.bandwidth);
+/// # Ok::<(), wallet_cli::Error>(())
+/// ```
+pub fn get_transaction_detail(id: &str) -> Result<TransactionDetail, Error> {
+    transaction_detail_of(fetch_transaction(&parse_txid(id)?)?)
+}
+
+fn transaction_detail_of(payload: Transaction) -> Result<TransactionDetail, Error> {
     let mut transaction = serde_json::to_value(&payload)?;
     if transaction["raw_data"].is_null() {
-        return Err(Error::Runtime("transaction not found"));
+        return Err(Error::NotFound("transaction not found"));
     }
     jsont::fix_transaction(&mut transaction)?;
-    println!("{}", serde_json::to_string_pretty(&transaction).unwrap());
-
-    if !payload.get_raw_data().get_data().is_empty() {
-        eprintln!(
-            "! Data: {:?}",
-            String::from_utf8_lossy(payload.get_raw_data().get_data())
-        );
-    }
 
-    if payload.get_raw_data().timestamp < Utc::now().timestamp_millis() {
-        eprintln!(
-            "! Timestamp: {}",
-            Local.timestamp(
-                payload.get_raw_data().timestamp / 1_000,
-                (payload.get_raw_data().timestamp % 1_000 * 1_000_000) as _
-            )
-        );
+    let timestamp = if payload.get_raw_data().timestamp < Utc::now().timestamp_millis() {
+        Some(payload.get_raw_data().timestamp)
     } else {
-        eprintln!("! Timestamp: N/A");
-    }
-    eprintln!(
-        "! Expiration: {}",
-        Local.timestamp(
-            payload.get_raw_data().expiration / 1_000,
-            (payload.get_raw_data().expiration % 1_000 * 1_000_000) as _
-        )
-    );
-
-    let sender = trx::extract_owner_address_from_parameter(payload.get_raw_data().get_contract()[0].get_parameter())?;
-    eprintln!("! Sender Address(base58check):   {}", sender);
+        None
+    };
 
-    // eprintln!("Raw data => {}", hex::encode(payload.get_raw_data().write_to_bytes()?));
+    let contract = payload
+        .get_raw_data()
+        .get_contract()
+        .first()
+        .ok_or(Error::Runtime("transaction has no contract"))?;
+    let sender = trx::extract_owner_address_from_parameter(contract.get_parameter())?;
+    let contract_type = contract.get_field_type();
+    let ret = payload.get_ret().first();
+    let result = ret.map(|ret| ret.get_ret());
 
-    if payload.get_raw_data().get_contract()[0].get_field_type() == ContractType::TriggerSmartContract &&
-        payload.get_ret()[0].get_ret() == ResultCode::SUCESS
-    {
+    let contract_call = if contract_type == ContractType::TriggerSmartContract && result == Some(ResultCode::SUCESS) {
         let contract_address = transaction["raw_data"]["contract"][0]["parameter"]["value"]["contract_address"]
             .as_str()
             .ok_or(Error::Runtime("unreachable field"))
@@ -77,141 +300,419 @@ pub fn get_transaction(id: &str) -> Result<(), Error> {
         let data = transaction["raw_data"]["contract"][0]["parameter"]["value"]["data"]
             .as_str()
             .unwrap();
-        eprintln!(
-            "! Contract Address(base58check): {} {}",
+        let raw_data = jsont::hex_string_to_bytes(data)?;
+        Some(executor::block_on(lookup_contract_call(
+            &GrpcLookup,
+            &sender,
             contract_address,
-            contract_address.to_well_known_name().unwrap_or_default()
-        );
-        eprintln!("! Contract result: {:?}", payload.get_ret()[0].get_contractRet());
-        pprint_contract_call_data(&contract_address, data)?;
+            ret.map(|ret| ret.get_contractRet()).unwrap_or_default(),
+            &raw_data,
+        )))
+    } else {
+        None
+    };
+
+    let data = payload.get_raw_data().get_data().to_owned();
+    let expiration = payload.get_raw_data().expiration;
+
+    let size = SizeBreakdown::of(&payload);
+    let bandwidth = trx::bandwidth_of(&payload);
+
+    Ok(TransactionDetail {
+        transaction,
+        data,
+        timestamp,
+        expiration,
+        sender,
+        contract_type,
+        result,
+        contract_call,
+        bandwidth,
+        size,
+    })
+}
+
+pub fn get_transaction(id: &str, decode_input: bool, show_size: bool) -> Result<(), Error> {
+    let mut detail = get_transaction_detail(id)?;
+    if decode_input {
+        if let Some(Ok(ref decoded)) = detail.contract_call.as_ref().map(|call| call.decoded.as_ref()) {
+            detail.transaction["decoded_input"] = decoded.to_json();
+        }
     }
+    println!("{}", serde_json::to_string_pretty(&detail.transaction).unwrap());
+
+    trx::print_memo("Data", &detail.data);
 
-    // NOTE: when calculating bandwidth, `Transaction.ret` must be excluded.
-    payload.clear_ret();
-    eprintln!(
-        "! Bandwidth: {}",
-        payload.compute_size() as usize + trx::MAX_RESULT_SIZE_IN_TX
+    if let Some(timestamp) = detail.timestamp {
+        info!(
+            "Timestamp: {}",
+            time::format_timestamp(timestamp / 1_000, (timestamp % 1_000 * 1_000_000) as _)
+        );
+    } else {
+        info!("Timestamp: N/A");
+    }
+    info!(
+        "Expiration: {}",
+        time::format_timestamp(detail.expiration / 1_000, (detail.expiration % 1_000 * 1_000_000) as _)
     );
 
+    info!("Contract Type: {}", trx::format_contract_type(detail.contract_type));
+    info!("Sender Address(base58check):   {}", detail.sender);
+    if detail.result.is_none() {
+        info!("Result: pending/unknown");
+    }
+
+    if let Some(call) = detail.contract_call {
+        info!(
+            "Contract Address(base58check): {} {}",
+            call.contract_address,
+            call.contract_address.to_well_known_name().unwrap_or_default()
+        );
+        info!("Contract result: {:?}", call.contract_result);
+        if let Some(ref transfer) = call.trc20_transfer {
+            info!("TRC20 Transfer: {}", trc20::summarize_transfer(transfer)?);
+        }
+        pprint_contract_call_data(call);
+    }
+
+    info!("Bandwidth: {}", trx::format_number(detail.bandwidth));
+    if show_size {
+        let size = &detail.size;
+        info!("  raw_data: {}", size.raw_data);
+        for (i, len) in size.signatures.iter().enumerate() {
+            info!("  signature #{}: {}", i, len);
+        }
+        info!("  overhead: {}", size.overhead);
+        info!("  result reserve: {}", trx::result_reserve());
+    }
+
     Ok(())
 }
 
-pub fn get_transaction_info(id: &str) -> Result<(), Error> {
-    let mut req = BytesMessage::new();
-    req.value = parse_hex(id)?;
+/// Confirmations for finality, one super-majority round of SRs.
+pub const DEFAULT_CONFIRM_COUNT: i64 = 19;
 
-    let payload = executor::block_on(
-        client::GRPC_CLIENT
-            .get_transaction_info_by_id(Default::default(), req)
-            .drop_metadata(),
-    )?;
+/// Wait until the transaction is `confirm_count` blocks deep, polling every 3 secs.
+pub fn wait_for_confirmations(id: &str, confirm_count: i64, timeout: Duration) -> Result<(), Error> {
+    let started = Instant::now();
+    let mut last_confirmations = -1;
+
+    loop {
+        let info = client::get_transaction_info_by_id(&parse_txid(id)?)?;
+
+        // not yet in a block if id is empty
+        if !info.get_id().is_empty() {
+            let block = executor::block_on(
+                client::GRPC_CLIENT
+                    .get_now_block2(Default::default(), Default::default())
+                    .drop_metadata(),
+            )?;
+            let confirmations = block.get_block_header().get_raw_data().number - info.get_blockNumber();
+            if confirmations != last_confirmations {
+                info!("Confirmations: {}/{}", confirmations, confirm_count);
+                last_confirmations = confirmations;
+            }
+            if confirmations >= confirm_count {
+                info!("Confirmed in block #{}", info.get_blockNumber());
+                return Ok(());
+            }
+        }
+
+        if started.elapsed() >= timeout {
+            // exit code 1 as documented for --confirm-timeout, not the generic timeout code
+            return Err(Error::Runtime("timeout waiting for confirmations"));
+        }
+        thread::sleep(Duration::from_secs(3));
+    }
+}
+
+/// Fetch a transaction receipt and collect its annotations, without printing.
+///
+/// ```no_run
+/// let detail = wallet_cli::get_transaction_info_detail(
+///     "26ff621bcb2c74da8a14c77c20d55cbad8cb0ea022e1174d9522c283c133355c",
+/// )?;
+/// println!("fee = {} SUN, {} event logs", detail.fee, detail.logs.len());
+/// # Ok::<(), wallet_cli::Error>(())
+/// ```
+pub fn get_transaction_info_detail(id: &str) -> Result<TransactionInfoDetail, Error> {
+    let txid = parse_txid(id)?;
+    let payload = record::fetch(RecordKind::TransactionInfo, &txid, || {
+        client::get_transaction_info_by_id(&txid)
+    })?;
 
     if payload.get_id().is_empty() {
-        return Err(Error::Runtime("transaction not found"));
+        return Err(Error::NotFound("transaction not found"));
     }
     let mut json = serde_json::to_value(&payload)?;
     jsont::fix_transaction_info(&mut json);
 
-    println!("{}", serde_json::to_string_pretty(&json)?);
+    let contract_address = if !payload.get_contract_address().is_empty() {
+        Some(Address::try_from(payload.get_contract_address())?)
+    } else {
+        None
+    };
+    // the current setting, it might have been updated since the transaction
+    let user_resource_percent = match contract_address {
+        Some(ref addr) if payload.get_receipt().origin_energy_usage > 0 => trx::get_smart_contract(addr)
+            .ok()
+            .map(|contract| contract.consume_user_resource_percent),
+        _ => None,
+    };
 
-    eprintln!(
-        "! Block Timestamp: {}",
-        Local.timestamp(payload.get_blockTimeStamp() / 1_000, 0)
-    );
+    let revert_message = if payload.get_receipt().result == ContractResult::REVERT {
+        payload
+            .get_contractResult()
+            .get(0)
+            .and_then(|data| trx::decode_revert_reason(data))
+    } else {
+        None
+    };
+
+    let transaction = fetch_transaction(payload.get_id())?;
+    let contract_type = transaction
+        .get_raw_data()
+        .get_contract()
+        .get(0)
+        .map(|contract| contract.get_field_type());
+    let created_account = created_account_of(&transaction)?;
 
-    if payload.get_receipt().net_usage > 0 {
-        eprintln!("! Free/Frozen Bandwidth Used: {}", payload.get_receipt().net_usage);
+    let receipt = payload.get_receipt();
+    Ok(TransactionInfoDetail {
+        info: json,
+        block_timestamp: payload.get_blockTimeStamp(),
+        net_usage: receipt.net_usage,
+        net_fee: receipt.net_fee,
+        energy_usage: receipt.energy_usage,
+        energy_fee: receipt.energy_fee,
+        origin_energy_usage: receipt.origin_energy_usage,
+        energy_usage_total: receipt.energy_usage_total,
+        fee: payload.fee,
+        contract_type,
+        created_account,
+        contract_result: receipt.get_result(),
+        failed: payload.get_result() == TransactionInfoCode::FAILED,
+        contract_address,
+        user_resource_percent,
+        revert_message,
+        logs: decode_contract_logs(payload.get_log()),
+    })
+}
+
+/// Outcome of a transaction, the contract result like `REVERT`, or `SUCCESS`/`FAILED` for other contracts.
+pub fn outcome_of(contract_result: ContractResult, failed: bool) -> String {
+    match contract_result {
+        ContractResult::DEFAULT if failed => "FAILED".to_owned(),
+        ContractResult::DEFAULT => "SUCCESS".to_owned(),
+        result => format!("{:?}", result),
     }
-    if payload.get_receipt().net_fee > 0 {
-        eprintln!(
-            "! Burnt for Bandwidth: {} TRX",
-            payload.get_receipt().net_fee as f64 / 1_000_000.0
-        );
+}
+
+/// Parse `--expected-result`, case insensitive: SUCCESS, FAILED or a contract result. `DEFAULT` is never an outcome,
+/// see `outcome_of`.
+fn parse_expected_result(expected: &str) -> Result<String, Error> {
+    let expected = expected.trim().to_uppercase();
+    let is_known = expected == "FAILED" ||
+        ContractResult::values()
+            .iter()
+            .filter(|&&result| result != ContractResult::DEFAULT)
+            .any(|result| format!("{:?}", result) == expected);
+    if !is_known {
+        return Err(Error::InvalidInput(
+            "invalid --expected-result, use SUCCESS, FAILED or a contract result like REVERT",
+        ));
     }
-    if payload.get_receipt().net_fee == 100000 {
-        eprintln!("! Might be an account creation");
+    Ok(expected)
+}
+
+/// Does an outcome match a parsed `--expected-result`. `FAILED` matches any outcome but `SUCCESS`.
+fn matches_expected_result(expected: &str, outcome: &str) -> bool {
+    match expected {
+        "FAILED" => outcome != "SUCCESS",
+        _ => expected == outcome,
     }
+}
+
+pub fn get_transaction_info(
+    id: &str,
+    usd: bool,
+    fiat: Option<&FiatPrice>,
+    expected_result: Option<&str>,
+) -> Result<(), Error> {
+    let expected_result = expected_result.map(parse_expected_result).transpose()?;
+    let detail = get_transaction_info_detail(id)?;
+    let price = if usd { price::trx_usd_price() } else { None };
+    // a fiat rate of the block date takes the place of the current USD price
+    let fiat_rate =
+        fiat.and_then(|fiat| fiat.rate_on(NaiveDateTime::from_timestamp(detail.block_timestamp / 1_000, 0).date()));
+    let value_suffix = |amount: i64| match fiat_rate {
+        Some(rate) => rate.suffix(amount),
+        None => price::usd_suffix(price, amount),
+    };
+
+    println!("{}", serde_json::to_string_pretty(&detail.info)?);
+
+    info!(
+        "Block Timestamp: {}",
+        time::format_timestamp(detail.block_timestamp / 1_000, 0)
+    );
 
-    if payload.get_receipt().energy_usage > 0 {
-        eprintln!("! Frozen Energy Used: {}", payload.get_receipt().energy_usage);
+    if detail.net_usage > 0 {
+        info!("Free/Frozen Bandwidth Used: {}", trx::format_number(detail.net_usage));
     }
-    if payload.get_receipt().energy_fee > 0 {
-        eprintln!(
-            "! Burnt for Energy: {} TRX",
-            payload.get_receipt().energy_fee as f64 / 1_000_000.0
+    if detail.net_fee > 0 {
+        // activation fees are burnt as net_fee too, they are not bandwidth
+        let burn =
+            if detail.created_account.is_none() && !might_be_account_creation(detail.net_fee, detail.contract_type) {
+                trx::bandwidth_price_in_sun()
+                    .ok()
+                    .and_then(|price| describe_bandwidth_burn(detail.net_fee, price))
+            } else {
+                None
+            };
+        info!(
+            "Burnt for Bandwidth: {} TRX{}{}",
+            trx::format_trx(detail.net_fee),
+            burn.unwrap_or_default(),
+            value_suffix(detail.net_fee)
         );
     }
-    if payload.get_receipt().origin_energy_usage > 0 {
-        eprintln!(
-            "! Contract Owner's Energy Used: {}",
-            payload.get_receipt().origin_energy_usage
+    match detail.created_account {
+        Some((ref address, account_type)) => info!("Account Created: {} ({:?})", address, account_type),
+        None if might_be_account_creation(detail.net_fee, detail.contract_type) => {
+            info!("Might be an account creation")
+        }
+        None => (),
+    }
+
+    if detail.energy_usage > 0 {
+        info!("Frozen Energy Used: {}", trx::format_number(detail.energy_usage));
+    }
+    if detail.energy_fee > 0 {
+        info!(
+            "Burnt for Energy: {} TRX{}",
+            trx::format_trx(detail.energy_fee),
+            value_suffix(detail.energy_fee)
         );
     }
-    if payload.get_receipt().energy_usage_total > 0 {
-        eprintln!("! Total Energy: {}", payload.get_receipt().energy_usage_total);
+    if let Some(split) = EnergySplit::of(detail.origin_energy_usage, detail.energy_usage_total) {
+        for line in split.describe(detail.user_resource_percent) {
+            info!("{}", line);
+        }
     }
 
-    if payload.fee > 0 {
-        eprintln!("! Total Fee: {} TRX", payload.fee as f64 / 1_000_000.0);
+    if detail.fee > 0 {
+        info!(
+            "Total Fee: {} TRX{}",
+            trx::format_trx(detail.fee),
+            value_suffix(detail.fee)
+        );
     }
 
-    if [ContractResult::OUT_OF_TIME, ContractResult::JVM_STACK_OVER_FLOW].contains(&payload.get_receipt().result) {
-        eprintln!("!! All of Fee Limit Spent!");
+    if [ContractResult::OUT_OF_TIME, ContractResult::JVM_STACK_OVER_FLOW].contains(&detail.contract_result) {
+        warn!("All of Fee Limit Spent!");
     }
 
-    if !payload.get_contract_address().is_empty() {
-        let cntr_addr = Address::try_from(payload.get_contract_address())?;
-        eprintln!(
-            "! Contract Address: {} {}",
+    if let Some(cntr_addr) = detail.contract_address {
+        info!(
+            "Contract Address: {} {}",
             cntr_addr,
             cntr_addr.to_well_known_name().unwrap_or_default()
         );
-        eprintln!("! Contract result: {:?}", payload.get_receipt().get_result());
+        info!("Contract result: {:?}", detail.contract_result);
     }
 
-    if payload.get_receipt().result == ContractResult::REVERT {
-        if let Some(revert_msg) = payload.get_contractResult().get(0) {
-            // function selecter: 4 bytes
-            // memory offset of string: 32 bytes
-            // length of string: 32 bytes
-            // remain: the string
-            if revert_msg.len() > 4 + 32 + 32 {
-                eprintln!("! Revert Message: {:?}", str::from_utf8(&revert_msg[4 + 32 + 32..]))
-            }
-        }
+    if let Some(ref revert_msg) = detail.revert_message {
+        info!("Revert Message: {:?}", revert_msg)
     }
-    if !payload.get_log().is_empty() {
-        eprintln!("! Event Logs: {}", payload.get_log().len());
+    if !detail.logs.is_empty() {
+        info!("Event Logs: {}", detail.logs.len());
 
-        let _ = pprint_contract_logs(payload.get_log());
+        pprint_contract_logs(&detail.logs);
+    }
+
+    if let Some(expected) = expected_result {
+        let outcome = outcome_of(detail.contract_result, detail.failed);
+        if matches_expected_result(&expected, &outcome) {
+            info!("PASS: result is {}, expected {}", outcome, expected);
+        } else {
+            warn!("FAIL: result is {}, expected {}", outcome, expected);
+            return Err(Error::Mismatch("transaction result does not match --expected-result"));
+        }
     }
 
     Ok(())
 }
 
-fn pprint_contract_logs(logs: &[Log]) -> Result<(), Error> {
-    use primitive_types::U256;
-    use proto::core::SmartContract_ABI_Entry_EntryType as AbiEntryType;
+/// The account created explicitly by an `AccountCreateContract`.
+fn created_account_of(transaction: &Transaction) -> Result<Option<(Address, AccountType)>, Error> {
+    match transaction.get_raw_data().get_contract().get(0) {
+        Some(contract) if contract.get_field_type() == ContractType::AccountCreateContract => {
+            let pb: AccountCreateContract = protobuf::parse_from_bytes(contract.get_parameter().get_value())?;
+            Ok(Some((
+                Address::try_from(pb.get_account_address())?,
+                pb.get_field_type(),
+            )))
+        }
+        _ => Ok(None),
+    }
+}
 
-    for (i, log) in logs.iter().enumerate() {
-        let cntr_addr = Address::from_tvm_bytes(log.get_address());
-        let abi_entries = trx::get_contract_abi(&cntr_addr)?;
+/// Bandwidth burnt at the current price, like ` (267 bandwidth × 1000 sun/bw)`. `None` if the fee is not a multiple of
+/// the price, when it was burnt at another price.
+fn describe_bandwidth_burn(net_fee: i64, price: i64) -> Option<String> {
+    if price <= 0 || net_fee % price != 0 {
+        return None;
+    }
+    Some(format!(
+        " ({} bandwidth × {} sun/bw)",
+        trx::format_number(net_fee / price),
+        trx::format_number(price)
+    ))
+}
 
-        let entry = abi_entries
-            .iter()
-            .chain(abi::DEFAULT_EVENT_ABI.iter())
-            .filter(|e| e.get_field_type() == AbiEntryType::Event)
-            .find(|e| crypto::keccak256(abi::entry_to_method_name(&e).as_bytes()).as_ref() == log.get_topics()[0]);
+/// Account creation burns 0.1 TRX, as net_fee. Only transfers can create an account implicitly.
+fn might_be_account_creation(net_fee: i64, contract_type: Option<ContractType>) -> bool {
+    net_fee == 100000 &&
+        match contract_type {
+            Some(ContractType::TransferContract) | Some(ContractType::TransferAssetContract) => true,
+            _ => false,
+        }
+}
 
-        eprintln!(
-            "! Event#{} {} {}",
-            i,
-            cntr_addr,
-            cntr_addr.to_well_known_name().unwrap_or_default()
-        );
-        if let Some(entry) = entry {
-            eprintln!("  {}", abi::entry_to_method_name_pretty(entry)?);
+/// Decode event logs with ABIs of emitting contracts. Logs that can not be decoded are kept raw.
+fn decode_contract_logs(logs: &[Log]) -> Vec<DecodedLog> {
+    logs.iter()
+        .map(|log| decode_contract_log(log).unwrap_or_else(|_| raw_contract_log(log)))
+        .collect()
+}
+
+fn raw_contract_log(log: &Log) -> DecodedLog {
+    DecodedLog {
+        address: Address::from_tvm_bytes(log.get_address()),
+        topics: log.get_topics().to_vec(),
+        data: log.get_data().to_owned(),
+        event: None,
+    }
+}
+
+pub(crate) fn decode_contract_log(log: &Log) -> Result<DecodedLog, Error> {
+    use proto::core::SmartContract_ABI_Entry_EntryType as AbiEntryType;
+
+    let cntr_addr = Address::from_tvm_bytes(log.get_address());
+    let abi_entries = trx::get_contract_abi(&cntr_addr)?;
 
+    let entry = abi_entries
+        .iter()
+        .chain(abi::DEFAULT_EVENT_ABI.iter())
+        .filter(|e| e.get_field_type() == AbiEntryType::Event)
+        .find(|e| {
+            log.get_topics().first().map(Vec::as_slice) ==
+                Some(&crypto::keccak256(abi::entry_to_method_name(&e).as_bytes())[..])
+        });
+
+    let event = match entry {
+        Some(entry) => {
             let indexed_param_types = abi::entry_to_indexed_types(entry);
             let indexed_params = abi::decode_params(
                 &indexed_param_types,
@@ -221,71 +722,568 @@ fn pprint_contract_logs(logs: &[Log]) -> Result<(), Error> {
             let param_types = abi::entry_to_non_indexed_types(entry);
             let params = abi::decode_params(&param_types, &hex::encode(&log.get_data()))?;
 
-            let mut i = 0;
-            let mut j = 0;
-            for arg in entry.get_inputs().iter() {
-                if arg.get_indexed() {
-                    eprintln!(
-                        "      {} indexed {} = {}",
-                        arg.get_field_type(),
-                        arg.get_name(),
-                        indexed_params[i]
-                    );
-                    i += 1;
+            let mut indexed_params = indexed_params.into_iter();
+            let mut params = params.into_iter();
+            let params = entry
+                .get_inputs()
+                .iter()
+                .filter_map(|arg| {
+                    let value = if arg.get_indexed() {
+                        indexed_params.next()
+                    } else {
+                        params.next()
+                    }?;
+                    Some(DecodedParam {
+                        name: arg.get_name().to_owned(),
+                        ty: arg.get_field_type().to_owned(),
+                        indexed: arg.get_indexed(),
+                        value,
+                    })
+                })
+                .collect();
+            Some(DecodedEvent {
+                event_pretty: abi::entry_to_method_name_pretty(entry)?,
+                params,
+            })
+        }
+        None => None,
+    };
+
+    Ok(DecodedLog {
+        event,
+        ..raw_contract_log(log)
+    })
+}
+
+fn pprint_contract_logs(logs: &[DecodedLog]) {
+    use primitive_types::U256;
+
+    for (i, log) in logs.iter().enumerate() {
+        let cntr_addr = &log.address;
+        info!(
+            "Event#{} {} {}",
+            i,
+            cntr_addr,
+            cntr_addr.to_well_known_name().unwrap_or_default()
+        );
+        if let Some(ref event) = log.event {
+            info!("  {}", event.event_pretty);
+
+            for param in &event.params {
+                if param.indexed {
+                    info!("      {} indexed {} = {}", param.ty, param.name, param.value);
                 } else {
-                    eprintln!("      {} {} = {}", arg.get_field_type(), arg.get_name(), params[j]);
-                    j += 1;
+                    info!("      {} {} = {}", param.ty, param.name, param.value);
                 }
             }
         } else {
-            eprintln!("  (ABI not found, cannot parse)");
-            eprintln!("  topic[0]: {}", hex::encode(&log.get_topics()[0]));
-            for (i, t) in log.get_topics().iter().enumerate().skip(1) {
-                eprintln!("  topic[{}]: {}", i, hex::encode(t));
+            info!("  (ABI not found, cannot parse)");
+            match log.topics.first() {
+                Some(topic) => info!("  topic[0]: {}", hex::encode(topic)),
+                None => info!("  (no topics)"),
+            }
+            for (i, t) in log.topics.iter().enumerate().skip(1) {
+                info!("  topic[{}]: {}", i, hex::encode(t));
                 if t.starts_with(&[0; 24]) {
-                    eprintln!("    as uint256: {}", U256::from_big_endian(t));
+                    info!("    as uint256: {}", U256::from_big_endian(t));
                 } else if t.starts_with(&[0; 4]) {
-                    eprintln!("    as address: {}", Address::from_tvm_bytes(&t[12..]));
+                    info!("    as address: {}", Address::from_tvm_bytes(&t[12..]));
                 }
             }
-            if !log.get_data().is_empty() {
-                eprintln!("  data: {}", hex::encode(log.get_data()));
+            if !log.data.is_empty() {
+                info!("  data: {}", hex::encode(&log.data));
             }
         }
     }
+}
 
-    Ok(())
+/// Annotate a contract call. The ABI, TRC721 check and token meta are fetched concurrently, failures only affect
+/// their own annotation.
+async fn lookup_contract_call<L: ContractLookup>(
+    lookup: &L,
+    sender: &Address,
+    contract_address: Address,
+    contract_result: ContractResult,
+    data: &[u8],
+) -> ContractCall {
+    let nft_call = trc721::parse_call(data);
+    let trc20_transfer = trc20::parse_transfer(sender, &contract_address, data);
+
+    let (abi, is_trc721, _) = future::join3(
+        lookup.contract_abi(&contract_address),
+        async {
+            match nft_call {
+                Some(_) => lookup.is_trc721(&contract_address).await.unwrap_or(false),
+                None => false,
+            }
+        },
+        // warm the token meta cache for `summarize_transfer`
+        async {
+            if trc20_transfer.is_some() {
+                let _ = lookup.token_meta(&contract_address).await;
+            }
+        },
+    )
+    .await;
+
+    // transferFrom and approve share selectors between TRC20 and TRC721
+    let nft_call = nft_call.filter(|_| is_trc721);
+    ContractCall {
+        contract_address,
+        contract_result,
+        decoded: abi.and_then(|abi| decode_contract_call_data(&abi, &hex::encode(data))),
+        trc20_transfer: if nft_call.is_none() { trc20_transfer } else { None },
+        nft_call,
+    }
 }
 
-fn pprint_contract_call_data(contract: &Address, data: &str) -> Result<(), Error> {
-    let abi = trx::get_contract_abi(contract)?;
+fn decode_contract_call_data(abi: &[AbiEntry], data: &str) -> Result<DecodedCall, Error> {
+    // calls to the fallback function have no selector, like plain TRX transfers to a contract
+    if data.len() < 8 {
+        return Err(Error::NotFound("fallback call, no function selector"));
+    }
     let fnhash = hex::decode(&data[..8])?;
-    abi.iter()
-        .find(|entry| abi::fnhash(&abi::entry_to_method_name(entry)) == fnhash[..])
-        .ok_or(Error::Runtime("ABI not found, can not parse result"))
+    abi::find_entry_by_selector(abi, &fnhash)
+        .ok_or(Error::NotFound("ABI not found, can not parse result"))
         .and_then(|entry| {
-            eprintln!("! {}", abi::entry_to_method_name_pretty(entry)?);
-            eprintln!(
-                "!          {} [{}]",
-                abi::entry_to_method_name(entry),
-                hex::encode(fnhash)
-            );
             let types = abi::entry_to_input_types(&entry);
             let params = abi::decode_params(&types, &data[8..])?;
-            if !types.is_empty() {
-                eprintln!("! Arguments:");
-                for (input, param) in entry.get_inputs().iter().zip(params.iter()) {
-                    eprintln!("  {}: {} = {}", input.get_name(), input.get_field_type(), param);
-                }
-            }
-            Ok(())
+            let mut selector = [0u8; 4];
+            selector.copy_from_slice(&fnhash);
+            Ok(DecodedCall {
+                method_pretty: abi::entry_to_method_name_pretty(entry)?,
+                method: abi::entry_to_method_name(entry),
+                selector,
+                arguments: entry
+                    .get_inputs()
+                    .iter()
+                    .zip(params.into_iter())
+                    .map(|(input, value)| DecodedParam {
+                        name: input.get_name().to_owned(),
+                        ty: input.get_field_type().to_owned(),
+                        indexed: false,
+                        value,
+                    })
+                    .collect(),
+            })
         })
 }
 
-fn parse_hex(s: &str) -> Result<Vec<u8>, Error> {
-    if s.starts_with("0x") {
-        Ok(hex::decode(&s[2..])?)
+/// Print call data, TRC721 calls are recognized even when the ABI is not available. Call data that can not be
+/// decoded, like a fallback call or a call to a contract without ABI, is only noted.
+fn pprint_contract_call_data(call: ContractCall) {
+    if let Some(ref nft_call) = call.nft_call {
+        info!("{}", nft_call);
+    }
+    let decoded = match call.decoded {
+        Ok(decoded) => decoded,
+        Err(_) if call.nft_call.is_some() => return,
+        Err(Error::NotFound(reason)) => {
+            info!("({})", reason);
+            return;
+        }
+        Err(e) => {
+            info!("(can not parse call data: {})", e);
+            return;
+        }
+    };
+    info!("{}", decoded.method_pretty);
+    info!("         {} [{}]", decoded.method, hex::encode(decoded.selector));
+    if !decoded.arguments.is_empty() {
+        info!("Arguments:");
+        for param in &decoded.arguments {
+            info!("  {}: {} = {}", param.name, param.ty, param.value);
+        }
+    }
+}
+
+fn fetch_transaction(txid: &[u8]) -> Result<Transaction, Error> {
+    record::fetch(RecordKind::Transaction, txid, || client::get_transaction_by_id(txid))
+}
+
+/// Parse a transaction id in hex, optionally `0x`-prefixed, or in base64 as in proto JSON dumps.
+pub(crate) fn parse_txid(s: &str) -> Result<Vec<u8>, Error> {
+    let s = s.trim();
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    let txid = if digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+        hex::decode(digits)?
     } else {
-        Ok(hex::decode(s)?)
+        base64::decode(s).map_err(|_| Error::InvalidInput("transaction id is neither hex nor base64"))?
+    };
+    if txid.len() != 32 {
+        return Err(Error::InvalidInput("transaction id must be 32 bytes"));
+    }
+    Ok(txid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::task::Poll;
+
+    #[test]
+    fn test_decode_contract_logs_keeps_undecodable() {
+        let contract: Address = "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap();
+        trx::test_cache_contract_abi(&contract, vec![]);
+        let approval = |data: Vec<u8>| {
+            let mut log = Log::new();
+            log.set_address(contract.as_tvm_bytes().to_vec());
+            log.set_topics(
+                vec![
+                    crypto::keccak256(b"Approval(address,address,uint256)").to_vec(),
+                    vec![0; 32],
+                    vec![0; 32],
+                ]
+                .into(),
+            );
+            log.set_data(data);
+            log
+        };
+        // the uint256 value is truncated
+        let logs = decode_contract_logs(&[approval(vec![0; 3]), approval(vec![0; 32])]);
+        assert_eq!(logs.len(), 2);
+        assert!(logs[0].event.is_none());
+        assert_eq!(logs[0].data, vec![0; 3]);
+        assert!(logs[1]
+            .event
+            .as_ref()
+            .unwrap()
+            .event_pretty
+            .starts_with("event Approval("));
+    }
+
+    #[test]
+    fn test_decoded_input_json() {
+        let recipient: Address = "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap();
+        let call = DecodedCall {
+            method_pretty: "function transfer(address _to, uint256 _value) returns (bool)".into(),
+            method: "transfer(address,uint256)".into(),
+            selector: abi::fnhash("transfer(address,uint256)"),
+            arguments: vec![
+                DecodedParam {
+                    name: "_to".into(),
+                    ty: "address".into(),
+                    indexed: false,
+                    value: AbiValue::Address(recipient),
+                },
+                DecodedParam {
+                    name: "_value".into(),
+                    ty: "uint256".into(),
+                    indexed: false,
+                    value: AbiValue::Uint(125_500_000.into()),
+                },
+            ],
+        };
+        assert_eq!(
+            call.to_json(),
+            json!([
+                {"name": "_to", "type": "address", "value": "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt"},
+                {"name": "_value", "type": "uint256", "value": "125500000"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_created_account_of() {
+        use proto::core::Transaction_raw as TransactionRaw;
+
+        let mut create = AccountCreateContract::new();
+        create.set_owner_address(hex::decode("41a614f803b6fd780986a42c78ec9c7f77e6ded13c").unwrap());
+        create.set_account_address(
+            "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt"
+                .parse::<Address>()
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+        );
+        let mut txn = Transaction::new();
+        txn.set_raw_data(TransactionRaw::new());
+        assert!(created_account_of(&txn).unwrap().is_none());
+
+        let txn = trx::test_transaction_of(&create);
+        let (address, account_type) = created_account_of(&txn).unwrap().unwrap();
+        assert_eq!(address.to_string(), "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt");
+        assert_eq!(account_type, AccountType::Normal);
+    }
+
+    #[test]
+    fn test_account_creation_heuristic() {
+        assert!(might_be_account_creation(100000, Some(ContractType::TransferContract)));
+        assert!(might_be_account_creation(
+            100000,
+            Some(ContractType::TransferAssetContract)
+        ));
+        assert!(!might_be_account_creation(
+            100000,
+            Some(ContractType::TriggerSmartContract)
+        ));
+        assert!(!might_be_account_creation(100000, None));
+        assert!(!might_be_account_creation(2000, Some(ContractType::TransferContract)));
+    }
+
+    #[test]
+    fn test_energy_split() {
+        use proto::core::{ResourceReceipt, TransactionInfo};
+
+        // the contract owner pays 40% with user resource percent 60
+        let mut receipt = ResourceReceipt::new();
+        receipt.set_energy_usage(10_000);
+        receipt.set_energy_fee(800_000);
+        receipt.set_origin_energy_usage(12_000);
+        receipt.set_energy_usage_total(30_000);
+        let mut info = TransactionInfo::new();
+        info.set_receipt(receipt);
+
+        let receipt = info.get_receipt();
+        let split = EnergySplit::of(receipt.origin_energy_usage, receipt.energy_usage_total).unwrap();
+        assert_eq!(
+            split,
+            EnergySplit {
+                caller: 18_000,
+                owner: 12_000,
+                total: 30_000,
+            }
+        );
+        let lines = split.describe(Some(60));
+        assert_eq!(
+            lines[0],
+            format!("Energy Paid by Caller: {} (60.0%)", trx::format_number(18_000))
+        );
+        assert_eq!(
+            lines[1],
+            format!(
+                "Energy Subsidized by Contract Owner: {} (40.0%)",
+                trx::format_number(12_000)
+            )
+        );
+        assert_eq!(lines[2], format!("Total Energy: {}", trx::format_number(30_000)));
+        assert_eq!(
+            lines[3],
+            "  contract setting: caller pays 60%, owner 40% up to origin_energy_limit"
+        );
+        assert_eq!(split.describe(None).len(), 3);
+
+        assert_eq!(EnergySplit::of(0, 0), None);
+        assert_eq!(EnergySplit::of(0, 500).unwrap().caller, 500);
+    }
+
+    #[test]
+    fn test_expected_result() {
+        let success = outcome_of(ContractResult::SUCCESS, false);
+        let transfer = outcome_of(ContractResult::DEFAULT, false);
+        let reverted = outcome_of(ContractResult::REVERT, true);
+        assert_eq!(transfer, "SUCCESS");
+        assert_eq!(outcome_of(ContractResult::DEFAULT, true), "FAILED");
+
+        let expected = |s| parse_expected_result(s).unwrap();
+        assert!(matches_expected_result(&expected("SUCCESS"), &success));
+        assert!(matches_expected_result(&expected("success"), &transfer));
+        assert!(matches_expected_result(&expected("REVERT"), &reverted));
+        assert!(matches_expected_result(&expected(" failed"), &reverted));
+
+        assert!(!matches_expected_result(&expected("SUCCESS"), &reverted));
+        assert!(!matches_expected_result(&expected("OUT_OF_ENERGY"), &reverted));
+        assert!(!matches_expected_result(&expected("FAILED"), &success));
+        assert!(parse_expected_result("REVERTED").is_err());
+        assert!(parse_expected_result("DEFAULT").is_err());
+    }
+
+    #[test]
+    fn test_describe_bandwidth_burn() {
+        assert_eq!(
+            describe_bandwidth_burn(267_000, 1_000).unwrap(),
+            format!(
+                " ({} bandwidth × {} sun/bw)",
+                trx::format_number(267),
+                trx::format_number(1_000)
+            )
+        );
+        // burnt before a price change
+        assert_eq!(describe_bandwidth_burn(26_700, 1_000), None);
+        assert_eq!(describe_bandwidth_burn(267_000, 0), None);
+    }
+
+    #[test]
+    fn test_parse_txid() {
+        let txid = parse_txid("26ff621bcb2c74da8a14c77c20d55cbad8cb0ea022e1174d9522c283c133355c").unwrap();
+        assert_eq!(
+            parse_txid("0x26ff621bcb2c74da8a14c77c20d55cbad8cb0ea022e1174d9522c283c133355c").unwrap(),
+            txid
+        );
+        assert_eq!(
+            parse_txid("Jv9iG8ssdNqKFMd8INVcutjLDqAi4RdNlSLCg8EzNVw=").unwrap(),
+            txid
+        );
+        assert!(parse_txid("26ff621bcb2c74da").is_err());
+        assert!(parse_txid("0x0x26ff621bcb2c74da8a14c77c20d55cbad8cb0ea022e1174d9522c283c133355c").is_err());
+        assert!(parse_txid("Jv9iG8ssdNqKFMd8INVcutjLDqAi").is_err());
+    }
+
+    #[test]
+    fn test_pending_transaction_without_ret() {
+        use proto::core::Transaction_raw as TransactionRaw;
+
+        let mut txn = Transaction::new();
+        txn.set_raw_data(TransactionRaw::new());
+        assert!(transaction_detail_of(txn).is_err());
+
+        let detail = transaction_detail_of(trx::test_transfer_transaction()).unwrap();
+        assert_eq!(detail.result, None);
+        assert!(detail.contract_call.is_none());
+        assert_eq!(detail.sender.to_string(), "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+    }
+
+    #[test]
+    fn test_size_breakdown() {
+        use proto::core::Transaction_Result as TransactionResult;
+
+        let mut txn = trx::test_transfer_transaction();
+        txn.set_signature(vec![vec![1; 65], vec![2; 65]].into());
+        let expected = txn.compute_size() as usize;
+        txn.set_ret(vec![TransactionResult::new()].into());
+
+        let size = SizeBreakdown::of(&txn);
+        assert_eq!(size.signatures, vec![65, 65]);
+        assert_eq!(size.total(), expected);
+        assert_eq!(size.bandwidth(), expected + trx::MAX_RESULT_SIZE_IN_TX);
+        assert_eq!(size.overhead, 2 + 2 * 2);
+    }
+
+    /// Every lookup yields once before returning, recording how many are in flight.
+    struct MockLookup {
+        abi: Result<serde_json::Value, &'static str>,
+        in_flight: Cell<usize>,
+        max_in_flight: Cell<usize>,
+    }
+
+    impl MockLookup {
+        fn new(abi: Result<serde_json::Value, &'static str>) -> Self {
+            MockLookup {
+                abi,
+                in_flight: Cell::new(0),
+                max_in_flight: Cell::new(0),
+            }
+        }
+
+        fn pending<'a, T: 'a>(&'a self, value: T) -> LocalBoxFuture<'a, T> {
+            async move {
+                self.in_flight.set(self.in_flight.get() + 1);
+                self.max_in_flight
+                    .set(self.max_in_flight.get().max(self.in_flight.get()));
+                let mut yielded = false;
+                future::poll_fn(|cx| {
+                    if yielded {
+                        Poll::Ready(())
+                    } else {
+                        yielded = true;
+                        cx.waker().wake_by_ref();
+                        Poll::Pending
+                    }
+                })
+                .await;
+                self.in_flight.set(self.in_flight.get() - 1);
+                value
+            }
+            .boxed_local()
+        }
+    }
+
+    impl ContractLookup for MockLookup {
+        fn contract_abi<'a>(&'a self, _: &'a Address) -> LocalBoxFuture<'a, Result<Vec<AbiEntry>, Error>> {
+            let abi = self
+                .abi
+                .clone()
+                .map_err(Error::Runtime)
+                .and_then(|json| abi::json_to_abi(&json))
+                .map(|mut abi| abi.take_entrys().into());
+            self.pending(abi)
+        }
+
+        fn is_trc721<'a>(&'a self, _: &'a Address) -> LocalBoxFuture<'a, Result<bool, Error>> {
+            self.pending(Ok(false))
+        }
+
+        fn token_meta<'a>(&'a self, _: &'a Address) -> LocalBoxFuture<'a, Result<TokenMeta, Error>> {
+            self.pending(Ok(TokenMeta {
+                symbol: Some("USDT".into()),
+                decimals: Some(6),
+            }))
+        }
+    }
+
+    fn transfer_from_call() -> Vec<u8> {
+        let mut data = abi::fnhash("transferFrom(address,address,uint256)").to_vec();
+        data.extend(abi::encode_values(&[
+            AbiValue::Address("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap()),
+            AbiValue::Address("TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap()),
+            AbiValue::Uint(42.into()),
+        ]));
+        data
+    }
+
+    #[test]
+    fn test_lookup_contract_call_concurrently() {
+        let lookup = MockLookup::new(Ok(json!([{
+            "type": "function",
+            "name": "transferFrom",
+            "inputs": [
+                {"name": "from", "type": "address"},
+                {"name": "to", "type": "address"},
+                {"name": "value", "type": "uint256"},
+            ],
+            "outputs": [{"name": "", "type": "bool"}],
+            "stateMutability": "nonpayable",
+        }])));
+        let sender = "TPhiVyQZ5xyvVK2KS2LTke8YvXJU5wxnbN".parse().unwrap();
+        let contract = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap();
+
+        let call = executor::block_on(lookup_contract_call(
+            &lookup,
+            &sender,
+            contract,
+            ContractResult::SUCCESS,
+            &transfer_from_call(),
+        ));
+        assert_eq!(lookup.max_in_flight.get(), 3);
+        assert_eq!(call.decoded.unwrap().method, "transferFrom(address,address,uint256)");
+        assert!(call.nft_call.is_none());
+        assert!(call.trc20_transfer.is_some());
+    }
+
+    #[test]
+    fn test_lookup_contract_call_without_abi() {
+        let lookup = MockLookup::new(Err("contract not found"));
+        let sender = "TPhiVyQZ5xyvVK2KS2LTke8YvXJU5wxnbN".parse().unwrap();
+        let contract = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap();
+
+        let call = executor::block_on(lookup_contract_call(
+            &lookup,
+            &sender,
+            contract,
+            ContractResult::SUCCESS,
+            &transfer_from_call(),
+        ));
+        assert!(call.decoded.is_err());
+        assert!(call.trc20_transfer.is_some());
+    }
+
+    #[test]
+    fn test_lookup_contract_call_without_selector() {
+        let lookup = MockLookup::new(Ok(json!([{"type": "fallback", "stateMutability": "payable"}])));
+        let sender = "TPhiVyQZ5xyvVK2KS2LTke8YvXJU5wxnbN".parse().unwrap();
+        let contract = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap();
+
+        for data in &[&[][..], &[0xa9, 0x05, 0x9c][..]] {
+            let call = executor::block_on(lookup_contract_call(
+                &lookup,
+                &sender,
+                contract,
+                ContractResult::SUCCESS,
+                data,
+            ));
+            assert!(matches!(call.decoded, Err(Error::NotFound(_))));
+            assert!(call.trc20_transfer.is_none());
+        }
     }
 }
@@ -0,0 +1,186 @@
+//! Calls of a contract in a block range, by scanning `TriggerSmartContract` transactions.
+//!
+//! Only direct calls are counted, calls from other contracts are internal transactions and not in blocks.
+
+use clap::ArgMatches;
+use keys::Address;
+use log::info;
+use proto::api::BlockExtention;
+use proto::core::{
+    Transaction_Contract_ContractType as ContractType, Transaction_Result_contractResult as ContractResult,
+    TriggerSmartContract,
+};
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::convert::TryFrom;
+
+use crate::error::Error;
+use crate::utils::block::BlockBatches;
+use crate::utils::trx;
+
+/// Blocks scanned when `--from-block` is omitted.
+const DEFAULT_BLOCK_COUNT: i64 = 100;
+
+#[derive(Debug, Default)]
+struct TriggerCount {
+    successful: usize,
+    failed: usize,
+    callers: HashSet<Address>,
+    /// Number of blocks by calls in the block.
+    histogram: BTreeMap<usize, usize>,
+}
+
+impl TriggerCount {
+    fn total(&self) -> usize {
+        self.successful + self.failed
+    }
+
+    fn add_block(&mut self, contract: &Address, block: &BlockExtention) -> Result<(), Error> {
+        let mut calls = 0;
+        for txn_ext in block.get_transactions() {
+            let txn = txn_ext.get_transaction();
+            let contract_pb = match txn.get_raw_data().get_contract().get(0) {
+                Some(contract_pb) if contract_pb.get_field_type() == ContractType::TriggerSmartContract => contract_pb,
+                _ => continue,
+            };
+            let trigger: TriggerSmartContract = protobuf::parse_from_bytes(contract_pb.get_parameter().get_value())?;
+            if trigger.get_contract_address() != contract.as_bytes() {
+                continue;
+            }
+            calls += 1;
+            self.callers.insert(Address::try_from(trigger.get_owner_address())?);
+            match txn.get_ret().get(0).map(|ret| ret.get_contractRet()) {
+                Some(ContractResult::SUCCESS) => self.successful += 1,
+                _ => self.failed += 1,
+            }
+        }
+        *self.histogram.entry(calls).or_default() += 1;
+        Ok(())
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "total": self.total(),
+            "successful": self.successful,
+            "failed": self.failed,
+            "unique_callers": self.callers.len(),
+            "calls_per_block": self
+                .histogram
+                .iter()
+                .map(|(calls, blocks)| json!({ "calls": calls, "blocks": blocks }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn head_block_number() -> Result<i64, Error> {
+    let block = trx::get_latest_block()?;
+    Ok(block.get_block_header().get_raw_data().number)
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let contract = trx::parse_address(matches.value_of("CONTRACT").expect("required in cli.yml; qed"))?;
+    let to_block = match matches.value_of("to-block") {
+        Some(num) => num.parse()?,
+        None => head_block_number()?,
+    };
+    let from_block = match matches.value_of("from-block") {
+        Some(num) => num.parse()?,
+        None => (to_block - DEFAULT_BLOCK_COUNT + 1).max(0),
+    };
+    if from_block > to_block {
+        return Err(Error::InvalidInput("--from-block must not be greater than --to-block"));
+    }
+
+    let mut count = TriggerCount::default();
+    for batch in BlockBatches::new(from_block, to_block).verify(matches.is_present("verify")) {
+        let (last, blocks) = batch?;
+        for block in &blocks {
+            count.add_block(&contract, block)?;
+        }
+        info!(
+            "Scanned to block #{} ({}%)",
+            last,
+            (last - from_block + 1) * 100 / (to_block - from_block + 1)
+        );
+    }
+
+    let ret = json!({
+        "contract": contract.to_string(),
+        "from_block": from_block,
+        "to_block": to_block,
+        "calls": count.to_json(),
+    });
+    println!("{}", serde_json::to_string_pretty(&ret)?);
+
+    info!(
+        "Calls: {} ({} successful, {} failed) from {} callers",
+        count.total(),
+        count.successful,
+        count.failed,
+        count.callers.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::api::TransactionExtention;
+    use proto::core::Transaction_Result as TransactionResult;
+
+    fn addr(i: u8) -> Address {
+        Address::from_tvm_bytes(&[i; 20])
+    }
+
+    fn trigger(caller: u8, contract: u8, result: ContractResult) -> TransactionExtention {
+        let mut trigger = TriggerSmartContract::new();
+        trigger.set_owner_address(addr(caller).as_bytes().to_vec());
+        trigger.set_contract_address(addr(contract).as_bytes().to_vec());
+        let mut txn = trx::test_transaction_of(&trigger);
+        let mut ret = TransactionResult::new();
+        ret.set_contractRet(result);
+        txn.mut_ret().push(ret);
+        let mut txn_ext = TransactionExtention::new();
+        txn_ext.set_transaction(txn);
+        txn_ext
+    }
+
+    fn block_of(txns: Vec<TransactionExtention>) -> BlockExtention {
+        let mut block = BlockExtention::new();
+        block.set_transactions(txns.into());
+        block
+    }
+
+    #[test]
+    fn test_trigger_count() {
+        let contract = addr(9);
+        let mut count = TriggerCount::default();
+        let blocks = vec![
+            block_of(vec![
+                trigger(1, 9, ContractResult::SUCCESS),
+                trigger(2, 9, ContractResult::REVERT),
+                trigger(1, 8, ContractResult::SUCCESS),
+            ]),
+            block_of(vec![]),
+            block_of(vec![trigger(1, 9, ContractResult::OUT_OF_ENERGY)]),
+            block_of(vec![trigger(3, 8, ContractResult::SUCCESS)]),
+        ];
+        for block in &blocks {
+            count.add_block(&contract, block).unwrap();
+        }
+
+        assert_eq!(count.total(), 3);
+        assert_eq!(count.successful, 1);
+        assert_eq!(count.failed, 2);
+        assert_eq!(count.callers.len(), 2);
+        assert_eq!(
+            count.to_json()["calls_per_block"],
+            json!([
+                { "calls": 0, "blocks": 2 },
+                { "calls": 1, "blocks": 1 },
+                { "calls": 2, "blocks": 1 },
+            ])
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Votes of an account, with the witnesses voted for.
+
+use clap::ArgMatches;
+use futures::executor;
+use keys::Address;
+use log::{info, warn};
+use proto::api::EmptyMessage;
+use proto::core::Account;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use crate::error::Error;
+use crate::utils::client;
+use crate::utils::trx;
+
+/// Number of active SRs, top witnesses by votes.
+const ACTIVE_WITNESS_COUNT: usize = 27;
+
+/// TRON power of an account, frozen TRX in total, including delegated to others. One TRX is one vote.
+fn tron_power(account: &Account) -> i64 {
+    let resource = account.get_account_resource();
+    let frozen = account
+        .get_frozen()
+        .iter()
+        .map(|frozen| frozen.frozen_balance)
+        .sum::<i64>() +
+        resource.get_frozen_balance_for_energy().frozen_balance +
+        account.delegated_frozen_balance_for_bandwidth +
+        resource.delegated_frozen_balance_for_energy;
+    frozen / 1_000_000
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let addr = trx::parse_address(matches.value_of("ADDRESS").expect("required in cli.yml; qed"))?;
+
+    let mut req = Account::new();
+    req.set_address(addr.as_bytes().to_owned());
+    let account = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
+    if account.get_address().is_empty() {
+        return Err(Error::NotFound("account not found on chain"));
+    }
+
+    let mut witnesses = executor::block_on(
+        client::GRPC_CLIENT
+            .list_witnesses(Default::default(), EmptyMessage::new())
+            .drop_metadata(),
+    )?
+    .take_witnesses()
+    .into_vec();
+    witnesses.sort_by_key(|wit| -wit.get_voteCount());
+    // address => (url, is active SR)
+    let witnesses: HashMap<&[u8], (&str, bool)> = witnesses
+        .iter()
+        .enumerate()
+        .map(|(i, wit)| (wit.get_address(), (wit.get_url(), i < ACTIVE_WITNESS_COUNT)))
+        .collect();
+
+    let mut votes = account.get_votes().iter().collect::<Vec<_>>();
+    votes.sort_by_key(|vote| -vote.vote_count);
+    for vote in &votes {
+        let witness = Address::try_from(vote.get_vote_address())
+            .map(|addr| addr.to_string())
+            .unwrap_or_else(|_| hex::encode(vote.get_vote_address()));
+        let (url, active) = witnesses.get(vote.get_vote_address()).copied().unwrap_or(("", false));
+        println!("{}  {:>14}  {}", witness, trx::format_number(vote.vote_count), url);
+        if !active {
+            warn!("{} is no longer an active SR", witness);
+        }
+    }
+
+    let used = votes.iter().map(|vote| vote.vote_count).sum::<i64>();
+    info!(
+        "TRON Power Used: {}/{}",
+        trx::format_number(used),
+        trx::format_number(tron_power(&account))
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::core::Account_Frozen as Frozen;
+
+    #[test]
+    fn test_tron_power() {
+        let mut account = Account::new();
+        assert_eq!(tron_power(&account), 0);
+
+        let mut frozen = Frozen::new();
+        frozen.set_frozen_balance(10_000_000);
+        account.set_frozen(vec![frozen].into());
+        account
+            .mut_account_resource()
+            .mut_frozen_balance_for_energy()
+            .set_frozen_balance(5_000_000);
+        account.set_delegated_frozen_balance_for_bandwidth(2_000_000);
+        account
+            .mut_account_resource()
+            .set_delegated_frozen_balance_for_energy(1_500_000);
+        assert_eq!(tron_power(&account), 18);
+    }
+}
@@ -0,0 +1,100 @@
+//! Journal of broadcast transactions, see `utils::journal`.
+
+use chrono::Utc;
+use clap::ArgMatches;
+use keys::Address;
+use log::warn;
+use std::path::Path;
+
+use crate::commands::get::transaction;
+use crate::error::Error;
+use crate::utils::journal;
+use crate::utils::time;
+use crate::utils::trx;
+
+fn filter_records<'a>(
+    records: &'a [serde_json::Value],
+    since: Option<i64>,
+    account: Option<&Address>,
+) -> Vec<&'a serde_json::Value> {
+    let account = account.map(|addr| addr.to_string());
+    records
+        .iter()
+        .filter(|record| since.map_or(true, |since| record["timestamp"].as_i64().unwrap_or(0) >= since))
+        .filter(|record| account.as_ref().map_or(true, |addr| record["signer"] == addr.as_str()))
+        .collect()
+}
+
+fn list(path: &Path, matches: &ArgMatches) -> Result<(), Error> {
+    let since = matches
+        .value_of("since")
+        .map(|since| journal::parse_since(since, Utc::now()))
+        .transpose()?;
+    let account = matches.value_of("account").map(trx::parse_address).transpose()?;
+
+    let records = journal::load(path)?;
+    for record in filter_records(&records, since, account.as_ref()) {
+        println!(
+            "{}  {}  {:<28}  {:<10}  {}",
+            time::format_timestamp(record["timestamp"].as_i64().unwrap_or(0) / 1_000, 0),
+            record["txid"].as_str().unwrap_or_default(),
+            record["contract_type"].as_str().unwrap_or_default(),
+            record["result"].as_str().unwrap_or_default(),
+            record["signer"].as_str().unwrap_or("-"),
+        );
+    }
+    Ok(())
+}
+
+fn show(path: &Path, txid: &str) -> Result<(), Error> {
+    let txid = txid.trim_start_matches("0x").to_lowercase();
+    let mut record = journal::load(path)?
+        .into_iter()
+        .rev()
+        .find(|record| record["txid"] == txid.as_str())
+        .ok_or(Error::NotFound("transaction not found in journal"))?;
+
+    record["transaction_info"] = match transaction::get_transaction_info_detail(&txid) {
+        Ok(detail) => detail.info,
+        Err(e) => {
+            warn!("Can not get transaction info: {}", e);
+            serde_json::Value::Null
+        }
+    };
+    println!("{}", serde_json::to_string_pretty(&record)?);
+    Ok(())
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    let path = journal::journal_path().ok_or(Error::Runtime("journal is disabled or no data directory"))?;
+    match matches.subcommand() {
+        ("list", Some(arg_matches)) => list(&path, arg_matches),
+        ("show", Some(arg_matches)) => show(&path, arg_matches.value_of("TXID").expect("required in cli.yml; qed")),
+        _ => {
+            eprintln!("{}", matches.usage());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_filter_records() {
+        let records = vec![
+            json!({"timestamp": 1_000, "txid": "aa", "signer": "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"}),
+            json!({"timestamp": 2_000, "txid": "bb", "signer": "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt"}),
+            json!({"timestamp": 3_000, "txid": "cc", "signer": null}),
+        ];
+        assert_eq!(filter_records(&records, None, None).len(), 3);
+        assert_eq!(filter_records(&records, Some(2_000), None).len(), 2);
+
+        let account: Address = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap();
+        let filtered = filter_records(&records, None, Some(&account));
+        assert_eq!(filtered, vec![&records[0]]);
+        assert!(filter_records(&records, Some(2_000), Some(&account)).is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 use clap::ArgMatches;
 use futures::executor;
+use log::info;
 use proto::api::{BytesMessage, EmptyMessage};
 use serde_json::json;
 
@@ -37,7 +38,7 @@ fn list_witnesses() -> Result<(), Error> {
         .unwrap()
         .iter_mut()
         .map(|witness| {
-            witness["address"] = json!(jsont::bytes_to_hex_string(&witness["address"]));
+            jsont::fix_hex_field(witness, &["address"]);
         })
         .last();
     println!("{}", serde_json::to_string_pretty(&witnesses["witnesses"])?);
@@ -59,8 +60,8 @@ fn list_witnesses() -> Result<(), Error> {
         )?
         .get_num();
         let share_percent = 100 - kept_percent;
-        eprintln!(
-            "! {}\t{}\t{}%\t{}",
+        info!(
+            "{}\t{}\t{}%\t{}",
             keys::b58encode_check(wit.get_address()),
             wit.get_voteCount(),
             share_percent,
@@ -169,7 +170,7 @@ pub fn list_exchanges() -> Result<(), Error> {
         .unwrap()
         .iter_mut()
         .map(|ex| {
-            ex["creator_address"] = json!(jsont::bytes_to_hex_string(&ex["creator_address"]));
+            jsont::fix_hex_field(ex, &["creator_address"]);
             ex["first_token_id"] = json!(jsont::bytes_to_string(&ex["first_token_id"]));
             ex["second_token_id"] = json!(jsont::bytes_to_string(&ex["second_token_id"]));
         })
@@ -1,12 +1,18 @@
+pub mod abi;
 pub mod asset;
 pub mod batch;
 pub mod contract;
 pub mod create;
 pub mod get;
+pub mod journal;
 pub mod key;
 pub mod list;
+pub mod pay;
+pub mod repl;
 pub mod set;
 pub mod sign;
 pub mod system;
 pub mod transfer;
+pub mod tx;
 pub mod wallet;
+pub mod watch;
@@ -0,0 +1,188 @@
+//! Pay with TRON payment URIs, see `utils::uri`.
+
+use clap::ArgMatches;
+use keys::Address;
+use log::{info, warn};
+use proto::core::{TransferAssetContract, TransferContract, TriggerSmartContract};
+
+use crate::commands::contract::call::fee_limit_from_history;
+use crate::commands::tx::render_qr;
+use crate::error::Error;
+use crate::utils::abi::{self, AbiValue};
+use crate::utils::trc20;
+use crate::utils::trx::{self, TransactionHandler};
+use crate::utils::uri::PaymentUri;
+
+/// Token of a payment.
+enum PaymentToken {
+    Trx,
+    Trc10(String),
+    Trc20(Address),
+}
+
+impl PaymentToken {
+    /// Resolve `TRX`, a TRC10 token id, or a TRC20 contract address or well-known symbol.
+    fn resolve(token: Option<&str>) -> Result<Self, Error> {
+        match token {
+            None => Ok(PaymentToken::Trx),
+            Some(token) if token.eq_ignore_ascii_case("TRX") => Ok(PaymentToken::Trx),
+            Some(token) if !token.is_empty() && token.bytes().all(|c| c.is_ascii_digit()) => {
+                Ok(PaymentToken::Trc10(token.to_owned()))
+            }
+            Some(token) => trx::parse_address(token)
+                .or_else(|_| format!("/{}", token).parse::<Address>().map_err(Error::from))
+                .map(PaymentToken::Trc20)
+                .map_err(|_| Error::Runtime("unknown token, use TRX, a TRC10 id or a TRC20 address")),
+        }
+    }
+
+    fn symbol_and_decimals(&self) -> Result<(String, u8), Error> {
+        match self {
+            PaymentToken::Trx => Ok(("TRX".to_owned(), 6)),
+            PaymentToken::Trc10(id) => {
+                let asset = trx::get_asset(id)?;
+                let name = String::from_utf8_lossy(asset.get_name()).into_owned();
+                Ok((format!("{} (TRC10 #{})", name, id), asset.get_precision() as u8))
+            }
+            PaymentToken::Trc20(addr) => {
+                let meta = trc20::get_token_meta(addr)?;
+                let decimals = meta
+                    .decimals
+                    .ok_or(Error::Runtime("TRC20 token does not implement decimals()"))?;
+                let symbol = meta.symbol.unwrap_or_else(|| addr.to_string());
+                Ok((format!("{} (TRC20 {})", symbol, addr), decimals))
+            }
+        }
+    }
+}
+
+fn amount_to_i64(amount: &ethabi::Uint) -> Result<i64, Error> {
+    if *amount > ethabi::Uint::from(i64::max_value()) {
+        return Err(Error::Runtime("amount overflows"));
+    }
+    Ok(amount.low_u64() as i64)
+}
+
+fn pay_uri<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
+    let sender = trx::parse_address(matches.value_of("SENDER").expect("required in cli.yml; qed"))?;
+    let uri: PaymentUri = matches.value_of("URI").expect("required in cli.yml; qed").parse()?;
+
+    for (key, value) in &uri.unknown_params {
+        warn!("Unknown URI parameter ignored: {}={}", key, value);
+    }
+    let amount = uri.amount.as_ref().ok_or(Error::Runtime("payment URI has no amount"))?;
+    let token = PaymentToken::resolve(uri.token.as_deref())?;
+    let (symbol, decimals) = token.symbol_and_decimals()?;
+    let units = trc20::parse_units(amount, decimals)?;
+    // a payment is confirmed anyway, activation is only warned
+    trx::check_activation(&uri.address, !matches!(token, PaymentToken::Trc20(_)))?;
+    // NOTE: --fee-limit always overrides, it is applied after `map_raw_transaction`
+    let fee_limit = match token {
+        PaymentToken::Trc20(ref contract) if !matches.is_present("fee-limit") => Some(
+            fee_limit_from_history(contract, &abi::fnhash("transfer(address,uint256)")).ok_or(Error::InvalidInput(
+                "no previous transfers of the token to suggest a fee limit from, use --fee-limit",
+            ))?,
+        ),
+        _ => None,
+    };
+
+    info!("Sender:    {}", sender);
+    info!("Recipient: {}", uri.address);
+    info!("Amount:    {} {}", trc20::format_units(&units, decimals), symbol);
+    if let Some(ref memo) = uri.memo {
+        info!("Memo:      {}", memo);
+    }
+    if !matches.is_present("yes") && !trx::confirm("Send this payment?")? {
+        return Err(Error::Runtime("aborted by user"));
+    }
+
+    let memo = uri.memo.clone();
+    match token {
+        PaymentToken::Trx => {
+            let transfer_contract = TransferContract {
+                owner_address: sender.as_bytes().to_owned(),
+                to_address: uri.address.as_bytes().to_owned(),
+                amount: amount_to_i64(&units)?,
+                ..Default::default()
+            };
+            let mut handler = TransactionHandler::handle(transfer_contract, matches);
+            if let Some(memo) = memo {
+                handler.map_raw_transaction(move |raw| raw.set_data(memo.as_bytes().to_owned()));
+            }
+            handler.run()
+        }
+        PaymentToken::Trc10(id) => {
+            let transfer_contract = TransferAssetContract {
+                owner_address: sender.as_bytes().to_owned(),
+                to_address: uri.address.as_bytes().to_owned(),
+                asset_name: id.into_bytes(),
+                amount: amount_to_i64(&units)?,
+                ..Default::default()
+            };
+            let mut handler = TransactionHandler::handle(transfer_contract, matches);
+            if let Some(memo) = memo {
+                handler.map_raw_transaction(move |raw| raw.set_data(memo.as_bytes().to_owned()));
+            }
+            handler.run()
+        }
+        PaymentToken::Trc20(contract) => {
+            let mut data = abi::fnhash("transfer(address,uint256)").to_vec();
+            data.extend(abi::encode_values(&[
+                AbiValue::Address(uri.address),
+                AbiValue::Uint(units),
+            ]));
+            let trigger_contract = TriggerSmartContract {
+                owner_address: sender.as_bytes().to_owned(),
+                contract_address: contract.as_bytes().to_owned(),
+                data,
+                ..Default::default()
+            };
+            let mut handler = TransactionHandler::handle(trigger_contract, matches);
+            handler.map_raw_transaction(move |raw| {
+                if let Some(fee_limit) = fee_limit {
+                    raw.set_fee_limit(fee_limit);
+                }
+                if let Some(ref memo) = memo {
+                    raw.set_data(memo.as_bytes().to_owned());
+                }
+            });
+            handler.run()
+        }
+    }
+}
+
+fn make_uri(matches: &ArgMatches) -> Result<(), Error> {
+    let mut uri = PaymentUri::new(trx::parse_address(
+        matches.value_of("to").expect("required in cli.yml; qed"),
+    )?);
+    uri.amount = matches.value_of("amount").map(ToOwned::to_owned);
+    uri.token = matches.value_of("token").map(ToOwned::to_owned);
+    uri.memo = matches.value_of("memo").map(ToOwned::to_owned);
+
+    if let Some(ref amount) = uri.amount {
+        // validate amount format only, token decimals are checked when paying
+        let decimal_places = amount.find('.').map_or(0, |pos| amount.len() - pos - 1);
+        trc20::parse_units(amount, decimal_places.min(u8::max_value() as usize) as u8)?;
+    }
+    PaymentToken::resolve(uri.token.as_deref())?;
+
+    let uri = uri.to_string();
+    if matches.is_present("qr") {
+        println!("{}", render_qr(&uri)?);
+        info!("{}", uri);
+    } else {
+        println!("{}", uri);
+    }
+    Ok(())
+}
+
+pub fn main<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
+    match matches.subcommand() {
+        ("uri", Some(arg_matches)) => pay_uri(arg_matches),
+        ("make_uri", Some(arg_matches)) => make_uri(arg_matches),
+        _ => {
+            eprintln!("{}", matches.usage());
+            Err(Error::Runtime("error parsing command line"))
+        }
+    }
+}
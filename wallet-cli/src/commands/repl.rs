@@ -0,0 +1,134 @@
+//! Interactive mode, commands are read line by line and run in the same process.
+//!
+//! The gRPC connection and in-process caches, like TRC20 token metadata, contract ABIs and the latest block, are kept
+//! between commands. Global options are taken from the command line starting the REPL, and rejected on a line.
+//!
+//! - `history` lists previous commands, `!N` runs the N-th one and `!!` the last one.
+//! - `quit`, `exit` or end of input leaves.
+
+use log::warn;
+use std::io::{self, BufRead, Write};
+
+use crate::error::Error;
+
+const PROMPT: &str = "wallet-cli> ";
+
+/// Split a line into arguments like a shell: whitespace separated, with single or double quotes and backslash
+/// escapes.
+fn split_args(line: &str) -> Result<Vec<String>, Error> {
+    let mut args = vec![];
+    let mut arg: Option<String> = None;
+    let mut quote = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                let escaped = chars.next().ok_or(Error::InvalidInput("trailing backslash"))?;
+                arg.get_or_insert_with(String::new).push(escaped);
+            }
+            (Some(_), c) => arg.get_or_insert_with(String::new).push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                arg.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => args.extend(arg.take()),
+            (None, c) => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(Error::InvalidInput("unterminated quote"));
+    }
+    args.extend(arg);
+    Ok(args)
+}
+
+/// Expand `!!` and `!N` to a command in history, 1-based.
+fn expand_history<'a>(line: &'a str, history: &'a [String]) -> Result<&'a str, Error> {
+    match line {
+        "!!" => history
+            .last()
+            .map(String::as_str)
+            .ok_or(Error::InvalidInput("history is empty")),
+        _ if line.starts_with('!') => line[1..]
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| history.get(n.checked_sub(1)?))
+            .map(String::as_str)
+            .ok_or(Error::InvalidInput("no such command in history")),
+        _ => Ok(line),
+    }
+}
+
+/// Run commands read from stdin, until `quit` or end of input. `execute` gets arguments without the program name.
+pub fn main<F>(mut execute: F) -> Result<(), Error>
+where
+    F: FnMut(Vec<String>) -> Result<(), Error>,
+{
+    let stdin = io::stdin();
+    let mut history: Vec<String> = vec![];
+    loop {
+        eprint!("{}", PROMPT);
+        io::stderr().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            eprintln!();
+            return Ok(());
+        }
+        let line = match expand_history(line.trim(), &history) {
+            Ok(line) => line.to_owned(),
+            Err(e) => {
+                warn!("{:?}", e);
+                continue;
+            }
+        };
+        match &*line {
+            "" => continue,
+            "quit" | "exit" => return Ok(()),
+            "history" => {
+                for (i, cmd) in history.iter().enumerate() {
+                    println!("{:>4}  {}", i + 1, cmd);
+                }
+                continue;
+            }
+            _ => (),
+        }
+        history.push(line.clone());
+
+        // errors are printed, the session goes on
+        if let Err(e) = split_args(&line).and_then(&mut execute) {
+            eprintln!("Error: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(
+            split_args("get  transaction 0xabc").unwrap(),
+            vec!["get", "transaction", "0xabc"]
+        );
+        assert_eq!(
+            split_args(r#"abi_encode --types 'address,uint256' "a b" c\ d ''"#).unwrap(),
+            vec!["abi_encode", "--types", "address,uint256", "a b", "c d", ""]
+        );
+        assert_eq!(split_args(r#""say \"hi\"""#).unwrap(), vec![r#"say "hi""#]);
+        assert!(split_args("get 'block").is_err());
+        assert!(split_args("get block\\").is_err());
+    }
+
+    #[test]
+    fn test_expand_history() {
+        let history = vec!["get block".to_owned(), "get node".to_owned()];
+        assert_eq!(expand_history("!!", &history).unwrap(), "get node");
+        assert_eq!(expand_history("!1", &history).unwrap(), "get block");
+        assert!(expand_history("!0", &history).is_err());
+        assert!(expand_history("!3", &history).is_err());
+        assert!(expand_history("!!", &[]).is_err());
+        assert_eq!(expand_history("get account", &history).unwrap(), "get account");
+    }
+}
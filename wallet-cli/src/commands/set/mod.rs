@@ -1,21 +1,24 @@
 use clap::ArgMatches;
-use keys::Address;
-use proto::core::{AccountPermissionUpdateContract, AccountUpdateContract};
+use futures::executor;
+use log::info;
+use proto::core::{Account, AccountPermissionUpdateContract, AccountUpdateContract, UpdateBrokerageContract};
 use serde_json::json;
 use std::fs;
 use std::io;
 use std::io::Read;
 
 use crate::error::Error;
+use crate::utils::client;
 use crate::utils::jsont;
 use crate::utils::trx;
 
 /// Set account permission info.
 fn set_account_permission(matches: &ArgMatches) -> Result<(), Error> {
-    let addr = matches
-        .value_of("ADDR")
-        .expect("account address is required is cli.yml; qed")
-        .parse::<Address>()?;
+    let addr = trx::parse_address(
+        matches
+            .value_of("ADDR")
+            .expect("account address is required is cli.yml; qed"),
+    )?;
     let permission = matches.value_of("PERMISSION").expect("required in cli.yml; qed");
 
     let mut permission_info: serde_json::Value = if permission == "-" {
@@ -39,10 +42,11 @@ fn set_account_permission(matches: &ArgMatches) -> Result<(), Error> {
 
 /// Set account name.
 fn set_account_name(matches: &ArgMatches) -> Result<(), Error> {
-    let addr = matches
-        .value_of("ADDR")
-        .expect("account name is required is cli.yml; qed")
-        .parse::<Address>()?;
+    let addr = trx::parse_address(
+        matches
+            .value_of("ADDR")
+            .expect("account name is required is cli.yml; qed"),
+    )?;
     let name = matches.value_of("NAME").expect("required in cli.yml; qed");
 
     let update_contract: AccountUpdateContract = AccountUpdateContract {
@@ -54,10 +58,53 @@ fn set_account_name(matches: &ArgMatches) -> Result<(), Error> {
     trx::TransactionHandler::handle(update_contract, matches).run()
 }
 
+/// Set brokerage of an SR, the percentage of rewards kept by the SR.
+fn set_brokerage(matches: &ArgMatches) -> Result<(), Error> {
+    let addr = trx::parse_address(matches.value_of("ADDR").expect("required in cli.yml; qed"))?;
+    let brokerage = matches
+        .value_of("BROKERAGE")
+        .expect("required in cli.yml; qed")
+        .parse::<i32>()?;
+    if !(0..=100).contains(&brokerage) {
+        return Err(Error::Runtime("brokerage must be in range 0 to 100"));
+    }
+
+    let mut req = Account::new();
+    req.set_address(addr.as_bytes().to_owned());
+    let account = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
+    if !account.get_is_witness() {
+        return Err(Error::Runtime("account is not a witness, only SRs can set brokerage"));
+    }
+
+    let current = trx::get_brokerage(&addr)?;
+    info!("Current brokerage: kept {}%, sharing {}%", current, 100 - current);
+    info!("New brokerage:     kept {}%, sharing {}%", brokerage, 100 - brokerage);
+    if !matches.is_present("yes") && !trx::confirm("Continue?")? {
+        return Err(Error::Runtime("aborted by user"));
+    }
+
+    let update_contract = UpdateBrokerageContract {
+        owner_address: addr.as_bytes().to_owned(),
+        brokerage,
+        ..Default::default()
+    };
+    let mut handler = trx::TransactionHandler::handle(update_contract, matches);
+    handler.run()?;
+    handler.watch(|_| {
+        let updated = trx::get_brokerage(&addr)?;
+        info!("Brokerage on chain: kept {}%, sharing {}%", updated, 100 - updated);
+        if updated != brokerage {
+            info!("New brokerage is not visible yet, it might take effect after next maintenance");
+        }
+        Ok(())
+    })
+}
+
 pub fn main(matches: &ArgMatches) -> Result<(), Error> {
     match matches.subcommand() {
         ("account_permission", Some(arg_matches)) => set_account_permission(arg_matches),
         ("account_name", Some(arg_matches)) => set_account_name(arg_matches),
+        ("brokerage", Some(arg_matches)) => set_brokerage(arg_matches),
         _ => {
             eprintln!("{}", matches.usage());
             Err(Error::Runtime("error parsing command line"))
@@ -3,22 +3,20 @@
 use clap::ArgMatches;
 use futures::executor;
 use hex::{FromHex, ToHex};
-use keys::{Address, Private, Public};
+use keys::{Address, Public};
+use log::{info, warn};
 use proto::core::{Transaction, Transaction_raw as TransactionRaw};
-use protobuf::Message;
 use serde_json::json;
 use sha3::{Digest, Keccak256};
 use std::fs;
 use std::io::{self, Read};
 use std::path::Path;
 
-use crate::commands::wallet::sign_digest;
 use crate::error::Error;
 use crate::utils::client;
-use crate::utils::crypto;
+use crate::utils::journal;
 use crate::utils::jsont;
 use crate::utils::trx;
-use crate::CHAIN_ID;
 
 // Message Signature
 //
@@ -31,8 +29,8 @@ const TRX_MESSAGE_HEADER: &[u8; 22] = b"\x19TRON Signed Message:\n";
 pub fn sign_message(matches: &ArgMatches) -> Result<(), Error> {
     let message = matches.value_of("TRANSACTION").expect("required in cli.yml; qed");
 
-    if !matches.is_present("account") && !matches.is_present("private-key") {
-        return Err(Error::Runtime("-k/-K is required for sign a message"));
+    if !matches.is_present("account") && !matches.is_present("private-key") && !matches.is_present("keystore") {
+        return Err(Error::Runtime("-k/-K/--keystore is required for sign a message"));
     }
     if matches.is_present("broadcast") || matches.is_present("skip-sign") {
         return Err(Error::Runtime("-b/-s is not required for sign a message"));
@@ -44,14 +42,14 @@ pub fn sign_message(matches: &ArgMatches) -> Result<(), Error> {
         message.to_owned().into_bytes()
     };
     if origin_message.len() != 32 {
-        eprintln!("!! Warning: message is not 32 bytes long")
+        warn!("Warning: message is not 32 bytes long")
     }
 
     let mut raw_message = TRX_MESSAGE_HEADER.to_vec();
     raw_message.extend(origin_message.len().to_string().into_bytes());
 
-    eprintln!("! Raw message header => {:?}", String::from_utf8_lossy(&raw_message));
-    eprintln!("! Hex message body   => {}", hex::encode(&origin_message));
+    info!("Raw message header => {:?}", String::from_utf8_lossy(&raw_message));
+    info!("Hex message body   => {}", hex::encode(&origin_message));
 
     raw_message.extend(origin_message);
 
@@ -61,23 +59,12 @@ pub fn sign_message(matches: &ArgMatches) -> Result<(), Error> {
 
     assert_eq!(digest.len(), 32);
 
-    let mut signature = if let Some(raw_key) = matches.value_of("private-key") {
-        eprintln!("! Signing using raw private key from --private-key");
-        let priv_key = raw_key.parse::<Private>()?;
-        priv_key.sign_digest(&digest)?[..].to_owned()
-    } else {
-        let owner_address = matches
-            .value_of("account")
-            .and_then(|addr| addr.parse().ok())
-            .ok_or(Error::Runtime("can not determine owner address for signing"))?;
-        eprintln!("! Signing using wallet key {:}", owner_address);
-        sign_digest(&digest, &owner_address)?
-    };
+    let mut signature = trx::signer_of(matches, None)?.sign(&digest.into())?;
 
     // yep, the magic
     signature[64] += 27;
 
-    println!("! Signature = {}", hex::encode(signature));
+    println!("! Signature = {}", hex::encode(&signature[..]));
 
     Ok(())
 }
@@ -88,7 +75,7 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
     let trx_raw: String = match trx {
         fname if Path::new(fname).exists() => fs::read_to_string(Path::new(fname))?,
         "-" => {
-            eprintln!("Loading transaction from STDIN...");
+            info!("Loading transaction from STDIN...");
             let mut buffer = String::new();
             io::stdin().read_to_string(&mut buffer)?;
             buffer
@@ -109,7 +96,10 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
                 .as_array()
                 .unwrap()
                 .iter()
-                .map(|sig| signatures.push(sig.as_str().expect("malformed json").to_owned()))
+                .map(|sig| {
+                    let sig = sig.as_str().expect("malformed json");
+                    signatures.push(sig.trim_start_matches("0x").to_owned())
+                })
                 .last();
         }
         Vec::from_hex(trx["raw_data_hex"].as_str().expect("raw_data_hex field required"))?
@@ -120,42 +110,30 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
     let raw = protobuf::parse_from_bytes::<TransactionRaw>(&raw_data)?;
     let mut trx_json = serde_json::to_value(&raw)?;
     jsont::fix_transaction_raw(&mut trx_json)?;
-    eprintln!("{:}", serde_json::to_string_pretty(&trx_json)?);
+    info!("{:}", serde_json::to_string_pretty(&trx_json)?);
 
     // signature
-    let txid = crypto::sha256(&raw.write_to_bytes()?);
-    let digest = if let Some(chain_id) = unsafe { CHAIN_ID } {
-        let mut raw = (&txid[..]).to_owned();
-        raw.extend(Vec::from_hex(chain_id)?);
-        crypto::sha256(&raw)
-    } else {
-        txid
-    };
+    let txid = trx::transaction_hash(&raw)?;
+    let digest = trx::signing_digest(&txid)?;
 
     if !signatures.is_empty() {
-        eprintln!("! Already signed by:");
+        info!("Already signed by:");
         for sig in &signatures {
             let public = Public::recover_digest(&digest[..], &FromHex::from_hex(sig)?)?;
-            eprintln!("  {}", Address::from_public(&public));
+            info!("  {}", Address::from_public(&public));
         }
     }
 
+    let mut signer_address = None;
     if !matches.is_present("skip-sign") {
-        let signature = if let Some(raw_key) = matches.value_of("private-key") {
-            eprintln!("! Signing using raw private key from --private-key");
-            let priv_key = raw_key.parse::<Private>()?;
-            priv_key.sign_digest(&digest)?[..].to_owned()
-        } else {
-            let owner_address = matches
-                .value_of("account")
-                .and_then(|addr| addr.parse().ok())
-                .or_else(|| trx::extract_owner_address_from_parameter(raw.contract[0].get_parameter()).ok())
-                .ok_or(Error::Runtime("can not determine owner address for signing"))?;
-            eprintln!("! Signing using wallet key {:}", owner_address);
-            sign_digest(&digest, &owner_address)?
-        };
-
-        let sig_hex = signature.encode_hex::<String>();
+        let signer = trx::signer_of(
+            matches,
+            trx::extract_owner_address_from_parameter(raw.contract[0].get_parameter()).ok(),
+        )?;
+        let signature = signer.sign(&digest)?;
+        signer_address = Some(signer.address());
+
+        let sig_hex = hex::encode(&signature[..]);
         if signatures.contains(&sig_hex) {
             return Err(Error::Runtime("already signed by this key"));
         } else {
@@ -166,14 +144,23 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
     let ret = json!({
         "raw_data": trx_json,
         "raw_data_hex": json!(raw_data.encode_hex::<String>()),
-        "signature": json!(signatures),
+        "signature": json!(signatures.iter().map(|sig| format!("0x{}", sig)).collect::<Vec<_>>()),
         "txID": json!(txid.encode_hex::<String>()),
     });
 
     println!("{:}", serde_json::to_string_pretty(&ret)?);
 
     if matches.is_present("broadcast") {
-        eprintln!("! Broadcasting transaction ...");
+        if !trx::check_ref_block(&raw)? {
+            if !matches.is_present("force") {
+                info!("Use --force to broadcast anyway");
+                return Err(Error::Runtime(
+                    "transaction references a block not on this chain (built for a different network?)",
+                ));
+            }
+            warn!("Transaction references a block not on this chain (built for a different network?)");
+        }
+        info!("Broadcasting transaction ...");
         let mut req = Transaction::new();
         req.set_raw_data(raw);
         req.set_signature(
@@ -186,12 +173,14 @@ pub fn main(matches: &ArgMatches) -> Result<(), Error> {
 
         let payload = executor::block_on(
             client::GRPC_CLIENT
-                .broadcast_transaction(Default::default(), req)
+                .broadcast_transaction(Default::default(), req.clone())
                 .drop_metadata(),
         )?;
+        journal::append(&req, &txid, signer_address.as_ref(), &journal::result_of(&payload));
         let mut result = serde_json::to_value(&payload)?;
         jsont::fix_api_return(&mut result);
-        eprintln!("got => {:}", serde_json::to_string_pretty(&result)?);
+        info!("got => {:}", serde_json::to_string_pretty(&result)?);
+        trx::check_broadcast_return(&payload)?;
     }
 
     Ok(())
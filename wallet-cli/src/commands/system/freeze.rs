@@ -1,19 +1,26 @@
 use clap::ArgMatches;
+use futures::executor;
 use keys::Address;
-use proto::core::{FreezeBalanceContract, ResourceCode, UnfreezeBalanceContract};
+use log::warn;
+use proto::api::DelegatedResourceMessage;
+use proto::core::{Account, FreezeBalanceContract, ResourceCode, UnfreezeBalanceContract};
 
 use crate::error::Error;
+use crate::utils::client;
+use crate::utils::time;
 use crate::utils::trx;
 
 pub fn freeze(matches: &ArgMatches) -> Result<(), Error> {
     let from = matches
         .value_of("FROM")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
     let receiver = matches
         .value_of("RECEIVER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong receiver address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing receiver address"))?;
     let amount = matches.value_of("AMOUNT").expect("required in cli.yml; qed");
     let duration = matches.value_of("duration").expect("has default in cli.yml; qed");
 
@@ -38,15 +45,69 @@ pub fn freeze(matches: &ArgMatches) -> Result<(), Error> {
     trx::TransactionHandler::handle(freeze_contract, matches).run()
 }
 
+/// Earliest unlock time of frozen balances (`(amount, expire_time)`) when none can be unfrozen at `now`.
+///
+/// An unfreeze releases all expired frozen balances, and fails on chain if there is none.
+fn locked_until(frozen: &[(i64, i64)], now: i64) -> Option<i64> {
+    let frozen = frozen.iter().filter(|(amount, _)| *amount > 0);
+    if frozen.clone().any(|&(_, expire_time)| expire_time <= now) {
+        None
+    } else {
+        frozen.map(|&(_, expire_time)| expire_time).min()
+    }
+}
+
+/// Frozen balances and their expire times, for the resource, frozen by `from` for `receiver`.
+fn frozen_balances(from: &Address, receiver: &Address, resource: ResourceCode) -> Result<Vec<(i64, i64)>, Error> {
+    if from == receiver {
+        let mut req = Account::new();
+        req.set_address(from.as_bytes().to_owned());
+        let account = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
+        Ok(match resource {
+            ResourceCode::BANDWIDTH => account
+                .get_frozen()
+                .iter()
+                .map(|frozen| (frozen.frozen_balance, frozen.expire_time))
+                .collect(),
+            _ => {
+                let frozen = account.get_account_resource().get_frozen_balance_for_energy();
+                vec![(frozen.frozen_balance, frozen.expire_time)]
+            }
+        })
+    } else {
+        let mut req = DelegatedResourceMessage::new();
+        req.set_fromAddress(from.as_bytes().to_owned());
+        req.set_toAddress(receiver.as_bytes().to_owned());
+        let payload = executor::block_on(
+            client::GRPC_CLIENT
+                .get_delegated_resource(Default::default(), req)
+                .drop_metadata(),
+        )?;
+        Ok(payload
+            .get_delegatedResource()
+            .iter()
+            .map(|delegate| match resource {
+                ResourceCode::BANDWIDTH => (
+                    delegate.frozen_balance_for_bandwidth,
+                    delegate.expire_time_for_bandwidth,
+                ),
+                _ => (delegate.frozen_balance_for_energy, delegate.expire_time_for_energy),
+            })
+            .collect())
+    }
+}
+
 pub fn unfreeze(matches: &ArgMatches) -> Result<(), Error> {
     let from = matches
         .value_of("FROM")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
     let receiver = matches
         .value_of("RECEIVER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong receiver address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing receiver address"))?;
 
     // if receiver is self, receiver_address must be empty
     let unfreeze_contract = UnfreezeBalanceContract {
@@ -64,5 +125,34 @@ pub fn unfreeze(matches: &ArgMatches) -> Result<(), Error> {
         ..Default::default()
     };
 
+    let frozen = frozen_balances(&from, &receiver, unfreeze_contract.resource)?;
+    if let Some(expire_time) = locked_until(&frozen, trx::timestamp_millis()) {
+        warn!(
+            "Frozen balance is locked until {}",
+            time::format_timestamp(expire_time / 1_000, 0)
+        );
+        return Err(Error::Runtime("frozen balance is still locked"));
+    }
+
     trx::TransactionHandler::handle(unfreeze_contract, matches).run()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locked_until() {
+        let now = 1_600_000_000_000;
+        assert_eq!(locked_until(&[], now), None);
+        assert_eq!(locked_until(&[(0, now + 1_000)], now), None);
+        assert_eq!(
+            locked_until(&[(1_000_000, now - 1_000), (1_000_000, now + 1_000)], now),
+            None
+        );
+        assert_eq!(
+            locked_until(&[(1_000_000, now + 2_000), (1_000_000, now + 1_000)], now),
+            Some(now + 1_000)
+        );
+    }
+}
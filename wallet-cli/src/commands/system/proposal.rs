@@ -1,5 +1,4 @@
 use clap::ArgMatches;
-use keys::Address;
 use proto::core::{ProposalApproveContract, ProposalCreateContract, ProposalDeleteContract};
 use std::collections::HashMap;
 
@@ -9,8 +8,9 @@ use crate::utils::trx;
 pub fn create(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
 
     let params = matches
         .values_of("PARAMS")
@@ -35,8 +35,9 @@ pub fn create(matches: &ArgMatches) -> Result<(), Error> {
 pub fn approve(approve: bool, matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
     let id = matches.value_of("ID").expect("required in cli.yml; qed");
 
     let approve_contract = ProposalApproveContract {
@@ -51,8 +52,9 @@ pub fn approve(approve: bool, matches: &ArgMatches) -> Result<(), Error> {
 pub fn delete(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
     let id = matches.value_of("ID").expect("required in cli.yml; qed");
 
     let delete_contract = ProposalDeleteContract {
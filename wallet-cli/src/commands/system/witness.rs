@@ -1,6 +1,5 @@
 use clap::ArgMatches;
 use itertools::Itertools;
-use keys::Address;
 use proto::core::{
     UpdateBrokerageContract, VoteWitnessContract, VoteWitnessContract_Vote as Vote, WithdrawBalanceContract,
     WitnessCreateContract, WitnessUpdateContract,
@@ -12,8 +11,9 @@ use crate::utils::trx;
 pub fn vote(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
 
     let votes = match matches.values_of("VOTES") {
         Some(vote_args) => vote_args
@@ -22,7 +22,7 @@ pub fn vote(matches: &ArgMatches) -> Result<(), Error> {
             .map(|chunk| {
                 if let &[addr, count] = &chunk.collect::<Vec<_>>()[..] {
                     Ok(Vote {
-                        vote_address: addr.parse::<Address>()?.as_bytes().to_owned(),
+                        vote_address: trx::parse_address(addr)?.as_bytes().to_owned(),
                         vote_count: count.parse()?,
                         ..Default::default()
                     })
@@ -46,8 +46,9 @@ pub fn vote(matches: &ArgMatches) -> Result<(), Error> {
 pub fn create(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
     let url = matches.value_of("URL").expect("required in cli.yml; qed");
 
     let create_contract = WitnessCreateContract {
@@ -61,8 +62,9 @@ pub fn create(matches: &ArgMatches) -> Result<(), Error> {
 pub fn update(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
     let url = matches.value_of("URL").expect("required in cli.yml; qed");
 
     let update_contract = WitnessUpdateContract {
@@ -76,8 +78,9 @@ pub fn update(matches: &ArgMatches) -> Result<(), Error> {
 pub fn withdraw_reward(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
 
     let withdraw_contract = WithdrawBalanceContract {
         owner_address: sender.as_bytes().to_owned(),
@@ -89,8 +92,9 @@ pub fn withdraw_reward(matches: &ArgMatches) -> Result<(), Error> {
 pub fn update_brokerage(matches: &ArgMatches) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong from address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing from address"))?;
     let brokerage = matches
         .value_of("BROKERAGE")
         .expect("required in cli.yml; qed")
@@ -1,5 +1,5 @@
 use clap::ArgMatches;
-use keys::Address;
+use log::info;
 use proto::core::TransferContract;
 
 use crate::error::Error;
@@ -9,12 +9,11 @@ use crate::utils::trx::TransactionHandler;
 pub fn main<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
     let sender = matches
         .value_of("SENDER")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong sender address format"))?;
-    let recipient = matches
-        .value_of("RECIPIENT")
-        .and_then(|s| s.parse::<Address>().ok())
-        .ok_or(Error::Runtime("wrong recipient address format"))?;
+        .map(trx::parse_address)
+        .transpose()?
+        .ok_or(Error::Runtime("missing sender address"))?;
+    let recipient = trx::parse_address(matches.value_of("RECIPIENT").expect("required in cli.yml; qed"))?;
+    let activates = trx::check_activation(&recipient, true)?;
     let amount = matches.value_of("AMOUNT").expect("required in cli.yml; qed");
 
     let transfer_contract = TransferContract {
@@ -24,8 +23,11 @@ pub fn main<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
         ..Default::default()
     };
 
-    eprintln!("sender:    {:}", sender);
-    eprintln!("recipient: {:}", recipient);
+    info!("sender:    {:}", sender);
+    info!("recipient: {:}", recipient);
+    if activates && !matches.is_present("yes") && !trx::confirm("Activate the recipient account?")? {
+        return Err(Error::Runtime("aborted by user"));
+    }
 
     TransactionHandler::handle(transfer_contract, matches).run()
 }
@@ -0,0 +1,277 @@
+//! Move unsigned/signed transactions through QR codes, for air-gapped signing, and inspect signatures of partially
+//! signed multisig transactions.
+//!
+//! See `utils::qr` for the envelope format.
+
+use clap::ArgMatches;
+use futures::executor;
+use keys::Address;
+use log::{info, warn};
+use proto::api::{
+    TransactionApprovedList_Result_response_code as ApprovedListCode,
+    TransactionSignWeight_Result_response_code as SignWeightCode,
+};
+use proto::core::{Account, Key, Permission, Permission_PermissionType as PermissionType};
+use qrcode::render::unicode;
+use qrcode::QrCode;
+use std::convert::TryFrom;
+use std::io::{self, BufRead};
+use std::thread;
+use std::time::Duration;
+
+use crate::commands::wallet;
+use crate::error::Error;
+use crate::utils::client;
+use crate::utils::qr;
+use crate::utils::trx;
+
+pub(crate) fn render_qr(envelope: &str) -> Result<String, Error> {
+    let code = QrCode::new(envelope.as_bytes()).map_err(|_| Error::Runtime("can not encode QR code"))?;
+    Ok(code
+        .render::<unicode::Dense1x2>()
+        .dark_color(unicode::Dense1x2::Light)
+        .light_color(unicode::Dense1x2::Dark)
+        .build())
+}
+
+fn to_qr(matches: &ArgMatches) -> Result<(), Error> {
+    let raw = matches.value_of("HEX").expect("required in cli.yml; qed");
+    let data = hex::decode(raw.trim_start_matches("0x"))?;
+    let chunk_size = matches
+        .value_of("chunk-size")
+        .expect("has default in cli.yml; qed")
+        .parse::<usize>()?;
+    let max_chunks = matches
+        .value_of("max-chunks")
+        .expect("has default in cli.yml; qed")
+        .parse::<usize>()?;
+    if chunk_size == 0 {
+        return Err(Error::Runtime("chunk size must be positive"));
+    }
+
+    let envelopes = qr::encode_chunks(&data, chunk_size);
+    if envelopes.len() > max_chunks {
+        info!(
+            "Transaction needs {} QR codes, more than --max-chunks {}",
+            envelopes.len(),
+            max_chunks
+        );
+        info!("Use file transfer instead, or increase --chunk-size");
+        return Err(Error::Runtime("too many QR chunks"));
+    }
+    let frames = envelopes
+        .iter()
+        .map(|envelope| render_qr(envelope))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    match matches.value_of("animate") {
+        Some(interval) if frames.len() > 1 => {
+            let interval = Duration::from_millis(interval.parse()?);
+            info!("Cycling {} QR codes, Ctrl-C to stop", frames.len());
+            for (i, frame) in frames.iter().enumerate().cycle() {
+                // clear screen, move cursor to top-left
+                print!("\x1b[2J\x1b[H");
+                println!("[{}/{}]\n{}", i + 1, frames.len(), frame);
+                thread::sleep(interval);
+            }
+        }
+        _ => {
+            for (i, (frame, envelope)) in frames.iter().zip(envelopes.iter()).enumerate() {
+                println!("[{}/{}]\n{}", i + 1, frames.len(), frame);
+                info!("{}", envelope);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn from_qr() -> Result<(), Error> {
+    let envelopes = io::stdin()
+        .lock()
+        .lines()
+        .filter(|line| line.as_ref().map(|l| !l.trim().is_empty()).unwrap_or(true))
+        .collect::<Result<Vec<_>, _>>()?;
+    let data = qr::decode_chunks(&envelopes)?;
+    info!(
+        "Reassembled {} chunks, {} bytes, checksum OK",
+        envelopes.len(),
+        data.len()
+    );
+    println!("{}", hex::encode(data));
+    Ok(())
+}
+
+fn print_approved_list(approved_list: &[Vec<u8>]) {
+    println!("Approved by:");
+    for raw in approved_list {
+        match Address::try_from(&raw[..]) {
+            Ok(addr) => println!("  {}", addr),
+            Err(_) => println!("  {} (invalid address)", hex::encode(raw)),
+        }
+    }
+}
+
+fn print_ready(ready: bool) {
+    println!("READY TO BROADCAST: {}", if ready { "yes" } else { "no" });
+}
+
+fn sign_weight(matches: &ArgMatches) -> Result<(), Error> {
+    let txn = wallet::parse_transaction(matches.value_of("TRANSACTION").expect("required in cli.yml; qed"))?;
+    let payload = executor::block_on(
+        client::GRPC_CLIENT
+            .get_transaction_sign_weight(Default::default(), txn)
+            .drop_metadata(),
+    )?;
+
+    let permission = payload.get_permission();
+    println!(
+        "Permission: #{} {:?} {:?}",
+        permission.id,
+        permission.get_field_type(),
+        permission.permission_name
+    );
+    println!("Weight: {}/{}", payload.current_weight, permission.threshold);
+    print_approved_list(payload.get_approved_list());
+
+    let result = payload.get_result();
+    match result.code {
+        SignWeightCode::ENOUGH_PERMISSION | SignWeightCode::NOT_ENOUGH_PERMISSION => (),
+        code => warn!("Node reports {:?}: {}", code, result.message),
+    }
+    print_ready(result.code == SignWeightCode::ENOUGH_PERMISSION);
+    Ok(())
+}
+
+/// Permission targeted by `Permission_id` of a contract, the implicit owner permission if the account has none.
+fn permission_of(account: &Account, owner: &Address, permission_id: i32) -> Option<Permission> {
+    if permission_id == 0 {
+        if account.has_owner_permission() {
+            return Some(account.get_owner_permission().clone());
+        }
+        let mut key = Key::new();
+        key.set_address(owner.as_bytes().to_vec());
+        key.set_weight(1);
+        let mut permission = Permission::new();
+        permission.set_field_type(PermissionType::Owner);
+        permission.set_permission_name("owner".into());
+        permission.set_threshold(1);
+        permission.set_keys(vec![key].into());
+        return Some(permission);
+    }
+    account
+        .get_active_permission()
+        .iter()
+        .find(|permission| permission.id == permission_id)
+        .cloned()
+}
+
+/// Accumulated weight of approving addresses in a permission.
+fn approved_weight(permission: &Permission, approved_list: &[Vec<u8>]) -> i64 {
+    permission
+        .get_keys()
+        .iter()
+        .filter(|key| approved_list.contains(&key.address))
+        .map(|key| key.weight)
+        .sum()
+}
+
+fn approved_list(matches: &ArgMatches) -> Result<(), Error> {
+    let txn = wallet::parse_transaction(matches.value_of("TRANSACTION").expect("required in cli.yml; qed"))?;
+    let contract = txn
+        .get_raw_data()
+        .get_contract()
+        .first()
+        .ok_or(Error::Runtime("transaction has no contract"))?;
+    let owner = trx::extract_owner_address_from_parameter(contract.get_parameter())?;
+    let permission_id = contract.get_Permission_id();
+
+    let payload = executor::block_on(
+        client::GRPC_CLIENT
+            .get_transaction_approved_list(Default::default(), txn.clone())
+            .drop_metadata(),
+    )?;
+    print_approved_list(payload.get_approved_list());
+
+    let result = payload.get_result();
+    if result.code != ApprovedListCode::SUCCESS {
+        warn!("Node reports {:?}: {}", result.code, result.message);
+        print_ready(false);
+        return Ok(());
+    }
+
+    let mut req = Account::new();
+    req.set_address(owner.as_bytes().to_owned());
+    let account = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
+    match permission_of(&account, &owner, permission_id) {
+        Some(permission) => {
+            let weight = approved_weight(&permission, payload.get_approved_list());
+            println!(
+                "Permission: #{} {:?} {:?}",
+                permission_id,
+                permission.get_field_type(),
+                permission.permission_name
+            );
+            println!("Weight: {}/{}", weight, permission.threshold);
+            print_ready(weight >= permission.threshold);
+        }
+        None => {
+            warn!("Permission #{} not found in account {}", permission_id, owner);
+            print_ready(false);
+        }
+    }
+    Ok(())
+}
+
+pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    match matches.subcommand() {
+        ("qr", Some(arg_matches)) => to_qr(arg_matches),
+        ("from-qr", _) => from_qr(),
+        ("sign-weight", Some(arg_matches)) => sign_weight(arg_matches),
+        ("approved-list", Some(arg_matches)) => approved_list(arg_matches),
+        _ => {
+            eprintln!("{}", matches.usage());
+            Err(Error::Runtime("error parsing command line"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approved_weight() {
+        let owner: Address = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap();
+        let other: Address = "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap();
+        let account = Account::new();
+
+        let permission = permission_of(&account, &owner, 0).unwrap();
+        assert_eq!(permission.threshold, 1);
+        assert_eq!(approved_weight(&permission, &[owner.as_bytes().to_vec()]), 1);
+        assert_eq!(approved_weight(&permission, &[other.as_bytes().to_vec()]), 0);
+        assert!(permission_of(&account, &owner, 2).is_none());
+
+        let mut active = Permission::new();
+        active.set_id(2);
+        active.set_threshold(3);
+        active.set_keys(
+            vec![(&owner, 2), (&other, 1)]
+                .into_iter()
+                .map(|(addr, weight)| {
+                    let mut key = Key::new();
+                    key.set_address(addr.as_bytes().to_vec());
+                    key.set_weight(weight);
+                    key
+                })
+                .collect(),
+        );
+        let mut account = Account::new();
+        account.set_active_permission(vec![active].into());
+        let permission = permission_of(&account, &owner, 2).unwrap();
+        assert_eq!(approved_weight(&permission, &[owner.as_bytes().to_vec()]), 2);
+        assert_eq!(
+            approved_weight(&permission, &[owner.as_bytes().to_vec(), other.as_bytes().to_vec()]),
+            3
+        );
+    }
+}
@@ -1,23 +1,50 @@
 use clap::ArgMatches;
 use hex::{FromHex, ToHex};
-use keys::{Address, KeyPair, Private, Public};
+use keys::{Address, KeyPair, MemorySigner, Private, Public, Signer};
+use log::{debug, info, warn};
+use proto::core::{Transaction, Transaction_raw as TransactionRaw};
+use protobuf::Message;
 use std::convert::TryFrom;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
 use tokio::runtime::Builder;
+use tonic::transport::Channel;
 use tonic::Request;
 use walletd::api::local_wallet_client::LocalWalletClient;
 use walletd::api::sign_digest_request::PrivateKeyOf;
 use walletd::api::{
     CreateKeyRequest, CreateKeyResponse, CreateRequest, CreateZkeyRequest, CreateZkeyResponse, ImportKeyRequest,
-    ImportZkeyRequest, ListKeysRequest, ListKeysResponse, ListZkeysRequest, ListZkeysResponse, LockRequest,
-    OpenRequest, SignDigestRequest, SignDigestResponse, StatusResponse, UnlockRequest,
+    ImportZkeyRequest, ListKeysRequest, ListKeysResponse, ListPrivateKeysRequest, ListPrivateKeysResponse,
+    ListZkeysRequest, ListZkeysResponse, LockRequest, OpenRequest, SignDigestRequest, SignDigestResponse,
+    StatusResponse, UnlockRequest,
 };
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::error::Error;
+#[cfg(unix)]
+use crate::utils::agent;
+use crate::utils::crypto;
+use crate::utils::keystore::{self, KeystoreFormat};
+use crate::utils::session;
+use crate::utils::trx;
+use crate::utils::walletd::ensure_walletd;
+use crate::{IN_REPL, REPLAY_DIR};
 
 const WALLETD_RPC_URL: &str = "http://[::1]:8888";
 
+/// Connect to walletd, started on first use. Replays never need, or start, walletd.
+async fn connect_walletd() -> Result<LocalWalletClient<Channel>, Error> {
+    if unsafe { REPLAY_DIR }.is_some() {
+        return Err(Error::Runtime("walletd is not available in replay mode"));
+    }
+    ensure_walletd()?;
+    Ok(LocalWalletClient::connect(WALLETD_RPC_URL).await?)
+}
+
 async fn create_wallet(name: &str, password: &str) -> Result<(), Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+    let mut wallet_client = connect_walletd().await?;
 
     let request = Request::new(CreateRequest {
         name: name.into(),
@@ -31,7 +58,7 @@ async fn create_wallet(name: &str, password: &str) -> Result<(), Error> {
 }
 
 async fn open_wallet(name: &str) -> Result<(), Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+    let mut wallet_client = connect_walletd().await?;
 
     let request = Request::new(OpenRequest { name: name.into() });
     let response = wallet_client.open(request).await?;
@@ -42,7 +69,7 @@ async fn open_wallet(name: &str) -> Result<(), Error> {
 }
 
 async fn lock_wallet() -> Result<(), Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+    let mut wallet_client = connect_walletd().await?;
 
     let request = Request::new(LockRequest { name: "".into() });
     let response = wallet_client.lock(request).await?;
@@ -53,7 +80,7 @@ async fn lock_wallet() -> Result<(), Error> {
 }
 
 async fn unlock_wallet(password: &str) -> Result<(), Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+    let mut wallet_client = connect_walletd().await?;
 
     let request = Request::new(UnlockRequest {
         name: "".into(),
@@ -66,8 +93,53 @@ async fn unlock_wallet(password: &str) -> Result<(), Error> {
     Ok(())
 }
 
+/// Unlock a keystore key, in this process in the REPL, otherwise in the agent. See `utils::session`.
+fn unlock_keystore(matches: &ArgMatches) -> Result<(), Error> {
+    let path = Path::new(matches.value_of("keystore").expect("checked by caller; qed"));
+    let idle_timeout = matches
+        .value_of("timeout")
+        .map(|secs| secs.parse().map(Duration::from_secs))
+        .transpose()
+        .map_err(|_| Error::InvalidInput("invalid --timeout, use a number of seconds"))?
+        .unwrap_or(session::DEFAULT_IDLE_TIMEOUT);
+    let password = match matches.value_of("password") {
+        Some(password) => Zeroizing::new(password.to_owned()),
+        None => prompt_password("Keystore Password:")?,
+    };
+    let private = keystore::decrypt_file(path, &password)?;
+    let address = Address::from_private(&private);
+    if unsafe { IN_REPL } {
+        session::unlock(MemorySigner::new(private), Some(path), idle_timeout);
+        info!(
+            "Unlocked {} in this process, locked after {}s idle",
+            address,
+            idle_timeout.as_secs()
+        );
+    } else {
+        unlock_in_agent(&private, path, idle_timeout)?;
+        info!(
+            "Unlocked {} in the agent, locked after {}s idle",
+            address,
+            idle_timeout.as_secs()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn unlock_in_agent(private: &Private, path: &Path, idle_timeout: Duration) -> Result<(), Error> {
+    agent::unlock(private, path, idle_timeout)
+}
+
+#[cfg(not(unix))]
+fn unlock_in_agent(_: &Private, _: &Path, _: Duration) -> Result<(), Error> {
+    Err(Error::InvalidInput(
+        "no agent on this platform, unlock the keystore in the REPL",
+    ))
+}
+
 async fn create_key_in_wallet() -> Result<(), Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+    let mut wallet_client = connect_walletd().await?;
 
     let request = Request::new(CreateKeyRequest { name: "".into() });
     let response = wallet_client.create_key(request).await?;
@@ -81,16 +153,109 @@ async fn create_key_in_wallet() -> Result<(), Error> {
         println!("Public:  {:}", kp.public());
         println!("Private: {:}", kp.private());
     } else {
-        eprintln!("{:?}", &reply);
+        debug!("{:?}", &reply);
     }
     Ok(())
 }
 
-async fn import_key_to_wallet(private_key: &str) -> Result<(), Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+fn invalid_key(detected: &str) -> Error {
+    warn!("Invalid private key: {}", detected);
+    Error::InvalidInput("invalid private key")
+}
+
+/// Strip the leading 0x80 byte, the compression flag and the checksum of a WIF-style key.
+fn strip_wif(raw: &[u8], has_checksum: bool) -> Result<&[u8], Error> {
+    if raw.first() != Some(&0x80) {
+        return Err(invalid_key(&format!(
+            "{} bytes without the leading 0x80 byte of WIF",
+            raw.len()
+        )));
+    }
+    let payload = if has_checksum {
+        let (payload, checksum) = raw.split_at(raw.len() - 4);
+        if crypto::sha256(&crypto::sha256(payload))[..4] != *checksum {
+            return Err(invalid_key("WIF with a wrong checksum, mistyped?"));
+        }
+        payload
+    } else {
+        raw
+    };
+    match payload.len() {
+        33 => Ok(&payload[1..]),
+        34 if payload[33] == 0x01 => Ok(&payload[1..33]),
+        _ => Err(invalid_key(&format!("WIF of {} bytes", payload.len()))),
+    }
+}
+
+/// Parse a private key, in 64-char hex with or without `0x`, hex with a leading 0x80 byte and optionally the WIF
+/// checksum, or WIF in base58check.
+fn parse_private_key_input(input: &str) -> Result<Private, Error> {
+    let input = input.trim();
+    let digits = input.trim_start_matches("0x").trim_start_matches("0X");
+    let key = if !digits.is_empty() && digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+        let raw = Vec::from_hex(digits).map_err(|_| invalid_key(&format!("hex of odd length {}", digits.len())))?;
+        match raw.len() {
+            32 => raw,
+            33 | 34 => strip_wif(&raw, false)?.to_vec(),
+            37 | 38 => strip_wif(&raw, true)?.to_vec(),
+            len => {
+                return Err(invalid_key(&format!(
+                    "hex of {} bytes, expected 32, or 33 to 38 with a leading 0x80 byte",
+                    len
+                )))
+            }
+        }
+    } else if input.len() < 40 {
+        return Err(invalid_key(&format!("{} chars, too short for a key", input.len())));
+    } else {
+        let raw = keys::b58decode_check(input)
+            .map_err(|_| invalid_key("neither hex nor base58check, or a wrong checksum"))?;
+        strip_wif(&raw, false)?.to_vec()
+    };
+    Private::try_from(&key[..])
+        .and_then(|private| KeyPair::from_private(private.clone()).map(|_| private))
+        .map_err(|_| invalid_key("not a valid secp256k1 private key"))
+}
+
+async fn import_key_to_wallet<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
+    let private = parse_private_key_input(matches.value_of("private-key").expect("required in cli.yml; qed"))?;
+    let address = Address::from_private(&private);
+    info!("Address of the key: {}", address);
+    match matches.value_of("address") {
+        Some(expected) if trx::parse_address(expected)? != address => {
+            return Err(Error::Runtime("address of the key does not match --address"));
+        }
+        Some(_) => (),
+        None if !trx::confirm("Is this the address you expect?")? => return Err(Error::Runtime("aborted by user")),
+        None => (),
+    }
+    import_private_to_wallet(&private).await
+}
+
+/// Addresses of keys in the wallet.
+async fn addresses_in_wallet() -> Result<Vec<Address>, Error> {
+    let mut wallet_client = connect_walletd().await?;
 
-    let private: Private = private_key.parse()?;
-    println!("Importing private key for {:} ...", Address::from_private(&private));
+    let request = Request::new(ListKeysRequest { name: "".into() });
+    let reply: ListKeysResponse = wallet_client.list_keys(request).await?.into_inner();
+    if reply.code != 200 {
+        debug!("{:?}", &reply);
+        return Err(Error::Runtime("can not read keys from wallet, is it unlocked?"));
+    }
+    reply
+        .public_keys
+        .into_iter()
+        .map(|raw| Ok(Address::from_public(&Public::try_from(raw)?)))
+        .collect()
+}
+
+async fn import_private_to_wallet(private: &Private) -> Result<(), Error> {
+    if addresses_in_wallet().await?.contains(&Address::from_private(private)) {
+        return Err(Error::Runtime("key is already in the wallet"));
+    }
+    let mut wallet_client = connect_walletd().await?;
+
+    println!("Importing private key for {:} ...", Address::from_private(private));
     let request = Request::new(ImportKeyRequest {
         name: "".into(),
         private_key: private.as_bytes().to_owned(),
@@ -102,8 +267,92 @@ async fn import_key_to_wallet(private_key: &str) -> Result<(), Error> {
     Ok(())
 }
 
+pub(crate) fn prompt_password(prompt: &str) -> Result<Zeroizing<String>, Error> {
+    rpassword::prompt_password_stderr(prompt)
+        .map(Zeroizing::new)
+        .map_err(|_| Error::Runtime("can not get password"))
+}
+
+async fn import_keystore_to_wallet<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
+    let path = matches.value_of("FILE").expect("required in cli.yml; qed");
+    let format: KeystoreFormat = matches.value_of("format").expect("required in cli.yml; qed").parse()?;
+
+    let content = Zeroizing::new(fs::read_to_string(path)?);
+    let keystore: serde_json::Value =
+        serde_json::from_str(&content).map_err(|_| Error::Runtime("corrupted keystore file, not a JSON"))?;
+
+    let password = prompt_password("Keystore Password:")?;
+    let (address, private) = keystore::decrypt(&keystore, &password, format)?;
+    info!("Address {} verified", address);
+
+    import_private_to_wallet(&private).await
+}
+
+async fn export_keystore_from_wallet<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
+    let address = trx::parse_address(matches.value_of("ADDRESS").expect("required in cli.yml; qed"))?;
+    let format: KeystoreFormat = matches
+        .value_of("format")
+        .expect("has default in cli.yml; qed")
+        .parse()?;
+
+    let wallet_password = prompt_password("Wallet Password:")?;
+    let mut wallet_client = connect_walletd().await?;
+    let request = Request::new(ListPrivateKeysRequest {
+        name: "".into(),
+        password: wallet_password.to_string(),
+    });
+    let response = wallet_client.list_private_keys(request).await?;
+    let mut reply: ListPrivateKeysResponse = response.into_inner();
+    if reply.code == 403 {
+        return Err(Error::Runtime("wrong wallet password"));
+    }
+    if reply.code != 200 {
+        debug!("{:?}", &reply.message);
+        return Err(Error::Runtime("can not read keys from wallet, is it unlocked?"));
+    }
+    let private = reply
+        .private_keys
+        .iter()
+        .filter_map(|raw| Private::try_from(raw).ok())
+        .find(|private| Address::from_private(private) == address);
+    reply.private_keys.iter_mut().for_each(|raw| raw.zeroize());
+    let private = private.ok_or(Error::Runtime("key not in wallet"))?;
+
+    let password = prompt_password("Keystore Password:")?;
+    if *password != *prompt_password("Retype Password:")? {
+        return Err(Error::Runtime("password mismatch"));
+    }
+    let keystore = serde_json::to_string_pretty(&keystore::encrypt(&private, &password, format)?)?;
+
+    match matches.value_of("output") {
+        Some(path) => {
+            write_new_private_file(Path::new(path), keystore.as_bytes())?;
+            info!("Keystore of {} written to {}", address, path);
+        }
+        None => println!("{}", keystore),
+    }
+    Ok(())
+}
+
+/// Write a new file readable by the owner only. An existing file is never overwritten.
+fn write_new_private_file(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(|e| match e.kind() {
+        io::ErrorKind::AlreadyExists => Error::InvalidInput("output file exists, refusing to overwrite"),
+        _ => Error::from(e),
+    })?;
+    file.write_all(contents)?;
+    Ok(())
+}
+
 async fn list_keys_in_wallet() -> Result<(), Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+    let mut wallet_client = connect_walletd().await?;
 
     let request = Request::new(ListKeysRequest { name: "".into() });
     let response = wallet_client.list_keys(request).await?;
@@ -117,13 +366,13 @@ async fn list_keys_in_wallet() -> Result<(), Error> {
             println!("         Public: {:}\n", pub_key);
         }
     } else {
-        eprintln!("{:?}", &reply);
+        debug!("{:?}", &reply);
     }
     Ok(())
 }
 
 async fn create_zkey_in_wallet() -> Result<(), Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+    let mut wallet_client = connect_walletd().await?;
 
     let request = Request::new(CreateZkeyRequest {});
     let response = wallet_client.create_zkey(request).await?;
@@ -133,13 +382,13 @@ async fn create_zkey_in_wallet() -> Result<(), Error> {
         println!("Address: {:}", reply.address);
         println!("SK:      {:}", reply.sk.encode_hex::<String>());
     } else {
-        eprintln!("{:?}", &reply);
+        debug!("{:?}", &reply);
     }
     Ok(())
 }
 
 async fn import_zkey_to_wallet<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+    let mut wallet_client = connect_walletd().await?;
 
     let address = matches.value_of("ADDR").expect("required in cli.yml; qed").to_owned();
     let sk = Vec::from_hex(matches.value_of("sk").expect("required in cli.yml; qed"))?;
@@ -154,7 +403,7 @@ async fn import_zkey_to_wallet<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Er
 }
 
 async fn list_zkeys_in_wallet() -> Result<(), Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+    let mut wallet_client = connect_walletd().await?;
 
     let request = Request::new(ListZkeysRequest {});
     let response = wallet_client.list_zkeys(request).await?;
@@ -164,13 +413,13 @@ async fn list_zkeys_in_wallet() -> Result<(), Error> {
             println!("Address: {:}", addr);
         }
     } else {
-        eprintln!("{:?}", &reply);
+        debug!("{:?}", &reply);
     }
     Ok(())
 }
 
 async fn sign_digest_via_address(digest: &[u8], address: &Address) -> Result<Vec<u8>, Error> {
-    let mut wallet_client = LocalWalletClient::connect(WALLETD_RPC_URL).await?;
+    let mut wallet_client = connect_walletd().await?;
 
     let request = Request::new(SignDigestRequest {
         name: "default".to_owned(), // TODO: refine wallet name handling
@@ -183,7 +432,7 @@ async fn sign_digest_via_address(digest: &[u8], address: &Address) -> Result<Vec
     if reply.code == 200 {
         Ok(reply.signature)
     } else {
-        eprintln!("{:?}", &reply);
+        debug!("{:?}", &reply);
         Err(Error::Runtime("fail to sign digest"))
     }
 }
@@ -212,7 +461,21 @@ async fn run<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
             let wallet_name = arg_matches.value_of("name").expect("havs default in cli.yml; qed");
             open_wallet(wallet_name).await
         }
-        ("lock", _) => lock_wallet().await,
+        ("lock", _) => {
+            let count = session::lock();
+            if count > 0 {
+                info!("Locked {} keys unlocked in this session", count);
+            }
+            #[cfg(unix)]
+            {
+                let count = agent::lock()?;
+                if count > 0 {
+                    info!("Locked {} keys unlocked in the agent", count);
+                }
+            }
+            lock_wallet().await
+        }
+        ("unlock", Some(arg_matches)) if arg_matches.is_present("keystore") => unlock_keystore(arg_matches),
         ("unlock", Some(arg_matches)) => match arg_matches.value_of("password") {
             Some(password) => unlock_wallet(password).await,
             _ => {
@@ -221,11 +484,12 @@ async fn run<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
                 unlock_wallet(&password).await
             }
         },
+        #[cfg(unix)]
+        ("agent", _) => agent::serve(),
         ("create_key", _) => create_key_in_wallet().await,
-        ("import_key", Some(arg_matches)) => {
-            let priv_key = arg_matches.value_of("private-key").expect("required in cli.yml; qed");
-            import_key_to_wallet(priv_key).await
-        }
+        ("import_key", Some(arg_matches)) => import_key_to_wallet(arg_matches).await,
+        ("import", Some(arg_matches)) => import_keystore_to_wallet(arg_matches).await,
+        ("export", Some(arg_matches)) => export_keystore_from_wallet(arg_matches).await,
         ("keys", _) => list_keys_in_wallet().await,
         ("create_zkey", _) => create_zkey_in_wallet().await,
         ("import_zkey", Some(arg_matches)) => import_zkey_to_wallet(arg_matches).await,
@@ -237,7 +501,87 @@ async fn run<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
     }
 }
 
+/// Parse a transaction, serialized in hex, or JSON with `raw_data_hex` and `signature` as printed by `sign`.
+pub(crate) fn parse_transaction(input: &str) -> Result<Transaction, Error> {
+    let input = input.trim();
+    if !input.starts_with('{') {
+        return Ok(protobuf::parse_from_bytes(&Vec::from_hex(
+            input.trim_start_matches("0x"),
+        )?)?);
+    }
+
+    let json: serde_json::Value = serde_json::from_str(input)?;
+    let raw_data_hex = json["raw_data_hex"]
+        .as_str()
+        .ok_or(Error::Runtime("raw_data_hex field required"))?;
+    let mut txn = Transaction::new();
+    txn.set_raw_data(protobuf::parse_from_bytes::<TransactionRaw>(&Vec::from_hex(
+        raw_data_hex,
+    )?)?);
+    if let Some(signatures) = json["signature"].as_array() {
+        txn.set_signature(
+            signatures
+                .iter()
+                .map(|sig| {
+                    let sig = sig.as_str().ok_or(Error::Runtime("malformed signature"))?;
+                    Ok(Vec::from_hex(sig.trim_start_matches("0x"))?)
+                })
+                .collect::<Result<Vec<_>, Error>>()?
+                .into(),
+        );
+    }
+    Ok(txn)
+}
+
+/// Add a signature, replacing existing ones unless `append`, for multisig.
+fn add_signature(txn: &mut Transaction, signature: Vec<u8>, append: bool) -> Result<(), Error> {
+    if !append {
+        txn.clear_signature();
+    }
+    if txn.get_signature().contains(&signature) {
+        return Err(Error::Runtime("already signed by this key"));
+    }
+    txn.mut_signature().push(signature);
+    Ok(())
+}
+
+/// Sign a transaction built elsewhere, like on a watch-only device, and print it serialized in hex.
+fn sign_transaction(matches: &ArgMatches) -> Result<(), Error> {
+    let input = matches.value_of("TRANSACTION").expect("required in cli.yml; qed");
+    let mut txn = if Path::new(input).exists() {
+        parse_transaction(&fs::read_to_string(input)?)?
+    } else {
+        parse_transaction(input)?
+    };
+    let raw = txn.get_raw_data();
+    let contract = raw
+        .get_contract()
+        .first()
+        .ok_or(Error::Runtime("transaction has no contract"))?;
+
+    let txid = trx::transaction_hash(raw)?;
+    let signer = trx::signer_of(
+        matches,
+        trx::extract_owner_address_from_parameter(contract.get_parameter()).ok(),
+    )?;
+    let signature = signer.sign(&trx::signing_digest(&txid)?)?;
+    add_signature(&mut txn, signature.to_vec(), matches.is_present("append"))?;
+
+    info!("TX: {}", hex::encode(txid));
+    info!(
+        "Signed by {}, {} signature(s)",
+        signer.address(),
+        txn.get_signature().len()
+    );
+    println!("{}", hex::encode(txn.write_to_bytes()?));
+    Ok(())
+}
+
 pub fn main(matches: &ArgMatches) -> Result<(), Error> {
+    // signing by walletd runs its own runtime
+    if let ("sign_transaction", Some(arg_matches)) = matches.subcommand() {
+        return sign_transaction(arg_matches);
+    }
     let fut = run(matches);
     let mut rt = Builder::new().basic_scheduler().enable_all().build().unwrap();
     rt.block_on(fut)
@@ -248,3 +592,125 @@ pub fn sign_digest(digest: &[u8], address: &Address) -> Result<Vec<u8>, Error> {
     let mut rt = Builder::new().basic_scheduler().enable_all().build().unwrap();
     rt.block_on(fut)
 }
+
+/// Signer with a key of the unlocked walletd wallet.
+pub struct WalletdSigner {
+    address: Address,
+}
+
+impl WalletdSigner {
+    pub fn new(address: Address) -> Self {
+        WalletdSigner { address }
+    }
+}
+
+impl Signer for WalletdSigner {
+    fn sign(&self, hash: &[u8; 32]) -> Result<[u8; 65], keys::Error> {
+        let signature = sign_digest(hash, &self.address).map_err(|e| {
+            warn!("walletd: {}", e);
+            keys::Error::FailedSigning
+        })?;
+        if signature.len() != 65 {
+            return Err(keys::Error::InvalidSignature);
+        }
+        let mut raw = [0u8; 65];
+        raw.copy_from_slice(&signature);
+        Ok(raw)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_private_key_input() {
+        let key = "0101010101010101010101010101010101010101010101010101010101010101";
+        let expected: Private = key.parse().unwrap();
+        let wif = |raw: &[u8]| {
+            let mut raw = raw.to_vec();
+            raw.extend_from_slice(&crypto::sha256(&crypto::sha256(&raw))[..4]);
+            raw
+        };
+        let prefixed = Vec::from_hex(format!("80{}", key)).unwrap();
+        let compressed = Vec::from_hex(format!("80{}01", key)).unwrap();
+
+        for input in vec![
+            key.to_owned(),
+            format!("0x{}", key),
+            format!("  {}\n", key.to_uppercase()),
+            hex::encode(&prefixed),
+            hex::encode(wif(&prefixed)),
+            hex::encode(wif(&compressed)),
+            keys::b58encode_check(&prefixed),
+            keys::b58encode_check(&compressed),
+        ] {
+            assert_eq!(parse_private_key_input(&input).unwrap(), expected, "{}", input);
+        }
+
+        let mut bad_checksum = wif(&prefixed);
+        *bad_checksum.last_mut().unwrap() ^= 1;
+        assert!(parse_private_key_input(&hex::encode(bad_checksum)).is_err());
+        assert!(parse_private_key_input(&key[2..]).is_err());
+        assert!(parse_private_key_input(&format!("{}0", key)).is_err());
+        assert!(parse_private_key_input(&format!("81{}", key)).is_err());
+        assert!(parse_private_key_input(&"00".repeat(32)).is_err());
+        assert!(parse_private_key_input("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t").is_err());
+        assert!(parse_private_key_input("abc").is_err());
+    }
+
+    #[test]
+    fn test_sign_transaction_append() {
+        let mut txn = trx::test_transfer_transaction();
+
+        let unsigned = parse_transaction(&hex::encode(txn.write_to_bytes().unwrap())).unwrap();
+        assert_eq!(unsigned, txn);
+
+        let txid = trx::transaction_hash(txn.get_raw_data()).unwrap();
+        let sign = |private: &str| {
+            MemorySigner::new(private.parse().unwrap())
+                .sign(&txid)
+                .unwrap()
+                .to_vec()
+        };
+        let first = sign("b5a4cea271ff424d7c31dc12a3e43e401df7a40d7412a15750f3f0b6b5449a28");
+        let second = sign("9a28b5a4cea271ff424d7c31dc12a3e43e401df7a40d7412a15750f3f0b6b544");
+
+        add_signature(&mut txn, first.clone(), true).unwrap();
+        assert!(add_signature(&mut txn, first.clone(), true).is_err());
+        add_signature(&mut txn, second.clone(), true).unwrap();
+        assert_eq!(txn.get_signature(), &[first, second.clone()][..]);
+
+        let json = format!(
+            r#"{{"raw_data_hex": "{}", "signature": ["0x{}"]}}"#,
+            hex::encode(txn.get_raw_data().write_to_bytes().unwrap()),
+            hex::encode(&second)
+        );
+        let mut resigned = parse_transaction(&json).unwrap();
+        add_signature(&mut resigned, second.clone(), false).unwrap();
+        assert_eq!(resigned.get_signature(), &[second][..]);
+    }
+
+    #[test]
+    fn test_write_new_private_file() {
+        let path = std::env::temp_dir().join(format!("wallet-cli-export-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        write_new_private_file(&path, b"{}").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"{}");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        assert!(matches!(
+            write_new_private_file(&path, b"[]"),
+            Err(Error::InvalidInput(_))
+        ));
+        assert_eq!(fs::read(&path).unwrap(), b"{}");
+        fs::remove_file(&path).unwrap();
+    }
+}
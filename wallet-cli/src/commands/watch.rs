@@ -0,0 +1,250 @@
+//! Watch on-chain activities.
+
+use clap::ArgMatches;
+use futures::executor;
+use keys::Address;
+use log::{info, warn};
+use proto::api::AccountPaginated;
+use proto::core::{Account, Transaction, TransferContract, TriggerSmartContract};
+use protobuf::parse_from_bytes;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::thread;
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::utils::client;
+use crate::utils::time;
+use crate::utils::trc20::{self, Trc20Transfer};
+use crate::utils::trx;
+
+// Max transactions fetched per direction in one poll.
+const POLL_LIMIT: i64 = 50;
+// Max wait between polls after failures.
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+pub fn main<'a>(matches: &'a ArgMatches<'a>) -> Result<(), Error> {
+    match matches.subcommand() {
+        ("address", Some(arg_matches)) => {
+            let addr = trx::parse_address(arg_matches.value_of("ADDRESS").expect("required in cli.yml; qed"))?;
+            let interval = arg_matches
+                .value_of("poll-interval-secs")
+                .expect("has default in cli.yml; qed")
+                .parse::<u64>()?;
+            watch_address(&addr, Duration::from_secs(interval))
+        }
+        _ => {
+            eprintln!("{}", matches.usage());
+            Err(Error::Runtime("error parsing command line"))
+        }
+    }
+}
+
+/// Set while a watch session runs, cleared by Ctrl-C.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static SET_CTRLC_HANDLER: Once = Once::new();
+
+/// Ctrl-C ends a running watch session, otherwise it exits as without a handler, e.g. at the REPL prompt. The
+/// handler can only be set once per process, so it is shared by all sessions.
+fn set_ctrlc_handler() -> Result<(), Error> {
+    let mut result = Ok(());
+    SET_CTRLC_HANDLER.call_once(|| {
+        result = ctrlc::set_handler(|| {
+            if !RUNNING.swap(false, Ordering::SeqCst) {
+                process::exit(130);
+            }
+        })
+        .map_err(|_| Error::Runtime("can not set Ctrl-C handler"));
+    });
+    result
+}
+
+fn watch_address(addr: &Address, interval: Duration) -> Result<(), Error> {
+    set_ctrlc_handler()?;
+    RUNNING.store(true, Ordering::SeqCst);
+
+    let mut session = WatchSession::default();
+    let mut num_failures = 0_u32;
+
+    info!("Watching {} every {}s, Ctrl-C to stop", addr, interval.as_secs());
+    while RUNNING.load(Ordering::SeqCst) {
+        // transient node errors do not end the session, polling is retried with a backoff
+        let wait = match session.poll(addr) {
+            Ok(()) => {
+                num_failures = 0;
+                interval
+            }
+            Err(e) => {
+                num_failures += 1;
+                let wait = backoff(interval, num_failures);
+                warn!("Poll failed: {:?}, retrying in {}s", e, wait.as_secs());
+                wait
+            }
+        };
+
+        // sleep in small steps, so that Ctrl-C is handled in time
+        let mut slept = Duration::from_secs(0);
+        while slept < wait && RUNNING.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(100));
+            slept += Duration::from_millis(100);
+        }
+    }
+
+    info!("Session summary");
+    info!("  Transactions seen: {}", session.num_transactions);
+    info!(
+        "  Net TRX flow: {}",
+        trx::format_amount_with_surfix(session.net_flow, "TRX", 6)
+    );
+    Ok(())
+}
+
+/// Wait before the next poll after `num_failures` failed polls in a row, doubled each time up to `MAX_BACKOFF`.
+fn backoff(interval: Duration, num_failures: u32) -> Duration {
+    let max_wait = MAX_BACKOFF.max(interval);
+    interval
+        .checked_mul(1 << num_failures.min(16))
+        .map_or(max_wait, |wait| wait.min(max_wait))
+}
+
+#[derive(Default)]
+struct WatchSession {
+    seen: HashSet<String>,
+    num_transactions: usize,
+    net_flow: i64,
+    is_polled: bool,
+}
+
+impl WatchSession {
+    fn poll(&mut self, addr: &Address) -> Result<(), Error> {
+        let mut incoming = get_transactions_to(addr)?;
+        let mut outgoing = get_transactions_from(addr)?;
+
+        for txn in outgoing.drain(..).chain(incoming.drain(..)) {
+            let txid = hex::encode(trx::transaction_hash(txn.get_raw_data())?);
+            if !self.seen.insert(txid.clone()) || !self.is_polled {
+                continue;
+            }
+            self.num_transactions += 1;
+
+            let flow = transfer_flow_of(&txn, addr)?;
+            self.net_flow += flow;
+            let amount = if flow != 0 {
+                trx::format_amount_with_surfix(flow, "TRX", 6)
+            } else if let Some(transfer) = trc20_transfer_of(&txn)? {
+                trc20::summarize_transfer(&transfer)?.to_string()
+            } else {
+                "".into()
+            };
+            println!("{}", txid);
+            info!(
+                "{} {} {}",
+                time::format_now("%Y-%m-%d %H:%M:%S"),
+                trx::format_contract_type(
+                    txn.get_raw_data()
+                        .get_contract()
+                        .get(0)
+                        .map(|c| c.get_field_type())
+                        .unwrap_or_default()
+                ),
+                amount
+            );
+        }
+        if !self.is_polled {
+            info!("{} existing transactions skipped", self.seen.len());
+            self.is_polled = true;
+        }
+        info!(
+            "Balance: {}",
+            trx::format_amount_with_surfix(get_balance(addr)?, "TRX", 6)
+        );
+        Ok(())
+    }
+}
+
+/// Signed TRX amount a transaction moves into(positive) or out of(negative) the address.
+fn transfer_flow_of(txn: &Transaction, addr: &Address) -> Result<i64, Error> {
+    let mut flow = 0;
+    for contract in txn.get_raw_data().get_contract() {
+        if contract.get_parameter().get_type_url() != "type.googleapis.com/protocol.TransferContract" {
+            continue;
+        }
+        let transfer = parse_from_bytes::<TransferContract>(contract.get_parameter().get_value())?;
+        if transfer.get_to_address() == addr.as_bytes() {
+            flow += transfer.amount;
+        }
+        if transfer.get_owner_address() == addr.as_bytes() {
+            flow -= transfer.amount;
+        }
+    }
+    Ok(flow)
+}
+
+fn trc20_transfer_of(txn: &Transaction) -> Result<Option<Trc20Transfer>, Error> {
+    match txn.get_raw_data().get_contract().get(0) {
+        Some(contract)
+            if contract.get_parameter().get_type_url() == "type.googleapis.com/protocol.TriggerSmartContract" =>
+        {
+            let call = parse_from_bytes::<TriggerSmartContract>(contract.get_parameter().get_value())?;
+            Ok(trc20::parse_transfer(
+                &Address::try_from(call.get_owner_address())?,
+                &Address::try_from(call.get_contract_address())?,
+                call.get_data(),
+            ))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn paginated_request_of(addr: &Address) -> AccountPaginated {
+    let mut account = Account::new();
+    account.set_address(addr.as_bytes().to_owned());
+    let mut req = AccountPaginated::new();
+    req.set_account(account);
+    req.set_offset(0);
+    req.set_limit(POLL_LIMIT);
+    req
+}
+
+fn get_transactions_from(addr: &Address) -> Result<Vec<Transaction>, Error> {
+    let mut payload = executor::block_on(
+        client::GRPC_EXTENSION_CLIENT
+            .get_transactions_from_this(Default::default(), paginated_request_of(addr))
+            .drop_metadata(),
+    )?;
+    Ok(payload.take_transaction().into())
+}
+
+fn get_transactions_to(addr: &Address) -> Result<Vec<Transaction>, Error> {
+    let mut payload = executor::block_on(
+        client::GRPC_EXTENSION_CLIENT
+            .get_transactions_to_this(Default::default(), paginated_request_of(addr))
+            .drop_metadata(),
+    )?;
+    Ok(payload.take_transaction().into())
+}
+
+fn get_balance(addr: &Address) -> Result<i64, Error> {
+    let mut req = Account::new();
+    req.set_address(addr.as_bytes().to_owned());
+    let payload = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
+    Ok(payload.balance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let interval = Duration::from_secs(3);
+        assert_eq!(backoff(interval, 1), Duration::from_secs(6));
+        assert_eq!(backoff(interval, 2), Duration::from_secs(12));
+        assert_eq!(backoff(interval, 10), MAX_BACKOFF);
+        assert_eq!(backoff(interval, 100), MAX_BACKOFF);
+        assert_eq!(backoff(Duration::from_secs(600), 1), Duration::from_secs(600));
+    }
+}
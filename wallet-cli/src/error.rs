@@ -18,6 +18,29 @@ pub enum Error {
     Protobuf(#[from] ::protobuf::error::ProtobufError),
     #[error("runtime error: {0:}")]
     Runtime(&'static str),
+    /// A transaction, block, account or other item is not on chain.
+    #[error("runtime error: {0:}")]
+    NotFound(&'static str),
+    /// Malformed user input, like a transaction id or an address.
+    #[error("runtime error: {0:}")]
+    InvalidInput(&'static str),
+    /// The node can not be reached, other than by gRPC.
+    #[error("runtime error: {0:}")]
+    Transport(&'static str),
+    #[error("runtime error: {0:}")]
+    Timeout(&'static str),
+    /// A computed value, like a merkle root, does not match the expected one.
+    #[error("runtime error: {0:}")]
+    Mismatch(&'static str),
+    #[error(
+        "broadcast error {code:?}: {message}\n  {}\n  {}",
+        crate::utils::trx::explain_return_code(*.code).0,
+        crate::utils::trx::explain_return_code(*.code).1
+    )]
+    Broadcast {
+        code: ::proto::api::Return_response_code,
+        message: String,
+    },
     #[error("error: {0:?}")]
     Keys(#[from] ::keys::Error),
     #[error("{0:}")]
@@ -28,6 +51,29 @@ pub enum Error {
     ParseInt(#[from] ::std::num::ParseIntError),
 }
 
+impl Error {
+    /// Exit code of the command, for scripts:
+    ///
+    /// - 1 other errors
+    /// - 2 not found
+    /// - 3 node or transport error
+    /// - 4 invalid input
+    /// - 5 broadcast rejected
+    /// - 6 timeout
+    /// - 7 mismatch of a computed value
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NotFound(_) => 2,
+            Error::Grpc(_) | Error::TonicTransport(_) | Error::TonicStatus(_) | Error::Transport(_) => 3,
+            Error::InvalidInput(_) | Error::FromHex(_) | Error::ParseInt(_) | Error::Keys(_) | Error::Abi(_) => 4,
+            Error::Broadcast { .. } => 5,
+            Error::Timeout(_) => 6,
+            Error::Mismatch(_) => 7,
+            _ => 1,
+        }
+    }
+}
+
 impl From<&'static str> for Error {
     fn from(s: &'static str) -> Self {
         Error::Runtime(s)
@@ -0,0 +1,39 @@
+//! Tron Protocol wallet command line util, as a library.
+//!
+//! The `wallet-cli` binary is a thin printing layer over this crate. Query helpers such as
+//! [`get_transaction_detail`] return the normalized JSON together with the annotations the CLI
+//! prints to stderr, so they can be used from other Rust programs without side effects.
+
+pub mod commands;
+pub mod error;
+pub mod utils;
+
+pub use commands::get::transaction::{
+    get_transaction_detail, get_transaction_info_detail, TransactionDetail, TransactionInfoDetail,
+};
+pub use error::Error;
+
+// FIXME: should use AppConfig, for now, use static var
+pub static mut RPC_ADDR: &str = "grpc.trongrid.io:50051";
+/// RPC address of a solidity node, for finalized data
+pub static mut SOLIDITY_ADDR: Option<&str> = None;
+/// Used for sun-network
+pub static mut CHAIN_ID: Option<&str> = None;
+/// Use the HTTP API of the node instead of gRPC for transaction queries, see `utils::http`
+pub static mut HTTP_ENDPOINT: Option<&str> = None;
+/// Render times in UTC instead of local timezone
+pub static mut USE_UTC: bool = false;
+/// Save raw gRPC responses to the directory, see `utils::record`
+pub static mut RAW_OUTPUT_DIR: Option<&str> = None;
+/// Load responses from the directory instead of the node, see `utils::record`
+pub static mut REPLAY_DIR: Option<&str> = None;
+/// Network name, or the raw RPC address when `--rpc-addr` is used
+pub static mut NETWORK: &str = "mainnet";
+/// Do not write the journal of broadcast transactions, see `utils::journal`
+pub static mut JOURNAL_DISABLED: bool = false;
+/// Group digits of numbers in human-readable output, see `utils::trx::format_number`
+pub static mut HUMAN_NUMBERS: bool = false;
+/// Commands are read from the REPL, see `commands::repl`
+pub static mut IN_REPL: bool = false;
+/// Size reserved for `Transaction.ret` in bandwidth estimates, see `utils::trx::MAX_RESULT_SIZE_IN_TX`
+pub static mut RESULT_SIZE: usize = utils::trx::MAX_RESULT_SIZE_IN_TX;
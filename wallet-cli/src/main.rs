@@ -1,25 +1,45 @@
-use clap::load_yaml;
-
-mod commands;
-mod error;
-mod utils;
-
-use error::Error;
-
-// FIXME: should use AppConfig, for now, use static var
-static mut RPC_ADDR: &str = "grpc.trongrid.io:50051";
-/// Used for sun-network
-static mut CHAIN_ID: Option<&str> = None;
-
-fn main() -> Result<(), Error> {
-    utils::walletd::ensure_walletd()?;
+use clap::{load_yaml, ArgMatches, ErrorKind, Shell};
+use std::io;
+use std::iter;
+use std::process;
+use wallet_cli::{
+    commands, utils, Error, CHAIN_ID, HTTP_ENDPOINT, HUMAN_NUMBERS, IN_REPL, JOURNAL_DISABLED, NETWORK, RAW_OUTPUT_DIR,
+    REPLAY_DIR, RESULT_SIZE, RPC_ADDR, SOLIDITY_ADDR, USE_UTC,
+};
 
+fn main() {
     let yaml = load_yaml!("cli.yml");
     let matches = clap::App::from_yaml(yaml).get_matches();
 
+    // before `run`, completions need no node or walletd
+    if let ("completions", Some(arg_matches)) = matches.subcommand() {
+        let shell = arg_matches
+            .value_of("SHELL")
+            .expect("required in cli.yml; qed")
+            .parse::<Shell>()
+            .expect("possible values in cli.yml; qed");
+        clap::App::from_yaml(yaml).gen_completions_to("wallet-cli", shell, &mut io::stdout());
+        return;
+    }
+
+    utils::logger::init(
+        matches.occurrences_of("verbose"),
+        matches.is_present("quiet"),
+        !matches.is_present("no-color"),
+    );
+    // printed even with --quiet, exit code by `Error::exit_code`
+    if let Err(e) = run(&matches) {
+        eprintln!("Error: {:?}", e);
+        process::exit(e.exit_code());
+    }
+}
+
+fn run(matches: &ArgMatches) -> Result<(), Error> {
     unsafe {
         RPC_ADDR = match (matches.value_of("network"), matches.value_of("rpc-addr")) {
-            // NOTE: matches lasts till main() ends, which is OK to use `leak`.
+            // NOTE: values are leaked, they live till the process ends.
+            // an HTTP API has no gRPC, gRPC clients refuse to connect, see `utils::client`
+            (_, Some(host)) if utils::http::is_http_endpoint(host) => "",
             (_, Some(host)) => Box::leak(host.to_owned().into_boxed_str()),
             (Some("mainnet"), _) => "grpc.trongrid.io:50051",
             (Some("shasta"), _) => "grpc.shasta.trongrid.io:50051",
@@ -29,13 +49,55 @@ fn main() -> Result<(), Error> {
             (Some("dappchain-testnet"), _) => "47.252.85.90:50051",
             _ => unreachable!(),
         };
+        SOLIDITY_ADDR = matches
+            .value_of("solidity-node")
+            .map(ToOwned::to_owned)
+            .or_else(|| std::env::var("TRON_SOLIDITY_NODE").ok().filter(|addr| !addr.is_empty()))
+            .map(|addr| &*Box::leak(addr.into_boxed_str()));
         CHAIN_ID = match matches.value_of("network") {
             Some("dappchain") => Some("41E209E4DE650F0150788E8EC5CAFA240A23EB8EB7"),
             Some("dappchain-testnet") => Some("413AF23F37DA0D48234FDD43D89931E98E1144481B"),
             _ => None,
         };
+        USE_UTC = matches.value_of("timezone") == Some("utc");
+        RAW_OUTPUT_DIR = matches
+            .value_of("raw-output-dir")
+            .map(|dir| &*Box::leak(dir.to_owned().into_boxed_str()));
+        REPLAY_DIR = matches
+            .value_of("replay-dir")
+            .map(|dir| &*Box::leak(dir.to_owned().into_boxed_str()));
+        NETWORK = match matches.value_of("rpc-addr") {
+            Some(host) => Box::leak(host.to_owned().into_boxed_str()),
+            None => Box::leak(
+                matches
+                    .value_of("network")
+                    .expect("has default in cli.yml; qed")
+                    .to_owned()
+                    .into_boxed_str(),
+            ),
+        };
+        HTTP_ENDPOINT = match matches.value_of("rpc-addr") {
+            Some(host) if utils::http::is_http_endpoint(host) => Some(Box::leak(host.to_owned().into_boxed_str())),
+            _ if matches.is_present("http") => Some(
+                utils::http::default_endpoint(NETWORK)
+                    .ok_or(Error::Runtime("no HTTP endpoint known for the network"))?,
+            ),
+            _ => None,
+        };
+        JOURNAL_DISABLED = matches.is_present("no-journal") || utils::journal::disabled_by_env();
+        HUMAN_NUMBERS = matches.is_present("human");
+        RESULT_SIZE = match matches.value_of("result-size") {
+            Some(size) => size
+                .parse()
+                .map_err(|_| Error::InvalidInput("invalid --result-size, use a number of bytes"))?,
+            None => utils::trx::MAX_RESULT_SIZE_IN_TX,
+        };
     }
 
+    dispatch(matches)
+}
+
+fn dispatch(matches: &ArgMatches) -> Result<(), Error> {
     match matches.subcommand() {
         ("get", Some(arg_matches)) => commands::get::main(arg_matches),
         ("list", Some(arg_matches)) => commands::list::main(arg_matches),
@@ -44,11 +106,18 @@ fn main() -> Result<(), Error> {
         ("asset", Some(arg_matches)) => commands::asset::main(arg_matches),
         ("contract", Some(arg_matches)) => commands::contract::main(arg_matches),
         ("transfer", Some(arg_matches)) => commands::transfer::main(arg_matches),
+        ("pay", Some(arg_matches)) => commands::pay::main(arg_matches),
+        ("tx", Some(arg_matches)) => commands::tx::main(arg_matches),
         ("batch", Some(arg_matches)) => commands::batch::main(arg_matches),
         ("sign", Some(arg_matches)) => commands::sign::main(arg_matches),
         ("wallet", Some(arg_matches)) => commands::wallet::main(arg_matches),
+        ("watch", Some(arg_matches)) => commands::watch::main(arg_matches),
         ("create", Some(arg_matches)) => commands::create::main(arg_matches),
         ("key", Some(arg_matches)) => commands::key::main(arg_matches),
+        ("journal", Some(arg_matches)) => commands::journal::main(arg_matches),
+        ("abi_decode", Some(arg_matches)) => commands::abi::decode(arg_matches),
+        ("abi_encode", Some(arg_matches)) => commands::abi::encode(arg_matches),
+        ("repl", _) => repl(),
         ("shielded", _) => {
             eprintln!("Removed from repo.");
             unimplemented!()
@@ -56,3 +125,41 @@ fn main() -> Result<(), Error> {
         _ => unreachable!("handled by cli.yml; qed"),
     }
 }
+
+/// Each line is parsed as a command line, global options are kept from the REPL's own command line and rejected on
+/// a line.
+fn repl() -> Result<(), Error> {
+    let yaml = load_yaml!("cli.yml");
+    let global_args = yaml["args"]
+        .as_vec()
+        .expect("args in cli.yml; qed")
+        .iter()
+        .filter_map(|arg| arg.as_hash()?.keys().next()?.as_str())
+        .collect::<Vec<_>>();
+    unsafe {
+        IN_REPL = true;
+    }
+    commands::repl::main(|args| {
+        let matches =
+            match clap::App::from_yaml(yaml).get_matches_from_safe(iter::once("wallet-cli".to_owned()).chain(args)) {
+                Ok(matches) => matches,
+                Err(e) if e.kind == ErrorKind::HelpDisplayed || e.kind == ErrorKind::VersionDisplayed => {
+                    println!("{}", e.message);
+                    return Ok(());
+                }
+                Err(e) => {
+                    eprintln!("{}", e.message);
+                    return Err(Error::InvalidInput("error parsing command line"));
+                }
+            };
+        if global_args.iter().any(|&name| matches.occurrences_of(name) > 0) {
+            return Err(Error::InvalidInput(
+                "global options are taken from the command line starting the REPL",
+            ));
+        }
+        match matches.subcommand_name() {
+            Some("repl") | Some("completions") => Err(Error::InvalidInput("not available in the REPL")),
+            _ => dispatch(&matches),
+        }
+    })
+}
@@ -3,7 +3,7 @@
 use ethabi::param_type::{ParamType, Reader};
 use ethabi::token::{LenientTokenizer, StrictTokenizer, Token, Tokenizer};
 use ethabi::{decode, encode};
-use hex::{FromHex, ToHex};
+use hex::FromHex;
 use keys::Address;
 use lazy_static::lazy_static;
 use proto::core::{
@@ -11,6 +11,10 @@ use proto::core::{
     SmartContract_ABI_Entry_Param as AbiEntryParam, SmartContract_ABI_Entry_StateMutabilityType as StateMutabilityType,
     SmartContract_ABI_Entry_StateMutabilityType as AbiEntryStateMutabilityType,
 };
+use serde::ser::{Serialize, Serializer};
+use serde_json::json;
+use std::convert::TryFrom;
+use std::fmt;
 use std::fmt::Write as FmtWrite;
 
 use crate::error::Error;
@@ -19,7 +23,7 @@ use crate::utils::crypto;
 lazy_static! {
     pub static ref DEFAULT_EVENT_ABI: Vec<AbiEntry> = {
         let json = serde_json::from_str(include_str!("./events.abi")).unwrap();
-        json_to_abi(&json).entrys.to_vec()
+        json_to_abi(&json).expect("valid events.abi; qed").entrys.to_vec()
     };
 }
 
@@ -31,45 +35,186 @@ pub fn fnhash(fname: &str) -> [u8; 4] {
     hash_code
 }
 
-// ref: https://github.com/paritytech/ethabi/blob/master/cli/src/main.rs
-pub fn encode_params(types: &[&str], values: &[String]) -> Result<Vec<u8>, Error> {
-    assert_eq!(types.len(), values.len());
+/// A typed ABI value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AbiValue {
+    Address(Address),
+    Uint(ethabi::Uint),
+    /// Signed integer, in two's complement.
+    Int(ethabi::Int),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    FixedBytes(Vec<u8>),
+    String(String),
+    Array(Vec<AbiValue>),
+    FixedArray(Vec<AbiValue>),
+    Tuple(Vec<AbiValue>),
+}
+
+impl AbiValue {
+    fn from_token(tok: Token) -> Self {
+        match tok {
+            Token::Address(raw) => AbiValue::Address(Address::from_tvm_bytes(raw.as_ref())),
+            Token::Uint(val) => AbiValue::Uint(val),
+            Token::Int(val) => AbiValue::Int(val),
+            Token::Bool(val) => AbiValue::Bool(val),
+            Token::Bytes(val) => AbiValue::Bytes(val),
+            Token::FixedBytes(val) => AbiValue::FixedBytes(val),
+            Token::String(val) => AbiValue::String(val),
+            Token::Array(vals) => AbiValue::Array(vals.into_iter().map(AbiValue::from_token).collect()),
+            Token::FixedArray(vals) => AbiValue::FixedArray(vals.into_iter().map(AbiValue::from_token).collect()),
+            Token::Tuple(vals) => AbiValue::Tuple(vals.into_iter().map(AbiValue::from_token).collect()),
+        }
+    }
+
+    fn to_token(&self) -> Token {
+        match self {
+            AbiValue::Address(addr) => Token::Address(ethabi::Address::from_slice(addr.as_tvm_bytes())),
+            AbiValue::Uint(val) => Token::Uint(*val),
+            AbiValue::Int(val) => Token::Int(*val),
+            AbiValue::Bool(val) => Token::Bool(*val),
+            AbiValue::Bytes(val) => Token::Bytes(val.clone()),
+            AbiValue::FixedBytes(val) => Token::FixedBytes(val.clone()),
+            AbiValue::String(val) => Token::String(val.clone()),
+            AbiValue::Array(vals) => Token::Array(vals.iter().map(AbiValue::to_token).collect()),
+            AbiValue::FixedArray(vals) => Token::FixedArray(vals.iter().map(AbiValue::to_token).collect()),
+            AbiValue::Tuple(vals) => Token::Tuple(vals.iter().map(AbiValue::to_token).collect()),
+        }
+    }
+
+    /// Signed value of an `Int`, as (is_negative, magnitude).
+    fn int_to_sign_magnitude(val: &ethabi::Int) -> (bool, ethabi::Uint) {
+        if val.bit(255) {
+            (true, (!*val).overflowing_add(1.into()).0)
+        } else {
+            (false, *val)
+        }
+    }
+}
+
+/// The human readable format used by CLI output.
+impl fmt::Display for AbiValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AbiValue::Address(addr) => write!(f, "{}", addr),
+            AbiValue::String(s) => write!(f, "{:?}", s),
+            AbiValue::Uint(val) => write!(f, "{}", val),
+            AbiValue::Int(val) => write!(f, "Int({:?})", val),
+            AbiValue::Bool(val) => write!(f, "{}", val),
+            AbiValue::Array(vals) | AbiValue::FixedArray(vals) => write!(
+                f,
+                "[{}]",
+                vals.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            ),
+            AbiValue::Bytes(val) | AbiValue::FixedBytes(val) => write!(f, "{}", hex::encode(val)),
+            AbiValue::Tuple(_) => write!(f, "tuple(...)"),
+        }
+    }
+}
+
+/// JSON form: addresses in base58check, integers as decimal strings, bytes as hex strings.
+impl Serialize for AbiValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            AbiValue::Address(addr) => serializer.serialize_str(&addr.to_string()),
+            AbiValue::Uint(val) => serializer.serialize_str(&val.to_string()),
+            AbiValue::Int(val) => match AbiValue::int_to_sign_magnitude(val) {
+                (true, mag) => serializer.serialize_str(&format!("-{}", mag)),
+                (false, mag) => serializer.serialize_str(&mag.to_string()),
+            },
+            AbiValue::Bool(val) => serializer.serialize_bool(*val),
+            AbiValue::Bytes(val) | AbiValue::FixedBytes(val) => serializer.serialize_str(&hex::encode(val)),
+            AbiValue::String(val) => serializer.serialize_str(val),
+            AbiValue::Array(vals) | AbiValue::FixedArray(vals) | AbiValue::Tuple(vals) => {
+                serializer.collect_seq(vals.iter())
+            }
+        }
+    }
+}
+
+impl TryFrom<AbiValue> for i64 {
+    type Error = Error;
+
+    fn try_from(value: AbiValue) -> Result<Self, Self::Error> {
+        const OVERFLOW: Error = Error::Runtime("ABI value overflows i64");
+        match value {
+            AbiValue::Uint(val) if val <= ethabi::Uint::from(i64::MAX as u64) => Ok(val.low_u64() as i64),
+            AbiValue::Int(val) => match AbiValue::int_to_sign_magnitude(&val) {
+                (false, mag) if mag <= ethabi::Uint::from(i64::MAX as u64) => Ok(mag.low_u64() as i64),
+                (true, mag) if mag <= ethabi::Uint::from(1u64 << 63) => Ok((mag.low_u64() as i64).wrapping_neg()),
+                _ => Err(OVERFLOW),
+            },
+            AbiValue::Uint(_) => Err(OVERFLOW),
+            _ => Err(Error::Runtime("ABI value is not an integer")),
+        }
+    }
+}
+
+impl TryFrom<AbiValue> for Address {
+    type Error = Error;
+
+    fn try_from(value: AbiValue) -> Result<Self, Self::Error> {
+        match value {
+            AbiValue::Address(addr) => Ok(addr),
+            _ => Err(Error::Runtime("ABI value is not an address")),
+        }
+    }
+}
 
-    let types: Vec<ParamType> = types
+impl TryFrom<AbiValue> for Vec<u8> {
+    type Error = Error;
+
+    fn try_from(value: AbiValue) -> Result<Self, Self::Error> {
+        match value {
+            AbiValue::Bytes(val) | AbiValue::FixedBytes(val) => Ok(val),
+            _ => Err(Error::Runtime("ABI value is not bytes")),
+        }
+    }
+}
+
+fn parse_param_types(types: &[&str]) -> Result<Vec<ParamType>, Error> {
+    types
         .iter()
         .map(|&s| {
             if s == "trcToken" {
                 Reader::read("uint256")
             } else {
-                Reader::read(s)
+                Reader::read(&normalize_param_type(s))
             }
         })
-        .collect::<Result<_, _>>()?;
+        .collect::<Result<_, _>>()
+        .map_err(From::from)
+}
+
+// ref: https://github.com/paritytech/ethabi/blob/master/cli/src/main.rs
+/// Parse command line values into typed ABI values.
+pub fn parse_values(types: &[&str], values: &[String]) -> Result<Vec<AbiValue>, Error> {
+    assert_eq!(types.len(), values.len());
+
+    let types = parse_param_types(types)?;
     let params: Vec<_> = types.into_iter().zip(values.iter().map(|v| v as &str)).collect();
 
     let tokens = parse_tokens(&params, true)?;
-    let result = encode(&tokens);
+    Ok(tokens.into_iter().map(AbiValue::from_token).collect())
+}
 
-    Ok(result.to_vec())
+pub fn encode_values(values: &[AbiValue]) -> Vec<u8> {
+    let tokens = values.iter().map(AbiValue::to_token).collect::<Vec<_>>();
+    encode(&tokens).to_vec()
 }
 
-pub fn decode_params(types: &[&str], data: &str) -> Result<Vec<String>, Error> {
-    let types: Vec<ParamType> = types
-        .iter()
-        .map(|&s| {
-            if s == "trcToken" {
-                Reader::read("uint256")
-            } else {
-                Reader::read(s)
-            }
-        })
-        .collect::<Result<_, _>>()?;
+pub fn encode_params(types: &[&str], values: &[String]) -> Result<Vec<u8>, Error> {
+    Ok(encode_values(&parse_values(types, values)?))
+}
+
+pub fn decode_params(types: &[&str], data: &str) -> Result<Vec<AbiValue>, Error> {
+    let types = parse_param_types(types)?;
     let data: Vec<u8> = Vec::from_hex(data)?;
     let tokens = decode(&types, &data)?;
 
     assert_eq!(types.len(), tokens.len());
 
-    Ok(tokens.iter().map(pformat_abi_token).collect())
+    Ok(tokens.into_iter().map(AbiValue::from_token).collect())
 }
 
 fn parse_tokens(params: &[(ParamType, &str)], lenient: bool) -> Result<Vec<Token>, Error> {
@@ -83,22 +228,23 @@ fn parse_tokens(params: &[(ParamType, &str)], lenient: bool) -> Result<Vec<Token
         .map_err(From::from)
 }
 
-fn pformat_abi_token(tok: &Token) -> String {
-    match tok {
-        Token::Address(raw) => Address::from_tvm_bytes(raw.as_ref()).to_string(),
-        Token::String(s) => format!("{:?}", s),
-        Token::Uint(val) => val.to_string(),
-        Token::Bool(val) => val.to_string(),
-        Token::Array(val) | Token::FixedArray(val) => {
-            format!("[{}]", val.iter().map(pformat_abi_token).collect::<Vec<_>>().join(", "))
-        }
-        Token::Bytes(val) => val.encode_hex::<String>(),
-        Token::FixedBytes(val) => hex::encode(&val),
-        Token::Tuple(_) => "tuple(...)".into(),
-        ref t => format!("{:?}", t),
+/// Canonical form of a param type, as used in signatures. `uint` is an alias of `uint256`, `address payable` of
+/// `address`, etc.
+fn normalize_param_type(ty: &str) -> String {
+    let (base, suffix) = match ty.find('[') {
+        Some(pos) => ty.split_at(pos),
+        None => (ty, ""),
+    };
+    match base {
+        "uint" => format!("uint256{}", suffix),
+        "int" => format!("int256{}", suffix),
+        "byte" => format!("bytes1{}", suffix),
+        "address payable" => format!("address{}", suffix),
+        _ => ty.to_owned(),
     }
 }
 
+/// Canonical signature of an entry, used to calculate the selector.
 pub fn entry_to_method_name(entry: &AbiEntry) -> String {
     format!(
         "{}({})",
@@ -106,57 +252,61 @@ pub fn entry_to_method_name(entry: &AbiEntry) -> String {
         entry
             .get_inputs()
             .iter()
-            .map(|arg| arg.get_field_type().to_owned())
+            .map(|arg| normalize_param_type(arg.get_field_type()))
             .collect::<Vec<_>>()
             .join(",")
     )
 }
 
+/// Find function entry by its 4-byte selector. Overloaded functions share the name, but not the selector.
+pub fn find_entry_by_selector<'a>(entries: &'a [AbiEntry], selector: &[u8]) -> Option<&'a AbiEntry> {
+    entries
+        .iter()
+        .filter(|entry| entry.get_field_type() == AbiEntryType::Function)
+        .find(|entry| fnhash(&entry_to_method_name(entry))[..] == *selector)
+}
+
+/// Human readable signature of an entry, in Solidity style, rendered from its JSON form.
 pub fn entry_to_method_name_pretty(entry: &AbiEntry) -> Result<String, Error> {
-    let mut pretty = match entry.get_field_type() {
-        AbiEntryType::Function | AbiEntryType::Fallback => "function".to_owned(),
-        AbiEntryType::Event => "event".to_owned(),
-        AbiEntryType::Constructor => "constructor".to_owned(),
-        _ => "".to_owned(),
-    };
-    if entry.get_field_type() != AbiEntryType::Fallback {
-        write!(pretty, " {:}", entry.get_name())?;
-    }
-    write!(
-        pretty,
-        "({})",
-        entry
-            .get_inputs()
-            .iter()
-            .map(|arg| if arg.get_name().is_empty() {
-                arg.get_field_type().to_owned()
-            } else if arg.get_indexed() {
-                // used in event
-                format!("{:} indexed {:}", arg.get_field_type(), arg.get_name())
-            } else {
-                format!("{:} {:}", arg.get_field_type(), arg.get_name())
+    let json = entry_to_json(entry);
+    let params_pretty = |params: &serde_json::Value| {
+        params
+            .as_array()
+            .map(|params| {
+                params
+                    .iter()
+                    .map(|param| {
+                        let mut pretty = param["type"].as_str().unwrap_or_default().to_owned();
+                        if param["indexed"] == true {
+                            pretty.push_str(" indexed");
+                        }
+                        match param["name"].as_str() {
+                            Some(name) if !name.is_empty() => format!("{} {}", pretty, name),
+                            _ => pretty,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
             })
-            .collect::<Vec<_>>()
-            .join(", ")
-    )?;
-    if entry.payable {
-        write!(pretty, " payable")?;
+            .unwrap_or_default()
+    };
+
+    let mut pretty = match json["type"].as_str().unwrap_or_default() {
+        "function" => format!("function {}", entry.get_name()),
+        "event" => format!("event {}", entry.get_name()),
+        other => other.to_owned(),
+    };
+    write!(pretty, "({})", params_pretty(&json["inputs"]))?;
+    match state_mutability_to_str(entry) {
+        "nonpayable" => (),
+        _ if entry.get_field_type() == AbiEntryType::Event => (),
+        mutability => write!(pretty, " {}", mutability)?,
     }
-    if entry.get_stateMutability() == StateMutabilityType::View {
-        write!(pretty, " view")?;
+    if json["anonymous"] == true {
+        write!(pretty, " anonymous")?;
     }
-
     if !entry.get_outputs().is_empty() {
-        write!(
-            pretty,
-            " returns ({})",
-            entry
-                .get_outputs()
-                .iter()
-                .map(|arg| arg.get_field_type().to_owned())
-                .collect::<Vec<_>>()
-                .join(", "),
-        )?;
+        write!(pretty, " returns ({})", params_pretty(&json["outputs"]))?;
     }
     Ok(pretty)
 }
@@ -195,33 +345,107 @@ pub fn entry_to_non_indexed_types(entry: &AbiEntry) -> Vec<&str> {
         .collect::<Vec<_>>()
 }
 
-#[inline]
-fn translate_state_mutablility(val: &serde_json::Value) -> AbiEntryStateMutabilityType {
+fn state_mutability_to_str(entry: &AbiEntry) -> &'static str {
+    match entry.get_stateMutability() {
+        StateMutabilityType::Pure => "pure",
+        StateMutabilityType::View => "view",
+        StateMutabilityType::Nonpayable => "nonpayable",
+        StateMutabilityType::Payable => "payable",
+        // legacy ABI, before solidity 0.4.16
+        StateMutabilityType::UnknownMutabilityType if entry.get_payable() => "payable",
+        StateMutabilityType::UnknownMutabilityType if entry.get_constant() => "view",
+        StateMutabilityType::UnknownMutabilityType => "nonpayable",
+    }
+}
+
+fn params_to_json(params: &[AbiEntryParam], with_indexed: bool) -> serde_json::Value {
+    params
+        .iter()
+        .map(|param| {
+            let mut json = json!({
+                "name": param.get_name(),
+                "type": param.get_field_type(),
+            });
+            if with_indexed {
+                json["indexed"] = json!(param.get_indexed());
+            }
+            json
+        })
+        .collect()
+}
+
+/// Convert an ABI entry to solc-style JSON. Legacy `constant` and `payable` fields are kept, always for functions,
+/// constructors and fallbacks, otherwise when set, so that `json_to_abi` gives the same entry back.
+pub fn entry_to_json(entry: &AbiEntry) -> serde_json::Value {
+    let mut json = match entry.get_field_type() {
+        AbiEntryType::Event => json!({
+            "type": "event",
+            "name": entry.get_name(),
+            "inputs": params_to_json(entry.get_inputs(), true),
+            "anonymous": entry.get_anonymous(),
+        }),
+        AbiEntryType::Function => json!({
+            "type": "function",
+            "name": entry.get_name(),
+            "inputs": params_to_json(entry.get_inputs(), false),
+            "outputs": params_to_json(entry.get_outputs(), false),
+        }),
+        AbiEntryType::Constructor => json!({
+            "type": "constructor",
+            "inputs": params_to_json(entry.get_inputs(), false),
+        }),
+        AbiEntryType::Fallback => json!({
+            "type": "fallback",
+        }),
+        AbiEntryType::UnknownEntryType => json!({
+            "type": "",
+            "name": entry.get_name(),
+        }),
+    };
+    // legacy ABI has no `stateMutability`, only `constant` and `payable`
+    if entry.get_stateMutability() != StateMutabilityType::UnknownMutabilityType {
+        json["stateMutability"] = json!(state_mutability_to_str(entry));
+    }
+    let is_callable = match entry.get_field_type() {
+        AbiEntryType::Function | AbiEntryType::Constructor | AbiEntryType::Fallback => true,
+        _ => false,
+    };
+    if is_callable || entry.get_constant() {
+        json["constant"] = json!(entry.get_constant());
+    }
+    if is_callable || entry.get_payable() {
+        json["payable"] = json!(entry.get_payable());
+    }
+    json
+}
+
+/// Convert an ABI to solc-style JSON.
+pub fn abi_to_json(abi: &Abi) -> serde_json::Value {
+    abi.get_entrys().iter().map(entry_to_json).collect()
+}
+
+fn translate_state_mutablility(val: &serde_json::Value) -> Result<AbiEntryStateMutabilityType, Error> {
     match val.as_str().unwrap_or_default().to_ascii_lowercase().as_ref() {
-        "view" => AbiEntryStateMutabilityType::View,
-        "nonpayable" => AbiEntryStateMutabilityType::Nonpayable,
-        "payable" => AbiEntryStateMutabilityType::Payable,
-        "pure" => AbiEntryStateMutabilityType::Pure,
-        "" => AbiEntryStateMutabilityType::UnknownMutabilityType,
-        x => {
-            println!("unknown => {:?}", x);
-            unimplemented!()
-        }
+        "view" => Ok(AbiEntryStateMutabilityType::View),
+        "nonpayable" => Ok(AbiEntryStateMutabilityType::Nonpayable),
+        "payable" => Ok(AbiEntryStateMutabilityType::Payable),
+        "pure" => Ok(AbiEntryStateMutabilityType::Pure),
+        "" => Ok(AbiEntryStateMutabilityType::UnknownMutabilityType),
+        _ => Err(Error::Runtime("unknown stateMutability in ABI JSON")),
     }
 }
 
-#[inline]
-fn translate_abi_type(val: &serde_json::Value) -> AbiEntryType {
+fn translate_abi_type(val: &serde_json::Value) -> Result<AbiEntryType, Error> {
     match val.as_str().unwrap_or("").to_ascii_lowercase().as_ref() {
-        "function" => AbiEntryType::Function,
-        "event" => AbiEntryType::Event,
-        "constructor" => AbiEntryType::Constructor,
-        "fallback" => AbiEntryType::Fallback,
-        _ => unimplemented!(),
+        "function" => Ok(AbiEntryType::Function),
+        "event" => Ok(AbiEntryType::Event),
+        "constructor" => Ok(AbiEntryType::Constructor),
+        "fallback" => Ok(AbiEntryType::Fallback),
+        "" => Ok(AbiEntryType::UnknownEntryType),
+        _ => Err(Error::Runtime("unsupported entry type in ABI JSON")),
     }
 }
 
-#[inline]
 fn translate_abi_entry_params(val: &serde_json::Value) -> Vec<AbiEntryParam> {
     val.as_array()
         .map(|arr| {
@@ -237,29 +461,308 @@ fn translate_abi_entry_params(val: &serde_json::Value) -> Vec<AbiEntryParam> {
         .unwrap_or_default()
 }
 
-pub fn json_to_abi(json: &serde_json::Value) -> Abi {
-    let entries: Vec<AbiEntry> = json
+/// Convert solc-style ABI JSON to protobuf ABI, the reverse of `abi_to_json`.
+///
+/// Legacy `constant` and `payable` fields are derived from `stateMutability` when missing.
+pub fn json_to_abi(json: &serde_json::Value) -> Result<Abi, Error> {
+    let entries = json
         .as_array()
-        .unwrap()
+        .ok_or(Error::Runtime("ABI JSON must be an array"))?
         .iter()
         .map(|abi| {
+            let mutability = translate_state_mutablility(&abi["stateMutability"])?;
+
             let mut entry = AbiEntry::new();
             entry.set_anonymous(abi["anonymous"].as_bool().unwrap_or(false));
-            entry.set_constant(abi["constant"].as_bool().unwrap_or(false));
+            entry.set_constant(abi["constant"].as_bool().unwrap_or(match mutability {
+                AbiEntryStateMutabilityType::View | AbiEntryStateMutabilityType::Pure => true,
+                _ => false,
+            }));
             entry.set_name(abi["name"].as_str().unwrap_or("").to_owned());
-            entry.set_payable(abi["payable"].as_bool().unwrap_or(false));
-            entry.set_stateMutability(translate_state_mutablility(&abi["stateMutability"]));
-            entry.set_field_type(translate_abi_type(&abi["type"]));
+            entry.set_payable(
+                abi["payable"]
+                    .as_bool()
+                    .unwrap_or(mutability == AbiEntryStateMutabilityType::Payable),
+            );
+            entry.set_stateMutability(mutability);
+            entry.set_field_type(translate_abi_type(&abi["type"])?);
 
             entry.set_inputs(translate_abi_entry_params(&abi["inputs"]).into());
             entry.set_outputs(translate_abi_entry_params(&abi["outputs"]).into());
 
-            entry
+            Ok(entry)
         })
-        .collect();
+        .collect::<Result<Vec<_>, Error>>()?;
 
-    Abi {
+    Ok(Abi {
         entrys: entries.into(),
         ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr() -> Address {
+        "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap()
+    }
+
+    #[test]
+    fn test_abi_value_display() {
+        assert_eq!(AbiValue::Address(addr()).to_string(), "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+        assert_eq!(AbiValue::Uint(1000.into()).to_string(), "1000");
+        assert_eq!(AbiValue::Int(5.into()).to_string(), "Int(5)");
+        assert_eq!(AbiValue::Bool(true).to_string(), "true");
+        assert_eq!(AbiValue::Bytes(vec![0xde, 0xad]).to_string(), "dead");
+        assert_eq!(AbiValue::FixedBytes(vec![0xbe, 0xef]).to_string(), "beef");
+        assert_eq!(AbiValue::String("TRON".into()).to_string(), "\"TRON\"");
+        assert_eq!(
+            AbiValue::Array(vec![AbiValue::Uint(1.into()), AbiValue::Uint(2.into())]).to_string(),
+            "[1, 2]"
+        );
+        assert_eq!(AbiValue::FixedArray(vec![AbiValue::Bool(false)]).to_string(), "[false]");
+        assert_eq!(AbiValue::Tuple(vec![AbiValue::Bool(false)]).to_string(), "tuple(...)");
+    }
+
+    #[test]
+    fn test_abi_value_conversion() {
+        assert_eq!(i64::try_from(AbiValue::Uint(42.into())).unwrap(), 42);
+        assert!(i64::try_from(AbiValue::Uint(ethabi::Uint::max_value())).is_err());
+        // -1 in two's complement
+        assert_eq!(i64::try_from(AbiValue::Int(ethabi::Int::max_value())).unwrap(), -1);
+        assert_eq!(
+            i64::try_from(AbiValue::Int((!ethabi::Int::from(i64::MAX as u64)).overflowing_add(1.into()).0)).unwrap(),
+            -i64::MAX
+        );
+        assert!(i64::try_from(AbiValue::Bool(true)).is_err());
+
+        assert_eq!(Address::try_from(AbiValue::Address(addr())).unwrap(), addr());
+        assert!(Address::try_from(AbiValue::Uint(1.into())).is_err());
+
+        assert_eq!(Vec::<u8>::try_from(AbiValue::Bytes(vec![1, 2])).unwrap(), vec![1, 2]);
+        assert_eq!(Vec::<u8>::try_from(AbiValue::FixedBytes(vec![3])).unwrap(), vec![3]);
+        assert!(Vec::<u8>::try_from(AbiValue::String("".into())).is_err());
+    }
+
+    #[test]
+    fn test_abi_value_json() {
+        let value = AbiValue::Tuple(vec![
+            AbiValue::Address(addr()),
+            AbiValue::Uint(7.into()),
+            AbiValue::Int(ethabi::Int::max_value()),
+            AbiValue::Bool(true),
+            AbiValue::Bytes(vec![0xab]),
+            AbiValue::String("hi".into()),
+            AbiValue::Array(vec![]),
+        ]);
+        assert_eq!(
+            serde_json::to_value(&value).unwrap(),
+            serde_json::json!(["TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t", "7", "-1", true, "ab", "hi", []])
+        );
+    }
+
+    #[test]
+    fn test_find_overloaded_entry() {
+        let abi = json_to_abi(&json!([
+            {
+                "type": "function",
+                "name": "transfer",
+                "inputs": [{"name": "to", "type": "address"}, {"name": "value", "type": "uint"}],
+            },
+            {
+                "type": "function",
+                "name": "transfer",
+                "inputs": [
+                    {"name": "to", "type": "address"},
+                    {"name": "value", "type": "uint256"},
+                    {"name": "data", "type": "bytes"},
+                ],
+            },
+        ]))
+        .unwrap();
+        let entries = abi.get_entrys();
+
+        assert_eq!(entry_to_method_name(&entries[0]), "transfer(address,uint256)");
+
+        let entry = find_entry_by_selector(entries, &fnhash("transfer(address,uint256,bytes)")).unwrap();
+        assert_eq!(entry.get_inputs().len(), 3);
+        let entry = find_entry_by_selector(entries, &hex::decode("a9059cbb").unwrap()).unwrap();
+        assert_eq!(entry.get_inputs().len(), 2);
+        assert!(find_entry_by_selector(entries, &fnhash("transfer(address)")).is_none());
+    }
+
+    #[test]
+    fn test_address_payable() {
+        let abi = json_to_abi(&json!([{
+            "type": "function",
+            "name": "withdraw",
+            "inputs": [{"name": "to", "type": "address payable"}, {"name": "amount", "type": "uint256"}],
+        }]))
+        .unwrap();
+        let entry = &abi.get_entrys()[0];
+        assert_eq!(entry_to_method_name(entry), "withdraw(address,uint256)");
+
+        let values = vec![AbiValue::Address(addr()), AbiValue::Uint(7.into())];
+        let encoded = encode_values(&values);
+        assert_eq!(
+            decode_params(&entry_to_input_types(entry), &hex::encode(&encoded)).unwrap(),
+            values
+        );
+        assert_eq!(
+            decode_params(
+                &["address payable[]"],
+                &hex::encode(encode_values(&[AbiValue::Array(vec![AbiValue::Address(addr())])]))
+            )
+            .unwrap(),
+            vec![AbiValue::Array(vec![AbiValue::Address(addr())])]
+        );
+    }
+
+    #[test]
+    fn test_encode_decode_values() {
+        let types = ["address", "uint256", "bool", "string", "uint8[]"];
+        let values = vec![
+            AbiValue::Address(addr()),
+            AbiValue::Uint(1_000_000.into()),
+            AbiValue::Bool(true),
+            AbiValue::String("memo".into()),
+            AbiValue::Array(vec![AbiValue::Uint(1.into()), AbiValue::Uint(2.into())]),
+        ];
+        let encoded = encode_values(&values);
+        assert_eq!(decode_params(&types, &hex::encode(&encoded)).unwrap(), values);
+
+        let parsed = parse_values(
+            &types,
+            &[
+                hex::encode(addr().as_tvm_bytes()),
+                "1000000".into(),
+                "true".into(),
+                "memo".into(),
+                "[1,2]".into(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(parsed, values);
+    }
+
+    fn trc20_abi_json() -> serde_json::Value {
+        json!([
+            {"type": "constructor", "inputs": [{"name": "supply", "type": "uint256"}], "stateMutability": "nonpayable"},
+            {
+                "type": "function",
+                "name": "balanceOf",
+                "inputs": [{"name": "who", "type": "address"}],
+                "outputs": [{"name": "", "type": "uint256"}],
+                "stateMutability": "view",
+            },
+            {
+                "type": "function",
+                "name": "transfer",
+                "inputs": [{"name": "to", "type": "address"}, {"name": "value", "type": "uint256"}],
+                "outputs": [{"name": "", "type": "bool"}],
+                "stateMutability": "nonpayable",
+            },
+            {"type": "function", "name": "deposit", "inputs": [], "outputs": [], "stateMutability": "payable"},
+            {
+                "type": "function",
+                "name": "add",
+                "inputs": [{"name": "a", "type": "uint8"}, {"name": "b", "type": "uint8"}],
+                "outputs": [{"name": "", "type": "uint8"}],
+                "stateMutability": "pure",
+            },
+            {
+                "type": "event",
+                "name": "Transfer",
+                "inputs": [
+                    {"name": "from", "type": "address", "indexed": true},
+                    {"name": "to", "type": "address", "indexed": true},
+                    {"name": "value", "type": "uint256", "indexed": false},
+                ],
+                "anonymous": false,
+            },
+            {"type": "event", "name": "Log", "inputs": [{"name": "data", "type": "bytes", "indexed": false}], "anonymous": true},
+            {"type": "fallback", "stateMutability": "payable"},
+        ])
+    }
+
+    #[test]
+    fn test_abi_json_roundtrip() {
+        let abi = json_to_abi(&trc20_abi_json()).unwrap();
+        let entries = abi.get_entrys();
+        assert!(entries[1].get_constant() && !entries[1].get_payable());
+        assert!(entries[3].get_payable() && !entries[3].get_constant());
+        assert!(entries[5].get_inputs()[0].get_indexed());
+
+        let json = abi_to_json(&abi);
+        assert_eq!(json[1]["stateMutability"], "view");
+        assert_eq!(json[5]["inputs"][2]["indexed"], false);
+        assert_eq!(json[6]["anonymous"], true);
+        assert_eq!(json_to_abi(&json).unwrap(), abi);
+
+        assert!(json_to_abi(&json!([{"type": "receive", "stateMutability": "payable"}])).is_err());
+    }
+
+    #[test]
+    fn test_legacy_state_mutability() {
+        let legacy = json!([
+            {
+                "type": "function",
+                "name": "name",
+                "inputs": [],
+                "outputs": [{"name": "", "type": "string"}],
+                "constant": true,
+                "payable": false,
+            },
+            {"type": "function", "name": "buy", "inputs": [], "outputs": [], "constant": false, "payable": true},
+            {"type": "fallback", "constant": false, "payable": true},
+            {"type": "event", "name": "Log", "inputs": [], "anonymous": false},
+        ]);
+        let abi = json_to_abi(&legacy).unwrap();
+        let entries = abi.get_entrys();
+        assert!(entries
+            .iter()
+            .all(|entry| entry.get_stateMutability() == StateMutabilityType::UnknownMutabilityType));
+        assert!(entries[0].get_constant() && !entries[0].get_payable());
+        assert!(entries[1].get_payable() && !entries[1].get_constant());
+        assert_eq!(
+            entry_to_method_name_pretty(&entries[0]).unwrap(),
+            "function name() view returns (string)"
+        );
+
+        let json = abi_to_json(&abi);
+        assert_eq!(json, legacy);
+        assert_eq!(json_to_abi(&json).unwrap(), abi);
+
+        // flags that disagree with `stateMutability` are kept too
+        let mut entry = entries[0].clone();
+        entry.set_stateMutability(StateMutabilityType::Nonpayable);
+        let json = entry_to_json(&entry);
+        assert_eq!(json["stateMutability"], "nonpayable");
+        assert_eq!(json["constant"], true);
+        assert_eq!(json_to_abi(&json!([json])).unwrap().get_entrys()[0], entry);
+    }
+
+    #[test]
+    fn test_entry_to_method_name_pretty() {
+        let abi = json_to_abi(&trc20_abi_json()).unwrap();
+        let pretty = abi
+            .get_entrys()
+            .iter()
+            .map(|entry| entry_to_method_name_pretty(entry).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pretty,
+            vec![
+                "constructor(uint256 supply)",
+                "function balanceOf(address who) view returns (uint256)",
+                "function transfer(address to, uint256 value) returns (bool)",
+                "function deposit() payable",
+                "function add(uint8 a, uint8 b) pure returns (uint8)",
+                "event Transfer(address indexed from, address indexed to, uint256 value)",
+                "event Log(bytes data) anonymous",
+                "fallback() payable",
+            ]
+        );
     }
 }
@@ -0,0 +1,471 @@
+//! Agent keeping keystore keys unlocked for one-shot invocations, like `ssh-agent`.
+//!
+//! Outside the REPL, `wallet unlock --keystore FILE` hands the decrypted key to the agent, a background
+//! `wallet-cli wallet agent` process started on first use. Later invocations sign through its Unix socket, the key
+//! never leaves the agent. The agent keeps keys in a `session` of its own, with the same idle timeout, and exits
+//! when its last key is locked.
+//!
+//! The socket is in a directory of the user with mode 0700, `$XDG_RUNTIME_DIR/wallet-cli` or `wallet-cli-UID` in
+//! the temp directory, and is itself mode 0600. Both ends check the UID of the peer and refuse other users. Every
+//! process of the user can sign with an unlocked key, see `session` for the rest of the threat model.
+//!
+//! A connection is one request and one response, a JSON line each. The key of an unlock request follows the request
+//! on its own line, in hex.
+
+use hex::FromHex;
+use keys::{Address, MemorySigner, Private, Signer};
+use log::{info, warn};
+use serde_json::json;
+use std::env;
+use std::fs::{self, DirBuilder};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str;
+use std::thread;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
+
+use crate::error::Error;
+use crate::utils::session;
+
+const SOCKET_NAME: &str = "agent.sock";
+/// Max wait for a request or a response, and for a new agent to listen.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// A new agent exits after this if no key has been unlocked.
+const STARTUP_GRACE: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Max length of a request line, and of the key line of an unlock request.
+const MAX_LINE: usize = 4096;
+
+fn uid() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+fn socket_dir() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("wallet-cli"),
+        None => env::temp_dir().join(format!("wallet-cli-{}", uid())),
+    }
+}
+
+/// A directory only the user can access, created if missing.
+fn ensure_private_dir(dir: &Path) -> Result<(), Error> {
+    match DirBuilder::new().mode(0o700).create(dir) {
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => check_private_dir(dir),
+        result => result.map_err(Error::from),
+    }
+}
+
+fn check_private_dir(dir: &Path) -> Result<(), Error> {
+    let meta = fs::symlink_metadata(dir)?;
+    if !meta.is_dir() || meta.uid() != uid() || meta.mode() & 0o077 != 0 {
+        warn!("Agent directory {:?} must be owned by the user with mode 0700", dir);
+        return Err(Error::Runtime("agent directory is accessible by other users"));
+    }
+    Ok(())
+}
+
+/// UID of the process at the other end of the socket.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer_uid(stream: &UnixStream) -> io::Result<u32> {
+    let mut cred = libc::ucred { pid: 0, uid: 0, gid: 0 };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let ret = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if ret != 0 || len as usize != std::mem::size_of::<libc::ucred>() {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(cred.uid)
+}
+
+/// UID of the process at the other end of the socket.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn peer_uid(stream: &UnixStream) -> io::Result<u32> {
+    let mut uid = 0;
+    let mut gid = 0;
+    if unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(uid)
+}
+
+fn check_peer(stream: &UnixStream) -> Result<(), Error> {
+    if peer_uid(stream)? != uid() {
+        return Err(Error::Runtime("agent peer is another user"));
+    }
+    Ok(())
+}
+
+fn connect() -> io::Result<UnixStream> {
+    let stream = UnixStream::connect(socket_dir().join(SOCKET_NAME))?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    Ok(stream)
+}
+
+fn request(
+    stream: &UnixStream,
+    req: &serde_json::Value,
+    private: Option<&Private>,
+) -> Result<serde_json::Value, Error> {
+    check_peer(stream)?;
+    let mut writer = stream;
+    serde_json::to_writer(&mut writer, req)?;
+    writer.write_all(b"\n")?;
+    if let Some(private) = private {
+        let key = Zeroizing::new(hex::encode(private.as_bytes()));
+        writer.write_all(key.as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    let resp: serde_json::Value = serde_json::from_str(&line)?;
+    if let Some(reason) = resp["error"].as_str() {
+        warn!("Agent: {}", reason);
+        return Err(Error::Runtime("agent refused the request"));
+    }
+    Ok(resp)
+}
+
+/// Start an agent in the background, in a session of its own so that it outlives the terminal.
+fn spawn() -> Result<UnixStream, Error> {
+    let mut cmd = Command::new(env::current_exe()?);
+    cmd.args(&["wallet", "agent"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    unsafe {
+        cmd.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+    cmd.spawn()?;
+
+    let started = Instant::now();
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        match connect() {
+            Ok(stream) => return Ok(stream),
+            Err(_) if started.elapsed() < IO_TIMEOUT => continue,
+            Err(_) => return Err(Error::Timeout("timeout waiting for the agent to start")),
+        }
+    }
+}
+
+/// Keep a key unlocked in the agent until idle for `idle_timeout`, the agent is started if not running.
+pub fn unlock(private: &Private, keystore: &Path, idle_timeout: Duration) -> Result<(), Error> {
+    let keystore = fs::canonicalize(keystore)?;
+    let stream = match connect() {
+        Ok(stream) => stream,
+        Err(_) => spawn()?,
+    };
+    let req = json!({
+        "unlock": {
+            "keystore": keystore.to_str().ok_or(Error::InvalidInput("keystore path is not UTF-8"))?,
+            "timeout": idle_timeout.as_secs(),
+        }
+    });
+    request(&stream, &req, Some(private))?;
+    Ok(())
+}
+
+/// Signs with a key of the agent, which is looked up on each signing.
+struct AgentSigner {
+    address: Address,
+}
+
+impl AgentSigner {
+    fn sign_digest(&self, hash: &[u8; 32]) -> Result<[u8; 65], Error> {
+        let req = json!({
+            "sign": {
+                "address": self.address.to_string(),
+                "digest": hex::encode(hash),
+            }
+        });
+        let resp = request(&connect()?, &req, None)?;
+        let signature = resp["signature"]
+            .as_str()
+            .ok_or(Error::Runtime("malformed agent response"))?;
+        let raw = Vec::from_hex(signature)?;
+        if raw.len() != 65 {
+            return Err(Error::Runtime("malformed agent response"));
+        }
+        let mut signature = [0u8; 65];
+        signature.copy_from_slice(&raw);
+        Ok(signature)
+    }
+}
+
+impl Signer for AgentSigner {
+    fn sign(&self, hash: &[u8; 32]) -> Result<[u8; 65], keys::Error> {
+        self.sign_digest(hash).map_err(|e| {
+            warn!("Agent signing failed: {:?}", e);
+            keys::Error::FailedSigning
+        })
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+}
+
+fn signer_of_request(req: serde_json::Value) -> Option<Box<dyn Signer>> {
+    let stream = connect().ok()?;
+    let resp = request(&stream, &req, None)
+        .map_err(|e| warn!("Agent lookup failed: {:?}", e))
+        .ok()?;
+    let address = resp["address"].as_str()?.parse().ok()?;
+    Some(Box::new(AgentSigner { address }))
+}
+
+/// Signer of a key unlocked in the agent, by address. None if not unlocked, or no agent is running.
+pub fn signer_of(addr: &Address) -> Option<Box<dyn Signer>> {
+    signer_of_request(json!({ "address": { "account": addr.to_string() } }))
+}
+
+/// Signer of a key unlocked in the agent, by the keystore file it was unlocked from.
+pub fn signer_of_keystore(path: &Path) -> Option<Box<dyn Signer>> {
+    let path = fs::canonicalize(path).ok()?;
+    signer_of_request(json!({ "address": { "keystore": path.to_str()? } }))
+}
+
+/// Lock all keys of the agent, which then exits. Returns how many were unlocked.
+pub fn lock() -> Result<usize, Error> {
+    let stream = match connect() {
+        Ok(stream) => stream,
+        Err(_) => return Ok(0),
+    };
+    let resp = request(&stream, &json!({ "lock": {} }), None)?;
+    Ok(resp["locked"].as_u64().unwrap_or_default() as usize)
+}
+
+/// Read a line byte by byte, nothing past it is buffered. The key of an unlock request is only ever read into the
+/// zeroized buffer of its own line, a `BufReader` would keep it in a buffer of its own.
+fn read_line(reader: &mut impl Read, line: &mut Vec<u8>) -> Result<(), Error> {
+    let mut byte = [0u8; 1];
+    while line.len() < MAX_LINE {
+        match reader.read(&mut byte) {
+            Ok(0) => return Ok(()),
+            Ok(_) if byte[0] == b'\n' => return Ok(()),
+            Ok(_) => line.push(byte[0]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(Error::InvalidInput("agent request line is too long"))
+}
+
+fn respond(req: &serde_json::Value, reader: &mut impl Read) -> Result<serde_json::Value, Error> {
+    if let Some(unlock) = req.get("unlock") {
+        let key = {
+            // never grows, so the key is never left in a reallocated buffer
+            let mut line = Zeroizing::new(Vec::with_capacity(MAX_LINE));
+            read_line(reader, &mut line)?;
+            str::from_utf8(&line)
+                .map_err(|_| Error::InvalidInput("malformed agent request"))?
+                .trim()
+                .parse::<Private>()?
+        };
+        let idle_timeout = unlock["timeout"]
+            .as_u64()
+            .map(Duration::from_secs)
+            .unwrap_or(session::DEFAULT_IDLE_TIMEOUT);
+        let signer = MemorySigner::new(key);
+        let address = signer.address();
+        session::unlock(signer, unlock["keystore"].as_str().map(Path::new), idle_timeout);
+        info!("Unlocked {}, locked after {}s idle", address, idle_timeout.as_secs());
+        Ok(json!({ "address": address.to_string() }))
+    } else if let Some(lookup) = req.get("address") {
+        let signer = match (lookup["keystore"].as_str(), lookup["account"].as_str()) {
+            (Some(path), _) => session::signer_of_keystore(Path::new(path)),
+            (None, Some(addr)) => session::signer_of(&addr.parse()?),
+            _ => return Err(Error::InvalidInput("malformed agent request")),
+        };
+        Ok(json!({ "address": signer.map(|signer| signer.address().to_string()) }))
+    } else if let Some(sign) = req.get("sign") {
+        let addr: Address = sign["address"]
+            .as_str()
+            .ok_or(Error::InvalidInput("malformed agent request"))?
+            .parse()?;
+        let digest = <[u8; 32]>::from_hex(
+            sign["digest"]
+                .as_str()
+                .ok_or(Error::InvalidInput("malformed agent request"))?,
+        )?;
+        let signer = session::signer_of(&addr).ok_or(Error::NotFound("key is not unlocked"))?;
+        Ok(json!({ "signature": hex::encode(&signer.sign(&digest)?[..]) }))
+    } else if req.get("lock").is_some() {
+        Ok(json!({ "locked": session::lock() }))
+    } else {
+        Err(Error::InvalidInput("malformed agent request"))
+    }
+}
+
+fn handle(stream: UnixStream) -> Result<(), Error> {
+    // accepted sockets inherit non-blocking mode of the listener on some platforms
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    check_peer(&stream)?;
+
+    let mut reader = &stream;
+    let mut line = Vec::new();
+    read_line(&mut reader, &mut line)?;
+    let resp = serde_json::from_slice::<serde_json::Value>(&line)
+        .map_err(Error::from)
+        .and_then(|req| respond(&req, &mut reader))
+        .unwrap_or_else(|e| json!({ "error": format!("{:?}", e) }));
+
+    let mut writer = &stream;
+    serde_json::to_writer(&mut writer, &resp)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Run the agent, until its last key is locked or idle.
+pub fn serve() -> Result<(), Error> {
+    let dir = socket_dir();
+    ensure_private_dir(&dir)?;
+    let path = dir.join(SOCKET_NAME);
+    if UnixStream::connect(&path).is_ok() {
+        return Err(Error::Runtime("agent is already running"));
+    }
+    // left by an agent that was killed
+    let _ = fs::remove_file(&path);
+    // not reachable by others before chmod, the directory is owner-only
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    listener.set_nonblocking(true)?;
+    info!("Agent listening on {:?}", path);
+
+    serve_until_idle(&listener, STARTUP_GRACE);
+    fs::remove_file(&path)?;
+    info!("Agent exits, no key is unlocked");
+    Ok(())
+}
+
+/// Serve a non-blocking listener until no key is unlocked, the first key may be unlocked within `grace`.
+fn serve_until_idle(listener: &UnixListener, grace: Duration) {
+    // requests are served one by one, so that a key is never unlocked while exiting
+    let started = Instant::now();
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = handle(stream) {
+                    warn!("Agent request failed: {:?}", e);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                if session::count() == 0 && started.elapsed() >= grace {
+                    break;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+            Err(e) => warn!("Agent accept failed: {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use std::process;
+
+    /// One request to an agent handling it on another thread, keys are unlocked in the session of this process.
+    fn call(req: &serde_json::Value, private: Option<&Private>) -> Result<serde_json::Value, Error> {
+        let (client, server) = UnixStream::pair().unwrap();
+        let agent = thread::spawn(move || handle(server));
+        let resp = request(&client, req, private);
+        agent.join().unwrap().unwrap();
+        resp
+    }
+
+    #[test]
+    fn test_check_private_dir() {
+        let dir = env::temp_dir().join(format!("wallet-cli-agent-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        ensure_private_dir(&dir).unwrap();
+        assert_eq!(fs::metadata(&dir).unwrap().mode() & 0o777, 0o700);
+        ensure_private_dir(&dir).unwrap();
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        let result = ensure_private_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_request() {
+        let (client, server) = UnixStream::pair().unwrap();
+        assert_eq!(peer_uid(&client).unwrap(), uid());
+        let agent = thread::spawn(move || handle(server));
+
+        // an address never unlocked
+        let addr = Address::from_tvm_bytes(&[0xa5; 20]);
+        let resp = request(&client, &json!({ "address": { "account": addr.to_string() } }), None).unwrap();
+        assert!(resp["address"].is_null());
+        agent.join().unwrap().unwrap();
+
+        let (client, server) = UnixStream::pair().unwrap();
+        let agent = thread::spawn(move || handle(server));
+        assert!(request(&client, &json!({ "unknown": {} }), None).is_err());
+        agent.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_unlock_sign_lock() {
+        let _session = session::TEST_SESSION.lock().unwrap_or_else(|e| e.into_inner());
+        let private = Private::try_from(&[0x5a; 32][..]).unwrap();
+        let signer = MemorySigner::new(private.clone());
+        let addr = signer.address().to_string();
+
+        let resp = call(&json!({ "unlock": { "timeout": 300 } }), Some(&private)).unwrap();
+        assert_eq!(resp["address"], addr);
+        let resp = call(&json!({ "address": { "account": addr } }), None).unwrap();
+        assert_eq!(resp["address"], addr);
+
+        let sign = json!({ "sign": { "address": addr, "digest": hex::encode([1u8; 32]) } });
+        let resp = call(&sign, None).unwrap();
+        assert_eq!(resp["signature"], hex::encode(&signer.sign(&[1; 32]).unwrap()[..]));
+
+        let resp = call(&json!({ "lock": {} }), None).unwrap();
+        assert_eq!(resp["locked"], 1);
+        assert!(call(&sign, None).is_err());
+    }
+
+    #[test]
+    fn test_exit_after_idle_timeout() {
+        let _session = session::TEST_SESSION.lock().unwrap_or_else(|e| e.into_inner());
+        let path = env::temp_dir().join(format!("wallet-cli-agent-{}.sock", process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        listener.set_nonblocking(true).unwrap();
+
+        let private = Private::try_from(&[0x5b; 32][..]).unwrap();
+        let started = Instant::now();
+        call(&json!({ "unlock": { "timeout": 1 } }), Some(&private)).unwrap();
+        let agent = thread::spawn(move || serve_until_idle(&listener, Duration::from_secs(0)));
+        agent.join().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // locked by the session sweeper, checked every second
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert!(started.elapsed() < IO_TIMEOUT);
+        assert_eq!(session::count(), 0);
+    }
+}
@@ -0,0 +1,322 @@
+//! Header checks of blocks from untrusted nodes, a light-client sanity check.
+//!
+//! A block is checked against its id, the merkle root of its transactions and the signature of its witness, or of a
+//! key of the witness permission of the witness account. A range of blocks is also checked for parent linkage.
+
+use futures::executor;
+use keys::{Address, Public, Signature};
+use lazy_static::lazy_static;
+use log::warn;
+use merkle_tree::{MerkleHasher, MerkleTree, H256};
+use proto::api::{BlockExtention, BlockLimit};
+use proto::core::{Account, Block, Transaction};
+use protobuf::Message;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::Mutex;
+
+use crate::error::Error;
+use crate::utils::client;
+use crate::utils::crypto;
+use crate::utils::record;
+
+// Max blocks per `GetBlockByLimitNext` request.
+const BLOCK_BATCH_SIZE: i64 = 100;
+
+lazy_static! {
+    static ref WITNESS_PERMISSION_KEYS: Mutex<HashMap<Address, Vec<Address>>> = Mutex::new(HashMap::new());
+}
+
+/// Blocks of a range, fetched by `GetBlockByLimitNext` in batches. Each item is a batch, with the number of the
+/// last block of the batch for progress.
+pub struct BlockBatches {
+    next: i64,
+    last: i64,
+    verify: bool,
+    /// Last verified block, to check parent linkage across batches.
+    parent: Option<Block>,
+}
+
+impl BlockBatches {
+    /// Blocks from `first` to `last`, inclusive.
+    pub fn new(first: i64, last: i64) -> Self {
+        BlockBatches {
+            next: first,
+            last,
+            verify: false,
+            parent: None,
+        }
+    }
+
+    /// Verify every block and the parent linkage between them, a failed check is the error of its batch.
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    fn verify_batch(&mut self, blocks: &[BlockExtention]) -> Result<(), Error> {
+        for block in blocks {
+            let verified = record::block_of(block);
+            verify_block(&verified, Some(block.get_blockid()))?;
+            if let Some(ref parent) = self.parent {
+                verify_parent(parent, &verified)?;
+            }
+            self.parent = Some(verified);
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for BlockBatches {
+    type Item = Result<(i64, Vec<BlockExtention>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next > self.last {
+            return None;
+        }
+        let end = (self.next + BLOCK_BATCH_SIZE).min(self.last + 1);
+        let mut req = BlockLimit::new();
+        req.set_startNum(self.next);
+        req.set_endNum(end);
+        self.next = end;
+        let batch = executor::block_on(
+            client::GRPC_CLIENT
+                .get_block_by_limit_next2(Default::default(), req)
+                .drop_metadata(),
+        )
+        .map(|mut blocks| blocks.take_block().into_vec())
+        .map_err(Error::from);
+        Some(batch.and_then(|blocks| {
+            if self.verify {
+                self.verify_batch(&blocks)?;
+            }
+            Ok((end - 1, blocks))
+        }))
+    }
+}
+
+/// Merkle hasher of `txTrieRoot`, leaves are hashes of serialized `Transaction`s.
+pub struct TransactionHashHasher;
+
+impl MerkleHasher for TransactionHashHasher {
+    type Input = H256;
+
+    fn hash(input: &H256) -> H256 {
+        *input
+    }
+
+    fn hash_nodes(left: &H256, right: &H256) -> H256 {
+        let mut raw = left.as_bytes().to_vec();
+        raw.extend_from_slice(right.as_bytes());
+        H256::from(crypto::sha256(&raw))
+    }
+}
+
+/// Merkle root of transaction hashes, zero if empty.
+pub fn merkle_root(hashes: Vec<H256>) -> H256 {
+    *MerkleTree::<TransactionHashHasher>::from_vec(hashes).root_hash()
+}
+
+/// `txTrieRoot` of transactions.
+pub fn tx_trie_root(txns: &[Transaction]) -> Result<H256, Error> {
+    let hashes = txns
+        .iter()
+        .map(|txn| Ok(H256::from(crypto::sha256(&txn.write_to_bytes()?))))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(merkle_root(hashes))
+}
+
+fn failed(block: &Block, check: &str) -> Error {
+    warn!(
+        "Block #{} failed verification: {}",
+        block.get_block_header().get_raw_data().number,
+        check
+    );
+    Error::Mismatch("block verification failed")
+}
+
+/// Keys of the witness permission of a witness account, which sign blocks in place of the witness address. Cached.
+fn witness_permission_keys(witness: &Address) -> Result<Vec<Address>, Error> {
+    if let Some(keys) = WITNESS_PERMISSION_KEYS.lock().unwrap().get(witness) {
+        return Ok(keys.clone());
+    }
+    let mut req = Account::new();
+    req.set_address(witness.as_bytes().to_owned());
+    let account = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
+    let keys = account
+        .get_witness_permission()
+        .get_keys()
+        .iter()
+        .filter_map(|key| Address::try_from(key.get_address()).ok())
+        .collect::<Vec<_>>();
+    WITNESS_PERMISSION_KEYS.lock().unwrap().insert(*witness, keys.clone());
+    Ok(keys)
+}
+
+/// Verify a block, against the id reported by the node if any.
+pub fn verify_block(block: &Block, reported_id: Option<&[u8]>) -> Result<(), Error> {
+    verify_block_with(block, reported_id, witness_permission_keys)
+}
+
+/// `verify_block`, witness permission keys are looked up only for blocks not signed by the witness address.
+fn verify_block_with<F>(block: &Block, reported_id: Option<&[u8]>, permission_keys: F) -> Result<(), Error>
+where
+    F: FnOnce(&Address) -> Result<Vec<Address>, Error>,
+{
+    let header = block.get_block_header();
+    let raw = header.get_raw_data();
+
+    let block_id = record::block_id(block)?;
+    if reported_id.map_or(false, |id| id != block_id) {
+        return Err(failed(block, "block id does not match the header"));
+    }
+
+    if tx_trie_root(block.get_transactions())?.as_bytes() != raw.get_txTrieRoot() {
+        return Err(failed(block, "txTrieRoot does not match the transactions"));
+    }
+
+    let witness = Address::try_from(raw.get_witness_address()).map_err(|_| failed(block, "invalid witness address"))?;
+    let signature =
+        Signature::try_from(header.get_witness_signature()).map_err(|_| failed(block, "invalid witness signature"))?;
+    let digest = crypto::sha256(&raw.write_to_bytes()?);
+    let signer = Public::recover_digest(&digest, &signature)
+        .map(|public| Address::from_public(&public))
+        .map_err(|_| failed(block, "invalid witness signature"))?;
+    if signer == witness || permission_keys(&witness)?.contains(&signer) {
+        Ok(())
+    } else {
+        Err(failed(
+            block,
+            "witness signature is not signed by the witness or its witness permission",
+        ))
+    }
+}
+
+/// Verify that `block` is the child of `parent`.
+pub fn verify_parent(parent: &Block, block: &Block) -> Result<(), Error> {
+    let raw = block.get_block_header().get_raw_data();
+    if raw.number != parent.get_block_header().get_raw_data().number + 1 ||
+        raw.get_parentHash() != &record::block_id(parent)?[..]
+    {
+        return Err(failed(block, "parentHash does not link to the previous block"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keys::{KeyPair, Private};
+    use proto::core::{BlockHeader, BlockHeader_raw as BlockHeaderRaw};
+
+    fn signed_block(number: i64, parent_hash: Vec<u8>, private: &Private) -> Block {
+        let mut raw = BlockHeaderRaw::new();
+        raw.set_number(number);
+        raw.set_parentHash(parent_hash);
+        raw.set_txTrieRoot(H256::zero().as_bytes().to_vec());
+        raw.set_witness_address(Address::from_private(private).as_bytes().to_vec());
+        let signature = private
+            .sign_digest(&crypto::sha256(&raw.write_to_bytes().unwrap()))
+            .unwrap();
+
+        let mut header = BlockHeader::new();
+        header.set_raw_data(raw);
+        header.set_witness_signature(signature.as_bytes().to_vec());
+        let mut block = Block::new();
+        block.set_block_header(header);
+        block
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        // sha256 of 0x00000000, 0x00000001 and 0x00000002
+        let hashes = [
+            "df3f619804a92fdb4057192dc43dd748ea778adc52bc498ce80524c014b81119",
+            "b40711a88c7039756fb8a73827eabe2c0fe5a0346ca7e0a104adc0fc764f528d",
+            "433ebf5bc03dffa38536673207a21281612cef5faa9bc7a4d5b9be2fdb12cf1a",
+        ]
+        .iter()
+        .map(|hash| hash.parse().unwrap())
+        .collect::<Vec<H256>>();
+
+        assert_eq!(merkle_root(vec![]), H256::zero());
+        assert_eq!(merkle_root(hashes[..1].to_vec()), hashes[0]);
+        assert_eq!(
+            merkle_root(hashes),
+            "baab99a32bb15f1d10b9dd6958f98a729e8d237207b7d8b9e7789e382834d1eb"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify_block() {
+        let kp = KeyPair::generate();
+        let parent = signed_block(100, vec![0; 32], kp.private());
+        let block = signed_block(101, record::block_id(&parent).unwrap().to_vec(), kp.private());
+
+        verify_block(&block, Some(&record::block_id(&block).unwrap())).unwrap();
+        verify_parent(&parent, &block).unwrap();
+        assert!(verify_block(&block, Some(&[0; 32])).is_err());
+        assert!(verify_parent(&block, &parent).is_err());
+
+        let mut forged = block.clone();
+        forged.mut_block_header().mut_raw_data().set_timestamp(1);
+        assert!(verify_block(&forged, None).is_err());
+
+        let mut forged = block;
+        forged.set_transactions(vec![Transaction::new()].into());
+        assert!(verify_block(&forged, None).is_err());
+    }
+
+    #[test]
+    fn test_verify_block_with_witness_permission() {
+        let witness = KeyPair::generate();
+        let permission = KeyPair::generate();
+        let mut block = signed_block(101, vec![0; 32], permission.private());
+        block
+            .mut_block_header()
+            .mut_raw_data()
+            .set_witness_address(witness.address().as_bytes().to_vec());
+        let signature = permission
+            .private()
+            .sign_digest(&crypto::sha256(
+                &block.get_block_header().get_raw_data().write_to_bytes().unwrap(),
+            ))
+            .unwrap();
+        block
+            .mut_block_header()
+            .set_witness_signature(signature.as_bytes().to_vec());
+
+        verify_block_with(&block, None, |addr| {
+            assert_eq!(*addr, witness.address());
+            Ok(vec![permission.address()])
+        })
+        .unwrap();
+        assert!(verify_block_with(&block, None, |_| Ok(vec![])).is_err());
+        assert!(verify_block_with(&block, None, |_| Ok(vec![witness.address()])).is_err());
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let kp = KeyPair::generate();
+        let extention_of = |block: &Block| {
+            let mut block_ext = BlockExtention::new();
+            block_ext.set_block_header(block.get_block_header().clone());
+            block_ext.set_blockid(record::block_id(block).unwrap().to_vec());
+            block_ext
+        };
+        let first = signed_block(100, vec![0; 32], kp.private());
+        let second = signed_block(101, record::block_id(&first).unwrap().to_vec(), kp.private());
+        let orphan = signed_block(102, vec![0; 32], kp.private());
+
+        let mut batches = BlockBatches::new(100, 102).verify(true);
+        batches.verify_batch(&[extention_of(&first)]).unwrap();
+        // linked to the last block of the previous batch
+        batches.verify_batch(&[extention_of(&second)]).unwrap();
+        assert!(matches!(
+            batches.verify_batch(&[extention_of(&orphan)]),
+            Err(Error::Mismatch(_))
+        ));
+    }
+}
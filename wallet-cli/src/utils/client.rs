@@ -1,21 +1,51 @@
+use bytes::Bytes;
+use futures::executor;
+use grpc::rt::{ArcOrStatic, GrpcStreaming, MethodDescriptor, StringOrStatic};
 use grpc::ClientStubExt;
 use lazy_static::lazy_static;
-use proto::api_grpc::WalletClient;
-use std::net::ToSocketAddrs;
+use proto::api::{BytesMessage, NumberMessage};
+use proto::api_grpc::{WalletClient, WalletExtensionClient, WalletSolidityClient};
+use proto::core::{Transaction, TransactionInfo};
+use protobuf::wire_format::WireType;
+use protobuf::{CodedInputStream, CodedOutputStream, Message, ProtobufResult};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::process;
 
 use crate::error::Error;
-use crate::RPC_ADDR;
+use crate::utils::http;
+use crate::{RPC_ADDR, SOLIDITY_ADDR};
 
 lazy_static! {
     pub static ref GRPC_CLIENT: WalletClient = {
-        let addr = unsafe { RPC_ADDR }
-            .to_socket_addrs()
-            .ok()
-            .and_then(|mut addrs| addrs.next())
-            .expect("can not resolve rpc host");
+        let addr = rpc_socket_addr();
         WalletClient::new_plain(&addr.ip().to_string(), addr.port(), Default::default())
             .expect("can not create gRPC client")
     };
+    pub static ref GRPC_EXTENSION_CLIENT: WalletExtensionClient = {
+        let addr = rpc_socket_addr();
+        WalletExtensionClient::new_plain(&addr.ip().to_string(), addr.port(), Default::default())
+            .expect("can not create gRPC client")
+    };
+    /// Client for methods missing from the generated `WalletClient`.
+    static ref GRPC_RAW_CLIENT: grpc::Client = {
+        let addr = rpc_socket_addr();
+        grpc::Client::new_plain(&addr.ip().to_string(), addr.port(), Default::default())
+            .expect("can not create gRPC client")
+    };
+}
+
+/// Address of the gRPC node, exits if `--rpc-addr` is an HTTP API, instead of falling back to another node.
+fn rpc_socket_addr() -> SocketAddr {
+    let addr = unsafe { RPC_ADDR };
+    if addr.is_empty() {
+        let err = Error::Runtime("an HTTP --rpc-addr only serves transaction queries, use a gRPC address");
+        eprintln!("Error: {:?}", err);
+        process::exit(err.exit_code());
+    }
+    addr.to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .expect("can not resolve rpc host")
 }
 
 pub fn new_grpc_client(host: &str) -> Result<WalletClient, Error> {
@@ -30,3 +60,142 @@ pub fn new_grpc_client(host: &str) -> Result<WalletClient, Error> {
         Default::default(),
     )?)
 }
+
+/// Client of the solidity node, set by `--solidity-node` or `TRON_SOLIDITY_NODE`.
+pub fn new_solidity_client() -> Result<WalletSolidityClient, Error> {
+    let host = unsafe { SOLIDITY_ADDR }
+        .ok_or(Error::Runtime(
+            "no solidity node, use --solidity-node or TRON_SOLIDITY_NODE",
+        ))?
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or(Error::Runtime("can not resolve address"))?;
+    Ok(WalletSolidityClient::new_plain(
+        &host.ip().to_string(),
+        host.port(),
+        Default::default(),
+    )?)
+}
+
+/// `GetTransactionById`, over the HTTP API when in use.
+pub fn get_transaction_by_id(txid: &[u8]) -> Result<Transaction, Error> {
+    if let Some(endpoint) = http::endpoint() {
+        return http::get_transaction_by_id(endpoint, txid);
+    }
+    let mut req = BytesMessage::new();
+    req.set_value(txid.to_owned());
+    Ok(executor::block_on(
+        GRPC_CLIENT
+            .get_transaction_by_id(Default::default(), req)
+            .drop_metadata(),
+    )?)
+}
+
+/// `GetTransactionInfoById`, over the HTTP API when in use.
+pub fn get_transaction_info_by_id(txid: &[u8]) -> Result<TransactionInfo, Error> {
+    if let Some(endpoint) = http::endpoint() {
+        return http::get_transaction_info_by_id(endpoint, txid);
+    }
+    let mut req = BytesMessage::new();
+    req.set_value(txid.to_owned());
+    Ok(executor::block_on(
+        GRPC_CLIENT
+            .get_transaction_info_by_id(Default::default(), req)
+            .drop_metadata(),
+    )?)
+}
+
+/// `GetTransactionInfoByBlockNum`, infos of all transactions of a block, over the HTTP API when in use.
+///
+/// The method is newer than the generated `WalletClient`, so it is called on a raw client, and the
+/// `TransactionInfoList` response is decoded by `TransactionInfoListMarshaller`.
+pub fn get_transaction_info_by_block_num(num: i64) -> Result<Vec<TransactionInfo>, Error> {
+    if let Some(endpoint) = http::endpoint() {
+        return http::get_transaction_info_by_block_num(endpoint, num);
+    }
+    let mut req = NumberMessage::new();
+    req.set_num(num);
+    let descriptor = ArcOrStatic::Static(&MethodDescriptor {
+        name: StringOrStatic::Static("/protocol.Wallet/GetTransactionInfoByBlockNum"),
+        streaming: GrpcStreaming::Unary,
+        req_marshaller: ArcOrStatic::Static(&TransactionInfoListMarshaller),
+        resp_marshaller: ArcOrStatic::Static(&TransactionInfoListMarshaller),
+    });
+    Ok(executor::block_on(
+        GRPC_RAW_CLIENT
+            .call_unary(Default::default(), req, descriptor)
+            .drop_metadata(),
+    )?)
+}
+
+/// Marshaller of `GetTransactionInfoByBlockNum`, a `NumberMessage` request and a
+/// `TransactionInfoList { repeated TransactionInfo transactionInfo = 1; }` response.
+struct TransactionInfoListMarshaller;
+
+impl grpc::Marshaller<NumberMessage> for TransactionInfoListMarshaller {
+    fn write(&self, m: &NumberMessage, _size_estimate: u32, out: &mut Vec<u8>) -> grpc::Result<()> {
+        m.write_to_vec(out)
+            .map_err(|_| grpc::Error::Other("can not encode NumberMessage"))
+    }
+
+    fn read(&self, buf: Bytes) -> grpc::Result<NumberMessage> {
+        protobuf::parse_from_bytes(&buf).map_err(|_| grpc::Error::Other("can not decode NumberMessage"))
+    }
+}
+
+impl grpc::Marshaller<Vec<TransactionInfo>> for TransactionInfoListMarshaller {
+    fn write(&self, infos: &Vec<TransactionInfo>, _size_estimate: u32, out: &mut Vec<u8>) -> grpc::Result<()> {
+        write_transaction_info_list(infos, out).map_err(|_| grpc::Error::Other("can not encode TransactionInfoList"))
+    }
+
+    fn read(&self, buf: Bytes) -> grpc::Result<Vec<TransactionInfo>> {
+        read_transaction_info_list(&buf).map_err(|_| grpc::Error::Other("can not decode TransactionInfoList"))
+    }
+}
+
+fn write_transaction_info_list(infos: &[TransactionInfo], out: &mut Vec<u8>) -> ProtobufResult<()> {
+    let mut os = CodedOutputStream::vec(out);
+    for info in infos {
+        os.write_message(1, info)?;
+    }
+    os.flush()
+}
+
+fn read_transaction_info_list(buf: &[u8]) -> ProtobufResult<Vec<TransactionInfo>> {
+    let mut is = CodedInputStream::from_bytes(buf);
+    let mut infos = vec![];
+    while !is.eof()? {
+        let (field_number, wire_type) = is.read_tag_unpack()?;
+        if field_number == 1 && wire_type == WireType::WireTypeLengthDelimited {
+            infos.push(is.read_message()?);
+        } else {
+            is.skip_field(wire_type)?;
+        }
+    }
+    Ok(infos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_info_list() {
+        let infos = (1..=3)
+            .map(|i| {
+                let mut info = TransactionInfo::new();
+                info.set_id(vec![i; 32]);
+                info.set_fee(i as i64 * 1_000);
+                info
+            })
+            .collect::<Vec<_>>();
+        let mut buf = vec![];
+        write_transaction_info_list(&infos, &mut buf).unwrap();
+        assert_eq!(read_transaction_info_list(&buf).unwrap(), infos);
+        assert!(read_transaction_info_list(&[]).unwrap().is_empty());
+        // unknown fields are skipped
+        buf.extend_from_slice(&[0x10, 0x01]);
+        assert_eq!(read_transaction_info_list(&buf).unwrap().len(), 3);
+    }
+}
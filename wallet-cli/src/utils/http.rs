@@ -0,0 +1,342 @@
+//! HTTP API of a node, a fallback for nodes without gRPC.
+//!
+//! Selected by `--http`, or an `http://`/`https://` endpoint in `--rpc-addr`. REST JSON is mapped into the same
+//! protobuf structures the gRPC API returns, so that `jsont` and ABI decoding work unchanged. Only transaction and
+//! transaction info queries are supported, with an HTTP `--rpc-addr` other gRPC calls fail instead of reaching the
+//! default node of the network.
+
+use hex::FromHex;
+use log::debug;
+use proto::core::{
+    InternalTransaction, InternalTransaction_CallValueInfo as CallValueInfo, ResourceReceipt, Transaction,
+    TransactionInfo, TransactionInfo_Log as Log, Transaction_Result as TransactionResult,
+    Transaction_raw as TransactionRaw,
+};
+use protobuf::ProtobufEnum;
+use serde_json::json;
+use std::fmt::Debug;
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::HTTP_ENDPOINT;
+
+const REQUEST_TIMEOUT_MILLIS: u64 = 10_000;
+
+/// The HTTP endpoint in use, `None` when gRPC is used.
+pub fn endpoint() -> Option<&'static str> {
+    unsafe { HTTP_ENDPOINT }
+}
+
+/// Default HTTP endpoint of a network.
+pub fn default_endpoint(network: &str) -> Option<&'static str> {
+    match network {
+        "mainnet" => Some("https://api.trongrid.io"),
+        "shasta" => Some("https://api.shasta.trongrid.io"),
+        "nile" => Some("https://nile.trongrid.io"),
+        _ => None,
+    }
+}
+
+/// Is the RPC address an HTTP endpoint.
+pub fn is_http_endpoint(addr: &str) -> bool {
+    addr.starts_with("http://") || addr.starts_with("https://")
+}
+
+fn post(endpoint: &str, method: &str, body: serde_json::Value) -> Result<serde_json::Value, Error> {
+    let url = format!("{}/wallet/{}", endpoint.trim_end_matches('/'), method);
+    debug!("POST {} {}", url, body);
+    let resp = ureq::post(&url)
+        .timeout(Duration::from_millis(REQUEST_TIMEOUT_MILLIS))
+        .send_json(body);
+    if let Some(err) = resp.synthetic_error() {
+        debug!("HTTP error: {}", err);
+        return Err(Error::Transport("HTTP request failed"));
+    }
+    if !resp.ok() {
+        debug!("HTTP status: {}", resp.status());
+        return Err(Error::Runtime("HTTP API returned an error status"));
+    }
+    let json: serde_json::Value = resp.into_json()?;
+    if let Some(err) = json["Error"].as_str() {
+        debug!("HTTP API error: {}", err);
+        return Err(Error::Runtime("HTTP API returned an error"));
+    }
+    Ok(json)
+}
+
+fn parse_enum<E: ProtobufEnum + Debug>(name: &str) -> Result<E, Error> {
+    E::values()
+        .iter()
+        .find(|val| format!("{:?}", val) == name)
+        .copied()
+        .ok_or(Error::Runtime("unknown enum value in HTTP response"))
+}
+
+fn bytes_field(val: &serde_json::Value) -> Result<Vec<u8>, Error> {
+    match val.as_str() {
+        Some(s) => Ok(Vec::from_hex(s)?),
+        None => Ok(vec![]),
+    }
+}
+
+fn bytes_list(val: &serde_json::Value) -> Result<Vec<Vec<u8>>, Error> {
+    val.as_array()
+        .map(|vals| vals.iter().map(bytes_field).collect())
+        .unwrap_or_else(|| Ok(vec![]))
+}
+
+fn i64_field(val: &serde_json::Value) -> i64 {
+    val.as_i64().unwrap_or_default()
+}
+
+/// Map the response of `gettransactionbyid`. An empty object is an empty `Transaction`, as in gRPC.
+pub fn transaction_from_json(json: &serde_json::Value) -> Result<Transaction, Error> {
+    let mut txn = Transaction::new();
+    let raw_data_hex = match json["raw_data_hex"].as_str() {
+        Some(raw_data_hex) => raw_data_hex,
+        None => return Ok(txn),
+    };
+    txn.set_raw_data(protobuf::parse_from_bytes::<TransactionRaw>(&Vec::from_hex(
+        raw_data_hex,
+    )?)?);
+    txn.set_signature(bytes_list(&json["signature"])?.into());
+
+    let ret = json["ret"]
+        .as_array()
+        .map(|rets| {
+            rets.iter()
+                .map(|ret| {
+                    let mut result = TransactionResult::new();
+                    result.set_fee(i64_field(&ret["fee"]));
+                    if let Some(contract_ret) = ret["contractRet"].as_str() {
+                        result.set_contractRet(parse_enum(contract_ret)?);
+                    }
+                    Ok(result)
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    txn.set_ret(ret.into());
+    Ok(txn)
+}
+
+/// Map the response of `gettransactioninfobyid`. An empty object is an empty `TransactionInfo`, as in gRPC.
+pub fn transaction_info_from_json(json: &serde_json::Value) -> Result<TransactionInfo, Error> {
+    let mut info = TransactionInfo::new();
+    if json["id"].is_null() {
+        return Ok(info);
+    }
+    info.set_id(bytes_field(&json["id"])?);
+    info.set_fee(i64_field(&json["fee"]));
+    info.set_blockNumber(i64_field(&json["blockNumber"]));
+    info.set_blockTimeStamp(i64_field(&json["blockTimeStamp"]));
+    info.set_contractResult(bytes_list(&json["contractResult"])?.into());
+    info.set_contract_address(bytes_field(&json["contract_address"])?);
+    if let Some(result) = json["result"].as_str() {
+        info.set_result(parse_enum(result)?);
+    }
+    info.set_resMessage(bytes_field(&json["resMessage"])?);
+    if let Some(asset_id) = json["assetIssueID"].as_str() {
+        info.set_assetIssueID(asset_id.to_owned());
+    }
+    info.set_withdraw_amount(i64_field(&json["withdraw_amount"]));
+    info.set_unfreeze_amount(i64_field(&json["unfreeze_amount"]));
+    info.set_exchange_received_amount(i64_field(&json["exchange_received_amount"]));
+    info.set_exchange_inject_another_amount(i64_field(&json["exchange_inject_another_amount"]));
+    info.set_exchange_withdraw_another_amount(i64_field(&json["exchange_withdraw_another_amount"]));
+    info.set_exchange_id(i64_field(&json["exchange_id"]));
+
+    let receipt = &json["receipt"];
+    let mut resource = ResourceReceipt::new();
+    resource.set_energy_usage(i64_field(&receipt["energy_usage"]));
+    resource.set_energy_fee(i64_field(&receipt["energy_fee"]));
+    resource.set_origin_energy_usage(i64_field(&receipt["origin_energy_usage"]));
+    resource.set_energy_usage_total(i64_field(&receipt["energy_usage_total"]));
+    resource.set_net_usage(i64_field(&receipt["net_usage"]));
+    resource.set_net_fee(i64_field(&receipt["net_fee"]));
+    if let Some(result) = receipt["result"].as_str() {
+        resource.set_result(parse_enum(result)?);
+    }
+    info.set_receipt(resource);
+
+    let logs = json["log"]
+        .as_array()
+        .map(|logs| {
+            logs.iter()
+                .map(|log| {
+                    let mut pb = Log::new();
+                    pb.set_address(bytes_field(&log["address"])?);
+                    pb.set_topics(bytes_list(&log["topics"])?.into());
+                    pb.set_data(bytes_field(&log["data"])?);
+                    Ok(pb)
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    info.set_log(logs.into());
+
+    let internal_transactions = json["internal_transactions"]
+        .as_array()
+        .map(|txns| {
+            txns.iter()
+                .map(|txn| {
+                    let mut pb = InternalTransaction::new();
+                    pb.set_hash(bytes_field(&txn["hash"])?);
+                    pb.set_caller_address(bytes_field(&txn["caller_address"])?);
+                    pb.set_transferTo_address(bytes_field(&txn["transferTo_address"])?);
+                    pb.set_note(bytes_field(&txn["note"])?);
+                    pb.set_rejected(txn["rejected"].as_bool().unwrap_or_default());
+                    let call_values = txn["callValueInfo"]
+                        .as_array()
+                        .map(|vals| {
+                            vals.iter()
+                                .map(|val| {
+                                    let mut info = CallValueInfo::new();
+                                    info.set_callValue(i64_field(&val["callValue"]));
+                                    info.set_tokenId(val["tokenId"].as_str().unwrap_or_default().to_owned());
+                                    info
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default();
+                    pb.set_callValueInfo(call_values.into());
+                    Ok(pb)
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    info.set_internal_transactions(internal_transactions.into());
+
+    Ok(info)
+}
+
+pub fn get_transaction_by_id(endpoint: &str, txid: &[u8]) -> Result<Transaction, Error> {
+    transaction_from_json(&post(
+        endpoint,
+        "gettransactionbyid",
+        json!({ "value": hex::encode(txid) }),
+    )?)
+}
+
+pub fn get_transaction_info_by_id(endpoint: &str, txid: &[u8]) -> Result<TransactionInfo, Error> {
+    transaction_info_from_json(&post(
+        endpoint,
+        "gettransactioninfobyid",
+        json!({ "value": hex::encode(txid) }),
+    )?)
+}
+
+/// Infos of all transactions of a block, an empty object if there is none.
+pub fn get_transaction_info_by_block_num(endpoint: &str, num: i64) -> Result<Vec<TransactionInfo>, Error> {
+    match post(endpoint, "gettransactioninfobyblocknum", json!({ "num": num }))? {
+        serde_json::Value::Array(infos) => infos.iter().map(transaction_info_from_json).collect(),
+        _ => Ok(vec![]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::core::{
+        TransactionInfo_code as TransactionInfoCode, Transaction_Contract_ContractType as ContractType,
+        Transaction_Result_contractResult as ContractResult,
+    };
+
+    // hand-written in the shape of a `POST /wallet/gettransactionbyid` response, not a real transaction
+    const TRANSACTION_RESPONSE: &str = r#"{
+        "ret": [{"contractRet": "SUCCESS"}],
+        "signature": ["8e0f6d8e3a3ba4c2f8b8c1d2a3e4f5061728394a5b6c7d8e9f0a1b2c3d4e5f6071829304a5b6c7d8e9f0a1b2c3d4e5f60718293a4b5c6d7e8f9a0b1c2d3e4f5a6b"],
+        "txID": "335bf9cafacf0513c4f68f615f60095db6f64a7623e9e002828894c0d0a421dc",
+        "raw_data": {
+            "contract": [{
+                "parameter": {
+                    "value": {
+                        "amount": 1000000,
+                        "owner_address": "41a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                        "to_address": "416a6a5e9a2b4d7a8e2c2c5e4e2e4e6d4b6a4e3c2a"
+                    },
+                    "type_url": "type.googleapis.com/protocol.TransferContract"
+                },
+                "type": "TransferContract"
+            }],
+            "ref_block_bytes": "3a1c",
+            "ref_block_hash": "8f6b2c1d0e4a5b6c",
+            "expiration": 1602748860000,
+            "timestamp": 1602748800000
+        },
+        "raw_data_hex": "0a023a1c22088f6b2c1d0e4a5b6c40e08c9bdad22e5a67080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a1541a614f803b6fd780986a42c78ec9c7f77e6ded13c1215416a6a5e9a2b4d7a8e2c2c5e4e2e4e6d4b6a4e3c2a18c0843d7080b897dad22e"
+    }"#;
+
+    // hand-written in the shape of a `POST /wallet/gettransactioninfobyid` response of a TRC20 transfer
+    const TRANSACTION_INFO_RESPONSE: &str = r#"{
+        "id": "26ff621bcb2c74da8a14c77c20d55cbad8cb0ea022e1174d9522c283c133355c",
+        "fee": 2786160,
+        "blockNumber": 24390151,
+        "blockTimeStamp": 1602748803000,
+        "contractResult": ["0000000000000000000000000000000000000000000000000000000000000001"],
+        "contract_address": "41a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+        "receipt": {
+            "energy_fee": 1850160,
+            "energy_usage_total": 13215,
+            "net_fee": 936000,
+            "result": "SUCCESS"
+        },
+        "log": [{
+            "address": "a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+            "topics": [
+                "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                "000000000000000000000000a614f803b6fd780986a42c78ec9c7f77e6ded13c",
+                "0000000000000000000000006a6a5e9a2b4d7a8e2c2c5e4e2e4e6d4b6a4e3c2a"
+            ],
+            "data": "00000000000000000000000000000000000000000000000000000000077b0ca0"
+        }]
+    }"#;
+
+    #[test]
+    fn test_transaction_from_json() {
+        let txn = transaction_from_json(&serde_json::from_str(TRANSACTION_RESPONSE).unwrap()).unwrap();
+        let raw = txn.get_raw_data();
+        assert_eq!(raw.get_contract()[0].get_field_type(), ContractType::TransferContract);
+        assert_eq!(raw.get_ref_block_bytes(), &[0x3a, 0x1c]);
+        assert_eq!(raw.timestamp, 1602748800000);
+        assert_eq!(txn.get_signature()[0].len(), 65);
+        assert_eq!(txn.get_ret()[0].get_contractRet(), ContractResult::SUCCESS);
+        assert_eq!(
+            hex::encode(crate::utils::trx::transaction_hash(raw).unwrap()),
+            "335bf9cafacf0513c4f68f615f60095db6f64a7623e9e002828894c0d0a421dc"
+        );
+
+        let empty = transaction_from_json(&json!({})).unwrap();
+        assert!(!empty.has_raw_data());
+    }
+
+    #[test]
+    fn test_transaction_info_from_json() {
+        let info = transaction_info_from_json(&serde_json::from_str(TRANSACTION_INFO_RESPONSE).unwrap()).unwrap();
+        assert_eq!(info.get_id().len(), 32);
+        assert_eq!(info.get_fee(), 2786160);
+        assert_eq!(info.get_blockNumber(), 24390151);
+        assert_eq!(info.get_result(), TransactionInfoCode::SUCESS);
+        assert_eq!(info.get_receipt().get_energy_usage_total(), 13215);
+        assert_eq!(info.get_receipt().get_result(), ContractResult::SUCCESS);
+        assert_eq!(info.get_log()[0].get_topics().len(), 3);
+        assert_eq!(info.get_log()[0].get_address().len(), 20);
+        assert_eq!(info.get_contract_address()[0], 0x41);
+
+        let failed = transaction_info_from_json(&json!({
+            "id": "26ff621bcb2c74da8a14c77c20d55cbad8cb0ea022e1174d9522c283c133355c",
+            "result": "FAILED",
+            "resMessage": "52455645525420",
+            "receipt": {"result": "REVERT"}
+        }))
+        .unwrap();
+        assert_eq!(failed.get_result(), TransactionInfoCode::FAILED);
+        assert_eq!(failed.get_receipt().get_result(), ContractResult::REVERT);
+        assert_eq!(failed.get_resMessage(), b"REVERT ");
+
+        assert!(transaction_info_from_json(&json!({})).unwrap().get_id().is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+//! Append-only journal of broadcast transactions, one JSON record per line.
+//!
+//! The journal is `$XDG_DATA_HOME/rust-tron/journal.jsonl`, `~/.local/share/rust-tron/journal.jsonl` by default.
+//! Writing is best effort, errors are logged as warnings and never fail a broadcast. Disabled by `--no-journal` or
+//! `WALLET_CLI_JOURNAL=off`.
+//!
+//! Energy used by confirmed contract calls is kept next to it in `energy.jsonl`, for fee limit suggestions.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use keys::Address;
+use log::{debug, warn};
+use proto::api::Return;
+use proto::core::Transaction;
+use serde_json::json;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::utils::jsont;
+use crate::{JOURNAL_DISABLED, NETWORK};
+
+pub const JOURNAL_ENV: &str = "WALLET_CLI_JOURNAL";
+
+/// Result of a broadcast included in a block after the broadcast itself timed out.
+pub const RESULT_INCLUDED: &str = "INCLUDED";
+/// Result of a broadcast that timed out, not known to be included.
+pub const RESULT_TIMEOUT: &str = "TIMEOUT";
+
+/// Is journaling disabled by the environment variable.
+pub fn disabled_by_env() -> bool {
+    env::var(JOURNAL_ENV)
+        .map(|val| matches!(val.trim(), "off" | "0" | "false"))
+        .unwrap_or(false)
+}
+
+/// Path of the journal file, `None` if disabled or no data directory can be found.
+pub fn journal_path() -> Option<PathBuf> {
+    if unsafe { JOURNAL_DISABLED } {
+        return None;
+    }
+    let data_dir = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("share")))?;
+    Some(data_dir.join("rust-tron").join("journal.jsonl"))
+}
+
+/// Path of the energy history file, next to the journal.
+pub fn energy_path() -> Option<PathBuf> {
+    journal_path().map(|path| path.with_file_name("energy.jsonl"))
+}
+
+/// Broadcast result code, like `SUCCESS` or `SIGERROR`.
+pub fn result_of(ret: &Return) -> String {
+    format!("{:?}", ret.get_code())
+}
+
+/// Journal record of a broadcast transaction. Summary fields are the parameter of the first contract.
+pub fn record_of(
+    txn: &Transaction,
+    txid: &[u8],
+    signer: Option<&Address>,
+    result: &str,
+) -> Result<serde_json::Value, Error> {
+    let mut json = serde_json::to_value(txn)?;
+    jsont::fix_transaction(&mut json)?;
+    let raw = txn.get_raw_data();
+    let contract_type = raw
+        .get_contract()
+        .first()
+        .map(|contract| format!("{:?}", contract.get_field_type()))
+        .unwrap_or_default();
+
+    Ok(json!({
+        "timestamp": Utc::now().timestamp_millis(),
+        "network": unsafe { NETWORK },
+        "txid": hex::encode(txid),
+        "contract_type": contract_type,
+        "summary": json["raw_data"]["contract"][0]["parameter"]["value"],
+        "fee_limit": raw.get_fee_limit(),
+        "signer": signer.map(|addr| addr.to_string()),
+        "result": result,
+    }))
+}
+
+fn append_to(path: &Path, record: &serde_json::Value) -> Result<(), Error> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+    Ok(())
+}
+
+/// Append a broadcast to the journal. Never fails, errors are warnings.
+pub fn append(txn: &Transaction, txid: &[u8], signer: Option<&Address>, result: &str) {
+    let path = match journal_path() {
+        Some(path) => path,
+        None => return,
+    };
+    match record_of(txn, txid, signer, result).and_then(|record| append_to(&path, &record)) {
+        Ok(()) => debug!("Journal written to {}", path.display()),
+        Err(e) => warn!("Can not write journal {}: {}", path.display(), e),
+    }
+}
+
+/// Record energy used by a confirmed call of `selector` on `contract`. Never fails, errors are warnings.
+pub fn append_energy(contract: &Address, selector: &[u8], energy_usage_total: i64) {
+    let path = match energy_path() {
+        Some(path) => path,
+        None => return,
+    };
+    let record = json!({
+        "timestamp": Utc::now().timestamp_millis(),
+        "network": unsafe { NETWORK },
+        "contract": contract.to_string(),
+        "selector": hex::encode(selector),
+        "energy_usage_total": energy_usage_total,
+    });
+    if let Err(e) = append_to(&path, &record) {
+        warn!("Can not write energy history {}: {}", path.display(), e);
+    }
+}
+
+/// Energy used by previous calls of `selector` on `contract`, in the current network.
+pub fn energy_history(records: &[serde_json::Value], contract: &Address, selector: &[u8]) -> Vec<i64> {
+    let contract = contract.to_string();
+    let selector = hex::encode(selector);
+    records
+        .iter()
+        .filter(|record| {
+            record["network"] == unsafe { NETWORK } &&
+                record["contract"] == contract.as_str() &&
+                record["selector"] == selector.as_str()
+        })
+        .filter_map(|record| record["energy_usage_total"].as_i64())
+        .collect()
+}
+
+/// Load all records, malformed lines are skipped with a warning.
+pub fn load(path: &Path) -> Result<Vec<serde_json::Value>, Error> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(i, line)| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(_) => {
+                warn!("Journal line {} is malformed, skipped", i + 1);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Parse `--since`, a date like `2020-10-01` (UTC), an RFC 3339 time, or a duration ago like `7d`, `12h`, `30m`.
+/// Returns a timestamp in millis.
+pub fn parse_since(since: &str, now: DateTime<Utc>) -> Result<i64, Error> {
+    const INVALID: Error = Error::InvalidInput("invalid --since, use a date, an RFC 3339 time or a duration like 7d");
+    let since = since.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Ok(date.and_hms(0, 0, 0).timestamp_millis());
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(since) {
+        return Ok(time.timestamp_millis());
+    }
+    if since.len() < 2 {
+        return Err(INVALID);
+    }
+    let (num, unit) = since.split_at(since.len() - 1);
+    let num: i64 = num.parse().map_err(|_| INVALID)?;
+    let secs = match unit {
+        "d" => num * 86_400,
+        "h" => num * 3_600,
+        "m" => num * 60,
+        _ => return Err(INVALID),
+    };
+    Ok(now.timestamp_millis() - secs * 1_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    use crate::utils::trx;
+
+    #[test]
+    fn test_record_and_load() {
+        let mut txn = trx::test_transfer_transaction();
+        txn.mut_raw_data().set_fee_limit(10_000_000);
+
+        let signer: Address = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap();
+        let record = record_of(&txn, &[0xab; 32], Some(&signer), "SUCCESS").unwrap();
+        assert_eq!(record["contract_type"], "TransferContract");
+        assert_eq!(record["summary"]["amount"], 1_000_000);
+        assert_eq!(record["fee_limit"], 10_000_000);
+        assert_eq!(record["signer"], "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+
+        let path = env::temp_dir()
+            .join(format!("wallet-cli-journal-{}", std::process::id()))
+            .join("journal.jsonl");
+        append_to(&path, &record).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{not json\n")
+            .unwrap();
+        append_to(&path, &record).unwrap();
+        let records = load(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(records, vec![record.clone(), record]);
+    }
+
+    #[test]
+    fn test_energy_history() {
+        let contract: Address = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap();
+        let other: Address = "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap();
+        let record = |network: &str, contract: &Address, selector: &str, energy: i64| {
+            json!({
+                "network": network,
+                "contract": contract.to_string(),
+                "selector": selector,
+                "energy_usage_total": energy,
+            })
+        };
+        let network = unsafe { NETWORK };
+        let records = vec![
+            record(network, &contract, "a9059cbb", 14_000),
+            record(network, &other, "a9059cbb", 29_000),
+            record("nile", &contract, "a9059cbb", 1),
+            record(network, &contract, "095ea7b3", 2),
+            record(network, &contract, "a9059cbb", 29_000),
+        ];
+        assert_eq!(
+            energy_history(&records, &contract, &[0xa9, 0x05, 0x9c, 0xbb]),
+            vec![14_000, 29_000]
+        );
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = Utc.ymd(2020, 10, 15).and_hms(12, 0, 0);
+        assert_eq!(
+            parse_since("2020-10-01", now).unwrap(),
+            Utc.ymd(2020, 10, 1).and_hms(0, 0, 0).timestamp_millis()
+        );
+        assert_eq!(
+            parse_since("2020-10-01T08:00:00+08:00", now).unwrap(),
+            Utc.ymd(2020, 10, 1).and_hms(0, 0, 0).timestamp_millis()
+        );
+        assert_eq!(
+            parse_since("12h", now).unwrap(),
+            Utc.ymd(2020, 10, 15).and_hms(0, 0, 0).timestamp_millis()
+        );
+        assert!(parse_since("yesterday", now).is_err());
+    }
+}
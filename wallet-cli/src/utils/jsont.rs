@@ -1,6 +1,8 @@
 //! JSON transformations
 
 use hex::{FromHex, ToHex};
+use keys::Address;
+use log::warn;
 use proto::core::{
     AccountCreateContract, AccountPermissionUpdateContract, AccountUpdateContract, AssetIssueContract,
     ClearABIContract, CreateSmartContract, ExchangeInjectContract, FreezeBalanceContract,
@@ -10,16 +12,29 @@ use proto::core::{
     VoteWitnessContract, WithdrawBalanceContract, WitnessCreateContract, WitnessUpdateContract,
 };
 use serde_json::json;
+use std::convert::TryFrom;
 
 use crate::error::Error;
+use crate::utils::trx;
 
-pub fn bytes_to_hex_string(val: &serde_json::Value) -> String {
-    val.as_array()
-        .unwrap()
-        .iter()
-        .map(|v| v.as_i64().unwrap() as u8)
-        .collect::<Vec<_>>()
-        .encode_hex()
+/// Replace the `bytes` field at `path`, serialized as an array of numbers, with a `0x`-prefixed lowercase hex
+/// string. An empty path converts `v` itself. Missing fields and fields already converted are left as is.
+pub fn fix_hex_field(v: &mut serde_json::Value, path: &[&str]) {
+    if let Some(field) = path.iter().try_fold(v, |v, key| v.get_mut(*key)) {
+        let bytes = field.as_array().and_then(|arr| {
+            arr.iter()
+                .map(|b| b.as_u64().filter(|&b| b <= 0xff).map(|b| b as u8))
+                .collect::<Option<Vec<u8>>>()
+        });
+        if let Some(bytes) = bytes {
+            *field = json!(format!("0x{}", bytes.encode_hex::<String>()));
+        }
+    }
+}
+
+/// Decode a hex string converted by `fix_hex_field`, the `0x` prefix is optional.
+pub fn hex_string_to_bytes(s: &str) -> Result<Vec<u8>, hex::FromHexError> {
+    Vec::from_hex(s.trim_start_matches("0x"))
 }
 
 pub fn bytes_to_string(val: &serde_json::Value) -> String {
@@ -40,29 +55,37 @@ pub fn bytes_to_bytes(val: &serde_json::Value) -> Vec<u8> {
         .collect::<Vec<_>>()
 }
 
+/// Base58check of an address serialized as an array of numbers, `null` if invalid.
+fn address_to_base58check(val: &serde_json::Value) -> serde_json::Value {
+    val.as_array()
+        .and_then(|_| Address::try_from(&bytes_to_bytes(val)[..]).ok())
+        .map(|addr| json!(addr.to_string()))
+        .unwrap_or(serde_json::Value::Null)
+}
+
 // pb: TransferContract
 pub fn fix_transfer_contract(val: &mut serde_json::Value) {
-    val["owner_address"] = json!(bytes_to_hex_string(&val["owner_address"]));
-    val["to_address"] = json!(bytes_to_hex_string(&val["to_address"]));
+    fix_hex_field(val, &["owner_address"]);
+    fix_hex_field(val, &["to_address"]);
 }
 
 // pb: TransferAssetContract
 pub fn fix_transfer_asset_contract(val: &mut serde_json::Value) {
-    val["owner_address"] = json!(bytes_to_hex_string(&val["owner_address"]));
-    val["to_address"] = json!(bytes_to_hex_string(&val["to_address"]));
+    fix_hex_field(val, &["owner_address"]);
+    fix_hex_field(val, &["to_address"]);
     val["asset_name"] = json!(bytes_to_string(&val["asset_name"]));
 }
 
 // pb: TriggerSmartContract
 pub fn fix_trigger_smart_contract(val: &mut serde_json::Value) {
-    val["owner_address"] = json!(bytes_to_hex_string(&val["owner_address"]));
-    val["contract_address"] = json!(bytes_to_hex_string(&val["contract_address"]));
-    val["data"] = json!(bytes_to_hex_string(&val["data"]));
+    fix_hex_field(val, &["owner_address"]);
+    fix_hex_field(val, &["contract_address"]);
+    fix_hex_field(val, &["data"]);
 }
 
 // pb: AccountPermissionUpdateContract
 pub fn fix_account_permission_update_contract(val: &mut serde_json::Value) {
-    val["owner_address"] = json!(bytes_to_hex_string(&val["owner_address"]));
+    fix_hex_field(val, &["owner_address"]);
     val["actives"]
         .as_array_mut()
         .unwrap()
@@ -73,10 +96,10 @@ pub fn fix_account_permission_update_contract(val: &mut serde_json::Value) {
                 .unwrap()
                 .iter_mut()
                 .map(|key| {
-                    key["address"] = json!(bytes_to_hex_string(&key["address"]));
+                    fix_hex_field(key, &["address"]);
                 })
                 .last();
-            perm["operations"] = json!(bytes_to_hex_string(&perm["operations"]));
+            fix_hex_field(perm, &["operations"]);
         })
         .last();
     if !val["owner"].is_null() {
@@ -85,7 +108,7 @@ pub fn fix_account_permission_update_contract(val: &mut serde_json::Value) {
             .unwrap()
             .iter_mut()
             .map(|key| {
-                key["address"] = json!(bytes_to_hex_string(&key["address"]));
+                fix_hex_field(key, &["address"]);
             })
             .last();
     }
@@ -95,25 +118,67 @@ pub fn fix_account_permission_update_contract(val: &mut serde_json::Value) {
             .unwrap()
             .iter_mut()
             .map(|key| {
-                key["address"] = json!(bytes_to_hex_string(&key["address"]));
+                fix_hex_field(key, &["address"]);
             })
             .last();
     }
 }
 
+// pb: AccountCreateContract
+pub fn fix_account_create_contract(val: &mut serde_json::Value) {
+    val["creator"] = address_to_base58check(&val["owner_address"]);
+    val["new_account"] = address_to_base58check(&val["account_address"]);
+    fix_hex_field(val, &["owner_address"]);
+    fix_hex_field(val, &["account_address"]);
+}
+
+// pb: ProposalCreateContract
+pub fn fix_proposal_create_contract(val: &mut serde_json::Value) {
+    val["proposer"] = address_to_base58check(&val["owner_address"]);
+    fix_hex_field(val, &["owner_address"]);
+
+    // keys of a JSON object are strings, listed by parameter id
+    let mut params = val["parameters"]
+        .as_object()
+        .map(|params| {
+            params
+                .iter()
+                .filter_map(|(id, value)| Some((id.parse::<i64>().ok()?, value.as_i64()?)))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    params.sort();
+    val["decoded_parameters"] = params
+        .into_iter()
+        .map(|(id, value)| {
+            json!({
+                "id": id,
+                "key": trx::chain_parameter_key(id),
+                "value": value,
+            })
+        })
+        .collect();
+}
+
+// pb: ProposalApproveContract
+pub fn fix_proposal_approve_contract(val: &mut serde_json::Value) {
+    val["approver"] = address_to_base58check(&val["owner_address"]);
+    fix_hex_field(val, &["owner_address"]);
+}
+
 // pb: CreateSmartContract
 pub fn fix_create_smart_contract(val: &mut serde_json::Value) {
-    val["owner_address"] = json!(bytes_to_hex_string(&val["owner_address"]));
+    fix_hex_field(val, &["owner_address"]);
     let contract = &mut val["new_contract"];
-    contract["bytecode"] = json!(bytes_to_hex_string(&contract["bytecode"]));
-    contract["origin_address"] = json!(bytes_to_hex_string(&contract["origin_address"]));
+    fix_hex_field(contract, &["bytecode"]);
+    fix_hex_field(contract, &["origin_address"]);
 }
 
 // pb: ShieldedTransferContract
 pub fn fix_shielded_transfer_contract(val: &mut serde_json::Value) {
-    val["transparent_from_address"] = json!(bytes_to_hex_string(&val["transparent_from_address"]));
-    val["transparent_to_address"] = json!(bytes_to_hex_string(&val["transparent_to_address"]));
-    val["binding_signature"] = json!(bytes_to_hex_string(&val["binding_signature"]));
+    fix_hex_field(val, &["transparent_from_address"]);
+    fix_hex_field(val, &["transparent_to_address"]);
+    fix_hex_field(val, &["binding_signature"]);
 
     val["receive_description"]
         .as_array_mut()
@@ -128,7 +193,7 @@ pub fn fix_shielded_transfer_contract(val: &mut serde_json::Value) {
                 "c_out",
                 "zkproof",
             ] {
-                v[k] = json!(bytes_to_hex_string(&v[k]));
+                fix_hex_field(v, &[*k]);
             }
         })
         .last();
@@ -146,7 +211,7 @@ pub fn fix_shielded_transfer_contract(val: &mut serde_json::Value) {
                 "zkproof",
                 "spend_authority_signature",
             ] {
-                v[k] = json!(bytes_to_hex_string(&v[k]));
+                fix_hex_field(v, &[*k]);
             }
         })
         .last();
@@ -154,21 +219,21 @@ pub fn fix_shielded_transfer_contract(val: &mut serde_json::Value) {
 
 // pb: VoteWitnessContract
 pub fn fix_vote_witness_contract(val: &mut serde_json::Value) {
-    val["owner_address"] = json!(bytes_to_hex_string(&val["owner_address"]));
+    fix_hex_field(val, &["owner_address"]);
     val["votes"]
         .as_array_mut()
         .unwrap()
         .iter_mut()
         .map(|vote| {
-            vote["vote_address"] = json!(bytes_to_hex_string(&vote["vote_address"]));
+            fix_hex_field(vote, &["vote_address"]);
         })
         .last();
 }
 
 // pb: FreezeBalanceContract
 pub fn fix_freeze_balance_contract(val: &mut serde_json::Value) {
-    val["owner_address"] = json!(bytes_to_hex_string(&val["owner_address"]));
-    val["receiver_address"] = json!(bytes_to_hex_string(&val["receiver_address"]));
+    fix_hex_field(val, &["owner_address"]);
+    fix_hex_field(val, &["receiver_address"]);
 }
 
 // pb: Transaction.raw
@@ -235,46 +300,48 @@ pub fn fix_transaction_raw(transaction: &mut serde_json::Value) -> Result<(), Er
         Some("AccountUpdateContract") => {
             let pb: AccountUpdateContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
             contract["account_name"] = json!(bytes_to_string(&contract["account_name"]));
             contract
         }
         Some("WitnessCreateContract") => {
             let pb: WitnessCreateContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
             contract["url"] = json!(bytes_to_string(&contract["url"]));
             contract
         }
         Some("WitnessUpdateContract") => {
             let pb: WitnessUpdateContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
             contract["update_url"] = json!(bytes_to_string(&contract["update_url"]));
             contract
         }
         Some("WithdrawBalanceContract") => {
             let pb: WithdrawBalanceContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
             contract
         }
         Some("ProposalCreateContract") => {
             let pb: ProposalCreateContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
+            fix_proposal_create_contract(&mut contract);
             contract
         }
         Some("ProposalApproveContract") => {
             let pb: ProposalApproveContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
+            fix_proposal_approve_contract(&mut contract);
             contract
         }
         Some("ProposalDeleteContract") => {
             let pb: ProposalDeleteContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
+            // only the proposer can delete
+            contract["proposer"] = address_to_base58check(&contract["owner_address"]);
+            fix_hex_field(&mut contract, &["owner_address"]);
             contract
         }
         Some("AssetIssueContract") => {
@@ -286,28 +353,28 @@ pub fn fix_transaction_raw(transaction: &mut serde_json::Value) -> Result<(), Er
         Some("UpdateSettingContract") => {
             let pb: UpdateSettingContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
-            contract["contract_address"] = json!(bytes_to_hex_string(&contract["contract_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
+            fix_hex_field(&mut contract, &["contract_address"]);
             contract
         }
         Some("UpdateEnergyLimitContract") => {
             let pb: UpdateEnergyLimitContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
-            contract["contract_address"] = json!(bytes_to_hex_string(&contract["contract_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
+            fix_hex_field(&mut contract, &["contract_address"]);
             contract
         }
         Some("ClearABIContract") => {
             let pb: ClearABIContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
-            contract["contract_address"] = json!(bytes_to_hex_string(&contract["contract_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
+            fix_hex_field(&mut contract, &["contract_address"]);
             contract
         }
         Some("UpdateAssetContract") => {
             let pb: UpdateAssetContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
             contract["description"] = json!(bytes_to_string(&contract["description"]));
             contract["url"] = json!(bytes_to_string(&contract["url"]));
             contract
@@ -315,60 +382,59 @@ pub fn fix_transaction_raw(transaction: &mut serde_json::Value) -> Result<(), Er
         Some("ParticipateAssetIssueContract") => {
             let pb: ParticipateAssetIssueContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
-            contract["to_address"] = json!(bytes_to_hex_string(&contract["to_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
+            fix_hex_field(&mut contract, &["to_address"]);
             contract["asset_name"] = json!(bytes_to_string(&contract["asset_name"]));
             contract
         }
         Some("UnfreezeAssetContract") => {
             let pb: UnfreezeAssetContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
             contract
         }
         Some("UnfreezeBalanceContract") => {
             let pb: UnfreezeBalanceContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
-            contract["receiver_address"] = json!(bytes_to_hex_string(&contract["receiver_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
+            fix_hex_field(&mut contract, &["receiver_address"]);
             contract
         }
         Some("AccountCreateContract") => {
             let pb: AccountCreateContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
-            contract["account_address"] = json!(bytes_to_hex_string(&contract["account_address"]));
+            fix_account_create_contract(&mut contract);
             contract
         }
         Some("ExchangeInjectContract") => {
             let pb: ExchangeInjectContract = protobuf::parse_from_bytes(&raw_pb)?;
             let mut contract = serde_json::to_value(&pb)?;
-            contract["owner_address"] = json!(bytes_to_hex_string(&contract["owner_address"]));
+            fix_hex_field(&mut contract, &["owner_address"]);
             contract["token_id"] = json!(bytes_to_string(&contract["token_id"]));
             contract
         }
         x => {
-            eprintln!("unhandled contract type => {:?}", x);
-            json!(raw_pb.encode_hex::<String>())
+            warn!("unhandled contract type => {:?}", x);
+            json!(format!("0x{}", raw_pb.encode_hex::<String>()))
         }
     };
     transaction["contract"][0]["parameter"]["value"] = parsed_value;
 
-    transaction["ref_block_hash"] = json!(bytes_to_hex_string(&transaction["ref_block_hash"]));
-    transaction["ref_block_bytes"] = json!(bytes_to_hex_string(&transaction["ref_block_bytes"]));
-    transaction["data"] = json!(bytes_to_hex_string(&transaction["data"]));
+    fix_hex_field(transaction, &["ref_block_hash"]);
+    fix_hex_field(transaction, &["ref_block_bytes"]);
+    fix_hex_field(transaction, &["data"]);
     Ok(())
 }
 
 // pb: Transaction
 pub fn fix_transaction(transaction: &mut serde_json::Value) -> Result<(), Error> {
     fix_transaction_raw(&mut transaction["raw_data"])?;
-    transaction["signature"] = json!(transaction["signature"]
-        .as_array()
+    transaction["signature"]
+        .as_array_mut()
         .unwrap()
-        .iter()
-        .map(|sig| json!(bytes_to_hex_string(sig)))
-        .collect::<Vec<_>>());
+        .iter_mut()
+        .map(|sig| fix_hex_field(sig, &[]))
+        .last();
     Ok(())
 }
 
@@ -382,19 +448,19 @@ pub fn fix_transaction_ext(transaction_ext: &mut serde_json::Value) -> Result<()
             .as_array_mut()
             .unwrap()
             .iter_mut()
-            .map(|res| *res = json!(bytes_to_hex_string(res)))
+            .map(|res| fix_hex_field(res, &[]))
             .last();
     }
     if !transaction_ext["transaction"].is_null() {
         fix_transaction(&mut transaction_ext["transaction"])?;
     }
-    transaction_ext["txid"] = json!(bytes_to_hex_string(&mut transaction_ext["txid"]));
+    fix_hex_field(transaction_ext, &["txid"]);
     Ok(())
 }
 
 // pb: Account
 pub fn fix_account(account: &mut serde_json::Value) {
-    account["address"] = json!(bytes_to_hex_string(&account["address"]));
+    fix_hex_field(account, &["address"]);
     account["account_name"] = json!(bytes_to_string(&account["account_name"]));
     account["account_id"] = json!(bytes_to_string(&account["account_id"]));
     account["asset_issued_ID"] = json!(bytes_to_string(&account["asset_issued_ID"]));
@@ -403,7 +469,7 @@ pub fn fix_account(account: &mut serde_json::Value) {
         .as_array_mut()
         .unwrap()
         .iter_mut()
-        .map(|vote| vote["vote_address"] = json!(bytes_to_hex_string(&vote["vote_address"])))
+        .map(|vote| fix_hex_field(vote, &["vote_address"]))
         .last();
     // NOTE: one can remove owner_permission by setting null
     if !account["owner_permission"].is_null() {
@@ -412,7 +478,7 @@ pub fn fix_account(account: &mut serde_json::Value) {
             .unwrap()
             .iter_mut()
             .map(|key| {
-                key["address"] = json!(bytes_to_hex_string(&key["address"]));
+                fix_hex_field(key, &["address"]);
             })
             .last();
     }
@@ -426,10 +492,10 @@ pub fn fix_account(account: &mut serde_json::Value) {
                 .unwrap()
                 .iter_mut()
                 .map(|key| {
-                    key["address"] = json!(bytes_to_hex_string(&key["address"]));
+                    fix_hex_field(key, &["address"]);
                 })
                 .last();
-            perm["operations"] = json!(bytes_to_hex_string(&perm["operations"]));
+            fix_hex_field(perm, &["operations"]);
         })
         .last();
     if !account["witness_permission"].is_null() {
@@ -438,7 +504,7 @@ pub fn fix_account(account: &mut serde_json::Value) {
             .unwrap()
             .iter_mut()
             .map(|key| {
-                key["address"] = json!(bytes_to_hex_string(&key["address"]));
+                fix_hex_field(key, &["address"]);
             })
             .last();
     }
@@ -457,7 +523,7 @@ pub fn fix_asset_issue_contract(asset: &mut serde_json::Value) {
     asset["description"] = json!(bytes_to_string(&asset["description"]));
     asset["name"] = json!(bytes_to_string(&asset["name"]));
     asset["url"] = json!(bytes_to_string(&asset["url"]));
-    asset["owner_address"] = json!(bytes_to_hex_string(&asset["owner_address"]));
+    fix_hex_field(asset, &["owner_address"]);
 }
 
 // pb: IncrementalMerkleVoucherInfo
@@ -467,27 +533,27 @@ pub fn fix_voucher_info(voucher_info: &mut serde_json::Value) {
         .as_array_mut()
         .unwrap()
         .iter_mut()
-        .map(|path| *path = json!(bytes_to_hex_string(path)))
+        .map(|path| fix_hex_field(path, &[]))
         .last();
     voucher_info["vouchers"]
         .as_array_mut()
         .unwrap()
         .iter_mut()
         .map(|voucher| {
-            voucher["rt"] = json!(bytes_to_hex_string(&voucher["rt"]));
+            fix_hex_field(voucher, &["rt"]);
             // right or left may be null
             voucher["tree"]["left"]
                 .as_object_mut()
-                .map(|obj| obj["content"] = json!(bytes_to_hex_string(&obj["content"])));
+                .map(|obj| obj.get_mut("content").map(|content| fix_hex_field(content, &[])));
             voucher["tree"]["right"]
                 .as_object_mut()
-                .map(|obj| obj["content"] = json!(bytes_to_hex_string(&obj["content"])));
+                .map(|obj| obj.get_mut("content").map(|content| fix_hex_field(content, &[])));
 
             voucher["tree"]["parents"]
                 .as_array_mut()
                 .unwrap()
                 .iter_mut()
-                .map(|p| p["content"] = json!(bytes_to_hex_string(&p["content"])))
+                .map(|p| fix_hex_field(p, &["content"]))
                 .last();
         })
         .last();
@@ -495,25 +561,25 @@ pub fn fix_voucher_info(voucher_info: &mut serde_json::Value) {
 
 // pb: TransactionInfo
 pub fn fix_transaction_info(info: &mut serde_json::Value) {
-    info["id"] = json!(bytes_to_hex_string(&info["id"]));
-    info["contract_address"] = json!(bytes_to_hex_string(&info["contract_address"]));
+    fix_hex_field(info, &["id"]);
+    fix_hex_field(info, &["contract_address"]);
     info["resMessage"] = json!(bytes_to_string(&info["resMessage"]));
-    info["contractResult"] = json!(info["contractResult"]
-        .as_array()
+    info["contractResult"]
+        .as_array_mut()
         .unwrap()
-        .iter()
-        .map(bytes_to_hex_string)
-        .collect::<Vec<_>>());
+        .iter_mut()
+        .map(|res| fix_hex_field(res, &[]))
+        .last();
     info["internal_transactions"]
         .as_array_mut()
         .unwrap()
         .iter_mut()
         .map(|trx| {
-            trx["caller_address"] = json!(bytes_to_hex_string(&trx["caller_address"]));
-            trx["hash"] = json!(bytes_to_hex_string(&trx["hash"]));
+            fix_hex_field(trx, &["caller_address"]);
+            fix_hex_field(trx, &["hash"]);
             trx["note"] = json!(bytes_to_string(&trx["note"]));
             // NOTE: the ugly camEl_case naming
-            trx["transferTo_address"] = json!(bytes_to_hex_string(&trx["transferTo_address"]));
+            fix_hex_field(trx, &["transferTo_address"]);
         })
         .last();
     info["log"]
@@ -521,15 +587,13 @@ pub fn fix_transaction_info(info: &mut serde_json::Value) {
         .unwrap()
         .iter_mut()
         .map(|log| {
-            log["address"] = json!(bytes_to_hex_string(&log["address"]));
-            log["data"] = json!(bytes_to_hex_string(&log["data"]));
+            fix_hex_field(log, &["address"]);
+            fix_hex_field(log, &["data"]);
             log["topics"]
                 .as_array_mut()
                 .unwrap()
                 .iter_mut()
-                .map(|t| {
-                    *t = json!(bytes_to_hex_string(t));
-                })
+                .map(|t| fix_hex_field(t, &[]))
                 .last();
         })
         .last();
@@ -537,15 +601,12 @@ pub fn fix_transaction_info(info: &mut serde_json::Value) {
 
 // pb: Block / BlockExtention
 pub fn fix_block(block: &mut serde_json::Value) -> Result<(), Error> {
-    if block["blockid"].is_array() {
-        block["blockid"] = json!(bytes_to_hex_string(&block["blockid"]));
-    }
+    fix_hex_field(block, &["blockid"]);
 
     for key in &["parentHash", "txTrieRoot", "witness_address", "accountStateRoot"] {
-        block["block_header"]["raw_data"][key] = json!(bytes_to_hex_string(&block["block_header"]["raw_data"][key]));
+        fix_hex_field(block, &["block_header", "raw_data", *key]);
     }
-    block["block_header"]["witness_signature"] =
-        json!(bytes_to_hex_string(&block["block_header"]["witness_signature"]));
+    fix_hex_field(block, &["block_header", "witness_signature"]);
 
     block["transactions"]
         .as_array_mut()
@@ -554,7 +615,7 @@ pub fn fix_block(block: &mut serde_json::Value) -> Result<(), Error> {
         .map(|mut transaction| {
             // NOTE: structual difference of get_block requests
             if transaction["txid"].is_array() {
-                transaction["txid"] = json!(bytes_to_hex_string(&transaction["txid"]));
+                fix_hex_field(transaction, &["txid"]);
                 transaction = &mut transaction["transaction"];
             }
             fix_transaction(transaction)?;
@@ -571,7 +632,7 @@ pub fn revert_permission_info(permission: &mut serde_json::Value) {
             .as_array_mut()
             .unwrap()
             .iter_mut()
-            .map(|key| key["address"] = json!(Vec::from_hex(key["address"].as_str().unwrap()).unwrap()))
+            .map(|key| key["address"] = json!(hex_string_to_bytes(key["address"].as_str().unwrap()).unwrap()))
             .last();
     }
     permission["actives"]
@@ -584,10 +645,131 @@ pub fn revert_permission_info(permission: &mut serde_json::Value) {
                 .unwrap()
                 .iter_mut()
                 .map(|key| {
-                    key["address"] = json!(Vec::from_hex(key["address"].as_str().unwrap()).unwrap());
+                    key["address"] = json!(hex_string_to_bytes(key["address"].as_str().unwrap()).unwrap());
                 })
                 .last();
-            perm["operations"] = json!(Vec::from_hex(perm["operations"].as_str().unwrap()).unwrap());
+            perm["operations"] = json!(hex_string_to_bytes(perm["operations"].as_str().unwrap()).unwrap());
         })
         .last();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_fix_hex_field_roundtrip(bytes in proptest::collection::vec(any::<u8>(), 0..64)) {
+            let mut val = json!({ "raw_data": { "data": bytes.clone() } });
+            fix_hex_field(&mut val, &["raw_data", "data"]);
+
+            let hex = val["raw_data"]["data"].as_str().unwrap().to_owned();
+            prop_assert!(hex.starts_with("0x"));
+            prop_assert_eq!(hex.len(), 2 + 2 * bytes.len());
+            prop_assert!(hex[2..].bytes().all(|c| c.is_ascii_digit() || (b'a'..=b'f').contains(&c)));
+            prop_assert_eq!(hex_string_to_bytes(&hex).unwrap(), bytes);
+
+            // already converted, left as is
+            fix_hex_field(&mut val, &["raw_data", "data"]);
+            prop_assert_eq!(val["raw_data"]["data"].as_str(), Some(&hex[..]));
+        }
+
+        #[test]
+        fn test_fix_hex_field_ignores_non_bytes(nums in proptest::collection::vec(256u64..100_000, 1..8), key in "[a-z]{1,8}") {
+            let mut val = json!({ "nums": nums, "text": "not bytes" });
+            let orig = val.clone();
+            fix_hex_field(&mut val, &["nums"]);
+            fix_hex_field(&mut val, &["text"]);
+            fix_hex_field(&mut val, &["missing", key.as_str()]);
+            prop_assert_eq!(val, orig);
+        }
+    }
+
+    #[test]
+    fn test_fix_hex_field_in_place() {
+        let mut val = json!([1, 2, 255]);
+        fix_hex_field(&mut val, &[]);
+        assert_eq!(val, json!("0x0102ff"));
+
+        let mut val = json!({ "data": [] });
+        fix_hex_field(&mut val, &["data"]);
+        assert_eq!(val, json!({ "data": "0x" }));
+    }
+
+    #[test]
+    fn test_fix_account_create_contract() {
+        use proto::core::AccountType;
+
+        let mut create = AccountCreateContract::new();
+        create.set_owner_address(hex::decode("41a614f803b6fd780986a42c78ec9c7f77e6ded13c").unwrap());
+        create.set_account_address(
+            "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt"
+                .parse::<Address>()
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+        );
+        create.set_field_type(AccountType::Contract);
+
+        let mut val = serde_json::to_value(&trx::test_transaction_of(&create)).unwrap();
+        fix_transaction(&mut val).unwrap();
+        let value = &val["raw_data"]["contract"][0]["parameter"]["value"];
+        assert_eq!(value["creator"], "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+        assert_eq!(value["new_account"], "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt");
+        assert_eq!(value["owner_address"], "0x41a614f803b6fd780986a42c78ec9c7f77e6ded13c");
+        assert_eq!(value["field_type"], "Contract");
+
+        let mut val = json!({ "owner_address": [], "account_address": [1, 2] });
+        fix_account_create_contract(&mut val);
+        assert!(val["creator"].is_null());
+        assert!(val["new_account"].is_null());
+    }
+
+    #[test]
+    fn test_fix_proposal_contracts() {
+        use crate::utils::trx::ContractPbExt;
+
+        fn transaction_of(pb: &impl ContractPbExt) -> serde_json::Value {
+            let mut val = serde_json::to_value(&trx::test_transaction_of(pb)).unwrap();
+            fix_transaction(&mut val).unwrap();
+            val["raw_data"]["contract"][0]["parameter"]["value"].take()
+        }
+        let owner = hex::decode("41a614f803b6fd780986a42c78ec9c7f77e6ded13c").unwrap();
+
+        // energy fee to 140 SUN, and an unknown parameter
+        let mut create = ProposalCreateContract::new();
+        create.set_owner_address(owner.clone());
+        create.mut_parameters().insert(11, 140);
+        create.mut_parameters().insert(1000, 1);
+        create.mut_parameters().insert(3, 1000);
+        let value = transaction_of(&create);
+        assert_eq!(value["proposer"], "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+        assert_eq!(value["owner_address"], "0x41a614f803b6fd780986a42c78ec9c7f77e6ded13c");
+        assert_eq!(
+            value["decoded_parameters"],
+            json!([
+                { "id": 3, "key": "getTransactionFee", "value": 1000 },
+                { "id": 11, "key": "getEnergyFee", "value": 140 },
+                { "id": 1000, "key": null, "value": 1 },
+            ])
+        );
+
+        let mut approve = ProposalApproveContract::new();
+        approve.set_owner_address(owner.clone());
+        approve.set_proposal_id(42);
+        approve.set_is_add_approval(true);
+        let value = transaction_of(&approve);
+        assert_eq!(value["approver"], "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+        assert_eq!(value["proposal_id"], 42);
+        assert_eq!(value["is_add_approval"], true);
+
+        let mut delete = ProposalDeleteContract::new();
+        delete.set_owner_address(owner);
+        delete.set_proposal_id(42);
+        assert_eq!(
+            transaction_of(&delete)["proposer"],
+            "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Keystore files of other TRON wallets, for key migration.
+//!
+//! Both TronLink and java-tron's wallet-cli use the Web3 Secret Storage v3 layout: a scrypt or PBKDF2 derived key,
+//! AES-128-CTR encrypted private key and a keccak256 MAC. The address field is checked against the decrypted key.
+//! wallet-cli files are written by web3j, which may capitalize the `Crypto` section.
+
+use aes::Aes128;
+use ctr::cipher::{NewStreamCipher, SyncStreamCipher};
+use hex::{FromHex, ToHex};
+use hmac::Hmac;
+use keys::{Address, MemorySigner, Private, Signer};
+use rand::{thread_rng, Rng};
+use serde_json::json;
+use sha2::Sha256;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+use crate::error::Error;
+use crate::utils::crypto;
+use crate::utils::trx;
+
+type Aes128Ctr = ctr::Ctr128<Aes128>;
+
+const KEYSTORE_VERSION: u64 = 3;
+const DERIVED_KEY_LEN: usize = 32;
+
+// Standard scrypt parameters of Web3 Secret Storage, n = 2^18.
+const SCRYPT_LOG_N: u8 = 18;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+const WRONG_PASSWORD: &str = "wrong password for keystore file";
+const MALFORMED: &str = "corrupted keystore file, malformed crypto section";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeystoreFormat {
+    TronLink,
+    WalletCli,
+}
+
+impl FromStr for KeystoreFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "tronlink" => Ok(KeystoreFormat::TronLink),
+            "wallet-cli" => Ok(KeystoreFormat::WalletCli),
+            _ => Err(Error::Runtime("unknown keystore format")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kdf {
+    Scrypt { log_n: u8, r: u32, p: u32 },
+    Pbkdf2 { c: u32 },
+}
+
+impl Kdf {
+    fn from_json(crypto: &serde_json::Value) -> Result<Self, Error> {
+        let params = &crypto["kdfparams"];
+        if params["dklen"].as_u64() != Some(DERIVED_KEY_LEN as u64) {
+            return Err(Error::Runtime(MALFORMED));
+        }
+        let u32_param = |name: &str| {
+            params[name]
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or(Error::Runtime(MALFORMED))
+        };
+        match crypto["kdf"].as_str() {
+            Some("scrypt") => {
+                let n = u32_param("n")?;
+                if n < 2 || !n.is_power_of_two() {
+                    return Err(Error::Runtime(MALFORMED));
+                }
+                Ok(Kdf::Scrypt {
+                    log_n: n.trailing_zeros() as u8,
+                    r: u32_param("r")?,
+                    p: u32_param("p")?,
+                })
+            }
+            Some("pbkdf2") if params["prf"] == "hmac-sha256" => Ok(Kdf::Pbkdf2 { c: u32_param("c")? }),
+            _ => Err(Error::Runtime("corrupted keystore file, unsupported kdf")),
+        }
+    }
+
+    fn to_json(&self, salt: &[u8]) -> serde_json::Value {
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => json!({
+                "dklen": DERIVED_KEY_LEN,
+                "n": 1u32 << log_n,
+                "r": r,
+                "p": p,
+                "salt": salt.encode_hex::<String>(),
+            }),
+            Kdf::Pbkdf2 { c } => json!({
+                "dklen": DERIVED_KEY_LEN,
+                "c": c,
+                "prf": "hmac-sha256",
+                "salt": salt.encode_hex::<String>(),
+            }),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Kdf::Scrypt { .. } => "scrypt",
+            Kdf::Pbkdf2 { .. } => "pbkdf2",
+        }
+    }
+
+    fn derive_key(&self, password: &str, salt: &[u8]) -> Result<Zeroizing<Vec<u8>>, Error> {
+        let mut key = Zeroizing::new(vec![0u8; DERIVED_KEY_LEN]);
+        match *self {
+            Kdf::Scrypt { log_n, r, p } => {
+                let params =
+                    scrypt::ScryptParams::new(log_n, r, p).map_err(|_| Error::Runtime("invalid scrypt parameters"))?;
+                scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+                    .map_err(|_| Error::Runtime("invalid scrypt output length"))?;
+            }
+            Kdf::Pbkdf2 { c } => pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt, c, &mut key),
+        }
+        Ok(key)
+    }
+}
+
+fn hex_field(val: &serde_json::Value) -> Result<Vec<u8>, Error> {
+    val.as_str()
+        .and_then(|s| Vec::from_hex(s.trim_start_matches("0x")).ok())
+        .ok_or(Error::Runtime(MALFORMED))
+}
+
+fn mac_of(derived_key: &[u8], cipher_text: &[u8]) -> [u8; 32] {
+    let mut raw = derived_key[16..32].to_vec();
+    raw.extend_from_slice(cipher_text);
+    crypto::keccak256(&raw)
+}
+
+fn apply_aes_128_ctr(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), Error> {
+    Aes128Ctr::new_var(key, iv)
+        .map_err(|_| Error::Runtime(MALFORMED))?
+        .apply_keystream(data);
+    Ok(())
+}
+
+/// Decrypt a keystore file, returns the declared address and the private key.
+pub fn decrypt(
+    keystore: &serde_json::Value,
+    password: &str,
+    format: KeystoreFormat,
+) -> Result<(Address, Private), Error> {
+    if keystore["version"].as_u64() != Some(KEYSTORE_VERSION) {
+        return Err(Error::Runtime("corrupted keystore file, only version 3 is supported"));
+    }
+    let crypto = match format {
+        KeystoreFormat::WalletCli if keystore["crypto"].is_null() => &keystore["Crypto"],
+        _ => &keystore["crypto"],
+    };
+    if !crypto.is_object() {
+        return Err(Error::Runtime("corrupted keystore file, missing crypto section"));
+    }
+    if crypto["cipher"] != "aes-128-ctr" {
+        return Err(Error::Runtime("corrupted keystore file, unsupported cipher"));
+    }
+    let address = keystore["address"]
+        .as_str()
+        .ok_or(Error::Runtime("corrupted keystore file, missing address"))
+        .and_then(|addr| {
+            trx::parse_address(addr).map_err(|_| Error::Runtime("corrupted keystore file, invalid address"))
+        })?;
+
+    let kdf = Kdf::from_json(crypto)?;
+    let salt = hex_field(&crypto["kdfparams"]["salt"])?;
+    let iv = hex_field(&crypto["cipherparams"]["iv"])?;
+    let cipher_text = hex_field(&crypto["ciphertext"])?;
+    let mac = hex_field(&crypto["mac"])?;
+    if cipher_text.len() != 32 || iv.len() != 16 || mac.len() != 32 {
+        return Err(Error::Runtime(MALFORMED));
+    }
+
+    let derived_key = kdf.derive_key(password, &salt)?;
+    if mac_of(&derived_key, &cipher_text)[..] != mac[..] {
+        return Err(Error::Runtime(WRONG_PASSWORD));
+    }
+
+    let mut plain = Zeroizing::new(cipher_text);
+    apply_aes_128_ctr(&derived_key[..16], &iv, &mut plain)?;
+    let private = Private::try_from(&plain[..])?;
+
+    if Address::from_private(&private) != address {
+        return Err(Error::Runtime(
+            "address in keystore file does not match the decrypted key",
+        ));
+    }
+    Ok((address, private))
+}
+
+fn encrypt_with(
+    private: &Private,
+    password: &str,
+    kdf: &Kdf,
+    salt: &[u8],
+    iv: &[u8],
+    id: &str,
+) -> Result<serde_json::Value, Error> {
+    let derived_key = kdf.derive_key(password, salt)?;
+    let mut cipher_text = private.as_bytes().to_vec();
+    apply_aes_128_ctr(&derived_key[..16], iv, &mut cipher_text)?;
+
+    Ok(json!({
+        "version": KEYSTORE_VERSION,
+        "id": id,
+        "address": Address::from_private(private).to_string(),
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": { "iv": iv.encode_hex::<String>() },
+            "ciphertext": cipher_text.encode_hex::<String>(),
+            "kdf": kdf.name(),
+            "kdfparams": kdf.to_json(salt),
+            "mac": mac_of(&derived_key, &cipher_text).encode_hex::<String>(),
+        },
+    }))
+}
+
+/// Random UUID v4, the `id` field.
+fn random_uuid() -> String {
+    let mut raw: [u8; 16] = thread_rng().gen();
+    raw[6] = (raw[6] & 0x0f) | 0x40;
+    raw[8] = (raw[8] & 0x3f) | 0x80;
+    let h = raw.encode_hex::<String>();
+    format!("{}-{}-{}-{}-{}", &h[..8], &h[8..12], &h[12..16], &h[16..20], &h[20..])
+}
+
+/// Encrypt a private key into a keystore file.
+pub fn encrypt(private: &Private, password: &str, format: KeystoreFormat) -> Result<serde_json::Value, Error> {
+    if format != KeystoreFormat::TronLink {
+        return Err(Error::Runtime("only tronlink format is supported for export"));
+    }
+    let kdf = Kdf::Scrypt {
+        log_n: SCRYPT_LOG_N,
+        r: SCRYPT_R,
+        p: SCRYPT_P,
+    };
+    let salt: [u8; 32] = thread_rng().gen();
+    let iv: [u8; 16] = thread_rng().gen();
+    encrypt_with(private, password, &kdf, &salt, &iv, &random_uuid())
+}
+
+/// Decrypt the key of a keystore file, both TronLink and java-tron wallet-cli layouts are accepted.
+pub fn decrypt_file(path: &Path, password: &str) -> Result<Private, Error> {
+    let content = Zeroizing::new(fs::read_to_string(path)?);
+    let keystore: serde_json::Value =
+        serde_json::from_str(&content).map_err(|_| Error::Runtime("corrupted keystore file, not a JSON"))?;
+    let (_, private) = decrypt(&keystore, password, KeystoreFormat::WalletCli)?;
+    Ok(private)
+}
+
+/// Signer with the key of a keystore file, decrypted once when opened.
+pub struct KeystoreSignerImpl {
+    inner: MemorySigner,
+}
+
+impl KeystoreSignerImpl {
+    /// Open a keystore file, both TronLink and java-tron wallet-cli layouts are accepted.
+    pub fn open(path: &Path, password: &str) -> Result<Self, Error> {
+        Ok(KeystoreSignerImpl {
+            inner: MemorySigner::new(decrypt_file(path, password)?),
+        })
+    }
+}
+
+impl Signer for KeystoreSignerImpl {
+    fn sign(&self, hash: &[u8; 32]) -> Result<[u8; 65], keys::Error> {
+        self.inner.sign(hash)
+    }
+
+    fn address(&self) -> Address {
+        self.inner.address()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+    const ADDRESS: &str = "TA25SJ2Uo4NQk2SozYKmZ4wQnpq5T7FNBZ";
+
+    /// The PBKDF2 test vector of the Web3 Secret Storage Definition, as in
+    /// https://github.com/ethereum/wiki/wiki/Web3-Secret-Storage-Definition#test-vectors, with the TRON address of
+    /// the key added as TronLink does.
+    fn web3_keystore() -> serde_json::Value {
+        json!({
+            "version": 3,
+            "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+            "address": ADDRESS,
+            "crypto": {
+                "cipher": "aes-128-ctr",
+                "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+                "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+                "kdf": "pbkdf2",
+                "kdfparams": {
+                    "c": 262144,
+                    "dklen": 32,
+                    "prf": "hmac-sha256",
+                    "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+                },
+                "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+            }
+        })
+    }
+
+    fn runtime_error(err: Error) -> &'static str {
+        match err {
+            Error::Runtime(msg) => msg,
+            _ => panic!("unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_decrypt_tronlink() {
+        let (address, private) = decrypt(&web3_keystore(), "testpassword", KeystoreFormat::TronLink).unwrap();
+        assert_eq!(address.to_string(), ADDRESS);
+        assert_eq!(private.to_string(), PRIVATE_KEY);
+    }
+
+    #[test]
+    fn test_decrypt_wallet_cli() {
+        // web3j, used by java-tron's wallet-cli, writes the section capitalized
+        let mut keystore = web3_keystore();
+        let crypto = keystore["crypto"].take();
+        keystore.as_object_mut().unwrap().remove("crypto");
+        keystore["address"] = json!("41008aeeda4d805471df9b2a5b0f38a0c3bcba786b");
+        keystore["Crypto"] = crypto;
+
+        let (address, private) = decrypt(&keystore, "testpassword", KeystoreFormat::WalletCli).unwrap();
+        assert_eq!(address.to_string(), ADDRESS);
+        assert_eq!(private.to_string(), PRIVATE_KEY);
+
+        // capitalized section is web3j only
+        assert!(decrypt(&keystore, "testpassword", KeystoreFormat::TronLink).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_errors() {
+        let err = decrypt(&web3_keystore(), "wrongpassword", KeystoreFormat::TronLink).unwrap_err();
+        assert_eq!(runtime_error(err), WRONG_PASSWORD);
+
+        let mut keystore = web3_keystore();
+        keystore["crypto"]["ciphertext"] = json!("5318b4d5bcd28de64e");
+        let err = decrypt(&keystore, "testpassword", KeystoreFormat::TronLink).unwrap_err();
+        assert_eq!(runtime_error(err), MALFORMED);
+
+        let mut keystore = web3_keystore();
+        keystore["crypto"]["kdf"] = json!("argon2");
+        let err = decrypt(&keystore, "testpassword", KeystoreFormat::TronLink).unwrap_err();
+        assert_eq!(runtime_error(err), "corrupted keystore file, unsupported kdf");
+
+        let mut keystore = web3_keystore();
+        keystore["address"] = json!("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t");
+        let err = decrypt(&keystore, "testpassword", KeystoreFormat::TronLink).unwrap_err();
+        assert_eq!(
+            runtime_error(err),
+            "address in keystore file does not match the decrypted key"
+        );
+    }
+
+    #[test]
+    fn test_encrypt_roundtrip() {
+        let private: Private = PRIVATE_KEY.parse().unwrap();
+        let kdf = Kdf::Scrypt { log_n: 10, r: 8, p: 1 };
+        let keystore = encrypt_with(&private, "password", &kdf, &[0x11; 32], &[0x22; 16], &random_uuid()).unwrap();
+        assert_eq!(keystore["address"], ADDRESS);
+        assert_eq!(keystore["id"].as_str().unwrap().len(), 36);
+
+        let (_, decrypted) = decrypt(&keystore, "password", KeystoreFormat::TronLink).unwrap();
+        assert_eq!(decrypted, private);
+    }
+
+    #[test]
+    fn test_keystore_signer() {
+        let path = std::env::temp_dir().join(format!("wallet-cli-keystore-{}.json", std::process::id()));
+        fs::write(&path, web3_keystore().to_string()).unwrap();
+        let signer = KeystoreSignerImpl::open(&path, "testpassword");
+        fs::remove_file(&path).unwrap();
+        let signer: Box<dyn Signer> = Box::new(signer.unwrap());
+        assert_eq!(signer.address().to_string(), ADDRESS);
+
+        let private: Private = PRIVATE_KEY.parse().unwrap();
+        let hash = crypto::sha256(b"transaction");
+        assert_eq!(
+            &signer.sign(&hash).unwrap()[..],
+            &private.sign_digest(&hash).unwrap()[..]
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Diagnostic logging to stderr.
+//!
+//! Command output, normally JSON, goes to stdout. Everything else is logged to stderr, with the `!` prefix for
+//! informational messages and `!!` for warnings. Verbosity is controlled by the `WALLET_CLI_LOG` environment
+//! variable (`off`, `error`, `warn`, `info`, `debug`, `trace`) and raised by `-v`. `--quiet` turns it off.
+//!
+//! When stderr is a terminal, errors are red, warnings yellow and informational messages green. Colors are
+//! disabled by `--no-color` or the `NO_COLOR` environment variable, see <https://no-color.org>.
+
+use env_logger::fmt::Color;
+use env_logger::{Builder, WriteStyle};
+use log::{Level, LevelFilter};
+use std::env;
+use std::io::Write;
+
+pub const LOG_ENV: &str = "WALLET_CLI_LOG";
+
+pub const NO_COLOR_ENV: &str = "NO_COLOR";
+
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+fn level_filter(env: Option<&str>, verbose: u64, quiet: bool) -> LevelFilter {
+    if quiet {
+        return LevelFilter::Off;
+    }
+    let level = env.and_then(|s| s.trim().parse().ok()).unwrap_or(DEFAULT_LEVEL);
+    if level == LevelFilter::Off {
+        return level;
+    }
+    match verbose {
+        0 => level,
+        1 => level.max(LevelFilter::Debug),
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// `NO_COLOR` disables colors when present and not empty.
+fn color_disabled_by(no_color: Option<&str>) -> bool {
+    no_color.map_or(false, |val| !val.is_empty())
+}
+
+fn color_of(level: Level) -> Option<Color> {
+    match level {
+        Level::Error => Some(Color::Red),
+        Level::Warn => Some(Color::Yellow),
+        Level::Info => Some(Color::Green),
+        _ => None,
+    }
+}
+
+/// Init logger, must be called once before any log. Colors are used only if `color` and stderr is a terminal.
+pub fn init(verbose: u64, quiet: bool, color: bool) {
+    let level = level_filter(env::var(LOG_ENV).ok().as_deref(), verbose, quiet);
+    let color = color && !color_disabled_by(env::var(NO_COLOR_ENV).ok().as_deref());
+
+    Builder::new()
+        .filter_level(LevelFilter::Warn.min(level))
+        .filter_module("wallet_cli", level)
+        .write_style(if color { WriteStyle::Auto } else { WriteStyle::Never })
+        .format(|buf, record| {
+            let mut style = buf.style();
+            if let Some(color) = color_of(record.level()) {
+                style.set_color(color);
+            }
+            match record.level() {
+                Level::Error | Level::Warn => writeln!(buf, "{}", style.value(format!("!! {}", record.args()))),
+                Level::Info => writeln!(buf, "{}", style.value(format!("! {}", record.args()))),
+                lvl => writeln!(buf, "! [{}] {}", lvl, record.args()),
+            }
+        })
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_filter() {
+        assert_eq!(level_filter(None, 0, false), LevelFilter::Info);
+        assert_eq!(level_filter(Some("warn"), 0, false), LevelFilter::Warn);
+        assert_eq!(level_filter(Some("DEBUG"), 0, false), LevelFilter::Debug);
+        assert_eq!(level_filter(Some("off"), 0, false), LevelFilter::Off);
+        assert_eq!(level_filter(Some("loud"), 0, false), LevelFilter::Info);
+        assert_eq!(level_filter(Some("warn"), 1, false), LevelFilter::Debug);
+        assert_eq!(level_filter(None, 2, false), LevelFilter::Trace);
+        assert_eq!(level_filter(Some("off"), 2, false), LevelFilter::Off);
+        assert_eq!(level_filter(Some("debug"), 1, true), LevelFilter::Off);
+    }
+
+    #[test]
+    fn test_color() {
+        assert!(!color_disabled_by(None));
+        assert!(!color_disabled_by(Some("")));
+        assert!(color_disabled_by(Some("1")));
+        assert_eq!(color_of(Level::Error), Some(Color::Red));
+        assert_eq!(color_of(Level::Warn), Some(Color::Yellow));
+        assert_eq!(color_of(Level::Debug), None);
+    }
+}
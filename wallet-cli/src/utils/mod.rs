@@ -1,6 +1,21 @@
 pub mod abi;
+#[cfg(unix)]
+pub mod agent;
+pub mod block;
 pub mod client;
 pub mod crypto;
+pub mod http;
+pub mod journal;
 pub mod jsont;
+pub mod keystore;
+pub mod logger;
+pub mod price;
+pub mod qr;
+pub mod record;
+pub mod session;
+pub mod time;
+pub mod trc20;
+pub mod trc721;
 pub mod trx;
+pub mod uri;
 pub mod walletd;
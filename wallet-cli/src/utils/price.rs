@@ -0,0 +1,262 @@
+//! TRX/USD price from the CoinGecko public API, for display only.
+//!
+//! Price lookup never fails a command. When the API is not reachable, USD values are simply omitted.
+//!
+//! For reports, a fiat rate can be given instead with `--fiat CUR --price RATE`, or per day with
+//! `--price-from-file`. Values are then computed with decimal arithmetic and labelled with the rate and its source.
+
+use chrono::NaiveDate;
+use clap::ArgMatches;
+use log::warn;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+
+const COINGECKO_PRICE_URL: &str = "https://api.coingecko.com/api/v3/simple/price?ids=tron&vs_currencies=usd";
+const CACHE_TTL: Duration = Duration::from_secs(60);
+const REQUEST_TIMEOUT_MILLIS: u64 = 5_000;
+
+thread_local! {
+    static TRX_USD_CACHE: RefCell<Option<(Instant, f64)>> = RefCell::new(None);
+}
+
+fn parse_price(resp: &serde_json::Value) -> Option<f64> {
+    resp["tron"]["usd"].as_f64().filter(|price| *price > 0.0)
+}
+
+fn fetch_trx_usd_price() -> Option<f64> {
+    let resp = ureq::get(COINGECKO_PRICE_URL)
+        .timeout(Duration::from_millis(REQUEST_TIMEOUT_MILLIS))
+        .call();
+    if !resp.ok() {
+        return None;
+    }
+    resp.into_json().ok().as_ref().and_then(parse_price)
+}
+
+/// Current TRX price in USD, cached for 60 seconds. None if not available.
+pub fn trx_usd_price() -> Option<f64> {
+    if let Some(price) = TRX_USD_CACHE.with(|cache| {
+        cache
+            .borrow()
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < CACHE_TTL)
+            .map(|(_, price)| price)
+    }) {
+        return Some(price);
+    }
+
+    match fetch_trx_usd_price() {
+        Some(price) => {
+            TRX_USD_CACHE.with(|cache| *cache.borrow_mut() = Some((Instant::now(), price)));
+            Some(price)
+        }
+        None => {
+            warn!("Can not fetch TRX price from CoinGecko, USD values are omitted");
+            None
+        }
+    }
+}
+
+/// USD value of an amount in SUN, like ` (≈ $1.23 USD)`. Empty if price is not available.
+pub fn usd_suffix(price: Option<f64>, amount_in_sun: i64) -> String {
+    match price {
+        Some(price) => format!(" (≈ ${:.2} USD)", amount_in_sun as f64 / 1_000_000.0 * price),
+        None => String::new(),
+    }
+}
+
+/// TRX price in a fiat currency, an exact decimal given by the user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FiatRate {
+    pub currency: String,
+    /// The rate as given, like `0.0653`.
+    rate: String,
+    /// Digits of the rate and its decimal places, `0.0653` is `(653, 4)`.
+    mantissa: i128,
+    scale: u32,
+    /// Where the rate comes from, like `--price` or `prices.csv 2020-10-01`.
+    pub source: String,
+}
+
+impl FiatRate {
+    pub fn parse(currency: &str, rate: &str, source: &str) -> Result<Self, Error> {
+        const INVALID: Error = Error::InvalidInput("invalid price, use a positive decimal like 0.0653");
+        let rate = rate.trim();
+        let (int_part, frac_part) = match rate.find('.') {
+            Some(pos) => (&rate[..pos], &rate[pos + 1..]),
+            None => (rate, ""),
+        };
+        let digits = format!("{}{}", int_part, frac_part);
+        if digits.is_empty() || digits.len() > 17 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(INVALID);
+        }
+        let mantissa = digits.parse::<i128>().map_err(|_| INVALID)?;
+        if mantissa == 0 {
+            return Err(INVALID);
+        }
+        Ok(FiatRate {
+            currency: currency.to_uppercase(),
+            rate: rate.to_owned(),
+            mantissa,
+            scale: frac_part.len() as u32,
+            source: source.to_owned(),
+        })
+    }
+
+    /// Fiat value of an amount in SUN, rounded half away from zero to 2 decimal places.
+    pub fn value_of(&self, amount_in_sun: impl Into<i128>) -> String {
+        // value = sun * mantissa / 10^(6 + scale), in cents
+        let divisor = 10i128.pow(6 + self.scale);
+        let scaled = amount_in_sun.into() * self.mantissa * 100;
+        let cents = (scaled.abs() + divisor / 2) / divisor;
+        let sign = if scaled < 0 && cents > 0 { "-" } else { "" };
+        format!("{}{}.{:02}", sign, cents / 100, cents % 100)
+    }
+
+    /// The rate and its source, like `0.065 USD/TRX, --price`.
+    pub fn label(&self) -> String {
+        format!("{} {}/TRX, {}", self.rate, self.currency, self.source)
+    }
+
+    /// Value with its label, like ` (≈ 6.50 USD at 0.065 USD/TRX, --price)`.
+    pub fn suffix(&self, amount_in_sun: i64) -> String {
+        format!(
+            " (≈ {} {} at {})",
+            self.value_of(amount_in_sun),
+            self.currency,
+            self.label()
+        )
+    }
+}
+
+/// Fiat rate of `--fiat`, fixed or by date.
+#[derive(Debug)]
+pub enum FiatPrice {
+    Fixed(FiatRate),
+    Daily {
+        currency: String,
+        path: String,
+        rates: BTreeMap<NaiveDate, FiatRate>,
+    },
+}
+
+impl FiatPrice {
+    /// Rate on a date (UTC). A missing date of a price file is warned of, never interpolated.
+    pub fn rate_on(&self, date: NaiveDate) -> Option<&FiatRate> {
+        match self {
+            FiatPrice::Fixed(rate) => Some(rate),
+            FiatPrice::Daily { currency, path, rates } => {
+                let rate = rates.get(&date);
+                if rate.is_none() {
+                    warn!("No {} price for {} in {}, fiat values omitted", currency, date, path);
+                }
+                rate
+            }
+        }
+    }
+}
+
+/// Parse a price file, `date,rate` per line with dates like `2020-10-01`. Blank lines, `#` comments and a header
+/// line are skipped.
+pub fn parse_price_file(currency: &str, path: &str, content: &str) -> Result<BTreeMap<NaiveDate, FiatRate>, Error> {
+    let mut rates = BTreeMap::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.splitn(2, ',').map(str::trim);
+        let (date, rate) = (fields.next().unwrap_or_default(), fields.next().unwrap_or_default());
+        let date = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) if i == 0 => continue,
+            Err(_) => {
+                warn!("{} line {}: invalid date {:?}", path, i + 1, date);
+                return Err(Error::InvalidInput("invalid price file"));
+            }
+        };
+        let source = format!("{} {}", path, date);
+        rates.insert(date, FiatRate::parse(currency, rate, &source)?);
+    }
+    Ok(rates)
+}
+
+/// The fiat rate of `--fiat` with `--price` or `--price-from-file`, `None` if not given.
+pub fn fiat_price_of(matches: &ArgMatches) -> Result<Option<FiatPrice>, Error> {
+    let currency = match matches.value_of("fiat") {
+        Some(currency) => currency.to_uppercase(),
+        None => return Ok(None),
+    };
+    match (matches.value_of("price"), matches.value_of("price-from-file")) {
+        (Some(rate), _) => Ok(Some(FiatPrice::Fixed(FiatRate::parse(&currency, rate, "--price")?))),
+        (None, Some(path)) => {
+            let rates = parse_price_file(&currency, path, &fs::read_to_string(path)?)?;
+            Ok(Some(FiatPrice::Daily {
+                currency,
+                path: path.to_owned(),
+                rates,
+            }))
+        }
+        (None, None) => Err(Error::InvalidInput("--fiat requires --price or --price-from-file")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_price() {
+        assert_eq!(parse_price(&json!({"tron": {"usd": 0.0653}})), Some(0.0653));
+        assert_eq!(parse_price(&json!({"status": {"error_code": 429}})), None);
+    }
+
+    #[test]
+    fn test_usd_suffix() {
+        assert_eq!(usd_suffix(Some(0.065), 100_000_000), " (≈ $6.50 USD)");
+        assert_eq!(usd_suffix(None, 100_000_000), "");
+    }
+
+    #[test]
+    fn test_fiat_rate() {
+        let rate = FiatRate::parse("usd", "0.065", "--price").unwrap();
+        assert_eq!(rate.value_of(100_000_000), "6.50");
+        assert_eq!(rate.suffix(100_000_000), " (≈ 6.50 USD at 0.065 USD/TRX, --price)");
+        // 0.0653 * 0.1 TRX is 0.00653, rounded to 0.01
+        let rate = FiatRate::parse("USD", "0.0653", "--price").unwrap();
+        assert_eq!(rate.value_of(100_000), "0.01");
+        assert_eq!(rate.value_of(-100_000), "-0.01");
+        assert_eq!(rate.value_of(1), "0.00");
+        // exact where f64 is not
+        assert_eq!(rate.value_of(i64::MAX), "602286194006.62");
+
+        assert!(FiatRate::parse("USD", "0", "--price").is_err());
+        assert!(FiatRate::parse("USD", "-1", "--price").is_err());
+        assert!(FiatRate::parse("USD", "1.2.3", "--price").is_err());
+        assert!(FiatRate::parse("USD", "", "--price").is_err());
+    }
+
+    #[test]
+    fn test_price_file() {
+        let content = "date,usd\n2020-10-01,0.025\n\n# weekend\n2020-10-03, 0.026\n";
+        let rates = parse_price_file("USD", "prices.csv", content).unwrap();
+        assert_eq!(rates.len(), 2);
+        let price = FiatPrice::Daily {
+            currency: "USD".into(),
+            path: "prices.csv".into(),
+            rates,
+        };
+        let rate = price.rate_on(NaiveDate::from_ymd(2020, 10, 3)).unwrap();
+        assert_eq!(
+            rate.suffix(1_000_000),
+            " (≈ 0.03 USD at 0.026 USD/TRX, prices.csv 2020-10-03)"
+        );
+        assert!(price.rate_on(NaiveDate::from_ymd(2020, 10, 2)).is_none());
+
+        assert!(parse_price_file("USD", "prices.csv", "2020-10-01,0.025\nyesterday,0.1\n").is_err());
+    }
+}
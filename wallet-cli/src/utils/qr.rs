@@ -0,0 +1,116 @@
+//! QR code envelope for moving transactions between hot and cold wallets.
+//!
+//! A serialized transaction is split into chunks, each chunk is wrapped into an envelope and shown as one QR
+//! code. The envelope is plain text, made only of characters in the QR alphanumeric set:
+//!
+//! ```text
+//! TX1:<INDEX>/<TOTAL>:<CHECKSUM>:<PAYLOAD>
+//! ```
+//!
+//! - `TX1` - format tag and version
+//! - `INDEX` - 1-based chunk index, in decimal
+//! - `TOTAL` - total number of chunks, in decimal
+//! - `CHECKSUM` - first 4 bytes of sha256 of the whole serialized transaction, uppercase hex
+//! - `PAYLOAD` - bytes of this chunk, uppercase hex
+//!
+//! All chunks of a transaction carry the same `TOTAL` and `CHECKSUM`. Chunks may be scanned in any order.
+//! The reassembled bytes must match `CHECKSUM`.
+
+use std::collections::BTreeMap;
+
+use crate::error::Error;
+use crate::utils::crypto;
+
+const ENVELOPE_TAG: &str = "TX1";
+
+/// Default number of transaction bytes in one QR code.
+pub const DEFAULT_CHUNK_SIZE: usize = 256;
+
+fn checksum_of(data: &[u8]) -> String {
+    hex::encode_upper(&crypto::sha256(data)[..4])
+}
+
+/// Split serialized transaction into envelopes.
+pub fn encode_chunks(data: &[u8], chunk_size: usize) -> Vec<String> {
+    let checksum = checksum_of(data);
+    let total = (data.len() + chunk_size - 1) / chunk_size;
+    data.chunks(chunk_size)
+        .enumerate()
+        .map(|(i, chunk)| {
+            format!(
+                "{}:{}/{}:{}:{}",
+                ENVELOPE_TAG,
+                i + 1,
+                total,
+                checksum,
+                hex::encode_upper(chunk)
+            )
+        })
+        .collect()
+}
+
+/// Reassemble serialized transaction from scanned envelopes, in any order.
+pub fn decode_chunks<S: AsRef<str>>(envelopes: &[S]) -> Result<Vec<u8>, Error> {
+    let mut expected: Option<(usize, String)> = None;
+    let mut chunks = BTreeMap::new();
+
+    for envelope in envelopes {
+        let fields: Vec<&str> = envelope.as_ref().trim().splitn(4, ':').collect();
+        if fields.len() != 4 || fields[0] != ENVELOPE_TAG {
+            return Err(Error::Runtime("malformed QR envelope"));
+        }
+        let mut pos = fields[1].splitn(2, '/');
+        let index = pos.next().unwrap_or_default().parse::<usize>()?;
+        let total = pos
+            .next()
+            .ok_or(Error::Runtime("malformed QR envelope"))?
+            .parse::<usize>()?;
+        if index == 0 || index > total {
+            return Err(Error::Runtime("QR chunk index out of range"));
+        }
+        match expected {
+            Some((t, ref c)) if t != total || c != fields[2] => {
+                return Err(Error::Runtime("QR chunks are from different transactions"))
+            }
+            None => expected = Some((total, fields[2].to_owned())),
+            _ => (),
+        }
+        chunks.insert(index, hex::decode(fields[3])?);
+    }
+
+    let (total, checksum) = expected.ok_or(Error::Runtime("no QR chunk"))?;
+    if chunks.len() != total {
+        return Err(Error::Runtime("missing QR chunks"));
+    }
+    let data = chunks.into_iter().flat_map(|(_, chunk)| chunk).collect::<Vec<_>>();
+    if checksum_of(&data) != checksum {
+        return Err(Error::Runtime("QR checksum mismatch"));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qr_chunks_roundtrip() {
+        let data = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+        let mut envelopes = encode_chunks(&data, 300);
+        assert_eq!(envelopes.len(), 4);
+        assert!(envelopes[0].starts_with("TX1:1/4:"));
+
+        envelopes.reverse();
+        assert_eq!(decode_chunks(&envelopes).unwrap(), data);
+
+        envelopes.pop();
+        assert!(decode_chunks(&envelopes).is_err());
+    }
+
+    #[test]
+    fn test_qr_chunks_checksum_mismatch() {
+        let mut envelopes = encode_chunks(b"tron transaction", 8);
+        envelopes[1] = envelopes[1].replace(&hex::encode_upper(b"nsaction"), &hex::encode_upper(b"NSACTION"));
+        assert!(decode_chunks(&envelopes).is_err());
+    }
+}
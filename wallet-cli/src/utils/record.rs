@@ -0,0 +1,200 @@
+//! Record and replay of raw gRPC responses, saved as `.bin` files.
+//!
+//! With `--raw-output-dir`, fetched responses are saved. With `--replay-dir`, they are loaded from the directory
+//! instead of the node, a missing file is treated as an empty response, as the node returns for unknown IDs.
+
+use log::debug;
+use proto::api::BlockExtention;
+use proto::core::Block;
+use protobuf::Message;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::utils::crypto;
+use crate::{RAW_OUTPUT_DIR, REPLAY_DIR};
+
+/// Kind of a recorded response, used as the file name prefix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordKind {
+    Transaction,
+    TransactionInfo,
+    Block,
+    /// `SmartContract` by address.
+    Contract,
+    /// Constant call result, by `sha256(contract_address ++ data)`.
+    ConstantCall,
+}
+
+impl RecordKind {
+    fn prefix(&self) -> &'static str {
+        match self {
+            RecordKind::Transaction => "transaction",
+            RecordKind::TransactionInfo => "transaction_info",
+            RecordKind::Block => "block",
+            RecordKind::Contract => "contract",
+            RecordKind::ConstantCall => "constant_call",
+        }
+    }
+}
+
+/// File name of a record, the ID is always hex encoded so it is safe as a path component.
+pub fn file_name(kind: RecordKind, id: &[u8]) -> String {
+    format!("{}-{}.bin", kind.prefix(), hex::encode(id))
+}
+
+/// Save a response when `--raw-output-dir` is given, no-op otherwise. Empty responses are not saved.
+pub fn record<M: Message>(kind: RecordKind, id: &[u8], msg: &M) -> Result<(), Error> {
+    if msg.compute_size() == 0 {
+        return Ok(());
+    }
+    if let Some(dir) = unsafe { RAW_OUTPUT_DIR } {
+        let path = record_to(Path::new(dir), kind, id, msg)?;
+        debug!("Recorded {}", path.display());
+    }
+    Ok(())
+}
+
+pub fn record_to<M: Message>(dir: &Path, kind: RecordKind, id: &[u8], msg: &M) -> Result<PathBuf, Error> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(file_name(kind, id));
+    fs::write(&path, msg.write_to_bytes()?)?;
+    Ok(path)
+}
+
+/// Load a recorded response.
+pub fn load<M: Message>(path: &Path) -> Result<M, Error> {
+    Ok(protobuf::parse_from_bytes(&fs::read(path)?)?)
+}
+
+/// Load a response in replay mode, `None` otherwise.
+fn replay<M: Message>(kind: RecordKind, id: &[u8]) -> Result<Option<M>, Error> {
+    match unsafe { REPLAY_DIR } {
+        Some(dir) => {
+            let path = Path::new(dir).join(file_name(kind, id));
+            if path.exists() {
+                debug!("Replaying {}", path.display());
+                load(&path).map(Some)
+            } else {
+                debug!("Replaying {}, not found", path.display());
+                Ok(Some(M::new()))
+            }
+        }
+        None => Ok(None),
+    }
+}
+
+/// Fetch a response by ID, from the replay directory or the node, recorded if required.
+pub fn fetch<M, F>(kind: RecordKind, id: &[u8], fetch_from_node: F) -> Result<M, Error>
+where
+    M: Message,
+    F: FnOnce() -> Result<M, Error>,
+{
+    if let Some(msg) = replay(kind, id)? {
+        return Ok(msg);
+    }
+    let msg = fetch_from_node()?;
+    record(kind, id, &msg)?;
+    Ok(msg)
+}
+
+/// Async version of `fetch`.
+pub async fn fetch_async<M, F, Fut>(kind: RecordKind, id: &[u8], fetch_from_node: F) -> Result<M, Error>
+where
+    M: Message,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<M, Error>>,
+{
+    if let Some(msg) = replay(kind, id)? {
+        return Ok(msg);
+    }
+    let msg = fetch_from_node().await?;
+    record(kind, id, &msg)?;
+    Ok(msg)
+}
+
+/// Load a block by number, or the latest block, in replay mode. Block IDs start with the block number, so are the
+/// file names.
+pub fn replay_block(num: Option<i64>) -> Result<Option<Block>, Error> {
+    let dir = match unsafe { REPLAY_DIR } {
+        Some(dir) => dir,
+        None => return Ok(None),
+    };
+    let prefix = match num {
+        Some(num) => format!("{}-{:016x}", RecordKind::Block.prefix(), num),
+        None => format!("{}-", RecordKind::Block.prefix()),
+    };
+    let found = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(&prefix) && name.ends_with(".bin"))
+        .max();
+    match found {
+        Some(name) => load(&Path::new(dir).join(name)).map(Some),
+        None => Ok(Some(Block::new())),
+    }
+}
+
+/// Block ID, the header hash with the first 8 bytes replaced by the block number.
+pub fn block_id(block: &Block) -> Result<[u8; 32], Error> {
+    let raw = block.get_block_header().get_raw_data();
+    let mut id = crypto::sha256(&raw.write_to_bytes()?);
+    id[..8].copy_from_slice(&raw.number.to_be_bytes());
+    Ok(id)
+}
+
+/// Convert `BlockExtention` to `Block`, so all recorded blocks share one message type.
+pub fn block_of(block_ext: &BlockExtention) -> Block {
+    let mut block = Block::new();
+    block.set_block_header(block_ext.get_block_header().clone());
+    block.set_transactions(
+        block_ext
+            .get_transactions()
+            .iter()
+            .map(|txn_ext| txn_ext.get_transaction().clone())
+            .collect(),
+    );
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::jsont;
+    use crate::utils::trx;
+    use proto::core::Transaction;
+    use std::process;
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            file_name(RecordKind::TransactionInfo, &[0xde, 0xad, 0xbe, 0xef]),
+            "transaction_info-deadbeef.bin"
+        );
+    }
+
+    #[test]
+    fn test_record_and_decode_offline() {
+        let mut transaction = trx::test_transfer_transaction();
+        transaction.mut_raw_data().set_timestamp(1_600_000_000_000);
+
+        let dir = std::env::temp_dir().join(format!("wallet-cli-record-{}", process::id()));
+        let path = record_to(&dir, RecordKind::Transaction, &[0xab; 32], &transaction).unwrap();
+        assert!(path.ends_with(format!("transaction-{}.bin", "ab".repeat(32))));
+
+        let loaded: Transaction = load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded, transaction);
+
+        let mut live = serde_json::to_value(&transaction).unwrap();
+        jsont::fix_transaction(&mut live).unwrap();
+        let mut replayed = serde_json::to_value(&loaded).unwrap();
+        jsont::fix_transaction(&mut replayed).unwrap();
+        assert_eq!(replayed, live);
+        assert_eq!(
+            replayed["raw_data"]["contract"][0]["parameter"]["value"]["amount"],
+            1_000_000
+        );
+    }
+}
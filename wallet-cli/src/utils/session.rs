@@ -0,0 +1,238 @@
+//! Keys unlocked for a session, signing without a password prompt until locked or idle for too long.
+//!
+//! `wallet unlock --keystore FILE` in the REPL decrypts a keystore once and keeps the key in memory of the REPL
+//! process. Later `--keystore FILE` or `--account ADDR` of the same key sign with it. The key is dropped, and
+//! zeroized, by `wallet lock`, after its idle timeout or when the process exits. Outside the REPL the key is kept
+//! in a session of the agent process instead, see `agent`.
+//!
+//! # Threat model
+//!
+//! Protected against:
+//!
+//! - Keys at rest, they stay encrypted on disk, nothing is written unencrypted.
+//! - A forgotten session, keys are dropped after being unused for the idle timeout, checked every second.
+//! - Other users, the agent socket is owner-only and peers of other UIDs are refused.
+//!
+//! Not protected against:
+//!
+//! - Anything that can read the memory of this process or of the agent: the same user with ptrace, root, core
+//!   dumps or swap. The key is not copied for signing, it is signed with in place while the session is locked.
+//! - Commands run in the session, any command can sign with an unlocked key without confirmation.
+//! - Processes of the same user, they can sign through the agent socket while a key is unlocked in the agent.
+//!
+//! walletd also keeps keys of its wallet unlocked across invocations, but it is reached by gRPC on the loopback
+//! interface, and is open to every local user while unlocked.
+
+use keys::{Address, MemorySigner, Signer};
+use lazy_static::lazy_static;
+use log::info;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Idle timeout of an unlocked key when not given.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+struct UnlockedKey {
+    signer: MemorySigner,
+    keystore: Option<PathBuf>,
+    idle_timeout: Duration,
+    last_used: Instant,
+}
+
+#[derive(Default)]
+struct Session {
+    keys: HashMap<Address, UnlockedKey>,
+}
+
+impl Session {
+    fn unlock(&mut self, signer: MemorySigner, keystore: Option<PathBuf>, idle_timeout: Duration, now: Instant) {
+        self.keys.insert(
+            signer.address(),
+            UnlockedKey {
+                signer,
+                keystore,
+                idle_timeout,
+                last_used: now,
+            },
+        );
+    }
+
+    /// Drop keys idle for longer than their timeout, returns their addresses.
+    fn lock_expired(&mut self, now: Instant) -> Vec<Address> {
+        let expired = self
+            .keys
+            .iter()
+            .filter(|(_, key)| now.saturating_duration_since(key.last_used) >= key.idle_timeout)
+            .map(|(addr, _)| *addr)
+            .collect::<Vec<_>>();
+        for addr in &expired {
+            self.keys.remove(addr);
+        }
+        expired
+    }
+
+    /// Use an unlocked key, which resets its idle time. The signer is lent, never copied.
+    fn use_key(&mut self, addr: &Address, now: Instant) -> Option<&MemorySigner> {
+        self.lock_expired(now);
+        let key = self.keys.get_mut(addr)?;
+        key.last_used = now;
+        Some(&key.signer)
+    }
+
+    fn address_of_keystore(&self, path: &Path) -> Option<Address> {
+        self.keys
+            .iter()
+            .find(|(_, key)| key.keystore.as_deref() == Some(path))
+            .map(|(addr, _)| *addr)
+    }
+
+    fn lock_all(&mut self) -> usize {
+        let count = self.keys.len();
+        self.keys.clear();
+        count
+    }
+}
+
+lazy_static! {
+    static ref SESSION: Mutex<Session> = Mutex::new(Session::default());
+}
+
+#[cfg(test)]
+lazy_static! {
+    /// Held by tests of the process-wide session, `lock` would lock the keys of other tests.
+    pub static ref TEST_SESSION: Mutex<()> = Mutex::new(());
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+/// Keep a key unlocked, until idle for `idle_timeout`.
+pub fn unlock(signer: MemorySigner, keystore: Option<&Path>, idle_timeout: Duration) {
+    static SWEEPER: Once = Once::new();
+    SWEEPER.call_once(|| {
+        thread::spawn(|| loop {
+            thread::sleep(SWEEP_INTERVAL);
+            for addr in SESSION.lock().unwrap().lock_expired(Instant::now()) {
+                info!("Locked {} after idle timeout", addr);
+            }
+        });
+    });
+    SESSION
+        .lock()
+        .unwrap()
+        .unlock(signer, keystore.map(canonical), idle_timeout, Instant::now());
+}
+
+/// Signs with a key of the session, looked up on each signing. Fails if the key has been locked since.
+struct SessionSigner {
+    address: Address,
+}
+
+impl Signer for SessionSigner {
+    fn sign(&self, hash: &[u8; 32]) -> Result<[u8; 65], keys::Error> {
+        SESSION
+            .lock()
+            .unwrap()
+            .use_key(&self.address, Instant::now())
+            .ok_or(keys::Error::FailedSigning)?
+            .sign(hash)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+}
+
+/// Signer of an unlocked key, by address.
+pub fn signer_of(addr: &Address) -> Option<Box<dyn Signer>> {
+    SESSION.lock().unwrap().use_key(addr, Instant::now())?;
+    Some(Box::new(SessionSigner { address: *addr }))
+}
+
+/// Signer of an unlocked key, by the keystore file it was unlocked from.
+pub fn signer_of_keystore(path: &Path) -> Option<Box<dyn Signer>> {
+    let mut session = SESSION.lock().unwrap();
+    let address = session.address_of_keystore(&canonical(path))?;
+    session.use_key(&address, Instant::now())?;
+    Some(Box::new(SessionSigner { address }))
+}
+
+/// Lock all keys, returns how many were unlocked.
+pub fn lock() -> usize {
+    SESSION.lock().unwrap().lock_all()
+}
+
+/// Number of unlocked keys.
+pub fn count() -> usize {
+    SESSION.lock().unwrap().keys.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keys::Private;
+    use std::convert::TryFrom;
+
+    fn signer(i: u8) -> MemorySigner {
+        MemorySigner::new(Private::try_from(&[i; 32][..]).unwrap())
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let start = Instant::now();
+        let timeout = Duration::from_secs(300);
+        let mut session = Session::default();
+        let addr = signer(1).address();
+        session.unlock(signer(1), Some(PathBuf::from("/keys/1.json")), timeout, start);
+        session.unlock(signer(2), None, Duration::from_secs(10), start);
+
+        assert!(session.lock_expired(start + Duration::from_secs(9)).is_empty());
+        assert_eq!(
+            session.lock_expired(start + Duration::from_secs(10)),
+            vec![signer(2).address()]
+        );
+        assert!(session.use_key(&signer(2).address(), start).is_none());
+
+        // each use resets the idle time
+        let used = start + Duration::from_secs(200);
+        assert_eq!(session.use_key(&addr, used).unwrap().address(), addr);
+
+        assert_eq!(session.address_of_keystore(Path::new("/keys/1.json")), Some(addr));
+        assert!(session.lock_expired(used + timeout - Duration::from_secs(1)).is_empty());
+        assert!(session.use_key(&addr, used + timeout).is_none());
+        assert_eq!(session.address_of_keystore(Path::new("/keys/1.json")), None);
+    }
+
+    #[test]
+    fn test_lock_all() {
+        let now = Instant::now();
+        let mut session = Session::default();
+        session.unlock(signer(1), None, DEFAULT_IDLE_TIMEOUT, now);
+        session.unlock(signer(2), None, DEFAULT_IDLE_TIMEOUT, now);
+        assert_eq!(session.lock_all(), 2);
+        assert!(session.use_key(&signer(1).address(), now).is_none());
+    }
+
+    #[test]
+    fn test_session_signer() {
+        let _session = TEST_SESSION.lock().unwrap_or_else(|e| e.into_inner());
+        let addr = signer(7).address();
+        unlock(signer(7), None, DEFAULT_IDLE_TIMEOUT);
+        let session_signer = signer_of(&addr).unwrap();
+        assert_eq!(
+            session_signer.sign(&[1; 32]).unwrap()[..],
+            signer(7).sign(&[1; 32]).unwrap()[..]
+        );
+
+        // signs with the key of the session, not a copy
+        lock();
+        assert_eq!(session_signer.sign(&[1; 32]), Err(keys::Error::FailedSigning));
+        assert!(signer_of(&addr).is_none());
+    }
+}
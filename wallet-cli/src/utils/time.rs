@@ -0,0 +1,104 @@
+//! Timestamp rendering, in local timezone or UTC.
+//!
+//! Minimal container images often ship without timezone data. Local time is only used when the timezone can be
+//! resolved, otherwise times are rendered in UTC with a warning.
+
+use chrono::{Local, LocalResult, TimeZone, Utc};
+use log::warn;
+use std::env;
+use std::path::Path;
+use std::sync::Once;
+
+use crate::USE_UTC;
+
+const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+const LOCALTIME_FILE: &str = "/etc/localtime";
+
+static FALLBACK_WARNING: Once = Once::new();
+
+/// POSIX TZ string, like `CST-8` or `EST5EDT`, which needs no timezone data.
+fn is_posix_tz(tz: &str) -> bool {
+    tz.len() >= 4 && tz.chars().take(3).all(|c| c.is_ascii_alphabetic()) && tz.chars().any(|c| c.is_ascii_digit())
+}
+
+fn local_timezone_available(tz: Option<&str>, zoneinfo_dir: &Path, localtime_file: &Path) -> bool {
+    if !cfg!(unix) {
+        return true;
+    }
+    match tz.map(|tz| tz.trim_start_matches(':')) {
+        Some("") | Some("UTC") => true,
+        Some(tz) if is_posix_tz(tz) => true,
+        Some(tz) if Path::new(tz).is_absolute() => Path::new(tz).exists(),
+        Some(tz) => zoneinfo_dir.join(tz).exists(),
+        None => localtime_file.exists(),
+    }
+}
+
+fn use_utc() -> bool {
+    if unsafe { USE_UTC } {
+        return true;
+    }
+    let tz = env::var("TZ").ok();
+    if local_timezone_available(tz.as_deref(), Path::new(ZONEINFO_DIR), Path::new(LOCALTIME_FILE)) {
+        false
+    } else {
+        FALLBACK_WARNING.call_once(|| warn!("Local timezone is not available, times are shown in UTC"));
+        true
+    }
+}
+
+fn format_utc(secs: i64, nsecs: u32) -> String {
+    match Utc.timestamp_opt(secs, nsecs) {
+        LocalResult::Single(t) => t.to_string(),
+        _ => format!("{}.{:09} (out of range)", secs, nsecs),
+    }
+}
+
+/// Format a unix timestamp, in local timezone or UTC.
+pub fn format_timestamp(secs: i64, nsecs: u32) -> String {
+    if use_utc() {
+        return format_utc(secs, nsecs);
+    }
+    match Local.timestamp_opt(secs, nsecs) {
+        LocalResult::Single(t) => t.to_string(),
+        LocalResult::Ambiguous(t, _) => t.to_string(),
+        LocalResult::None => format_utc(secs, nsecs),
+    }
+}
+
+/// Format current time, in local timezone or UTC.
+pub fn format_now(fmt: &str) -> String {
+    if use_utc() {
+        Utc::now().format(fmt).to_string()
+    } else {
+        Local::now().format(fmt).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_timezone_available() {
+        let missing = Path::new("/nonexistent/zoneinfo");
+        let missing_file = Path::new("/nonexistent/localtime");
+
+        assert!(!local_timezone_available(Some("Asia/Nowhere"), missing, missing_file));
+        assert!(!local_timezone_available(
+            Some(":/nonexistent/tz"),
+            missing,
+            missing_file
+        ));
+        assert!(!local_timezone_available(None, missing, missing_file));
+        assert!(local_timezone_available(Some("UTC"), missing, missing_file));
+        assert!(local_timezone_available(Some(""), missing, missing_file));
+        assert!(local_timezone_available(Some("CST-8"), missing, missing_file));
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0, 0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_utc(1_600_000_000, 500_000_000), "2020-09-13 12:26:40.500 UTC");
+    }
+}
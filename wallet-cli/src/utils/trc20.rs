@@ -0,0 +1,287 @@
+//! TRC20 token helpers.
+
+use futures::{executor, future};
+use keys::Address;
+use lazy_static::lazy_static;
+use proto::core::TriggerSmartContract;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::error::Error;
+use crate::utils::abi::{self, AbiValue};
+use crate::utils::client;
+use crate::utils::crypto;
+use crate::utils::record::{self, RecordKind};
+
+/// Token metadata, `None` if the contract does not implement it.
+#[derive(Debug, Clone, Default)]
+pub struct TokenMeta {
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+/// Max decimals of an amount, `10^78` overflows `uint256`.
+pub const MAX_DECIMALS: u8 = 77;
+
+lazy_static! {
+    static ref TOKEN_META_CACHE: Mutex<HashMap<Address, TokenMeta>> = Mutex::new(HashMap::new());
+}
+
+/// A TRC20 `transfer` or `transferFrom` call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trc20Transfer {
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+    pub amount: ethabi::Uint,
+}
+
+/// Detect a TRC20 transfer from call data of a TriggerSmartContract.
+pub fn parse_transfer(owner: &Address, contract: &Address, data: &[u8]) -> Option<Trc20Transfer> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, params) = data.split_at(4);
+    if selector == abi::fnhash("transfer(address,uint256)") {
+        let mut values = abi::decode_params(&["address", "uint256"], &hex::encode(params))
+            .ok()?
+            .into_iter();
+        Some(Trc20Transfer {
+            token: *contract,
+            from: *owner,
+            to: Address::try_from(values.next()?).ok()?,
+            amount: uint_of(values.next()?)?,
+        })
+    } else if selector == abi::fnhash("transferFrom(address,address,uint256)") {
+        let mut values = abi::decode_params(&["address", "address", "uint256"], &hex::encode(params))
+            .ok()?
+            .into_iter();
+        Some(Trc20Transfer {
+            token: *contract,
+            from: Address::try_from(values.next()?).ok()?,
+            to: Address::try_from(values.next()?).ok()?,
+            amount: uint_of(values.next()?)?,
+        })
+    } else {
+        None
+    }
+}
+
+fn uint_of(value: AbiValue) -> Option<ethabi::Uint> {
+    match value {
+        AbiValue::Uint(val) => Some(val),
+        _ => None,
+    }
+}
+
+async fn call_constant(contract: &Address, method: &str) -> Result<Option<Vec<u8>>, Error> {
+    call_constant_with_data(contract, abi::fnhash(method).to_vec()).await
+}
+
+/// Call a constant method of a contract, returns None if the call fails or returns nothing.
+pub(crate) async fn call_constant_with_data(contract: &Address, data: Vec<u8>) -> Result<Option<Vec<u8>>, Error> {
+    let mut id = contract.as_bytes().to_owned();
+    id.extend_from_slice(&data);
+    let mut payload = record::fetch_async(RecordKind::ConstantCall, &crypto::sha256(&id), || async {
+        let trigger_contract = TriggerSmartContract {
+            owner_address: contract.as_bytes().to_owned(),
+            contract_address: contract.as_bytes().to_owned(),
+            data,
+            ..Default::default()
+        };
+        Ok(client::GRPC_CLIENT
+            .trigger_constant_contract(Default::default(), trigger_contract)
+            .drop_metadata()
+            .await?)
+    })
+    .await?;
+    if !payload.get_result().get_result() {
+        return Ok(None);
+    }
+    Ok(payload
+        .take_constant_result()
+        .into_iter()
+        .next()
+        .filter(|r| !r.is_empty()))
+}
+
+/// Get symbol and decimals of a TRC20 token, cached.
+pub fn get_token_meta(token: &Address) -> Result<TokenMeta, Error> {
+    executor::block_on(fetch_token_meta(token))
+}
+
+/// Async version of `get_token_meta`, `symbol()` and `decimals()` are called concurrently.
+pub async fn fetch_token_meta(token: &Address) -> Result<TokenMeta, Error> {
+    if let Some(meta) = TOKEN_META_CACHE.lock().unwrap().get(token) {
+        return Ok(meta.clone());
+    }
+
+    let (symbol, decimals) = future::join(call_constant(token, "symbol()"), call_constant(token, "decimals()")).await;
+    let symbol = symbol?
+        .and_then(|ret| abi::decode_params(&["string"], &hex::encode(ret)).ok())
+        .and_then(|vals| match vals.into_iter().next() {
+            Some(AbiValue::String(s)) => Some(s),
+            _ => None,
+        });
+    let decimals = decimals?.and_then(|ret| decimals_of(&ret));
+
+    let meta = TokenMeta { symbol, decimals };
+    TOKEN_META_CACHE.lock().unwrap().insert(*token, meta.clone());
+    Ok(meta)
+}
+
+/// Decode the return of `decimals()`, None if out of range, as from a hostile contract.
+fn decimals_of(ret: &[u8]) -> Option<u8> {
+    abi::decode_params(&["uint256"], &hex::encode(ret))
+        .ok()?
+        .into_iter()
+        .next()
+        .and_then(uint_of)
+        .filter(|val| val.bits() <= 8)
+        .and_then(|val| u8::try_from(val.low_u32()).ok())
+        .filter(|&decimals| decimals <= MAX_DECIMALS)
+}
+
+/// TRC20 `balanceOf`, an error if the call fails or returns no balance, never a made-up zero.
+pub async fn fetch_balance_of(token: &Address, owner: &Address) -> Result<ethabi::Uint, Error> {
+    let mut data = abi::fnhash("balanceOf(address)").to_vec();
+    data.extend(abi::encode_values(&[AbiValue::Address(*owner)]));
+    call_constant_with_data(token, data)
+        .await?
+        .and_then(|ret| abi::decode_params(&["uint256"], &hex::encode(ret)).ok())
+        .and_then(|vals| vals.into_iter().next())
+        .and_then(uint_of)
+        .ok_or(Error::Runtime("balanceOf of the token failed or returned no balance"))
+}
+
+/// Format an integer token amount with decimals, trailing zeros trimmed. The raw amount if decimals are out of
+/// range.
+pub fn format_units(amount: &ethabi::Uint, decimals: u8) -> String {
+    let base = match ethabi::Uint::from(10).checked_pow(decimals.into()) {
+        Some(base) => base,
+        None => return format!("{} (raw amount, decimals unknown)", amount),
+    };
+    let (int_part, frac_part) = amount.div_mod(base);
+    if frac_part.is_zero() {
+        int_part.to_string()
+    } else {
+        let frac = format!("{:0>width$}", frac_part.to_string(), width = decimals as usize);
+        format!("{}.{}", int_part, frac.trim_end_matches('0'))
+    }
+}
+
+/// Parse a decimal token amount into integer units, without going through floats.
+pub fn parse_units(amount: &str, decimals: u8) -> Result<ethabi::Uint, Error> {
+    let amount = amount.replace("_", "");
+    let mut parts = amount.splitn(2, '.');
+    let int_part = parts.next().unwrap_or_default();
+    let frac_part = parts.next().unwrap_or_default();
+    if int_part.is_empty() && frac_part.is_empty() {
+        return Err(Error::Runtime("can not parse empty amount"));
+    }
+    if !int_part.bytes().chain(frac_part.bytes()).all(|c| c.is_ascii_digit()) {
+        return Err(Error::Runtime(
+            "invalid amount, only digits and one decimal point allowed",
+        ));
+    }
+    if frac_part.len() > decimals as usize {
+        return Err(Error::Runtime("amount has more decimal places than the token supports"));
+    }
+    let digits = format!("{}{:0<width$}", int_part, frac_part, width = decimals as usize);
+    ethabi::Uint::from_dec_str(&digits).map_err(|_| Error::Runtime("amount overflows"))
+}
+
+/// A TRC20 transfer with token metadata, ready for display.
+pub struct Trc20TransferSummary<'a> {
+    pub transfer: &'a Trc20Transfer,
+    pub meta: TokenMeta,
+}
+
+impl fmt::Display for Trc20TransferSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symbol = self
+            .meta
+            .symbol
+            .clone()
+            .unwrap_or_else(|| self.transfer.token.to_string());
+        match self.meta.decimals {
+            Some(decimals) => write!(f, "{} {}", format_units(&self.transfer.amount, decimals), symbol)?,
+            None => write!(
+                f,
+                "{} {} (raw amount, decimals() not available)",
+                self.transfer.amount, symbol
+            )?,
+        }
+        write!(f, "  {} -> {}", self.transfer.from, self.transfer.to)
+    }
+}
+
+/// Summarize a TRC20 transfer, looking up token metadata.
+pub fn summarize_transfer(transfer: &Trc20Transfer) -> Result<Trc20TransferSummary, Error> {
+    Ok(Trc20TransferSummary {
+        transfer,
+        meta: get_token_meta(&transfer.token)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(&125_500_000.into(), 6), "125.5");
+        assert_eq!(format_units(&1_000_000.into(), 6), "1");
+        assert_eq!(format_units(&1.into(), 6), "0.000001");
+        assert_eq!(format_units(&42.into(), 0), "42");
+        assert_eq!(format_units(&1.into(), MAX_DECIMALS).len(), 2 + MAX_DECIMALS as usize);
+        assert_eq!(format_units(&42.into(), 78), "42 (raw amount, decimals unknown)");
+        assert_eq!(format_units(&42.into(), 255), "42 (raw amount, decimals unknown)");
+    }
+
+    #[test]
+    fn test_decimals_of() {
+        let ret = |val: u64| abi::encode_values(&[AbiValue::Uint(val.into())]);
+        assert_eq!(decimals_of(&ret(6)), Some(6));
+        assert_eq!(decimals_of(&ret(77)), Some(77));
+        assert_eq!(decimals_of(&ret(78)), None);
+        // not truncated to 0
+        assert_eq!(decimals_of(&ret(256)), None);
+        assert_eq!(decimals_of(b"\x06"), None);
+    }
+
+    #[test]
+    fn test_parse_units() {
+        assert_eq!(parse_units("125.5", 6).unwrap(), 125_500_000.into());
+        assert_eq!(parse_units("1_000", 6).unwrap(), 1_000_000_000.into());
+        assert_eq!(parse_units(".000001", 6).unwrap(), 1.into());
+        assert_eq!(parse_units("42", 0).unwrap(), 42.into());
+        assert_eq!(parse_units("0.1", 18).unwrap(), ethabi::Uint::from(10).pow(17.into()));
+        assert!(parse_units("0.0000001", 6).is_err());
+        assert!(parse_units("1.2.3", 6).is_err());
+        assert!(parse_units("1e6", 6).is_err());
+        assert!(parse_units("-1", 6).is_err());
+        assert!(parse_units("", 6).is_err());
+    }
+
+    #[test]
+    fn test_parse_transfer() {
+        let usdt: Address = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap();
+        let owner: Address = "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap();
+        let mut data = abi::fnhash("transfer(address,uint256)").to_vec();
+        data.extend(abi::encode_values(&[
+            AbiValue::Address(usdt),
+            AbiValue::Uint(125_500_000.into()),
+        ]));
+
+        let transfer = parse_transfer(&owner, &usdt, &data).unwrap();
+        assert_eq!(transfer.from, owner);
+        assert_eq!(transfer.to, usdt);
+        assert_eq!(transfer.amount, 125_500_000.into());
+
+        assert!(parse_transfer(&owner, &usdt, &data[..4]).is_none());
+        assert!(parse_transfer(&owner, &usdt, b"\x00\x00\x00\x00").is_none());
+    }
+}
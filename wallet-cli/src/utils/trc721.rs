@@ -0,0 +1,249 @@
+//! TRC721 (NFT) helpers.
+
+use futures::executor;
+use keys::Address;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::Mutex;
+
+use crate::error::Error;
+use crate::utils::abi::{self, AbiValue};
+use crate::utils::trc20;
+
+/// ERC-165 interface ID of TRC721.
+const TRC721_INTERFACE_ID: [u8; 4] = [0x80, 0xac, 0x58, 0xcd];
+
+lazy_static! {
+    static ref TRC721_CACHE: Mutex<HashMap<Address, bool>> = Mutex::new(HashMap::new());
+}
+
+/// A TRC721 call, `transferFrom`, `safeTransferFrom`, `approve` or `setApprovalForAll`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trc721Call {
+    Transfer {
+        from: Address,
+        to: Address,
+        token_id: ethabi::Uint,
+        /// Trailing bytes of `safeTransferFrom(address,address,uint256,bytes)`.
+        data: Option<Vec<u8>>,
+    },
+    Approve {
+        approved: Address,
+        token_id: ethabi::Uint,
+    },
+    ApprovalForAll {
+        operator: Address,
+        approved: bool,
+    },
+}
+
+/// Detect a TRC721 call from call data by selector. `transferFrom` and `approve` share selectors with TRC20, so
+/// the contract must be checked with `is_trc721`.
+pub fn parse_call(data: &[u8]) -> Option<Trc721Call> {
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, params) = data.split_at(4);
+    let decode = |types: &[&str]| abi::decode_params(types, &hex::encode(params)).ok();
+
+    if selector == abi::fnhash("transferFrom(address,address,uint256)") ||
+        selector == abi::fnhash("safeTransferFrom(address,address,uint256)")
+    {
+        let mut values = decode(&["address", "address", "uint256"])?.into_iter();
+        Some(Trc721Call::Transfer {
+            from: Address::try_from(values.next()?).ok()?,
+            to: Address::try_from(values.next()?).ok()?,
+            token_id: uint_of(values.next()?)?,
+            data: None,
+        })
+    } else if selector == abi::fnhash("safeTransferFrom(address,address,uint256,bytes)") {
+        let mut values = decode(&["address", "address", "uint256", "bytes"])?.into_iter();
+        Some(Trc721Call::Transfer {
+            from: Address::try_from(values.next()?).ok()?,
+            to: Address::try_from(values.next()?).ok()?,
+            token_id: uint_of(values.next()?)?,
+            data: Some(Vec::<u8>::try_from(values.next()?).ok()?),
+        })
+    } else if selector == abi::fnhash("approve(address,uint256)") {
+        let mut values = decode(&["address", "uint256"])?.into_iter();
+        Some(Trc721Call::Approve {
+            approved: Address::try_from(values.next()?).ok()?,
+            token_id: uint_of(values.next()?)?,
+        })
+    } else if selector == abi::fnhash("setApprovalForAll(address,bool)") {
+        let mut values = decode(&["address", "bool"])?.into_iter();
+        Some(Trc721Call::ApprovalForAll {
+            operator: Address::try_from(values.next()?).ok()?,
+            approved: match values.next()? {
+                AbiValue::Bool(approved) => approved,
+                _ => return None,
+            },
+        })
+    } else {
+        None
+    }
+}
+
+fn uint_of(value: AbiValue) -> Option<ethabi::Uint> {
+    match value {
+        AbiValue::Uint(val) => Some(val),
+        _ => None,
+    }
+}
+
+fn supports_interface_call_data(interface_id: &[u8; 4]) -> Vec<u8> {
+    let mut data = abi::fnhash("supportsInterface(bytes4)").to_vec();
+    data.extend_from_slice(interface_id);
+    data.extend_from_slice(&[0u8; 28]);
+    data
+}
+
+/// Whether the contract implements TRC721, by ERC-165 `supportsInterface`, cached.
+pub fn is_trc721(contract: &Address) -> Result<bool, Error> {
+    executor::block_on(fetch_is_trc721(contract))
+}
+
+/// Async version of `is_trc721`.
+pub async fn fetch_is_trc721(contract: &Address) -> Result<bool, Error> {
+    if let Some(&supported) = TRC721_CACHE.lock().unwrap().get(contract) {
+        return Ok(supported);
+    }
+
+    let supported = trc20::call_constant_with_data(contract, supports_interface_call_data(&TRC721_INTERFACE_ID))
+        .await?
+        .and_then(|ret| abi::decode_params(&["bool"], &hex::encode(ret)).ok())
+        .map(|vals| vals.first() == Some(&AbiValue::Bool(true)))
+        .unwrap_or(false);
+
+    TRC721_CACHE.lock().unwrap().insert(*contract, supported);
+    Ok(supported)
+}
+
+impl fmt::Display for Trc721Call {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Trc721Call::Transfer {
+                from,
+                to,
+                token_id,
+                data,
+            } => {
+                write!(f, "NFT Transfer tokenId={} from {} to {}", token_id, from, to)?;
+                if let Some(data) = data {
+                    write!(f, " data=0x{}", hex::encode(data))?;
+                }
+                Ok(())
+            }
+            Trc721Call::Approve { approved, token_id } => write!(f, "NFT Approve tokenId={} to {}", token_id, approved),
+            Trc721Call::ApprovalForAll { operator, approved } => {
+                write!(f, "NFT ApprovalForAll operator={} approved={}", operator, approved)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from() -> Address {
+        "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap()
+    }
+
+    fn to() -> Address {
+        "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap()
+    }
+
+    fn call_data(method: &str, values: &[AbiValue]) -> Vec<u8> {
+        let mut data = abi::fnhash(method).to_vec();
+        data.extend(abi::encode_values(values));
+        data
+    }
+
+    #[test]
+    fn test_parse_transfer_from() {
+        let data = call_data(
+            "transferFrom(address,address,uint256)",
+            &[
+                AbiValue::Address(from()),
+                AbiValue::Address(to()),
+                AbiValue::Uint(42.into()),
+            ],
+        );
+        let call = parse_call(&data).unwrap();
+        assert_eq!(
+            call.to_string(),
+            "NFT Transfer tokenId=42 from TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t to TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt"
+        );
+    }
+
+    #[test]
+    fn test_parse_safe_transfer_from() {
+        let data = call_data(
+            "safeTransferFrom(address,address,uint256)",
+            &[
+                AbiValue::Address(from()),
+                AbiValue::Address(to()),
+                AbiValue::Uint(7.into()),
+            ],
+        );
+        assert_eq!(
+            parse_call(&data).unwrap(),
+            Trc721Call::Transfer {
+                from: from(),
+                to: to(),
+                token_id: 7.into(),
+                data: None,
+            }
+        );
+
+        let data = call_data(
+            "safeTransferFrom(address,address,uint256,bytes)",
+            &[
+                AbiValue::Address(from()),
+                AbiValue::Address(to()),
+                AbiValue::Uint(7.into()),
+                AbiValue::Bytes(vec![0xca, 0xfe]),
+            ],
+        );
+        let call = parse_call(&data).unwrap();
+        assert!(call.to_string().ends_with(" data=0xcafe"));
+    }
+
+    #[test]
+    fn test_parse_approve() {
+        let data = call_data(
+            "approve(address,uint256)",
+            &[AbiValue::Address(to()), AbiValue::Uint(42.into())],
+        );
+        assert_eq!(
+            parse_call(&data).unwrap().to_string(),
+            "NFT Approve tokenId=42 to TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt"
+        );
+    }
+
+    #[test]
+    fn test_parse_set_approval_for_all() {
+        let data = call_data(
+            "setApprovalForAll(address,bool)",
+            &[AbiValue::Address(to()), AbiValue::Bool(true)],
+        );
+        assert_eq!(
+            parse_call(&data).unwrap().to_string(),
+            "NFT ApprovalForAll operator=TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt approved=true"
+        );
+
+        assert!(parse_call(&call_data("transfer(address,uint256)", &[])).is_none());
+        assert!(parse_call(&data[..20]).is_none());
+    }
+
+    #[test]
+    fn test_supports_interface_call_data() {
+        assert_eq!(
+            hex::encode(supports_interface_call_data(&TRC721_INTERFACE_ID)),
+            "01ffc9a780ac58cd00000000000000000000000000000000000000000000000000000000"
+        );
+    }
+}
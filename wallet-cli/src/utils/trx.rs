@@ -2,11 +2,21 @@
 
 use chrono::Utc;
 use clap::ArgMatches;
+use ethabi::{ParamType, Token};
 use futures::executor;
 use hex::{FromHex, ToHex};
-use keys::{Address, Private};
-use proto::api::{BytesMessage, NumberMessage};
+use keys::{Address, MemorySigner, Signer};
+use lazy_static::lazy_static;
+use log::{info, warn};
+use proto::api::{
+    BlockExtention, BytesMessage, EmptyMessage, NumberMessage, Return, Return_response_code as ReturnCode,
+};
 use proto::core::SmartContract_ABI_Entry as AbiEntry;
+use proto::core::{
+    Account, ChainParameters, SmartContract, Transaction, TransactionInfo, TransactionInfo_code as TransactionInfoCode,
+    Transaction_Contract as Contract, Transaction_Contract_ContractType as ContractType,
+    Transaction_raw as TransactionRaw,
+};
 use proto::core::{
     AccountCreateContract, AccountPermissionUpdateContract, AccountUpdateContract, AssetIssueContract,
     ClearABIContract, CreateSmartContract, ExchangeCreateContract, ExchangeInjectContract, ExchangeTransactionContract,
@@ -16,31 +26,94 @@ use proto::core::{
     UpdateAssetContract, UpdateBrokerageContract, UpdateEnergyLimitContract, UpdateSettingContract, VoteAssetContract,
     VoteWitnessContract, WithdrawBalanceContract, WitnessCreateContract, WitnessUpdateContract,
 };
-use proto::core::{
-    Transaction, TransactionInfo, TransactionInfo_code as TransactionInfoCode, Transaction_Contract as Contract,
-    Transaction_Contract_ContractType as ContractType, Transaction_raw as TransactionRaw,
-};
 use protobuf::well_known_types::Any;
 use protobuf::{parse_from_bytes, Message};
 use serde_json::json;
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::{mpsc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crate::commands::wallet::sign_digest;
+use crate::commands::wallet::{self, WalletdSigner};
 use crate::error::Error;
+#[cfg(unix)]
+use crate::utils::agent;
 use crate::utils::client;
 use crate::utils::crypto;
+use crate::utils::journal;
 use crate::utils::jsont;
-use crate::CHAIN_ID;
+use crate::utils::keystore::KeystoreSignerImpl;
+use crate::utils::record::{self, RecordKind};
+use crate::utils::session;
+use crate::{CHAIN_ID, HUMAN_NUMBERS, RESULT_SIZE};
 
-// To calculate bandwidth
+/// How long the latest block is reused, one block interval.
+const LATEST_BLOCK_TTL: Duration = Duration::from_secs(3);
+
+// Kept between commands in the REPL.
+lazy_static! {
+    static ref CONTRACT_ABI_CACHE: Mutex<HashMap<Address, Vec<AbiEntry>>> = Mutex::new(HashMap::new());
+    static ref LATEST_BLOCK_CACHE: Mutex<Option<(Instant, BlockExtention)>> = Mutex::new(None);
+}
+
+/// Size reserved for `Transaction.ret` when charging bandwidth, `Constant.MAX_RESULT_SIZE_IN_TX` of java-tron. The
+/// same on all known networks, a different value can be set by `--result-size`.
 pub const MAX_RESULT_SIZE_IN_TX: usize = 64;
 
+/// The reserved result size in use, `MAX_RESULT_SIZE_IN_TX` unless overridden.
+pub fn result_reserve() -> usize {
+    unsafe { RESULT_SIZE }
+}
+
+/// Serialized size of `Transaction.ret`, with field tags and length prefixes.
+pub fn result_size(txn: &Transaction) -> usize {
+    // `repeated Result ret = 5;` of `Transaction`
+    txn.get_ret()
+        .iter()
+        .map(|ret| {
+            let len = ret.compute_size();
+            (protobuf::rt::tag_size(5) + protobuf::rt::compute_raw_varint32_size(len) + len) as usize
+        })
+        .sum()
+}
+
+/// Bandwidth consumed by a transaction, the serialized size with `Transaction.ret` excluded, plus the reserved
+/// result size. Each signature, 65 bytes or with a surfix, is counted with its own tag and length prefix.
+pub fn bandwidth_of(txn: &Transaction) -> usize {
+    bandwidth_with_result_reserve(txn, result_reserve())
+}
+
+/// Bandwidth consumed by a transaction, with an explicit reserved result size.
+pub fn bandwidth_with_result_reserve(txn: &Transaction, reserve: usize) -> usize {
+    txn.compute_size() as usize - result_size(txn) + reserve
+}
+
 pub fn timestamp_millis() -> i64 {
     Utc::now().timestamp_millis()
 }
 
+/// Transaction hash(txid), sha256 of Transaction.raw.
+pub fn transaction_hash(raw: &TransactionRaw) -> Result<[u8; 32], Error> {
+    Ok(crypto::sha256(&raw.write_to_bytes()?))
+}
+
+/// Digest to sign of a transaction, the txid, or with the chain id for Sun-Network.
+pub fn signing_digest(txid: &[u8; 32]) -> Result<[u8; 32], Error> {
+    // special signature routine for Sun-Network
+    match unsafe { CHAIN_ID } {
+        Some(chain_id) => {
+            let mut raw = txid.to_vec();
+            raw.extend(Vec::from_hex(chain_id)?);
+            Ok(crypto::sha256(&raw))
+        }
+        None => Ok(*txid),
+    }
+}
+
 pub fn extract_owner_address_from_parameter(any: &Any) -> Result<Address, Error> {
     match any.get_type_url() {
         "type.googleapis.com/protocol.TransferContract" => Ok(Address::try_from(
@@ -128,7 +201,207 @@ pub fn extract_owner_address_from_parameter(any: &Any) -> Result<Address, Error>
         "type.googleapis.com/protocol.ExchangeInjectContract" => Ok(Address::try_from(
             parse_from_bytes::<ExchangeInjectContract>(any.get_value())?.get_owner_address(),
         )?),
-        _ => unimplemented!(),
+        _ => Err(Error::Runtime(
+            "can not extract owner address from unsupported contract type",
+        )),
+    }
+}
+
+/// Content of `Transaction.raw.data`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemoContent {
+    Text(String),
+    /// Text of hex digits, with the text it decodes to when that is printable UTF-8.
+    HexText(String, Option<String>),
+    /// A JSON object or array.
+    Json(serde_json::Value),
+    /// Binary data, `0x` prefixed hex string.
+    Hex(String),
+    Empty,
+}
+
+/// Memos above this size are warned of, with the bandwidth they add.
+pub const MEMO_WARN_SIZE: usize = 64;
+/// Maximum memo size by default, overridden by `WALLET_CLI_MAX_MEMO_SIZE`.
+pub const DEFAULT_MAX_MEMO_SIZE: usize = 1024;
+pub const MAX_MEMO_SIZE_ENV: &str = "WALLET_CLI_MAX_MEMO_SIZE";
+/// Price of a bandwidth point when burnt, `getTransactionFee`, in SUN.
+const BANDWIDTH_PRICE: i64 = 1_000;
+
+/// Printable ASCII, with newlines and tabs.
+fn is_printable(text: &str) -> bool {
+    text.bytes()
+        .all(|c| c.is_ascii_graphic() || c == b' ' || c == b'\n' || c == b'\t')
+}
+
+fn looks_like_hex(text: &str) -> bool {
+    let digits = text.trim_start_matches("0x");
+    digits.len() >= 8 &&
+        digits.len() % 2 == 0 &&
+        digits.bytes().all(|c| c.is_ascii_hexdigit()) &&
+        (text.starts_with("0x") || digits.bytes().any(|c| c.is_ascii_alphabetic()))
+}
+
+/// Parse memo(data field) of a transaction. Text is printable ASCII, anything else is shown as hex.
+pub fn parse_memo(data: &[u8]) -> MemoContent {
+    if data.is_empty() {
+        return MemoContent::Empty;
+    }
+    match std::str::from_utf8(data) {
+        Ok(text) if text.starts_with('{') || text.starts_with('[') => match serde_json::from_str(text) {
+            Ok(json) => MemoContent::Json(json),
+            Err(_) if is_printable(text) => MemoContent::Text(text.to_owned()),
+            Err(_) => MemoContent::Hex(format!("0x{}", hex::encode(data))),
+        },
+        Ok(text) if looks_like_hex(text) => {
+            let decoded = hex::decode(text.trim_start_matches("0x"))
+                .ok()
+                .and_then(|raw| String::from_utf8(raw).ok())
+                .filter(|decoded| is_printable(decoded));
+            MemoContent::HexText(text.to_owned(), decoded)
+        }
+        Ok(text) if text.bytes().all(|c| c.is_ascii_graphic() || c == b' ') => MemoContent::Text(text.to_owned()),
+        _ => MemoContent::Hex(format!("0x{}", hex::encode(data))),
+    }
+}
+
+/// Hexdump of binary data, 16 bytes per line with printable ASCII on the right.
+pub fn hexdump(data: &[u8]) -> Vec<String> {
+    data.chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex = chunk.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ");
+            let ascii = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            format!("{:08x}  {:<47}  |{}|", i * 16, hex, ascii)
+        })
+        .collect()
+}
+
+/// Bandwidth added to a transaction by a memo.
+pub fn memo_bandwidth(memo: &[u8]) -> usize {
+    // `bytes data = 10;` of `Transaction.raw`
+    protobuf::rt::bytes_size(10, memo) as usize
+}
+
+fn max_memo_size() -> usize {
+    env::var(MAX_MEMO_SIZE_ENV)
+        .ok()
+        .and_then(|val| val.trim().parse().ok())
+        .unwrap_or(DEFAULT_MAX_MEMO_SIZE)
+}
+
+/// Check memo size against the maximum, warn of the cost of large memos.
+pub fn check_memo_size(memo: &[u8], max_size: usize) -> Result<(), Error> {
+    if memo.len() > max_size {
+        warn!(
+            "Memo is {} bytes, the maximum is {} bytes, set {} to raise it",
+            memo.len(),
+            max_size,
+            MAX_MEMO_SIZE_ENV
+        );
+        return Err(Error::Runtime("memo is too large"));
+    }
+    if memo.len() > MEMO_WARN_SIZE {
+        let bandwidth = memo_bandwidth(memo);
+        warn!(
+            "Memo is {} bytes, adds {} bandwidth, {} TRX if bandwidth is burnt",
+            memo.len(),
+            bandwidth,
+            format_number(bandwidth as f64 * BANDWIDTH_PRICE as f64 / 1_000_000.0)
+        );
+    }
+    Ok(())
+}
+
+/// Memo from `--memo` as UTF-8 text, or `--memo-hex` as raw bytes, checked by `check_memo_size`.
+pub fn memo_of(matches: &ArgMatches) -> Result<Option<Vec<u8>>, Error> {
+    let memo = match (matches.value_of("memo"), matches.value_of("memo-hex")) {
+        (Some(text), _) => text.as_bytes().to_owned(),
+        (_, Some(hex)) => jsont::hex_string_to_bytes(hex)?,
+        _ => return Ok(None),
+    };
+    check_memo_size(&memo, max_memo_size())?;
+    Ok(Some(memo))
+}
+
+/// Log a memo, rendered by its content.
+pub fn print_memo(label: &str, data: &[u8]) {
+    match parse_memo(data) {
+        MemoContent::Text(text) => info!("{}: {:?}", label, text),
+        MemoContent::HexText(text, Some(decoded)) => info!("{}: {} (hex of {:?})", label, text, decoded),
+        MemoContent::HexText(text, None) => info!("{}: {} (hex)", label, text),
+        MemoContent::Json(json) => {
+            info!("{}: JSON", label);
+            for line in serde_json::to_string_pretty(&json).unwrap_or_default().lines() {
+                info!("  {}", line);
+            }
+        }
+        MemoContent::Hex(hex) => {
+            info!("{}: {} ({} bytes binary)", label, hex, data.len());
+            for line in hexdump(data) {
+                info!("  {}", line);
+            }
+        }
+        MemoContent::Empty => (),
+    }
+}
+
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+fn explain_panic_code(code: u64) -> &'static str {
+    match code {
+        0x00 => "generic compiler inserted panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "incorrectly encoded storage byte array",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "too much memory allocated",
+        0x51 => "call to zero-initialized internal function",
+        _ => "unknown panic code",
+    }
+}
+
+/// Decode revert reason from the return data of a reverted call.
+///
+/// Handles `Error(string)` from `require`/`revert`, `Panic(uint256)` from failed assertions and checked arithmetic,
+/// and plain UTF-8 text. None for empty or unrecognized data.
+pub fn decode_revert_reason(data: &[u8]) -> Option<String> {
+    if data.is_empty() {
+        return None;
+    }
+    if data.len() >= 4 && data[..4] == ERROR_SELECTOR {
+        return match ethabi::decode(&[ParamType::String], &data[4..]).ok()?.pop()? {
+            Token::String(reason) => Some(reason),
+            _ => None,
+        };
+    }
+    if data.len() >= 4 && data[..4] == PANIC_SELECTOR {
+        return match ethabi::decode(&[ParamType::Uint(256)], &data[4..]).ok()?.pop()? {
+            Token::Uint(code) if code.bits() <= 64 => Some(format!(
+                "Panic(0x{:02x}): {}",
+                code.low_u64(),
+                explain_panic_code(code.low_u64())
+            )),
+            Token::Uint(code) => Some(format!("Panic({:#x}): unknown panic code", code)),
+            _ => None,
+        };
+    }
+    match std::str::from_utf8(data) {
+        Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => Some(text.to_owned()),
+        _ => None,
     }
 }
 
@@ -141,6 +414,180 @@ pub fn parse_amount(amount: &str) -> Result<i64, Error> {
     Ok(amount.replace("_", "").parse()?)
 }
 
+/// Parse command line address, in base58check, `41`-prefixed hex, `0x41`-prefixed hex or 20-byte hex.
+///
+/// Well-known names like `/0` and `_` are also accepted.
+pub fn parse_address(s: &str) -> Result<Address, Error> {
+    if s == "_" || s == "0x0" || s.starts_with('/') {
+        return Ok(s.parse()?);
+    }
+
+    let is_plain_hex =
+        (s.len() == 42 && s.starts_with("41")) || (s.len() == 40 && s.bytes().all(|c| c.is_ascii_hexdigit()));
+    let raw = if s.starts_with("0x") || s.starts_with("0X") || is_plain_hex {
+        let mut raw = Vec::from_hex(s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).unwrap_or(s))
+            .map_err(|_| Error::InvalidInput("invalid hex in address"))?;
+        // 20-byte form, as shown by Ethereum explorers
+        if raw.len() == 20 {
+            raw.insert(0, 0x41);
+        }
+        if raw.len() != 21 {
+            return Err(Error::InvalidInput("wrong address length"));
+        }
+        raw
+    } else {
+        // b58decode_check requires at least 4 bytes of checksum
+        if s.len() < 6 {
+            return Err(Error::InvalidInput("wrong address length"));
+        }
+        let raw = keys::b58decode_check(s).map_err(|e| match e {
+            keys::Error::InvalidChecksum => Error::InvalidInput("invalid address checksum"),
+            _ => Error::InvalidInput("invalid base58 in address"),
+        })?;
+        if raw.len() != 21 {
+            return Err(Error::InvalidInput("wrong address length"));
+        }
+        raw
+    };
+    if raw[0] != 0x41 {
+        return Err(Error::InvalidInput("invalid address prefix, must be 0x41"));
+    }
+    Ok(Address::try_from(raw)?)
+}
+
+/// Is the account activated on chain, by `GetAccount`.
+pub fn account_exists(addr: &Address) -> Result<bool, Error> {
+    let mut req = Account::new();
+    req.set_address(addr.as_bytes().to_owned());
+    let account = executor::block_on(client::GRPC_CLIENT.get_account(Default::default(), req).drop_metadata())?;
+    Ok(!account.get_address().is_empty())
+}
+
+/// Value of a chain parameter, `default` if the node does not report it.
+pub fn chain_parameter(params: &ChainParameters, key: &str, default: i64) -> i64 {
+    params
+        .get_chainParameter()
+        .iter()
+        .find(|param| param.get_key() == key)
+        .map_or(default, |param| param.get_value())
+}
+
+/// Chain parameters by proposal parameter id, `ProposalType` of java-tron, with their `GetChainParameters` keys.
+const CHAIN_PARAMETER_KEYS: &[(i64, &str)] = &[
+    (0, "getMaintenanceTimeInterval"),
+    (1, "getAccountUpgradeCost"),
+    (2, "getCreateAccountFee"),
+    (3, "getTransactionFee"),
+    (4, "getAssetIssueFee"),
+    (5, "getWitnessPayPerBlock"),
+    (6, "getWitnessStandbyAllowance"),
+    (7, "getCreateNewAccountFeeInSystemContract"),
+    (8, "getCreateNewAccountBandwidthRate"),
+    (9, "getAllowCreationOfContracts"),
+    (10, "getRemoveThePowerOfTheGr"),
+    (11, "getEnergyFee"),
+    (12, "getExchangeCreateFee"),
+    (13, "getMaxCpuTimeOfOneTx"),
+    (14, "getAllowUpdateAccountName"),
+    (15, "getAllowSameTokenName"),
+    (16, "getAllowDelegateResource"),
+    (17, "getTotalEnergyLimit"),
+    (18, "getAllowTvmTransferTrc10"),
+    (19, "getTotalEnergyCurrentLimit"),
+    (20, "getAllowMultiSign"),
+    (21, "getAllowAdaptiveEnergy"),
+    (22, "getUpdateAccountPermissionFee"),
+    (23, "getMultiSignFee"),
+    (24, "getAllowProtoFilterNum"),
+    (25, "getAllowAccountStateRoot"),
+    (26, "getAllowTvmConstantinople"),
+    (27, "getAllowShieldedTransaction"),
+    (28, "getShieldedTransactionFee"),
+    (29, "getAdaptiveResourceLimitMultiplier"),
+    (30, "getChangeDelegation"),
+    (31, "getWitness127PayPerBlock"),
+    (32, "getAllowTvmSolidity059"),
+    (33, "getAdaptiveResourceLimitTargetRatio"),
+    (34, "getShieldedTransactionCreateAccountFee"),
+    (35, "getForbidTransferToContract"),
+    (39, "getAllowShieldedTRC20Transaction"),
+    (40, "getAllowPBFT"),
+    (41, "getAllowTvmIstanbul"),
+];
+
+/// Key of a chain parameter by its proposal parameter id, `None` if unknown.
+pub fn chain_parameter_key(id: i64) -> Option<&'static str> {
+    CHAIN_PARAMETER_KEYS
+        .iter()
+        .find(|&&(param_id, _)| param_id == id)
+        .map(|&(_, key)| key)
+}
+
+/// Current price of a bandwidth point when burnt, in SUN.
+pub fn bandwidth_price_in_sun() -> Result<i64, Error> {
+    let params = executor::block_on(
+        client::GRPC_CLIENT
+            .get_chain_parameters(Default::default(), EmptyMessage::new())
+            .drop_metadata(),
+    )?;
+    bandwidth_price_of(&params)
+}
+
+/// The bandwidth price is the `getTransactionFee` chain parameter.
+fn bandwidth_price_of(params: &ChainParameters) -> Result<i64, Error> {
+    match chain_parameter(params, "getTransactionFee", 0) {
+        price if price > 0 => Ok(price),
+        _ => Err(Error::Runtime("bandwidth price is not reported by the node")),
+    }
+}
+
+/// Fees of activating a new account by a transfer, in SUN.
+///
+/// Returns the fee burnt by the transfer, and the fee burnt instead of bandwidth when the sender has not enough.
+fn activation_fees(params: &ChainParameters) -> (i64, i64) {
+    (
+        chain_parameter(params, "getCreateNewAccountFeeInSystemContract", 1_000_000),
+        chain_parameter(params, "getCreateAccountFee", 100_000),
+    )
+}
+
+/// Check whether the recipient of a transfer is activated.
+///
+/// TRX and TRC10 transfers (`activates`) activate a new recipient, the cost is logged and `true` is returned, so
+/// that the caller can ask for confirmation. Other transfers only warn that the recipient can not pay fees. Failing
+/// lookups are warnings too.
+pub fn check_activation(recipient: &Address, activates: bool) -> Result<bool, Error> {
+    match account_exists(recipient) {
+        Ok(true) => Ok(false),
+        Ok(false) if activates => {
+            let params = executor::block_on(
+                client::GRPC_CLIENT
+                    .get_chain_parameters(Default::default(), EmptyMessage::new())
+                    .drop_metadata(),
+            )?;
+            let (fee, bandwidth_fee) = activation_fees(&params);
+            warn!("Recipient {} is not activated, this transfer activates it", recipient);
+            warn!(
+                "Activation burns {}, and consumes bandwidth of the sender or burns {}",
+                format_amount_with_surfix(fee, "TRX", 6),
+                format_amount_with_surfix(bandwidth_fee, "TRX", 6)
+            );
+            Ok(true)
+        }
+        Ok(false) => {
+            warn!(
+                "Recipient {} is not activated, tokens will be received but it can not pay fees",
+                recipient
+            );
+            Ok(false)
+        }
+        Err(e) => {
+            warn!("Can not check whether recipient {} is activated: {}", recipient, e);
+            Ok(false)
+        }
+    }
+}
+
 /// Parse command line amount to amount in pb.
 pub fn parse_amount_with_surfix(amount: &str, surfix: &str, precision: u32) -> Result<i64, Error> {
     if amount.is_empty() {
@@ -162,15 +609,231 @@ pub fn parse_amount_with_surfix(amount: &str, surfix: &str, precision: u32) -> R
     }
 }
 
+/// Timestamp of a new transaction in millis, `--timestamp` or now.
+pub fn transaction_timestamp(matches: &ArgMatches) -> Result<i64, Error> {
+    match matches.value_of("timestamp") {
+        Some(ts) => Ok(ts.parse()?),
+        None => Ok(timestamp_millis()),
+    }
+}
+
+/// ID of the reference block for TAPOS, from `--ref-block-hash`, `--ref-block` or the latest block.
+pub fn ref_block_id(matches: &ArgMatches) -> Result<Vec<u8>, Error> {
+    if let Some(block_id) = matches.value_of("ref-block-hash") {
+        let block_id = Vec::from_hex(block_id.trim_start_matches("0x"))?;
+        if block_id.len() != 32 {
+            return Err(Error::InvalidInput("ref block hash must be a 32-byte block ID"));
+        }
+        return Ok(block_id);
+    }
+
+    let ref_block = match matches.value_of("ref-block") {
+        Some(num) => {
+            let mut req = NumberMessage::new();
+            req.set_num(num.parse()?);
+            executor::block_on(
+                client::GRPC_CLIENT
+                    .get_block_by_num2(Default::default(), req)
+                    .drop_metadata(),
+            )?
+        }
+        None => get_latest_block()?,
+    };
+    Ok(ref_block.blockid)
+}
+
+/// The latest block, reused for `LATEST_BLOCK_TTL`.
+pub fn get_latest_block() -> Result<BlockExtention, Error> {
+    if let Some((fetched_at, block)) = &*LATEST_BLOCK_CACHE.lock().unwrap() {
+        if fetched_at.elapsed() < LATEST_BLOCK_TTL {
+            return Ok(block.clone());
+        }
+    }
+    let block = executor::block_on(
+        client::GRPC_CLIENT
+            .get_now_block2(Default::default(), EmptyMessage::new())
+            .drop_metadata(),
+    )?;
+    *LATEST_BLOCK_CACHE.lock().unwrap() = Some((Instant::now(), block.clone()));
+    Ok(block)
+}
+
+/// Fill TAPOS fields from a block ID, whose first 8 bytes are the block number.
+pub fn set_ref_block(raw: &mut TransactionRaw, block_id: &[u8]) {
+    raw.set_ref_block_bytes(block_id[6..8].to_owned());
+    raw.set_ref_block_hash(block_id[8..16].to_owned());
+}
+
+/// Whether TAPOS fields of a transaction point to the block ID.
+pub fn ref_block_matches(raw: &TransactionRaw, block_id: &[u8]) -> bool {
+    block_id.len() == 32 && raw.get_ref_block_bytes() == &block_id[6..8] && raw.get_ref_block_hash() == &block_id[8..16]
+}
+
+/// Number of the reference block, the latest block not above `head` whose low 16 bits are `ref_block_bytes`.
+fn ref_block_number(head: i64, ref_block_bytes: &[u8]) -> Option<i64> {
+    if ref_block_bytes.len() != 2 {
+        return None;
+    }
+    let low = i64::from(u16::from_be_bytes([ref_block_bytes[0], ref_block_bytes[1]]));
+    let num = (head & !0xffff) | low;
+    match if num > head { num - 0x1_0000 } else { num } {
+        num if num >= 0 => Some(num),
+        _ => None,
+    }
+}
+
+/// Check whether the reference block of a pre-built transaction is on the chain of the connected node.
+pub fn check_ref_block(raw: &TransactionRaw) -> Result<bool, Error> {
+    let head = get_latest_block()?;
+    let num = match ref_block_number(head.get_block_header().get_raw_data().number, raw.get_ref_block_bytes()) {
+        Some(num) => num,
+        None => return Ok(false),
+    };
+    let mut req = NumberMessage::new();
+    req.set_num(num);
+    let block = executor::block_on(
+        client::GRPC_CLIENT
+            .get_block_by_num2(Default::default(), req)
+            .drop_metadata(),
+    )?;
+    Ok(ref_block_matches(raw, block.get_blockid()))
+}
+
 #[inline]
 pub fn format_amount_with_surfix(amount: i64, surfix: &str, precision: u32) -> String {
-    format!("{} {}", amount as f64 / (10 as f64).powf(precision as f64), surfix)
+    format!(
+        "{} {}",
+        format_number(amount as f64 / (10 as f64).powf(precision as f64)),
+        surfix
+    )
 }
 
+/// Format an amount in SUN as TRX with all 6 decimal places, like `0.267000`. Exact, unlike dividing as `f64`.
+pub fn format_trx(amount_in_sun: i64) -> String {
+    format_token_amount(amount_in_sun, 6, "")
+}
+
+/// Format a raw token amount with its decimals, like `1.234567 USDT`. Exact for the whole `i64` range.
+pub fn format_token_amount(raw: i64, decimals: u32, symbol: &str) -> String {
+    let digits = format!("{:0>width$}", raw.unsigned_abs(), width = decimals as usize + 1);
+    let (int, frac) = digits.split_at(digits.len() - decimals as usize);
+    let sign = if raw < 0 { "-" } else { "" };
+    let amount = if frac.is_empty() {
+        format_number(format!("{}{}", sign, int))
+    } else {
+        format_number(format!("{}{}.{}", sign, int, frac))
+    };
+    if symbol.is_empty() {
+        amount
+    } else {
+        format!("{} {}", amount, symbol)
+    }
+}
+
+/// Format a raw amount of a TRC10 asset, by its precision and abbr. Falls back to the raw amount when the asset
+/// can not be fetched.
+pub fn format_asset_amount(raw: i64, asset_id: &str) -> String {
+    match get_asset(asset_id) {
+        Ok(asset) => format_token_amount(
+            raw,
+            asset.get_precision() as u32,
+            &String::from_utf8_lossy(asset.get_abbr()),
+        ),
+        Err(e) => {
+            warn!("Can not get asset {}: {}", asset_id, e);
+            format!("{} (raw)", format_number(raw))
+        }
+    }
+}
+
+/// Format a number for human-readable output, digits grouped by commas under `--human`.
+pub fn format_number<T: std::fmt::Display>(num: T) -> String {
+    render_number(num, unsafe { HUMAN_NUMBERS })
+}
+
+fn render_number<T: std::fmt::Display>(num: T, human: bool) -> String {
+    let raw = num.to_string();
+    if !human {
+        return raw;
+    }
+    let (sign, unsigned) = match raw.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", &raw[..]),
+    };
+    let (int, frac) = match unsigned.find('.') {
+        Some(pos) => unsigned.split_at(pos),
+        None => (unsigned, ""),
+    };
+    let mut grouped = String::new();
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    format!("{}{}{}", sign, grouped, frac)
+}
+
+/// Signer of a key unlocked in this process, or in the agent.
+fn unlocked_signer_of(addr: &Address) -> Option<Box<dyn Signer>> {
+    let signer = session::signer_of(addr);
+    #[cfg(unix)]
+    let signer = signer.or_else(|| agent::signer_of(addr));
+    signer
+}
+
+fn unlocked_signer_of_keystore(path: &Path) -> Option<Box<dyn Signer>> {
+    let signer = session::signer_of_keystore(path);
+    #[cfg(unix)]
+    let signer = signer.or_else(|| agent::signer_of_keystore(path));
+    signer
+}
+
+/// Signer selected by `--private-key`, `--keystore` or `--account`, otherwise the walletd key of the owner. Keys
+/// unlocked in the session or in the agent are used without a password prompt.
+pub fn signer_of(matches: &ArgMatches, owner_address: Option<Address>) -> Result<Box<dyn Signer>, Error> {
+    if let Some(raw_key) = matches.value_of("private-key") {
+        info!("Signing using raw private key from --private-key");
+        return Ok(Box::new(MemorySigner::new(raw_key.parse()?)));
+    }
+    if let Some(path) = matches.value_of("keystore") {
+        if let Some(signer) = unlocked_signer_of_keystore(Path::new(path)) {
+            info!("Signing using unlocked keystore key {:}", signer.address());
+            return Ok(signer);
+        }
+        let password = wallet::prompt_password("Keystore Password:")?;
+        let signer = KeystoreSignerImpl::open(Path::new(path), &password)?;
+        info!("Signing using keystore key {:}", signer.address());
+        return Ok(Box::new(signer));
+    }
+    let owner_address = matches
+        .value_of("account")
+        .and_then(|addr| addr.parse().ok())
+        .or(owner_address)
+        .ok_or(Error::Runtime("can not determine owner address for signing"))?;
+    if let Some(signer) = unlocked_signer_of(&owner_address) {
+        info!("Signing using unlocked key {:}", owner_address);
+        return Ok(signer);
+    }
+    info!("Signing using wallet key {:}", owner_address);
+    Ok(Box::new(WalletdSigner::new(owner_address)))
+}
+
+/// Builds, signs and broadcasts a transaction from command line arguments.
+///
+/// Besides the arguments, a transaction depends on the current time and the latest block. To build byte-identical
+/// transactions (and txids) on different machines, pin them with `--deterministic`, which requires:
+///
+/// - `--timestamp`, for `raw_data.timestamp`, `raw_data.expiration` is relative to it
+/// - `--ref-block-hash`, the block ID for `raw_data.ref_block_bytes` and `raw_data.ref_block_hash`
+///
+/// `--expiration`, `--fee-limit`, `--memo` and `--permission-id` must also be the same. Protobuf serialization is
+/// deterministic, fields are written in field number order.
 pub struct TransactionHandler<'a, C> {
     contract: C,
     arg_matches: &'a ArgMatches<'a>,
     raw_trx_fn: Option<Box<dyn FnMut(&mut TransactionRaw) -> () + 'static>>,
+    signer: Option<Box<dyn Signer>>,
     txid: Option<[u8; 32]>,
     broadcasted: bool,
 }
@@ -181,6 +844,7 @@ impl<'a, C: ContractPbExt> TransactionHandler<'a, C> {
             contract,
             arg_matches: matches,
             raw_trx_fn: None,
+            signer: None,
             txid: None,
             broadcasted: false,
         }
@@ -194,6 +858,12 @@ impl<'a, C: ContractPbExt> TransactionHandler<'a, C> {
         self
     }
 
+    /// Sign with the signer, instead of the one selected by command line options.
+    pub fn signer(&mut self, signer: Box<dyn Signer>) -> &mut Self {
+        self.signer = Some(signer);
+        self
+    }
+
     /// Extract the filled Transaction.raw
     pub fn to_raw_transaction(&mut self) -> Result<TransactionRaw, Error> {
         let matches = self.arg_matches;
@@ -211,8 +881,8 @@ impl<'a, C: ContractPbExt> TransactionHandler<'a, C> {
         let mut raw = TransactionRaw::new();
         raw.set_contract(vec![contract].into());
 
-        if let Some(memo) = matches.value_of("memo") {
-            raw.set_data(memo.as_bytes().to_owned())
+        if let Some(memo) = memo_of(matches)? {
+            raw.set_data(memo);
         }
 
         if let Some(f) = self.raw_trx_fn.as_mut() {
@@ -224,38 +894,13 @@ impl<'a, C: ContractPbExt> TransactionHandler<'a, C> {
             raw.set_fee_limit(limit);
         }
 
+        let timestamp = transaction_timestamp(matches)?;
         let expiration = matches.value_of("expiration").unwrap_or("60").parse::<i64>()?;
-        raw.set_expiration(timestamp_millis() + 1000 * expiration);
-
-        // fill ref_block info
-        let ref_block = match matches.value_of("ref-block") {
-            Some(num) => {
-                let mut req = NumberMessage::new();
-                req.set_num(num.parse()?);
-                let block = executor::block_on(
-                    client::GRPC_CLIENT
-                        .get_block_by_num2(Default::default(), req)
-                        .drop_metadata(),
-                )?;
-                block
-            }
-            None => {
-                let block = executor::block_on(
-                    client::GRPC_CLIENT
-                        .get_now_block2(Default::default(), Default::default())
-                        .drop_metadata(),
-                )?;
-                block
-            }
-        };
-        let ref_block_number = ref_block.get_block_header().get_raw_data().number;
-        raw.set_ref_block_bytes(vec![
-            ((ref_block_number & 0xff00) >> 8) as u8,
-            (ref_block_number & 0xff) as u8,
-        ]);
-        raw.set_ref_block_hash(ref_block.blockid[8..16].to_owned());
-
-        raw.set_timestamp(timestamp_millis());
+        raw.set_expiration(timestamp + 1000 * expiration);
+
+        set_ref_block(&mut raw, &ref_block_id(matches)?);
+
+        raw.set_timestamp(timestamp);
         Ok(raw)
     }
 
@@ -264,41 +909,31 @@ impl<'a, C: ContractPbExt> TransactionHandler<'a, C> {
         let matches = self.arg_matches;
 
         // signature
-        let txid = crypto::sha256(&raw.write_to_bytes()?);
+        let txid = transaction_hash(&raw)?;
         self.txid = Some(txid);
 
-        // special signature routine for Sun-Network
-        let digest = if let Some(chain_id) = unsafe { CHAIN_ID } {
-            let mut raw = (&txid[..]).to_owned();
-            raw.extend(Vec::from_hex(chain_id)?);
-            crypto::sha256(&raw)
-        } else {
-            txid
-        };
+        let digest = signing_digest(&txid)?;
         let mut signatures: Vec<Vec<u8>> = Vec::new();
+        let mut signer_address = None;
         if !matches.is_present("skip-sign") {
-            let signature = if let Some(raw_key) = matches.value_of("private-key") {
-                eprintln!("! Signing using raw private key from --private-key");
-                let priv_key = raw_key.parse::<Private>()?;
-                priv_key.sign_digest(&digest)?[..].to_owned()
-            } else {
-                let owner_address = matches
-                    .value_of("account")
-                    .and_then(|addr| addr.parse().ok())
-                    .or_else(|| extract_owner_address_from_parameter(raw.contract[0].get_parameter()).ok())
-                    .ok_or(Error::Runtime("can not determine owner address for signing"))?;
-                eprintln!("! Signing using wallet key {:}", owner_address);
-                sign_digest(&digest, &owner_address)?
+            let signer = match self.signer.take() {
+                Some(signer) => signer,
+                None => signer_of(
+                    matches,
+                    extract_owner_address_from_parameter(raw.contract[0].get_parameter()).ok(),
+                )?,
             };
             // NOTE: signature can have arbitrary surfix.
-            signatures.push(signature);
+            signatures.push(signer.sign(&digest)?.to_vec());
+            signer_address = Some(signer.address());
         }
 
         let mut req = Transaction::new();
         req.set_raw_data(raw);
         req.set_signature(signatures.into());
 
-        eprintln!("! TX: {:}", txid.encode_hex::<String>());
+        info!("TX: {:}", txid.encode_hex::<String>());
+        print_memo("Memo", req.get_raw_data().get_data());
 
         // skip-sign implies dont-broadcast
         if matches.is_present("skip-sign") || matches.is_present("dont-broadcast") {
@@ -310,23 +945,47 @@ impl<'a, C: ContractPbExt> TransactionHandler<'a, C> {
 
             Ok(())
         } else {
-            eprintln!("! Bandwidth: {}", req.compute_size() as usize + MAX_RESULT_SIZE_IN_TX);
+            info!("Bandwidth: {}", format_number(bandwidth_of(&req)));
 
-            let payload = executor::block_on(
-                client::GRPC_CLIENT
-                    .broadcast_transaction(Default::default(), req)
-                    .drop_metadata(),
+            let txn = req.clone();
+            let status = broadcast_with_lookup(
+                &txid,
+                move || {
+                    Ok(executor::block_on(
+                        client::GRPC_CLIENT
+                            .broadcast_transaction(Default::default(), req)
+                            .drop_metadata(),
+                    )?)
+                },
+                get_transaction_info_by_hash,
+                &BroadcastPolicy::default(),
             )?;
+            let payload = match status {
+                BroadcastStatus::Responded(payload) => payload,
+                BroadcastStatus::Included(info) => {
+                    journal::append(&txn, &txid, signer_address.as_ref(), journal::RESULT_INCLUDED);
+                    info!(
+                        "Broadcast timed out, but transaction is included in block #{}",
+                        info.get_blockNumber()
+                    );
+                    self.broadcasted = true;
+                    return Ok(());
+                }
+                BroadcastStatus::NotIncluded => {
+                    journal::append(&txn, &txid, signer_address.as_ref(), journal::RESULT_TIMEOUT);
+                    return Err(Error::Timeout(
+                        "broadcast timed out and transaction is not included yet, check with `get transaction_info`",
+                    ));
+                }
+            };
+            journal::append(&txn, &txid, signer_address.as_ref(), &journal::result_of(&payload));
             let mut result = serde_json::to_value(&payload)?;
             jsont::fix_api_return(&mut result);
-            eprintln!("got => {:}", serde_json::to_string_pretty(&result)?);
+            info!("got => {:}", serde_json::to_string_pretty(&result)?);
 
-            if result["result"].as_bool().unwrap_or(false) {
-                self.broadcasted = true;
-                Ok(())
-            } else {
-                Err(Error::Runtime("broadcast transaction failed!"))
-            }
+            check_broadcast_return(&payload)?;
+            self.broadcasted = true;
+            Ok(())
         }
     }
 
@@ -343,15 +1002,9 @@ impl<'a, C: ContractPbExt> TransactionHandler<'a, C> {
             return Ok(());
         }
         if let Some(ref txid) = self.txid {
-            eprintln!("! Watching ... sleep for 4 secs");
+            info!("Watching ... sleep for 4 secs");
             thread::sleep(Duration::from_secs(4));
-            let mut req = BytesMessage::new();
-            req.set_value(txid[..].to_owned());
-            let trx_info = executor::block_on(
-                client::GRPC_CLIENT
-                    .get_transaction_info_by_id(Default::default(), req)
-                    .drop_metadata(),
-            )?;
+            let trx_info = client::get_transaction_info_by_id(&txid[..])?;
             let mut json = serde_json::to_value(&trx_info)?;
             jsont::fix_transaction_info(&mut json);
 
@@ -364,15 +1017,260 @@ impl<'a, C: ContractPbExt> TransactionHandler<'a, C> {
     }
 }
 
-pub fn get_contract_abi(address: &Address) -> Result<Vec<AbiEntry>, Error> {
+/// How to resolve a broadcast when the node does not answer in time.
+pub struct BroadcastPolicy {
+    pub timeout: Duration,
+    pub lookup_attempts: usize,
+    pub lookup_interval: Duration,
+}
+
+impl Default for BroadcastPolicy {
+    fn default() -> Self {
+        BroadcastPolicy {
+            timeout: Duration::from_secs(10),
+            lookup_attempts: 3,
+            lookup_interval: Duration::from_secs(3),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BroadcastStatus {
+    /// The node answered the broadcast.
+    Responded(Return),
+    /// Broadcast timed out, but the transaction is found on chain.
+    Included(TransactionInfo),
+    /// Broadcast timed out, and the transaction is not found on chain after all lookups.
+    NotIncluded,
+}
+
+/// Broadcast a transaction. On timeout, look up the transaction by its locally computed hash instead of
+/// re-broadcasting, so the caller always gets a definitive status.
+pub fn broadcast_with_lookup<B, L>(
+    txid: &[u8; 32],
+    broadcast: B,
+    lookup: L,
+    policy: &BroadcastPolicy,
+) -> Result<BroadcastStatus, Error>
+where
+    B: FnOnce() -> Result<Return, Error> + Send + 'static,
+    L: Fn(&[u8; 32]) -> Result<Option<TransactionInfo>, Error>,
+{
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        // receiver might be gone after timeout
+        let _ = tx.send(broadcast());
+    });
+
+    match rx.recv_timeout(policy.timeout) {
+        Ok(result) => result.map(BroadcastStatus::Responded),
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            warn!("Broadcast timed out, looking up transaction ...");
+            for i in 0..policy.lookup_attempts {
+                if i > 0 {
+                    thread::sleep(policy.lookup_interval);
+                }
+                if let Some(info) = lookup(txid)? {
+                    return Ok(BroadcastStatus::Included(info));
+                }
+            }
+            Ok(BroadcastStatus::NotIncluded)
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(Error::Runtime("broadcast aborted")),
+    }
+}
+
+/// Explanation and suggested fix of a broadcast return code.
+pub fn explain_return_code(code: ReturnCode) -> (&'static str, &'static str) {
+    match code {
+        ReturnCode::SUCCESS => ("transaction accepted", "nothing to do"),
+        ReturnCode::SIGERROR => (
+            "signature does not match the owner or permission",
+            "sign with the key of the owner address, or check --permission-id",
+        ),
+        ReturnCode::CONTRACT_VALIDATE_ERROR => (
+            "contract validation failed, e.g. insufficient balance or invalid parameter",
+            "check the message below and the account state with `get account`",
+        ),
+        ReturnCode::CONTRACT_EXE_ERROR => (
+            "contract execution failed",
+            "check energy and fee limit, try the call with `--const` first",
+        ),
+        ReturnCode::BANDWITH_ERROR => (
+            "not enough bandwidth or TRX to burn for bandwidth",
+            "freeze TRX for bandwidth, or keep enough TRX in the account",
+        ),
+        ReturnCode::DUP_TRANSACTION_ERROR => (
+            "transaction already broadcasted",
+            "check it with `get transaction`, or rebuild it to get a new txid",
+        ),
+        ReturnCode::TAPOS_ERROR => (
+            "ref block too old or not on this chain",
+            "rebuild the transaction against a recent block",
+        ),
+        ReturnCode::TOO_BIG_TRANSACTION_ERROR => (
+            "transaction too big",
+            "shorten memo or contract data, or split into several transactions",
+        ),
+        ReturnCode::TRANSACTION_EXPIRATION_ERROR => (
+            "transaction expired",
+            "rebuild with a later --expiration, and sign and broadcast in time",
+        ),
+        ReturnCode::SERVER_BUSY => ("node is busy", "retry later, or use another node with --rpc-addr"),
+        ReturnCode::NO_CONNECTION => (
+            "node has no connection to peers",
+            "retry later, or use another node with --rpc-addr",
+        ),
+        ReturnCode::NOT_ENOUGH_EFFECTIVE_CONNECTION => (
+            "node has not enough effective connections to peers",
+            "retry later, or use another node with --rpc-addr",
+        ),
+        ReturnCode::OTHER_ERROR => ("unknown error", "check the message below"),
+    }
+}
+
+/// Human readable name of a contract type, like `TRX Transfer` for `TransferContract`.
+pub fn format_contract_type(ct: ContractType) -> &'static str {
+    match ct {
+        ContractType::AccountCreateContract => "Create Account",
+        ContractType::TransferContract => "TRX Transfer",
+        ContractType::TransferAssetContract => "TRC10 Transfer",
+        ContractType::VoteAssetContract => "Vote Asset",
+        ContractType::VoteWitnessContract => "Vote SR",
+        ContractType::WitnessCreateContract => "Create SR",
+        ContractType::AssetIssueContract => "Issue TRC10",
+        ContractType::WitnessUpdateContract => "Update SR",
+        ContractType::ParticipateAssetIssueContract => "Participate TRC10 Issue",
+        ContractType::AccountUpdateContract => "Update Account Name",
+        ContractType::FreezeBalanceContract => "Freeze Balance",
+        ContractType::UnfreezeBalanceContract => "Unfreeze Balance",
+        ContractType::WithdrawBalanceContract => "Claim Rewards",
+        ContractType::UnfreezeAssetContract => "Unfreeze TRC10",
+        ContractType::UpdateAssetContract => "Update TRC10",
+        ContractType::ProposalCreateContract => "Create Proposal",
+        ContractType::ProposalApproveContract => "Approve Proposal",
+        ContractType::ProposalDeleteContract => "Delete Proposal",
+        ContractType::SetAccountIdContract => "Set Account ID",
+        ContractType::CustomContract => "Custom Contract",
+        ContractType::CreateSmartContract => "Deploy Smart Contract",
+        ContractType::TriggerSmartContract => "Smart Contract Call",
+        ContractType::GetContract => "Get Contract",
+        ContractType::UpdateSettingContract => "Update Contract Setting",
+        ContractType::ExchangeCreateContract => "Create Exchange",
+        ContractType::ExchangeInjectContract => "Inject Exchange",
+        ContractType::ExchangeWithdrawContract => "Withdraw Exchange",
+        ContractType::ExchangeTransactionContract => "Exchange Trade",
+        ContractType::UpdateEnergyLimitContract => "Update Contract Energy Limit",
+        ContractType::AccountPermissionUpdateContract => "Update Account Permission",
+        ContractType::ClearABIContract => "Clear Contract ABI",
+        ContractType::UpdateBrokerageContract => "Update Brokerage",
+        ContractType::ShieldedTransferContract => "Shielded Transfer",
+    }
+}
+
+/// Ask a yes/no question on the terminal, defaults to no.
+pub fn confirm(question: &str) -> Result<bool, Error> {
+    eprint!("! {} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+/// Check a broadcast `Return`, with message decoded as UTF-8.
+pub fn check_broadcast_return(ret: &Return) -> Result<(), Error> {
+    if ret.get_result() {
+        Ok(())
+    } else {
+        Err(Error::Broadcast {
+            code: ret.get_code(),
+            message: String::from_utf8_lossy(ret.get_message()).into_owned(),
+        })
+    }
+}
+
+/// Get TransactionInfo by transaction hash, None if not found.
+pub fn get_transaction_info_by_hash(txid: &[u8; 32]) -> Result<Option<TransactionInfo>, Error> {
+    let info = client::get_transaction_info_by_id(&txid[..])?;
+    if info.get_id().is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(info))
+    }
+}
+
+/// Get a TRC10 asset by its numeric id, or by its name.
+pub fn get_asset(id_or_name: &str) -> Result<AssetIssueContract, Error> {
+    let mut req = BytesMessage::new();
+    req.set_value(id_or_name.as_bytes().to_owned());
+
+    let payload = if !id_or_name.is_empty() && id_or_name.bytes().all(|c| c.is_ascii_digit()) {
+        executor::block_on(
+            client::GRPC_CLIENT
+                .get_asset_issue_by_id(Default::default(), req)
+                .drop_metadata(),
+        )?
+    } else {
+        executor::block_on(
+            client::GRPC_CLIENT
+                .get_asset_issue_by_name(Default::default(), req)
+                .drop_metadata(),
+        )?
+    };
+    if payload.get_id().is_empty() && payload.get_name().is_empty() {
+        return Err(Error::NotFound("asset not found"));
+    }
+    Ok(payload)
+}
+
+/// Decimal places of a TRC10 asset.
+pub fn get_asset_precision(id: &str) -> Result<u8, Error> {
+    Ok(get_asset(id)?.get_precision() as u8)
+}
+
+/// Brokerage of an SR, the percentage of rewards kept by the SR.
+pub fn get_brokerage(address: &Address) -> Result<i32, Error> {
     let mut req = BytesMessage::new();
     req.set_value(address.as_bytes().to_owned());
-    let mut payload = executor::block_on(
+
+    let payload = executor::block_on(
         client::GRPC_CLIENT
-            .get_contract(Default::default(), req)
+            .get_brokerage_info(Default::default(), req)
             .drop_metadata(),
     )?;
-    Ok(payload.mut_abi().take_entrys().into())
+    Ok(payload.get_num() as i32)
+}
+
+pub fn get_contract_abi(address: &Address) -> Result<Vec<AbiEntry>, Error> {
+    executor::block_on(fetch_contract_abi(address))
+}
+
+/// ABI of a contract, cached.
+pub async fn fetch_contract_abi(address: &Address) -> Result<Vec<AbiEntry>, Error> {
+    if let Some(abi) = CONTRACT_ABI_CACHE.lock().unwrap().get(address) {
+        return Ok(abi.clone());
+    }
+    let mut payload = fetch_smart_contract(address).await?;
+    let abi: Vec<AbiEntry> = payload.mut_abi().take_entrys().into();
+    CONTRACT_ABI_CACHE.lock().unwrap().insert(*address, abi.clone());
+    Ok(abi)
+}
+
+/// The deployed contract, with its current settings like `consume_user_resource_percent`.
+pub fn get_smart_contract(address: &Address) -> Result<SmartContract, Error> {
+    executor::block_on(fetch_smart_contract(address))
+}
+
+pub async fn fetch_smart_contract(address: &Address) -> Result<SmartContract, Error> {
+    record::fetch_async(RecordKind::Contract, address.as_bytes(), || async {
+        let mut req = BytesMessage::new();
+        req.set_value(address.as_bytes().to_owned());
+        Ok(client::GRPC_CLIENT
+            .get_contract(Default::default(), req)
+            .drop_metadata()
+            .await?)
+    })
+    .await
 }
 
 /// Helper trait for packing contract.
@@ -430,3 +1328,395 @@ impl_contract_pb_ext_for!(AccountPermissionUpdateContract);
 impl_contract_pb_ext_for!(ClearABIContract);
 impl_contract_pb_ext_for!(UpdateBrokerageContract);
 impl_contract_pb_ext_for!(ShieldedTransferContract);
+
+/// Make `get_contract_abi` return `abi` for the contract without fetching it, for tests.
+#[cfg(test)]
+pub fn test_cache_contract_abi(address: &Address, abi: Vec<AbiEntry>) {
+    CONTRACT_ABI_CACHE.lock().unwrap().insert(*address, abi);
+}
+
+/// An unsigned transaction of a single contract, for tests.
+#[cfg(test)]
+pub fn test_transaction_of(pb: &impl ContractPbExt) -> Transaction {
+    let mut contract = Contract::new();
+    contract.set_field_type(pb.contract_type());
+    contract.set_parameter(pb.as_google_any().unwrap());
+    let mut raw = TransactionRaw::new();
+    raw.set_contract(vec![contract].into());
+    let mut txn = Transaction::new();
+    txn.set_raw_data(raw);
+    txn
+}
+
+/// An unsigned transfer of 1 TRX from TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t to itself, for tests.
+#[cfg(test)]
+pub fn test_transfer_transaction() -> Transaction {
+    let owner = hex::decode("41a614f803b6fd780986a42c78ec9c7f77e6ded13c").unwrap();
+    let mut transfer = TransferContract::new();
+    transfer.set_owner_address(owner.clone());
+    transfer.set_to_address(owner);
+    transfer.set_amount(1_000_000);
+    test_transaction_of(&transfer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_contract_type() {
+        use protobuf::ProtobufEnum;
+
+        let names = ContractType::values()
+            .iter()
+            .map(|&ct| format_contract_type(ct))
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(names.len(), ContractType::values().len());
+        assert_eq!(
+            format_contract_type(ContractType::TriggerSmartContract),
+            "Smart Contract Call"
+        );
+    }
+
+    #[test]
+    fn test_ref_block() {
+        // genesis block IDs of mainnet and nile
+        let mainnet = hex::decode("00000000000000001ebf88508a03865c71d452e25f4d51194196a1d22b6653dc").unwrap();
+        let nile = hex::decode("0000000000000000d698d4192c56cb6be724a558448e2684802de4d6cd8690dc").unwrap();
+        let mut raw = TransactionRaw::new();
+        set_ref_block(&mut raw, &mainnet);
+        assert!(ref_block_matches(&raw, &mainnet));
+        assert!(!ref_block_matches(&raw, &nile));
+        set_ref_block(&mut raw, &nile);
+        assert!(ref_block_matches(&raw, &nile));
+        assert!(!ref_block_matches(&raw, &mainnet));
+
+        assert_eq!(ref_block_number(0x12_3456, &[0x34, 0x00]), Some(0x12_3400));
+        assert_eq!(ref_block_number(0x12_3456, &[0x40, 0x00]), Some(0x11_4000));
+        assert_eq!(ref_block_number(100, &[0x40, 0x00]), None);
+        assert_eq!(ref_block_number(100, &[]), None);
+    }
+
+    #[test]
+    fn test_render_number() {
+        assert_eq!(render_number(1000000, true), "1,000,000");
+        assert_eq!(render_number(1000000, false), "1000000");
+        assert_eq!(render_number(999, true), "999");
+        assert_eq!(render_number(-1234567, true), "-1,234,567");
+        assert_eq!(render_number(12345.678901, true), "12,345.678901");
+    }
+
+    #[test]
+    fn test_activation_fees() {
+        let mut params = ChainParameters::new();
+        assert_eq!(activation_fees(&params), (1_000_000, 100_000));
+
+        let mut param = proto::core::ChainParameters_ChainParameter::new();
+        param.set_key("getCreateNewAccountFeeInSystemContract".into());
+        param.set_value(2_000_000);
+        params.set_chainParameter(vec![param].into());
+        assert_eq!(activation_fees(&params), (2_000_000, 100_000));
+    }
+
+    #[test]
+    fn test_bandwidth_price_of() {
+        let mut params = ChainParameters::new();
+        assert!(bandwidth_price_of(&params).is_err());
+
+        let mut param = proto::core::ChainParameters_ChainParameter::new();
+        param.set_key("getTransactionFee".into());
+        param.set_value(1_000);
+        params.set_chainParameter(vec![param].into());
+        assert_eq!(bandwidth_price_of(&params).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_explain_return_code() {
+        use protobuf::ProtobufEnum;
+
+        for &code in ReturnCode::values() {
+            let (explanation, suggestion) = explain_return_code(code);
+            assert!(!explanation.is_empty() && !suggestion.is_empty(), "{:?}", code);
+        }
+        assert!(explain_return_code(ReturnCode::TAPOS_ERROR).1.contains("recent block"));
+
+        let mut ret = Return::new();
+        ret.set_code(ReturnCode::TAPOS_ERROR);
+        ret.set_message(b"Tapos check error".to_vec());
+        match check_broadcast_return(&ret) {
+            Err(Error::Broadcast { code, message }) => {
+                assert_eq!(code, ReturnCode::TAPOS_ERROR);
+                assert_eq!(message, "Tapos check error");
+            }
+            _ => panic!("expected broadcast error"),
+        }
+
+        ret.set_result(true);
+        assert!(check_broadcast_return(&ret).is_ok());
+    }
+
+    #[test]
+    fn test_decode_revert_reason() {
+        let error = hex::decode(
+            "08c379a0\
+             0000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000014\
+             696e73756666696369656e742062616c616e6365000000000000000000000000",
+        )
+        .unwrap();
+        assert_eq!(decode_revert_reason(&error).unwrap(), "insufficient balance");
+
+        let panic = hex::decode("4e487b710000000000000000000000000000000000000000000000000000000000000011").unwrap();
+        assert_eq!(
+            decode_revert_reason(&panic).unwrap(),
+            "Panic(0x11): arithmetic overflow or underflow"
+        );
+
+        assert_eq!(
+            decode_revert_reason(b"Ownable: caller is not the owner").unwrap(),
+            "Ownable: caller is not the owner"
+        );
+
+        assert_eq!(decode_revert_reason(&[]), None);
+        assert_eq!(decode_revert_reason(&error[..40]), None);
+        assert_eq!(decode_revert_reason(&hex::decode("deadbeef00").unwrap()), None);
+    }
+
+    #[test]
+    fn test_parse_address() {
+        let addr: Address = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".parse().unwrap();
+        let hex_addr = "41a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+        assert_eq!(parse_address("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t").unwrap(), addr);
+        assert_eq!(parse_address(hex_addr).unwrap(), addr);
+        assert_eq!(parse_address(&format!("0x{}", hex_addr)).unwrap(), addr);
+        assert_eq!(parse_address(&format!("0X{}", hex_addr.to_uppercase())).unwrap(), addr);
+        assert_eq!(parse_address("_").unwrap(), parse_address("/0").unwrap());
+
+        let err = |s: &str| parse_address(s).unwrap_err().to_string();
+        assert!(err("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6u").contains("checksum"));
+        assert!(err("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj60").contains("base58"));
+        assert!(err("0x41a614f803b6fd780986a42c78ec9c7f77e6ded1zz").contains("hex"));
+        assert!(err("0x0x41a614f803b6fd780986a42c78ec9c7f77e6ded13c").contains("hex"));
+        assert_eq!(
+            parse_address("0xa614f803b6fd780986a42c78ec9c7f77e6ded13c").unwrap(),
+            addr
+        );
+        assert!(err("0x41a614f803b6fd780986a42c78ec9c7f77e6de").contains("length"));
+        assert!(err("0x42a614f803b6fd780986a42c78ec9c7f77e6ded13c").contains("prefix"));
+        assert!(err("TR7N").contains("length"));
+    }
+
+    #[test]
+    fn test_parse_memo() {
+        assert_eq!(parse_memo(b""), MemoContent::Empty);
+        assert_eq!(parse_memo(b"hello world"), MemoContent::Text("hello world".into()));
+        assert_eq!(parse_memo("波场".as_bytes()), MemoContent::Hex("0xe6b3a2e59cba".into()));
+        assert_eq!(parse_memo("café".as_bytes()), MemoContent::Hex("0x636166c3a9".into()));
+        assert_eq!(
+            parse_memo(b"tab\tor\nnewline"),
+            MemoContent::Hex("0x746162096f720a6e65776c696e65".into())
+        );
+        assert_eq!(parse_memo(&[0xff, 0xfe, 0x41]), MemoContent::Hex("0xfffe41".into()));
+        assert_eq!(parse_memo(&[0, 0, 0]), MemoContent::Hex("0x000000".into()));
+        assert_eq!(parse_memo(b"12345678"), MemoContent::Text("12345678".into()));
+        assert_eq!(
+            parse_memo(b"68656c6c6f"),
+            MemoContent::HexText("68656c6c6f".into(), Some("hello".into()))
+        );
+        assert_eq!(
+            parse_memo(b"0xdeadbeef"),
+            MemoContent::HexText("0xdeadbeef".into(), None)
+        );
+        assert_eq!(parse_memo(br#"{"order": 42}"#), MemoContent::Json(json!({"order": 42})));
+        assert_eq!(parse_memo(b"[not json"), MemoContent::Text("[not json".into()));
+        assert_eq!(
+            parse_memo("[波场".as_bytes()),
+            MemoContent::Hex("0x5be6b3a2e59cba".into())
+        );
+        assert_eq!(
+            parse_memo(b"e6b3a2e59cba"),
+            MemoContent::HexText("e6b3a2e59cba".into(), None)
+        );
+    }
+
+    #[test]
+    fn test_format_token_amount() {
+        assert_eq!(format_token_amount(1_234_567, 6, "USDT"), "1.234567 USDT");
+        assert_eq!(format_token_amount(1_000_000, 6, "USDT"), "1.000000 USDT");
+        assert_eq!(format_token_amount(5, 6, "USDT"), "0.000005 USDT");
+        assert_eq!(format_token_amount(-5, 6, "USDT"), "-0.000005 USDT");
+        assert_eq!(format_token_amount(1234, 0, "BTT"), "1234 BTT");
+        assert_eq!(format_token_amount(0, 0, ""), "0");
+        assert_eq!(format_token_amount(i64::MAX, 6, "X"), "9223372036854.775807 X");
+        assert_eq!(format_token_amount(i64::MIN, 6, "X"), "-9223372036854.775808 X");
+        assert_eq!(format_token_amount(i64::MAX, 20, "X"), "0.09223372036854775807 X");
+        assert_eq!(
+            render_number(format_token_amount(1_234_567_890, 3, ""), true),
+            "1,234,567.890"
+        );
+    }
+
+    #[test]
+    fn test_format_trx() {
+        assert_eq!(format_trx(267_000), "0.267000");
+        assert_eq!(format_trx(-1), "-0.000001");
+        assert_eq!(format_trx(i64::MIN), "-9223372036854.775808");
+        // above 2^53 SUN, the f64 path rounds the last digit
+        let sun = 9_007_199_254_740_993;
+        assert_eq!(format_trx(sun), "9007199254.740993");
+        assert_ne!(format!("{:.6}", sun as f64 / 1_000_000.0), format_trx(sun));
+    }
+
+    #[test]
+    fn test_hexdump() {
+        assert!(hexdump(b"").is_empty());
+        assert_eq!(
+            hexdump(b"\x00\x01hello, tron!\xff\x7f\n"),
+            vec![
+                "00000000  00 01 68 65 6c 6c 6f 2c 20 74 72 6f 6e 21 ff 7f  |..hello, tron!..|",
+                "00000010  0a                                               |.|",
+            ]
+        );
+    }
+
+    // compared against `net_usage` charged by java-tron in tests/integration
+    #[test]
+    fn test_bandwidth_of() {
+        use proto::core::Transaction_Result as TransactionResult;
+
+        let mut txn = test_transfer_transaction();
+        let raw = txn.mut_raw_data();
+        raw.set_ref_block_bytes(vec![0x12, 0x34]);
+        raw.set_ref_block_hash(vec![0xab; 8]);
+        raw.set_expiration(1_600_000_060_000);
+        raw.set_timestamp(1_600_000_000_000);
+
+        let unsigned = bandwidth_of(&txn);
+        assert_eq!(unsigned, txn.compute_size() as usize + MAX_RESULT_SIZE_IN_TX);
+        assert_eq!(bandwidth_with_result_reserve(&txn, 128), unsigned + 64);
+        assert_eq!(bandwidth_with_result_reserve(&txn, 0), txn.compute_size() as usize);
+
+        // each signature adds a tag and a length prefix
+        txn.set_signature(vec![vec![1; 65]].into());
+        let single_sig = bandwidth_of(&txn);
+        assert_eq!(single_sig, unsigned + 1 + 1 + 65);
+        txn.mut_signature().push(vec![2; 67]);
+        let multi_sig = bandwidth_of(&txn);
+        assert_eq!(multi_sig, single_sig + 1 + 1 + 67);
+
+        // `ret` is excluded, the transaction is not modified
+        let mut ret = TransactionResult::new();
+        ret.set_fee(100_000);
+        txn.set_ret(vec![ret].into());
+        assert_eq!(result_size(&txn), 2 + txn.get_ret()[0].compute_size() as usize);
+        assert_eq!(bandwidth_of(&txn), multi_sig);
+        assert_eq!(txn.get_ret().len(), 1);
+    }
+
+    #[test]
+    fn test_check_memo_size() {
+        assert_eq!(memo_bandwidth(b""), 2);
+        assert_eq!(memo_bandwidth(&[0; 100]), 102);
+        assert_eq!(memo_bandwidth(&[0; 200]), 203);
+
+        check_memo_size(&[0; MEMO_WARN_SIZE + 1], DEFAULT_MAX_MEMO_SIZE).unwrap();
+        check_memo_size(&[0; 16], 16).unwrap();
+        assert!(check_memo_size(&[0; 17], 16).is_err());
+    }
+
+    #[test]
+    fn test_broadcast_timeout_then_lookup() {
+        let txid = [0x42; 32];
+        let policy = BroadcastPolicy {
+            timeout: Duration::from_millis(50),
+            lookup_attempts: 3,
+            lookup_interval: Duration::from_millis(1),
+        };
+        let lookups = std::cell::Cell::new(0);
+
+        let status = broadcast_with_lookup(
+            &txid,
+            || {
+                thread::sleep(Duration::from_millis(500));
+                Ok(Return::new())
+            },
+            |id| {
+                assert_eq!(id, &txid);
+                lookups.set(lookups.get() + 1);
+                // not found in the first lookup
+                if lookups.get() < 2 {
+                    return Ok(None);
+                }
+                let mut info = TransactionInfo::new();
+                info.set_id(id[..].to_owned());
+                info.set_blockNumber(1000);
+                Ok(Some(info))
+            },
+            &policy,
+        )
+        .unwrap();
+
+        match status {
+            BroadcastStatus::Included(info) => assert_eq!(info.get_blockNumber(), 1000),
+            _ => panic!("expected included, got {:?}", status),
+        }
+        assert_eq!(lookups.get(), 2);
+    }
+
+    #[test]
+    fn test_deterministic_transaction() {
+        use proto::core::TransferContract;
+
+        let build = || {
+            let yaml = clap::load_yaml!("../cli.yml");
+            let matches = clap::App::from_yaml(yaml).get_matches_from(vec![
+                "wallet-cli",
+                "transfer",
+                "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t",
+                "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt",
+                "1TRX",
+                "--deterministic",
+                "--timestamp",
+                "1600000000000",
+                "--ref-block-hash",
+                "0000000001312d0012345678abcdef000000000000000000000000000000beef",
+                "--memo",
+                "audit",
+            ]);
+            let matches = matches.subcommand_matches("transfer").unwrap();
+
+            let mut transfer = TransferContract::new();
+            transfer.set_owner_address(
+                parse_address("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t")
+                    .unwrap()
+                    .as_bytes()
+                    .to_owned(),
+            );
+            transfer.set_to_address(
+                parse_address("TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt")
+                    .unwrap()
+                    .as_bytes()
+                    .to_owned(),
+            );
+            transfer.set_amount(1_000_000);
+            let raw = TransactionHandler::handle(transfer, matches)
+                .to_raw_transaction()
+                .unwrap();
+            raw.write_to_bytes().unwrap()
+        };
+
+        let first = build();
+        let second = build();
+        assert_eq!(first, second);
+
+        let raw: TransactionRaw = parse_from_bytes(&first).unwrap();
+        assert_eq!(
+            transaction_hash(&raw).unwrap(),
+            transaction_hash(&parse_from_bytes(&second).unwrap()).unwrap()
+        );
+        assert_eq!(raw.get_timestamp(), 1_600_000_000_000);
+        assert_eq!(raw.get_expiration(), 1_600_000_060_000);
+        assert_eq!(raw.get_ref_block_bytes(), &[0x2d, 0x00]);
+        assert_eq!(raw.get_ref_block_hash(), &hex::decode("12345678abcdef00").unwrap()[..]);
+    }
+}
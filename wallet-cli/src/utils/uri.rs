@@ -0,0 +1,164 @@
+//! TRON payment URI, as used by wallet apps.
+//!
+//! ```text
+//! tron:<ADDRESS>?amount=<AMOUNT>&token=<TOKEN>&memo=<MEMO>
+//! ```
+//!
+//! - `ADDRESS` - recipient, in base58check or hex
+//! - `AMOUNT` - decimal amount in token units, e.g. `10.5`
+//! - `TOKEN` - `TRX`, a TRC10 token id, or a TRC20 contract address or well-known symbol. Defaults to `TRX`
+//! - `MEMO` - memo of the transaction
+//!
+//! Query values are percent-encoded. Other query parameters are kept as is.
+
+use keys::Address;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::utils::trx;
+
+const URI_SCHEME: &str = "tron:";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentUri {
+    pub address: Address,
+    pub amount: Option<String>,
+    pub token: Option<String>,
+    pub memo: Option<String>,
+    /// Query parameters not understood, in original order.
+    pub unknown_params: Vec<(String, String)>,
+}
+
+impl PaymentUri {
+    pub fn new(address: Address) -> Self {
+        PaymentUri {
+            address,
+            amount: None,
+            token: None,
+            memo: None,
+            unknown_params: vec![],
+        }
+    }
+}
+
+fn percent_decode(s: &str) -> Result<String, Error> {
+    let mut raw = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(c) = bytes.next() {
+        match c {
+            b'%' => {
+                let hi = bytes
+                    .next()
+                    .ok_or(Error::Runtime("malformed percent-encoding in URI"))?;
+                let lo = bytes
+                    .next()
+                    .ok_or(Error::Runtime("malformed percent-encoding in URI"))?;
+                raw.extend(hex::decode(&[hi, lo]).map_err(|_| Error::Runtime("malformed percent-encoding in URI"))?);
+            }
+            b'+' => raw.push(b' '),
+            _ => raw.push(c),
+        }
+    }
+    String::from_utf8(raw).map_err(|_| Error::Runtime("URI is not valid UTF-8"))
+}
+
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|c| match c {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (c as char).to_string(),
+            _ => format!("%{:02X}", c),
+        })
+        .collect()
+}
+
+impl FromStr for PaymentUri {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        if s.len() < URI_SCHEME.len() || !s[..URI_SCHEME.len()].eq_ignore_ascii_case(URI_SCHEME) {
+            return Err(Error::Runtime("payment URI must start with tron:"));
+        }
+        let s = &s[URI_SCHEME.len()..];
+        let (addr, query) = match s.find('?') {
+            Some(pos) => (&s[..pos], &s[pos + 1..]),
+            None => (s, ""),
+        };
+
+        let mut uri = PaymentUri::new(trx::parse_address(addr.trim_start_matches("//"))?);
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let mut kv = pair.splitn(2, '=');
+            let key = percent_decode(kv.next().unwrap_or_default())?;
+            let value = percent_decode(kv.next().unwrap_or_default())?;
+            match &*key {
+                "amount" => uri.amount = Some(value),
+                "token" => uri.token = Some(value),
+                "memo" => uri.memo = Some(value),
+                _ => uri.unknown_params.push((key, value)),
+            }
+        }
+        Ok(uri)
+    }
+}
+
+impl fmt::Display for PaymentUri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", URI_SCHEME, self.address)?;
+        let params = self
+            .amount
+            .as_ref()
+            .map(|val| ("amount", val))
+            .into_iter()
+            .chain(self.token.as_ref().map(|val| ("token", val)))
+            .chain(self.memo.as_ref().map(|val| ("memo", val)))
+            .chain(self.unknown_params.iter().map(|(key, val)| (&**key, val)));
+        for (i, (key, val)) in params.enumerate() {
+            let sep = if i == 0 { '?' } else { '&' };
+            write!(f, "{}{}={}", sep, percent_encode(key), percent_encode(val))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_payment_uri() {
+        let uri: PaymentUri =
+            "tron:TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt?amount=10.5&token=USDT&memo=order%20%2342&foo=bar"
+                .parse()
+                .unwrap();
+        assert_eq!(uri.address.to_string(), "TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt");
+        assert_eq!(uri.amount.as_deref(), Some("10.5"));
+        assert_eq!(uri.token.as_deref(), Some("USDT"));
+        assert_eq!(uri.memo.as_deref(), Some("order #42"));
+        assert_eq!(uri.unknown_params, vec![("foo".to_owned(), "bar".to_owned())]);
+
+        let uri: PaymentUri = "TRON:TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap();
+        assert_eq!(uri, PaymentUri::new(uri.address));
+
+        assert!("bitcoin:TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt"
+            .parse::<PaymentUri>()
+            .is_err());
+        assert!("tron:TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt?memo=%2"
+            .parse::<PaymentUri>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_payment_uri_roundtrip() {
+        let mut uri = PaymentUri::new("TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt".parse().unwrap());
+        uri.amount = Some("1".to_owned());
+        uri.memo = Some("a&b=c".to_owned());
+        uri.unknown_params.push(("label".to_owned(), "shop".to_owned()));
+
+        let s = uri.to_string();
+        assert_eq!(
+            s,
+            "tron:TKfjV9RNKJJCqPvBtK8L7Knykh7DNWvnYt?amount=1&memo=a%26b%3Dc&label=shop"
+        );
+        assert_eq!(s.parse::<PaymentUri>().unwrap(), uri);
+    }
+}
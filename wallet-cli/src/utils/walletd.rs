@@ -1,6 +1,7 @@
 //! Interact with walletd daemon.
 #![allow(unused_imports, dead_code)]
 
+use log::warn;
 use std::env;
 use std::fs;
 use std::os::raw::c_int;
@@ -20,7 +21,7 @@ pub fn ensure_walletd() -> Result<(), Error> {
             Ok(())
         }
         Err(e) => {
-            eprintln!("walletd: {:?}", e);
+            warn!("walletd: {:?}", e);
             run_walletd()
         }
     }
@@ -28,7 +29,7 @@ pub fn ensure_walletd() -> Result<(), Error> {
 
 #[cfg(target_os = "windows")]
 pub fn ensure_walletd() -> Result<(), Error> {
-    eprintln!("warning: must run walletd manually");
+    warn!("must run walletd manually");
     Ok(())
 }
 
@@ -0,0 +1,16 @@
+//! Shell completion scripts, generated from cli.yml.
+
+use std::process::Command;
+
+#[test]
+fn test_bash_completions() {
+    let output = Command::new(env!("CARGO_BIN_EXE_wallet-cli"))
+        .args(&["completions", "bash"])
+        .output()
+        .expect("can not run wallet-cli");
+    assert!(output.status.success());
+    let script = String::from_utf8_lossy(&output.stdout);
+    assert!(script.contains("transaction_info"));
+    assert!(script.contains("--network"));
+    assert!(script.contains("dappchain-testnet"));
+}
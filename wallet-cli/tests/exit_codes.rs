@@ -0,0 +1,66 @@
+//! Exit codes of failing commands, by `Error::exit_code`.
+
+use std::path::PathBuf;
+use std::process::{self, Command, Output};
+
+fn wallet_cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wallet-cli"))
+        .args(args)
+        .env("WALLET_CLI_LOG", "info")
+        .output()
+        .expect("can not run wallet-cli")
+}
+
+/// An empty replay directory, every lookup is not found.
+fn empty_replay_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("wallet-cli-exit-{}-{}", name, process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_missing_transaction() {
+    let dir = empty_replay_dir("missing");
+    let txid = "00".repeat(32);
+    let dir_arg = dir.to_str().unwrap();
+
+    let output = wallet_cli(&["--replay-dir", dir_arg, "get", "transaction_info", &txid]);
+    assert_eq!(output.status.code(), Some(2));
+
+    // only the error is printed
+    let output = wallet_cli(&["--quiet", "--replay-dir", dir_arg, "get", "transaction_info", &txid]);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.lines().all(|line| line.starts_with("Error:")), "{}", stderr);
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_unreachable_node() {
+    let output = wallet_cli(&["--rpc-addr", "127.0.0.1:1", "get", "transaction", &"00".repeat(32)]);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn test_malformed_transaction_id() {
+    let output = wallet_cli(&["get", "transaction", "0xabc"]);
+    assert_eq!(output.status.code(), Some(4));
+
+    let output = wallet_cli(&["get", "transaction", "not-a-txid"]);
+    assert_eq!(output.status.code(), Some(4));
+}
+
+#[test]
+fn test_merkle_root_mismatch() {
+    let hash = "df3f619804a92fdb4057192dc43dd748ea778adc52bc498ce80524c014b81119";
+
+    let output = wallet_cli(&["get", "merkle_root", hash, "--expected", hash]);
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = wallet_cli(&["get", "merkle_root", hash, "--expected", &"00".repeat(32)]);
+    assert_eq!(output.status.code(), Some(7));
+
+    let output = wallet_cli(&["get", "merkle_root", &hash[2..]]);
+    assert_eq!(output.status.code(), Some(4));
+}
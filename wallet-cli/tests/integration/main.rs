@@ -0,0 +1,168 @@
+//! Integration tests against a local TRON Quickstart node, run with:
+//!
+//! ```console
+//! > cargo build -p walletd
+//! > cargo test -p wallet-cli --features integration --test integration
+//! ```
+#![cfg(feature = "integration")]
+
+use proto::core::{Transaction, TransactionInfo};
+use serde_json::json;
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::Duration;
+use wallet_cli::utils::record::{self, RecordKind};
+use wallet_cli::utils::trx;
+
+mod quickstart;
+
+fn stdout_json(output: &std::process::Output) -> serde_json::Value {
+    assert!(
+        output.status.success(),
+        "wallet-cli failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).expect("stdout is JSON")
+}
+
+#[test]
+fn test_get_latest_block() {
+    let node = quickstart::node();
+
+    let block = stdout_json(&node.wallet_cli(&["get", "block"]));
+    assert!(block["blockID"].is_string());
+    assert!(block["block_header"]["raw_data"]["number"].as_i64().unwrap_or_default() > 0);
+}
+
+#[test]
+fn test_get_genesis_account() {
+    let node = quickstart::node();
+
+    let account = stdout_json(&node.wallet_cli(&["get", "account", &node.address(2).to_string()]));
+    assert!(account["balance"].as_i64().unwrap() > 0);
+}
+
+#[test]
+fn test_transfer_and_get_transaction() {
+    let node = quickstart::node();
+    let private_key = node.accounts[0].to_string();
+    let sender = node.address(0).to_string();
+    let recipient = node.address(1).to_string();
+
+    let txn = stdout_json(&node.wallet_cli(&["transfer", &sender, &recipient, "1TRX", "-K", &private_key]));
+    let txid = txn["txID"].as_str().expect("txID in output").to_owned();
+
+    // wait for the transaction to be packed into a block
+    let mut fetched = None;
+    for _ in 0..10 {
+        let output = node.wallet_cli(&["get", "transaction", &txid]);
+        if output.status.success() {
+            fetched = Some(stdout_json(&output));
+            break;
+        }
+        thread::sleep(Duration::from_secs(3));
+    }
+    let fetched = fetched.expect("transaction not found on chain");
+
+    assert_eq!(fetched["txID"], txid);
+    let transfer = &fetched["raw_data"]["contract"][0]["parameter"]["value"];
+    assert_eq!(transfer["amount"], 1_000_000);
+}
+
+#[test]
+fn test_merkle_root_of_genesis_block() {
+    let node = quickstart::node();
+
+    let header = stdout_json(&node.wallet_cli(&["get", "block_header", "0"]));
+    let tx_trie_root = header["tx_trie_root"].as_str().expect("tx_trie_root in output");
+
+    // leaves are hashes of the serialized genesis transactions, as computed by the node
+    let output = node.wallet_cli(&["get", "merkle_tree", "0"]);
+    assert!(output.status.success());
+    let leaves = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1).map(str::to_owned))
+        .collect::<Vec<_>>();
+    assert!(!leaves.is_empty());
+
+    let mut args = vec!["get", "merkle_root", "--expected", tx_trie_root];
+    args.extend(leaves.iter().map(String::as_str));
+    let output = node.wallet_cli(&args);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), tx_trie_root);
+}
+
+/// The transaction and its info once packed into a block, as recorded by `get transaction --raw-output-dir`.
+fn wait_for_receipt(node: &quickstart::Quickstart, txid: &str) -> (Transaction, TransactionInfo) {
+    let dir = std::env::temp_dir().join(format!("wallet-cli-receipt-{}-{}", txid, process::id()));
+    let id = hex::decode(txid).unwrap();
+    for _ in 0..10 {
+        let output = node.wallet_cli(&["--raw-output-dir", dir.to_str().unwrap(), "get", "transaction", txid]);
+        if output.status.success() {
+            let load = |kind| dir.join(record::file_name(kind, &id));
+            let info: TransactionInfo = record::load(&load(RecordKind::TransactionInfo)).unwrap();
+            if !info.get_id().is_empty() {
+                let txn = record::load(&load(RecordKind::Transaction)).unwrap();
+                std::fs::remove_dir_all(&dir).unwrap();
+                return (txn, info);
+            }
+        }
+        thread::sleep(Duration::from_secs(3));
+    }
+    panic!("transaction {} not packed into a block", txid);
+}
+
+fn assert_bandwidth_charged(node: &quickstart::Quickstart, txid: &str, num_signatures: usize) {
+    let (txn, info) = wait_for_receipt(node, txid);
+    assert_eq!(txn.get_signature().len(), num_signatures);
+    // paid by free bandwidth of the genesis accounts, so it is `net_usage` rather than `net_fee`
+    assert_eq!(
+        trx::bandwidth_of(&txn) as i64,
+        info.get_receipt().get_net_usage(),
+        "bandwidth of {}",
+        txid
+    );
+}
+
+#[test]
+fn test_bandwidth_of_matches_net_usage() {
+    let node = quickstart::node();
+    let owner = node.address(3);
+    let owner_key = node.accounts[3].to_string();
+    let cosigner_key = node.accounts[4].to_string();
+    let recipient = node.address(1).to_string();
+
+    let txn = stdout_json(&node.wallet_cli(&["transfer", &owner.to_string(), &recipient, "1TRX", "-K", &owner_key]));
+    assert_bandwidth_charged(node, txn["txID"].as_str().expect("txID in output"), 1);
+
+    // add a second key to the owner permission, either key alone is enough
+    let mut permission = stdout_json(&node.wallet_cli(&["get", "account_permission", &owner.to_string()]));
+    permission["owner"]["keys"]
+        .as_array_mut()
+        .expect("owner keys")
+        .push(json!({ "address": hex::encode(node.address(4).as_bytes()), "weight": 1 }));
+    let output = node.wallet_cli(&[
+        "set",
+        "account_permission",
+        &owner.to_string(),
+        &permission.to_string(),
+        "-K",
+        &owner_key,
+    ]);
+    let txn = stdout_json(&output);
+    wait_for_receipt(node, txn["txID"].as_str().expect("txID in output"));
+
+    let txn = stdout_json(&node.wallet_cli(&[
+        "transfer",
+        &owner.to_string(),
+        &recipient,
+        "1TRX",
+        "-K",
+        &owner_key,
+        "--dont-broadcast",
+    ]));
+    let output = node.wallet_cli(&["sign", &txn.to_string(), "-K", &cosigner_key, "--broadcast"]);
+    let signed = stdout_json(&output);
+    assert_bandwidth_charged(node, signed["txID"].as_str().expect("txID in output"), 2);
+}
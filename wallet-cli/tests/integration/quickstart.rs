@@ -0,0 +1,121 @@
+//! A local TRON Quickstart node for integration tests.
+//!
+//! The node is started as a Docker container on first use, unless `TRON_QUICKSTART_GRPC` points to a running one,
+//! as in CI where it is a service container. The container is left running for later test runs, remove it with
+//! `docker rm -f wallet-cli-quickstart`.
+
+use keys::{Address, Private};
+use lazy_static::lazy_static;
+use std::env;
+use std::process::{Command, Output};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const CONTAINER_NAME: &str = "wallet-cli-quickstart";
+const IMAGE: &str = "trontools/quickstart";
+const DEFAULT_GRPC_ADDR: &str = "127.0.0.1:50051";
+const DEFAULT_HTTP_URL: &str = "http://127.0.0.1:9090";
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Genesis accounts, same as Quickstart defaults: 10 accounts with 10000 TRX each, from a fixed mnemonic.
+pub const ACCOUNTS: usize = 10;
+pub const DEFAULT_BALANCE_TRX: i64 = 10_000;
+const MNEMONIC: &str = "tattoo gift jealous ability anger eager awful coconut orphan twenty maximum unit";
+
+pub struct Quickstart {
+    pub grpc_addr: String,
+    pub accounts: Vec<Private>,
+}
+
+impl Quickstart {
+    pub fn address(&self, index: usize) -> Address {
+        Address::from_private(&self.accounts[index])
+    }
+
+    /// Run wallet-cli against the node.
+    pub fn wallet_cli(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_wallet-cli"))
+            .args(&["--rpc-addr", &self.grpc_addr])
+            .args(args)
+            .env("WALLET_CLI_LOG", "warn")
+            .output()
+            .expect("can not run wallet-cli")
+    }
+}
+
+lazy_static! {
+    static ref NODE: Quickstart = start();
+}
+
+/// The shared node, started and funded on first use.
+pub fn node() -> &'static Quickstart {
+    &NODE
+}
+
+fn start() -> Quickstart {
+    let http_url = env::var("TRON_QUICKSTART_HTTP").unwrap_or_else(|_| DEFAULT_HTTP_URL.to_owned());
+    let grpc_addr = match env::var("TRON_QUICKSTART_GRPC") {
+        Ok(addr) => addr,
+        Err(_) => {
+            run_container();
+            DEFAULT_GRPC_ADDR.to_owned()
+        }
+    };
+
+    let started_at = Instant::now();
+    let accounts = loop {
+        if let Some(accounts) = fetch_accounts(&http_url) {
+            break accounts;
+        }
+        assert!(started_at.elapsed() < STARTUP_TIMEOUT, "quickstart node not ready");
+        thread::sleep(Duration::from_secs(5));
+    };
+
+    let node = Quickstart { grpc_addr, accounts };
+    while !node.wallet_cli(&["get", "block"]).status.success() {
+        assert!(started_at.elapsed() < STARTUP_TIMEOUT, "quickstart gRPC not ready");
+        thread::sleep(Duration::from_secs(5));
+    }
+    node
+}
+
+fn run_container() {
+    let running = Command::new("docker")
+        .args(&["ps", "-q", "--filter", &format!("name={}", CONTAINER_NAME)])
+        .output()
+        .expect("docker is required for integration tests");
+    if !running.stdout.is_empty() {
+        return;
+    }
+
+    let status = Command::new("docker")
+        .args(&["run", "-d", "--rm", "--name", CONTAINER_NAME])
+        .args(&["-p", "9090:9090", "-p", "50051:50051"])
+        .args(&["-e", &format!("accounts={}", ACCOUNTS)])
+        .args(&["-e", &format!("defaultBalance={}", DEFAULT_BALANCE_TRX)])
+        .args(&["-e", &format!("mnemonic={}", MNEMONIC)])
+        .arg(IMAGE)
+        .status()
+        .expect("docker is required for integration tests");
+    assert!(status.success(), "can not start quickstart container");
+}
+
+/// Funded genesis accounts, available once Quickstart finishes seeding.
+fn fetch_accounts(http_url: &str) -> Option<Vec<Private>> {
+    let resp = ureq::get(&format!("{}/admin/accounts-json", http_url))
+        .timeout(Duration::from_secs(5))
+        .call();
+    if !resp.ok() {
+        return None;
+    }
+    let json: serde_json::Value = resp.into_json().ok()?;
+    let accounts = json["privateKeys"]
+        .as_array()?
+        .iter()
+        .filter_map(|key| key.as_str()?.parse().ok())
+        .collect::<Vec<Private>>();
+    if accounts.len() < ACCOUNTS {
+        return None;
+    }
+    Some(accounts)
+}
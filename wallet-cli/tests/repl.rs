@@ -0,0 +1,37 @@
+//! Commands run in one REPL session.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn test_repl_session() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_wallet-cli"))
+        .arg("repl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("can not run wallet-cli");
+    // a failing command does not end the session
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            b"abi_encode --types uint256 1\nget transaction not-a-txid\n!1\n--network nile abi_encode --types uint256 3\n\
+              history\nquit\nabi_encode --types uint256 2\n",
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let one = format!("{:064x}", 1);
+    assert_eq!(stdout.lines().filter(|line| *line == one).count(), 2);
+    assert!(stdout.contains("   2  get transaction not-a-txid"));
+    assert!(!stdout.contains(&format!("{:064x}", 2)));
+    assert!(!stdout.contains(&format!("{:064x}", 3)));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Error:"));
+    assert!(stderr.contains("global options are taken from the command line starting the REPL"));
+}
@@ -0,0 +1,118 @@
+//! Offline tests of `--replay-dir`, serving `get` commands from recorded responses.
+
+use proto::core::{
+    Block, BlockHeader, BlockHeader_raw as BlockHeaderRaw, Transaction, TransactionInfo,
+    Transaction_Contract as Contract, Transaction_raw as TransactionRaw, TransferContract,
+};
+use protobuf::Message;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Output};
+use wallet_cli::utils::crypto;
+use wallet_cli::utils::record::{self, RecordKind};
+use wallet_cli::utils::trx::ContractPbExt;
+
+const OWNER: &str = "41a614f803b6fd780986a42c78ec9c7f77e6ded13c";
+
+fn transaction() -> Transaction {
+    let mut transfer = TransferContract::new();
+    transfer.set_owner_address(hex::decode(OWNER).unwrap());
+    transfer.set_to_address(hex::decode(OWNER).unwrap());
+    transfer.set_amount(1_000_000);
+
+    let mut contract = Contract::new();
+    contract.set_field_type(transfer.contract_type());
+    contract.set_parameter(transfer.as_google_any().unwrap());
+    let mut raw = TransactionRaw::new();
+    raw.set_contract(vec![contract].into());
+    raw.set_timestamp(1_600_000_000_000);
+    let mut transaction = Transaction::new();
+    transaction.set_raw_data(raw);
+    transaction
+}
+
+fn block(num: i64, transactions: Vec<Transaction>) -> Block {
+    let mut raw = BlockHeaderRaw::new();
+    raw.set_number(num);
+    raw.set_timestamp(1_600_000_000_000);
+    let mut header = BlockHeader::new();
+    header.set_raw_data(raw);
+    let mut block = Block::new();
+    block.set_block_header(header);
+    block.set_transactions(transactions.into());
+    block
+}
+
+/// A replay directory with one transaction, its info, the block containing it and an earlier empty block.
+fn replay_dir(name: &str) -> (PathBuf, String) {
+    let dir = std::env::temp_dir().join(format!("wallet-cli-replay-{}-{}", name, process::id()));
+    let transaction = transaction();
+    let txid = crypto::sha256(&transaction.get_raw_data().write_to_bytes().unwrap());
+
+    let mut info = TransactionInfo::new();
+    info.set_id(txid.to_vec());
+    info.set_blockNumber(42);
+    info.set_fee(100_000);
+
+    record::record_to(&dir, RecordKind::Transaction, &txid, &transaction).unwrap();
+    record::record_to(&dir, RecordKind::TransactionInfo, &txid, &info).unwrap();
+    for block in vec![block(7, vec![]), block(42, vec![transaction])] {
+        record::record_to(&dir, RecordKind::Block, &record::block_id(&block).unwrap(), &block).unwrap();
+    }
+
+    (dir, hex::encode(txid))
+}
+
+fn wallet_cli(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wallet-cli"))
+        .arg("--replay-dir")
+        .arg(dir)
+        .args(args)
+        .env("WALLET_CLI_LOG", "warn")
+        .output()
+        .expect("can not run wallet-cli")
+}
+
+fn stdout_json(output: &Output) -> serde_json::Value {
+    assert!(
+        output.status.success(),
+        "wallet-cli failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).expect("stdout is JSON")
+}
+
+#[test]
+fn test_replay_transaction() {
+    let (dir, txid) = replay_dir("transaction");
+
+    let txn = stdout_json(&wallet_cli(&dir, &["get", "transaction", &txid]));
+    let transfer = &txn["raw_data"]["contract"][0]["parameter"]["value"];
+    assert_eq!(transfer["owner_address"], format!("0x{}", OWNER));
+    assert_eq!(transfer["amount"], 1_000_000);
+
+    let info = stdout_json(&wallet_cli(&dir, &["get", "transaction_info", &txid]));
+    assert_eq!(info["blockNumber"], 42);
+    assert_eq!(info["fee"], 100_000);
+
+    let output = wallet_cli(&dir, &["get", "transaction", &"00".repeat(32)]);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("transaction not found"));
+}
+
+#[test]
+fn test_replay_block() {
+    let (dir, _) = replay_dir("block");
+
+    let block = stdout_json(&wallet_cli(&dir, &["get", "block", "7"]));
+    assert_eq!(block["block_header"]["raw_data"]["number"], 7);
+
+    let latest = stdout_json(&wallet_cli(&dir, &["get", "block"]));
+    assert_eq!(latest["block_header"]["raw_data"]["number"], 42);
+    assert_eq!(latest["transactions"].as_array().unwrap().len(), 1);
+
+    let output = wallet_cli(&dir, &["get", "block", "43"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("block not found on chain"));
+}
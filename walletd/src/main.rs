@@ -233,9 +233,45 @@ impl LocalWallet for LocalWalletService {
 
     async fn list_private_keys(
         &self,
-        _request: Request<ListPrivateKeysRequest>,
+        request: Request<ListPrivateKeysRequest>,
     ) -> Result<Response<ListPrivateKeysResponse>, Status> {
-        unimplemented!()
+        // the request carries the wallet password, never log it
+        println!("INFO request {:?} ListPrivateKeys", request.remote_addr());
+        let password = &request.get_ref().password;
+
+        let reply = match *(*self.wallet).read().unwrap() {
+            Some(ref wallet) if !wallet.check_password(password).unwrap_or(false) => ListPrivateKeysResponse {
+                code: 403,
+                message: "Wrong wallet password".to_owned(),
+                ..Default::default()
+            },
+            Some(ref wallet) => match wallet
+                .keys()
+                .map(|public| {
+                    wallet
+                        .get_private_key(public)
+                        .map(|private| private.as_bytes().to_owned())
+                })
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(private_keys) => ListPrivateKeysResponse {
+                    code: 200,
+                    message: "OK".to_owned(),
+                    private_keys,
+                },
+                Err(e) => ListPrivateKeysResponse {
+                    code: 500,
+                    message: format!("{:?}", e),
+                    ..Default::default()
+                },
+            },
+            None => ListPrivateKeysResponse {
+                code: 500,
+                message: "No wallet opened".to_owned(),
+                ..Default::default()
+            },
+        };
+        Ok(Response::new(reply))
     }
 
     async fn create_zkey(&self, request: Request<CreateZkeyRequest>) -> Result<Response<CreateZkeyResponse>, Status> {